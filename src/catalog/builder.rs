@@ -6,12 +6,14 @@
 
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::Arc;
 use thiserror::Error;
 
 use crate::core::assembly::{ContigMergeError, FastaContig, FastaDistribution};
 use crate::core::contig::{Contig, SequenceRole};
 use crate::core::reference::KnownReference;
 use crate::core::types::{Assembly, ReferenceSource};
+use crate::parsing::digest_cache::DigestCache;
 use crate::utils::validation::{is_valid_md5, is_valid_sha512t24u};
 
 #[derive(Error, Debug)]
@@ -224,6 +226,9 @@ pub struct ReferenceBuilder {
     /// See module documentation for [`crate::parsing::ncbi_report`] for details on the
     /// naming convention and verification sources.
     generate_ucsc_names: bool,
+
+    /// Cache of FASTA digests, so rebuilding from the same FASTA skips hashing
+    digest_cache: Option<Arc<DigestCache>>,
 }
 
 impl ReferenceBuilder {
@@ -246,6 +251,7 @@ impl ReferenceBuilder {
             warnings: Vec::new(),
             species: None,
             generate_ucsc_names: true, // Default: generate UCSC names for patches
+            digest_cache: None,
         }
     }
 
@@ -274,6 +280,13 @@ impl ReferenceBuilder {
         self
     }
 
+    /// Use a persistent digest cache when hashing FASTA inputs
+    #[must_use]
+    pub fn digest_cache(mut self, cache: Arc<DigestCache>) -> Self {
+        self.digest_cache = Some(cache);
+        self
+    }
+
     #[must_use]
     pub fn assembly(mut self, assembly: Assembly) -> Self {
         self.assembly = Some(assembly);
@@ -557,8 +570,11 @@ impl ReferenceBuilder {
     }
 
     fn add_fasta(&mut self, path: &Path, path_str: &str) -> Result<(), BuilderError> {
-        let query = crate::parsing::fasta::parse_fasta_file_with_md5(path)
-            .map_err(|e| BuilderError::Parse(e.to_string()))?;
+        let query = crate::parsing::fasta::parse_fasta_file_with_md5_cached(
+            path,
+            self.digest_cache.as_deref(),
+        )
+        .map_err(|e| BuilderError::Parse(e.to_string()))?;
 
         let mut record = InputRecord {
             path: path_str.to_string(),
@@ -865,6 +881,7 @@ impl ReferenceBuilder {
             sha512t24u_set: HashSet::new(),
             name_length_set: HashSet::new(),
             signature: None,
+            sha512t24u_signature: None,
         };
 
         reference.rebuild_indexes();
@@ -1069,6 +1086,9 @@ pub struct DistributionBuilder {
 
     /// Whether to generate UCSC-style names for patches (see [`ReferenceBuilder`])
     generate_ucsc_names: bool,
+
+    /// Cache of FASTA digests (see [`ReferenceBuilder::digest_cache`])
+    digest_cache: Option<Arc<DigestCache>>,
 }
 
 impl Default for DistributionBuilder {
@@ -1090,6 +1110,7 @@ impl DistributionBuilder {
             insertion_order: Vec::new(),
             source_files: Vec::new(),
             generate_ucsc_names: true, // Default: generate UCSC names for patches
+            digest_cache: None,
         }
     }
 
@@ -1102,6 +1123,13 @@ impl DistributionBuilder {
         self
     }
 
+    /// Use a persistent digest cache when hashing FASTA inputs
+    #[must_use]
+    pub fn with_digest_cache(mut self, cache: Arc<DigestCache>) -> Self {
+        self.digest_cache = Some(cache);
+        self
+    }
+
     /// Set the display name
     #[must_use]
     pub fn with_display_name(mut self, name: impl Into<String>) -> Self {
@@ -1204,8 +1232,11 @@ impl DistributionBuilder {
             }
             InputFormat::Fasta => {
                 // Parse FASTA with MD5 computation
-                let query = crate::parsing::fasta::parse_fasta_file_with_md5(path)
-                    .map_err(|e| BuilderError::Parse(e.to_string()))?;
+                let query = crate::parsing::fasta::parse_fasta_file_with_md5_cached(
+                    path,
+                    self.digest_cache.as_deref(),
+                )
+                .map_err(|e| BuilderError::Parse(e.to_string()))?;
                 Ok(query.contigs)
             }
             InputFormat::NcbiReport => {
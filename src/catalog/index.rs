@@ -30,7 +30,28 @@ impl<'a> CandidateFinder<'a> {
         }
 
         let mut candidates: Vec<_> = ref_counts.into_iter().collect();
-        candidates.sort_by_key(|b| std::cmp::Reverse(b.1)); // Sort by count descending
+        // Sort by count descending, then catalog order so ties are deterministic
+        candidates.sort_by_key(|&(idx, count)| (std::cmp::Reverse(count), idx));
+        candidates
+    }
+
+    /// Find candidate references based on sha512t24u digest overlap
+    /// Returns indices sorted by number of overlapping digests (descending)
+    #[must_use]
+    pub fn find_candidates_by_sha512t24u(&self, query: &QueryHeader) -> Vec<(usize, usize)> {
+        let mut ref_counts: HashMap<usize, usize> = HashMap::new();
+
+        for digest in &query.sha512t24u_set {
+            if let Some(indices) = self.catalog.sha512t24u_to_refs.get(digest) {
+                for &idx in indices {
+                    *ref_counts.entry(idx).or_default() += 1;
+                }
+            }
+        }
+
+        let mut candidates: Vec<_> = ref_counts.into_iter().collect();
+        // Sort by count descending, then catalog order so ties are deterministic
+        candidates.sort_by_key(|&(idx, count)| (std::cmp::Reverse(count), idx));
         candidates
     }
 
@@ -68,34 +89,42 @@ impl<'a> CandidateFinder<'a> {
         }
 
         let mut candidates: Vec<_> = ref_counts.into_iter().collect();
-        candidates.sort_by_key(|b| std::cmp::Reverse(b.1));
+        candidates.sort_by_key(|&(idx, count)| (std::cmp::Reverse(count), idx));
         candidates
     }
 
-    /// Get top N candidates combining MD5 and name/length matching
+    /// Get top N candidates combining digest (MD5, sha512t24u) and name/length matching
+    ///
+    /// Candidates with as many overlapping contigs as the last one within the
+    /// limit are included too, so references that tie at the cutoff are all
+    /// scored and their ties can be reported.
     #[must_use]
     pub fn find_top_candidates(&self, query: &QueryHeader, limit: usize) -> Vec<usize> {
         let mut seen: HashSet<usize> = HashSet::new();
         let mut result = Vec::new();
 
-        // First add MD5-based candidates (higher priority)
-        for (idx, _) in self.find_candidates_by_md5(query) {
-            if seen.insert(idx) {
-                result.push(idx);
-                if result.len() >= limit {
-                    return result;
+        // Digest-based candidates first (higher priority), then name/length
+        let phases = [
+            self.find_candidates_by_md5(query),
+            self.find_candidates_by_sha512t24u(query),
+            self.find_candidates_by_name_length(query),
+        ];
+        let mut cutoff = None;
+        for candidates in phases {
+            for (idx, count) in candidates {
+                if cutoff.is_some_and(|cutoff| count < cutoff) {
+                    break;
                 }
-            }
-        }
-
-        // Then add name/length candidates
-        for (idx, _) in self.find_candidates_by_name_length(query) {
-            if seen.insert(idx) {
-                result.push(idx);
-                if result.len() >= limit {
-                    return result;
+                if seen.insert(idx) {
+                    result.push(idx);
+                    if result.len() >= limit && cutoff.is_none() {
+                        cutoff = Some(count);
+                    }
                 }
             }
+            if cutoff.is_some() {
+                break;
+            }
         }
 
         result
@@ -154,6 +183,37 @@ mod tests {
         assert_eq!(count, 2, "Should match both chr1 and chr2 via aliases");
     }
 
+    #[test]
+    fn test_find_candidates_by_sha512t24u() {
+        let mut catalog = ReferenceCatalog::new();
+
+        let ref_contigs = vec![
+            Contig::new("chr1", 248_956_422).with_sha512t24u("2YnepKM7OkBoOrKmvHbGqguVfF9amCST"),
+            Contig::new("chr2", 242_193_529).with_sha512t24u("aUiQCzCPZ2gBp8JlKgPrBQ4zeVgk1Wyb"),
+        ];
+
+        let reference = KnownReference::new(
+            "test_sha_ref",
+            "Test sha512t24u Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(ref_contigs);
+
+        catalog.add_reference(reference);
+
+        // Query uses different names and carries only sha512t24u digests
+        let query = QueryHeader::new(vec![
+            Contig::new("seq1", 248_956_422).with_sha512t24u("2YnepKM7OkBoOrKmvHbGqguVfF9amCST"),
+            Contig::new("seq2", 242_193_529).with_sha512t24u("aUiQCzCPZ2gBp8JlKgPrBQ4zeVgk1Wyb"),
+        ]);
+
+        let finder = CandidateFinder::new(&catalog);
+        assert!(finder.find_candidates_by_name_length(&query).is_empty());
+        assert_eq!(finder.find_candidates_by_sha512t24u(&query), vec![(0, 2)]);
+        assert_eq!(finder.find_top_candidates(&query, 5), vec![0]);
+    }
+
     #[test]
     fn test_find_candidates_real_catalog() {
         // Test with the real embedded catalog
@@ -188,4 +248,37 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_top_candidates_include_ties_at_cutoff() {
+        let mut catalog = ReferenceCatalog::new();
+        for (id, chr2_length) in [
+            ("ref_a", 242_193_529),
+            ("ref_b", 242_193_529),
+            ("ref_c", 242_193_529),
+            ("ref_d", 243_199_373),
+        ] {
+            catalog.add_reference(
+                KnownReference::new(
+                    id,
+                    id,
+                    Assembly::Grch38,
+                    ReferenceSource::Custom("test".to_string()),
+                )
+                .with_contigs(vec![
+                    Contig::new("chr1", 248_956_422),
+                    Contig::new("chr2", chr2_length),
+                ]),
+            );
+        }
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 248_956_422),
+            Contig::new("chr2", 242_193_529),
+        ]);
+
+        // ref_b and ref_c overlap as much as ref_a; ref_d overlaps less
+        let finder = CandidateFinder::new(&catalog);
+        assert_eq!(finder.find_top_candidates(&query, 1), vec![0, 1, 2]);
+        assert_eq!(finder.find_top_candidates(&query, 4), vec![0, 1, 2, 3]);
+    }
 }
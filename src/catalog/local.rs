@@ -0,0 +1,500 @@
+//! Index of reference files on local disk.
+//!
+//! [`LocalIndex::scan`] walks directories for FASTA (`.fa`, `.fasta`, `.fna`,
+//! optionally gzipped), `.dict`, `.fai` and `.2bit` files and reads the
+//! sequence dictionary of each with the existing parsers. A FASTA's `.dict` or
+//! `.fai` is read in place of the FASTA itself and is not listed separately.
+//! Digests already in the FASTA [`DigestCache`] are used when available, since
+//! they distinguish near-identical FASTAs that names and lengths cannot.
+//!
+//! The dictionaries are cached as JSON keyed by path, together with the size and
+//! modification time of the file they were read from, so rescanning a large
+//! shared reference directory only reads files that changed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::core::contig::Contig;
+use crate::core::reference::KnownReference;
+use crate::core::types::{Assembly, ReferenceSource};
+use crate::parsing;
+use crate::parsing::digest_cache::{CachedContig, DigestCache, FileFingerprint};
+use crate::utils::json_file::{self, JsonFileError};
+
+/// Index file format version
+const INDEX_VERSION: u32 = 1;
+
+/// Kind of reference file found on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalFileKind {
+    Fasta,
+    Dict,
+    Fai,
+    #[serde(rename = "2bit")]
+    TwoBit,
+}
+
+impl LocalFileKind {
+    /// Classify a path by its extension, `None` if it is not a reference file
+    #[must_use]
+    pub fn of(path: &Path) -> Option<Self> {
+        if parsing::fasta::is_fasta_file(path) {
+            return Some(Self::Fasta);
+        }
+        if parsing::twobit::is_twobit_file(path) {
+            return Some(Self::TwoBit);
+        }
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("dict") => Some(Self::Dict),
+            Some("fai") => Some(Self::Fai),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LocalFileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fasta => write!(f, "FASTA"),
+            Self::Dict => write!(f, "dict"),
+            Self::Fai => write!(f, "FAI"),
+            Self::TwoBit => write!(f, "2bit"),
+        }
+    }
+}
+
+/// A reference file on disk and its sequence dictionary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalReference {
+    /// The reference file: a FASTA, or a `.dict`, `.fai` or `.2bit` without one
+    pub path: PathBuf,
+
+    /// Kind of reference file
+    pub kind: LocalFileKind,
+
+    /// File the dictionary was read from: the reference itself, or a FASTA's
+    /// `.dict` or `.fai`
+    pub source: PathBuf,
+
+    /// Fingerprint of `source` when it was read
+    pub fingerprint: FileFingerprint,
+
+    /// Sequence dictionary
+    pub contigs: Vec<Contig>,
+}
+
+impl LocalReference {
+    /// Fraction of contigs with an MD5
+    #[must_use]
+    pub fn md5_coverage(&self) -> f64 {
+        if self.contigs.is_empty() {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)] // Contig counts are far below 2^52
+        let fraction = self.contigs.iter().filter(|c| c.md5.is_some()).count() as f64
+            / self.contigs.len() as f64;
+        fraction
+    }
+
+    /// Convert into a catalog reference whose ID is the file path
+    #[must_use]
+    pub fn to_known_reference(&self) -> KnownReference {
+        let display_name = self.path.file_name().map_or_else(
+            || self.path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        KnownReference::new(
+            self.path.display().to_string(),
+            display_name,
+            Assembly::Other("local".to_string()),
+            ReferenceSource::Custom("local".to_string()),
+        )
+        .with_contigs(self.contigs.clone())
+    }
+}
+
+/// Build a catalog from local references, for matching with the usual engine
+#[must_use]
+pub fn to_catalog(references: &[LocalReference]) -> ReferenceCatalog {
+    let mut catalog = ReferenceCatalog::new();
+    for reference in references {
+        catalog.add_reference(reference.to_known_reference());
+    }
+    catalog
+}
+
+/// Outcome of scanning directories
+#[derive(Debug, Default)]
+pub struct ScanResult {
+    /// Reference files found, in path order
+    pub references: Vec<LocalReference>,
+
+    /// Number of files whose dictionary was reused from the index or the
+    /// FASTA digest cache
+    pub reused: usize,
+
+    /// Number of files whose dictionary was read from disk
+    pub indexed: usize,
+
+    /// Files or directories that could not be read, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+/// Cached sequence dictionaries of local reference files, keyed by path
+#[derive(Debug, Default)]
+pub struct LocalIndex {
+    /// Backing file, `None` for an index that is never saved
+    path: Option<PathBuf>,
+    entries: BTreeMap<String, LocalReference>,
+}
+
+impl LocalIndex {
+    /// Default index location: `$XDG_CACHE_HOME/ref-solver/local-index.json`,
+    /// falling back to `~/.cache/ref-solver/local-index.json`.
+    ///
+    /// Returns `None` if neither `XDG_CACHE_HOME` nor `HOME` is set.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        crate::utils::cache_dir().map(|dir| dir.join("local-index.json"))
+    }
+
+    /// Open the index at `path`, loading existing entries if the file exists
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, JsonFileError> {
+        let path = path.into();
+        let entries = if path.exists() {
+            read_index_file(&path)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self {
+            path: Some(path),
+            entries,
+        })
+    }
+
+    /// An empty index that is not backed by a file
+    #[must_use]
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Scan directories recursively for reference files, reading the
+    /// dictionary of each file that is new or changed since it was indexed.
+    ///
+    /// Index entries under the scanned directories whose files no longer exist
+    /// are dropped. Files that cannot be read are reported in
+    /// [`ScanResult::skipped`] rather than failing the scan.
+    pub fn scan(&mut self, dirs: &[PathBuf], digests: Option<&DigestCache>) -> ScanResult {
+        let mut result = ScanResult::default();
+
+        let mut files = BTreeSet::new();
+        for dir in dirs {
+            walk(dir, &mut files, &mut result.skipped);
+        }
+
+        let scanned_roots: Vec<PathBuf> = dirs.iter().map(|dir| canonical(dir)).collect();
+        self.entries.retain(|key, _| {
+            let path = Path::new(key);
+            !scanned_roots.iter().any(|root| path.starts_with(root)) || files.contains(path)
+        });
+
+        for path in reference_files(&files) {
+            let Some(kind) = LocalFileKind::of(&path) else {
+                continue;
+            };
+            match self.index_file(&path, kind, digests) {
+                Ok((reference, reused)) => {
+                    if reused {
+                        result.reused += 1;
+                    } else {
+                        result.indexed += 1;
+                    }
+                    result.references.push(reference);
+                }
+                Err(reason) => {
+                    tracing::debug!("Skipping {}: {reason}", path.display());
+                    result.skipped.push((path, reason));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Save the index to disk (a no-op for an in-memory index)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index file cannot be written.
+    pub fn save(&self) -> Result<(), JsonFileError> {
+        match &self.path {
+            Some(path) => json_file::write_entries(path, INDEX_VERSION, &self.entries, false),
+            None => Ok(()),
+        }
+    }
+
+    /// Dictionary of one reference file, from the digest cache or the index if
+    /// still current. Returns whether the dictionary was reused rather than
+    /// read from disk.
+    fn index_file(
+        &mut self,
+        path: &Path,
+        kind: LocalFileKind,
+        digests: Option<&DigestCache>,
+    ) -> Result<(LocalReference, bool), String> {
+        let key = path.to_string_lossy().into_owned();
+
+        // Digests computed for a FASTA by another command beat any dictionary
+        if kind == LocalFileKind::Fasta {
+            if let Some(contigs) = cached_digests(path, digests) {
+                let reference = LocalReference {
+                    path: path.to_path_buf(),
+                    kind,
+                    source: path.to_path_buf(),
+                    fingerprint: FileFingerprint::of(path).map_err(|e| e.to_string())?,
+                    contigs,
+                };
+                self.entries.insert(key, reference.clone());
+                return Ok((reference, true));
+            }
+        }
+
+        let source = match kind {
+            LocalFileKind::Fasta => companion_files(path)
+                .into_iter()
+                .find(|companion| companion.is_file())
+                .unwrap_or_else(|| path.to_path_buf()),
+            _ => path.to_path_buf(),
+        };
+        let fingerprint = FileFingerprint::of(&source).map_err(|e| e.to_string())?;
+
+        if let Some(entry) = self.entries.get(&key) {
+            if entry.source == source && entry.fingerprint == fingerprint {
+                return Ok((entry.clone(), true));
+            }
+        }
+
+        let query = read_dictionary(&source).map_err(|e| e.to_string())?;
+        let reference = LocalReference {
+            path: path.to_path_buf(),
+            kind,
+            source,
+            fingerprint,
+            contigs: query.contigs,
+        };
+        self.entries.insert(key, reference.clone());
+        Ok((reference, false))
+    }
+}
+
+/// Contigs with digests from a complete digest cache entry for a FASTA
+fn cached_digests(fasta: &Path, digests: Option<&DigestCache>) -> Option<Vec<Contig>> {
+    let fingerprint = FileFingerprint::of(fasta).ok()?;
+    let entry = digests?.get(fasta, &fingerprint)?;
+    entry
+        .complete
+        .then(|| entry.contigs.iter().map(CachedContig::to_contig).collect())
+}
+
+/// Read a sequence dictionary with the parser for the file's kind
+fn read_dictionary(
+    path: &Path,
+) -> Result<crate::core::header::QueryHeader, parsing::sam::ParseError> {
+    match LocalFileKind::of(path) {
+        Some(LocalFileKind::Fasta) => parsing::fasta::parse_fasta_file(path),
+        Some(LocalFileKind::Dict) => parsing::dict::parse_dict_file(path),
+        Some(LocalFileKind::Fai) => parsing::fai::parse_fai_file(path),
+        Some(LocalFileKind::TwoBit) => parsing::twobit::parse_twobit_file(path),
+        None => Err(parsing::sam::ParseError::UnsupportedFormat(
+            path.display().to_string(),
+        )),
+    }
+}
+
+/// Files describing the same sequences as a FASTA, best first: its Picard
+/// dictionary (`ref.dict` or `ref.fa.dict`), then its `.fai` index
+fn companion_files(fasta: &Path) -> Vec<PathBuf> {
+    let name = fasta.to_string_lossy();
+    let lower = name.to_lowercase();
+    let uncompressed = [".gz", ".bgz"]
+        .iter()
+        .find_map(|ext| {
+            lower
+                .strip_suffix(ext)
+                .map(|_| &name[..name.len() - ext.len()])
+        })
+        .unwrap_or(&name);
+    let stem = Path::new(uncompressed).with_extension("");
+
+    vec![
+        stem.with_extension("dict"),
+        PathBuf::from(format!("{name}.dict")),
+        PathBuf::from(format!("{name}.fai")),
+    ]
+}
+
+/// Reference files among `files`, leaving out the companions of FASTAs
+fn reference_files(files: &BTreeSet<PathBuf>) -> Vec<PathBuf> {
+    let companions: BTreeSet<PathBuf> = files
+        .iter()
+        .filter(|path| LocalFileKind::of(path) == Some(LocalFileKind::Fasta))
+        .flat_map(|path| companion_files(path))
+        .collect();
+    files
+        .iter()
+        .filter(|path| LocalFileKind::of(path).is_some() && !companions.contains(*path))
+        .cloned()
+        .collect()
+}
+
+/// Collect the files under `dir`, recursively. Symlinked files are followed
+/// but symlinked directories are not, so link cycles cannot recurse forever.
+fn walk(dir: &Path, files: &mut BTreeSet<PathBuf>, skipped: &mut Vec<(PathBuf, String)>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            skipped.push((dir.to_path_buf(), e.to_string()));
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            walk(&path, files, skipped);
+        } else if file_type.is_file() || (file_type.is_symlink() && path.is_file()) {
+            files.insert(canonical(&path));
+        }
+    }
+}
+
+/// Canonical form of a path, or the path as given if it cannot be resolved
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn read_index_file(path: &Path) -> Result<BTreeMap<String, LocalReference>, JsonFileError> {
+    json_file::read_entries(path, INDEX_VERSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DICT: &str = "@HD\tVN:1.6\n\
+        @SQ\tSN:chr1\tLN:4\tM5:f1f8f4bf413b16ad135722aa4591043e\n\
+        @SQ\tSN:chr2\tLN:4\tM5:0e6b8a3c8bd6c5a1b8d2c2a1f0f8b8a1\n";
+
+    #[test]
+    fn test_scan_groups_companions_and_reuses_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let refs = dir.path().join("refs");
+        std::fs::create_dir_all(refs.join("nested")).unwrap();
+
+        // A FASTA with a .dict and .fai: only the FASTA is listed, read via its .dict
+        std::fs::write(refs.join("a.fa"), ">chr1\nACGT\n>chr2\nGGCC\n").unwrap();
+        std::fs::write(refs.join("a.dict"), DICT).unwrap();
+        std::fs::write(
+            refs.join("a.fa.fai"),
+            "chr1\t4\t6\t4\t5\nchr2\t4\t17\t4\t5\n",
+        )
+        .unwrap();
+        // A FASTA with no companions is parsed for names and lengths
+        std::fs::write(refs.join("nested/b.fasta"), ">1\nACGT\n>2\nGGCC\n").unwrap();
+        // A standalone .fai is listed on its own
+        std::fs::write(refs.join("nested/c.fa.fai"), "chr1\t4\t6\t4\t5\n").unwrap();
+        std::fs::write(refs.join("notes.txt"), "not a reference").unwrap();
+
+        let index_path = dir.path().join("index.json");
+        let mut index = LocalIndex::open(&index_path).unwrap();
+        let result = index.scan(std::slice::from_ref(&refs), None);
+        index.save().unwrap();
+
+        assert_eq!(result.references.len(), 3, "{:?}", result.references);
+        assert_eq!(result.indexed, 3);
+        assert!(result.skipped.is_empty());
+
+        let a = &result.references[0];
+        assert!(a.path.ends_with("a.fa"));
+        assert_eq!(a.kind, LocalFileKind::Fasta);
+        assert!(a.source.ends_with("a.dict"));
+        assert!((a.md5_coverage() - 1.0).abs() < f64::EPSILON);
+
+        let b = &result.references[1];
+        assert!(b.path.ends_with("b.fasta"));
+        assert_eq!(b.source, b.path);
+        assert_eq!(b.contigs.len(), 2);
+
+        let c = &result.references[2];
+        assert_eq!(c.kind, LocalFileKind::Fai);
+
+        // Rescanning reuses the saved dictionaries
+        let mut reopened = LocalIndex::open(&index_path).unwrap();
+        let rescan = reopened.scan(&[refs], None);
+        assert_eq!(rescan.reused, 3);
+        assert_eq!(rescan.indexed, 0);
+    }
+
+    #[test]
+    fn test_scan_reuses_digest_cache() {
+        use crate::parsing::digest_cache::{CachedContig, DigestCache};
+
+        let dir = tempfile::tempdir().unwrap();
+        let refs = dir.path().join("refs");
+        std::fs::create_dir_all(&refs).unwrap();
+        let fasta = refs.join("a.fa");
+        std::fs::write(&fasta, ">chr1\nACGT\n").unwrap();
+
+        let digests = DigestCache::open(dir.path().join("digests.json")).unwrap();
+        digests.insert(
+            &fasta,
+            FileFingerprint::of(&fasta).unwrap(),
+            vec![CachedContig {
+                name: "chr1".to_string(),
+                length: 4,
+                md5: "f1f8f4bf413b16ad135722aa4591043e".to_string(),
+                sha512t24u: "aKF498dAxcJAqme6QYQ7EZ07-fiw8Kw2".to_string(),
+            }],
+            true,
+        );
+
+        let result = LocalIndex::in_memory().scan(&[refs], Some(&digests));
+        assert_eq!(result.reused, 1);
+        assert_eq!(result.indexed, 0);
+        assert!((result.references[0].md5_coverage() - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_catalog_ids_are_paths() {
+        let reference = LocalReference {
+            path: PathBuf::from("/refs/hg38.fa"),
+            kind: LocalFileKind::Fasta,
+            source: PathBuf::from("/refs/hg38.dict"),
+            fingerprint: FileFingerprint {
+                size: 0,
+                modified_ns: 0,
+            },
+            contigs: vec![Contig::new("chr1", 248_956_422)],
+        };
+        let catalog = to_catalog(&[reference]);
+
+        let known = &catalog.references[0];
+        assert_eq!(known.id.0, "/refs/hg38.fa");
+        assert_eq!(known.display_name, "hg38.fa");
+    }
+}
@@ -49,4 +49,5 @@
 pub mod builder;
 pub mod hierarchical;
 pub mod index;
+pub mod local;
 pub mod store;
@@ -48,8 +48,11 @@ pub struct ReferenceCatalog {
     /// Separate from `name_length_to_refs` to distinguish primary names from aliases
     pub alias_length_to_refs: HashMap<(String, u64), Vec<usize>>,
 
-    /// Index: signature -> reference index (for exact matches)
-    signature_to_ref: HashMap<String, usize>,
+    /// Index: signature -> indices of references (for exact matches)
+    signature_to_refs: HashMap<String, Vec<usize>>,
+
+    /// Index: sha512t24u signature -> indices of references (for exact matches)
+    sha512t24u_signature_to_refs: HashMap<String, Vec<usize>>,
 }
 
 impl ReferenceCatalog {
@@ -63,7 +66,8 @@ impl ReferenceCatalog {
             sha512t24u_to_refs: HashMap::new(),
             name_length_to_refs: HashMap::new(),
             alias_length_to_refs: HashMap::new(),
-            signature_to_ref: HashMap::new(),
+            signature_to_refs: HashMap::new(),
+            sha512t24u_signature_to_refs: HashMap::new(),
         }
     }
 
@@ -152,9 +156,18 @@ impl ReferenceCatalog {
             }
         }
 
-        // Index by signature
+        // Index by signatures
         if let Some(sig) = &reference.signature {
-            self.signature_to_ref.insert(sig.clone(), index);
+            self.signature_to_refs
+                .entry(sig.clone())
+                .or_default()
+                .push(index);
+        }
+        if let Some(sig) = &reference.sha512t24u_signature {
+            self.sha512t24u_signature_to_refs
+                .entry(sig.clone())
+                .or_default()
+                .push(index);
         }
 
         self.references.push(reference);
@@ -166,12 +179,29 @@ impl ReferenceCatalog {
         self.id_to_index.get(id).map(|&idx| &self.references[idx])
     }
 
-    /// Find exact match by signature
+    /// Get a reference's index in `references` by ID
+    #[must_use]
+    pub fn index_of(&self, id: &ReferenceId) -> Option<usize> {
+        self.id_to_index.get(id).copied()
+    }
+
+    /// Indices of all references with this signature, in catalog order
+    ///
+    /// Several references can share a signature, e.g. analysis sets that
+    /// differ only in contig names or masking.
+    #[must_use]
+    pub fn indices_by_signature(&self, signature: &str) -> &[usize] {
+        self.signature_to_refs
+            .get(signature)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Indices of all references with this sha512t24u signature, in catalog order
     #[must_use]
-    pub fn find_by_signature(&self, signature: &str) -> Option<&KnownReference> {
-        self.signature_to_ref
+    pub fn indices_by_sha512t24u_signature(&self, signature: &str) -> &[usize] {
+        self.sha512t24u_signature_to_refs
             .get(signature)
-            .map(|&idx| &self.references[idx])
+            .map_or(&[], Vec::as_slice)
     }
 
     /// Export catalog to JSON
@@ -0,0 +1,219 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::cli::OutputFormat;
+use crate::core::reference::KnownReference;
+use crate::core::types::{Confidence, ReferenceId};
+use crate::matching::annotation::{Annotation, SkippedContig};
+use crate::matching::engine::{MatchingConfig, MatchingEngine};
+use crate::parsing;
+
+#[derive(Args)]
+pub struct AnnotateHeaderArgs {
+    /// Input file (BAM, SAM, CRAM or .dict)
+    /// Use '-' for stdin (expects header text)
+    #[arg(required = true)]
+    pub input: PathBuf,
+
+    /// Output file for the annotated header (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// ID of the catalog reference to annotate from (default: the best match;
+    /// if references tie for it, only values they all share are annotated)
+    #[arg(long)]
+    pub reference: Option<String>,
+
+    /// Path to custom catalog file
+    #[arg(long)]
+    pub catalog: Option<PathBuf>,
+}
+
+/// Execute annotate-header subcommand
+///
+/// # Errors
+///
+/// Returns an error if the input or catalog cannot be read, the reference
+/// cannot be identified with confidence, or the header cannot be written.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: AnnotateHeaderArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let mut header = if args.input.to_string_lossy() == "-" {
+        let mut buffer = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut buffer)?;
+        parsing::sam::read_header_text(&buffer)?
+    } else if args
+        .input
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("dict"))
+    {
+        parsing::sam::read_header_text(&std::fs::read_to_string(&args.input)?)?
+    } else {
+        parsing::sam::read_header(&args.input)?
+    };
+    let query = parsing::sam::header_to_query(&header, None)?;
+    if query.contigs.is_empty() {
+        anyhow::bail!("No @SQ lines found in {}", args.input.display());
+    }
+
+    let catalog = if let Some(path) = &args.catalog {
+        ReferenceCatalog::load_from_file(path)?
+    } else {
+        ReferenceCatalog::load_embedded()?
+    };
+    let references = find_references(&args, &catalog, &query)?;
+    let reference = references[0];
+    let tied_with: Vec<&str> = references[1..].iter().map(|r| r.id.0.as_str()).collect();
+    if verbose {
+        eprintln!(
+            "Annotating from {} ({})",
+            reference.id, reference.display_name
+        );
+        if !tied_with.is_empty() {
+            eprintln!(
+                "Tied with {}; annotating only values they all share",
+                tied_with.join(", ")
+            );
+        }
+    }
+
+    let annotation = Annotation::annotate_consensus(&query, &references);
+    let mut tags_added = 0;
+    for ((_, map), contig) in header
+        .reference_sequences_mut()
+        .iter_mut()
+        .zip(&annotation.contigs)
+    {
+        tags_added += parsing::sam::fill_reference_sequence_tags(map, contig).len();
+    }
+
+    let mut buffer = Vec::new();
+    noodles::sam::io::Writer::new(&mut buffer).write_header(&header)?;
+    match &args.output {
+        Some(path) => std::fs::write(path, &buffer)?,
+        None => std::io::stdout().lock().write_all(&buffer)?,
+    }
+
+    // The header goes to stdout unless written to a file, so the report
+    // only goes there in the latter case
+    if args.output.is_some() {
+        match format {
+            OutputFormat::Text => {
+                print_text_report(reference, &tied_with, &annotation, tags_added, verbose);
+            }
+            OutputFormat::Json => {
+                let output = serde_json::json!({
+                    "input": args.input,
+                    "output": args.output,
+                    "reference": reference.id.0,
+                    "tied_with": tied_with,
+                    "contigs": annotation.contigs.len(),
+                    "annotated": annotation.annotated,
+                    "tags_added": tags_added,
+                    "skipped": annotation.skipped,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+            OutputFormat::Tsv => {
+                println!("reference\ttied_with\tcontigs\tannotated\ttags_added\tskipped");
+                println!(
+                    "{}\t{}\t{}\t{}\t{tags_added}\t{}",
+                    reference.id,
+                    tied_with.join(","),
+                    annotation.contigs.len(),
+                    annotation.annotated,
+                    annotation.skipped.len()
+                );
+            }
+        }
+    } else if verbose {
+        eprintln!(
+            "Annotated {}/{} contigs ({tags_added} tags added, {} skipped)",
+            annotation.annotated,
+            annotation.contigs.len(),
+            annotation.skipped.len()
+        );
+        print_skipped(&annotation.skipped, |line| eprintln!("{line}"));
+    }
+
+    Ok(())
+}
+
+/// The requested catalog reference, or the best match for the header if it
+/// is confident, followed by any references tied with it
+fn find_references<'a>(
+    args: &AnnotateHeaderArgs,
+    catalog: &'a ReferenceCatalog,
+    query: &crate::core::header::QueryHeader,
+) -> anyhow::Result<Vec<&'a KnownReference>> {
+    let get = |id: &ReferenceId| {
+        catalog
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Reference '{id}' not found in catalog"))
+    };
+    if let Some(id) = &args.reference {
+        return Ok(vec![get(&ReferenceId::new(id))?]);
+    }
+
+    let engine = MatchingEngine::new(catalog, MatchingConfig::default());
+    let mut matches = engine.find_matches(query, 1);
+    // Score enough candidates to see every reference tied with the best
+    if let Some(ties) = matches.first().map(|best| best.ambiguity.tied_with.len()) {
+        if ties > 0 {
+            matches = engine.find_matches(query, ties + 1);
+        }
+    }
+    let Some(best) = matches.into_iter().next() else {
+        anyhow::bail!("No catalog reference matches the header");
+    };
+    if best.score.confidence == Confidence::Low {
+        anyhow::bail!(
+            "Best match {} has low confidence ({:.1}%); use --reference to annotate from it anyway",
+            best.reference.id,
+            best.score.composite * 100.0
+        );
+    }
+    std::iter::once(&best.reference.id)
+        .chain(&best.ambiguity.tied_with)
+        .map(get)
+        .collect()
+}
+
+fn print_text_report(
+    reference: &KnownReference,
+    tied_with: &[&str],
+    annotation: &Annotation,
+    tags_added: usize,
+    verbose: bool,
+) {
+    println!(
+        "Annotated from: {} ({})",
+        reference.id, reference.display_name
+    );
+    if !tied_with.is_empty() {
+        println!(
+            "   Tied with: {} (only values they all share are added)",
+            tied_with.join(", ")
+        );
+    }
+    println!(
+        "   Contigs annotated: {}/{}",
+        annotation.annotated,
+        annotation.contigs.len()
+    );
+    println!("   Tags added: {tags_added}");
+    if !annotation.skipped.is_empty() {
+        println!("   Not annotated: {}", annotation.skipped.len());
+        if verbose {
+            print_skipped(&annotation.skipped, |line| println!("{line}"));
+        }
+    }
+}
+
+fn print_skipped(skipped: &[SkippedContig], print: impl Fn(String)) {
+    for contig in skipped {
+        print(format!("      {}: {}", contig.name, contig.reason));
+    }
+}
@@ -0,0 +1,183 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::cli::identify::{self, InputFormat};
+use crate::cli::{DigestCacheArgs, OutputFormat};
+use crate::core::header::QueryHeader;
+use crate::matching::engine::{MatchResult, MatchingConfig, MatchingEngine};
+use crate::matching::policy::{Policy, Violation};
+use crate::parsing;
+
+/// Exit status when the input violates the policy, as opposed to 1 for errors
+pub const VIOLATION_EXIT_CODE: i32 = 2;
+
+#[derive(Args)]
+pub struct AssertArgs {
+    /// Input file (BAM, SAM, CRAM, VCF, .dict, FASTA, interval list, or TSV).
+    /// Use '-' for stdin.
+    #[arg(required = true)]
+    pub input: PathBuf,
+
+    /// Reference ID, assembly (e.g. GRCh38) or catalog tag the input must
+    /// match. May be repeated; replaces the policy's `allowed_references`.
+    #[arg(
+        long,
+        value_name = "ID|ASSEMBLY|TAG",
+        required_unless_present = "policy"
+    )]
+    pub expect: Vec<String>,
+
+    /// Policy file (JSON) with the rules the input must satisfy
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+
+    /// Path to custom catalog file
+    #[arg(long)]
+    pub catalog: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub digest_cache: DigestCacheArgs,
+}
+
+/// Execute assert subcommand
+///
+/// Exits with status 2 if the input violates the policy.
+///
+/// # Errors
+///
+/// Returns an error if the input, policy or catalog cannot be read.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: AssertArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let mut policy = match &args.policy {
+        Some(path) => Policy::load_from_file(path)?,
+        None => Policy::default(),
+    };
+    if !args.expect.is_empty() {
+        policy.allowed_references.clone_from(&args.expect);
+    }
+
+    let query = parse_input(&args)?;
+    if verbose {
+        eprintln!(
+            "Query: {} contigs ({:.0}% have MD5)",
+            query.contigs.len(),
+            query.md5_coverage() * 100.0,
+        );
+    }
+
+    let catalog = if let Some(path) = &args.catalog {
+        ReferenceCatalog::load_from_file(path)?
+    } else {
+        ReferenceCatalog::load_embedded()?
+    };
+    let engine = MatchingEngine::new(&catalog, MatchingConfig::default());
+    let mut matches = engine.find_matches(&query, 1);
+    // Score every reference tied with the top match, so that the policy can
+    // accept an allowed one among them
+    let ties = matches.first().map_or(0, |m| m.ambiguity.tied_with.len());
+    if ties > 0 {
+        matches = engine.find_matches(&query, ties + 1);
+    }
+    let best = policy.select(&matches);
+    let violations = policy.evaluate(&matches, &catalog);
+
+    match format {
+        OutputFormat::Text => print_text_report(&args, best, &violations),
+        OutputFormat::Json => print_json_report(&args, &policy, best, &violations)?,
+        OutputFormat::Tsv => print_tsv_report(&violations),
+    }
+
+    if !violations.is_empty() {
+        eprintln!(
+            "Assertion failed: {} violates the policy ({} violations)",
+            args.input.display(),
+            violations.len()
+        );
+        std::io::stdout().flush()?;
+        std::process::exit(VIOLATION_EXIT_CODE);
+    }
+    Ok(())
+}
+
+fn parse_input(args: &AssertArgs) -> anyhow::Result<QueryHeader> {
+    use std::io::Read;
+
+    if args.input.to_string_lossy() == "-" {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        return Ok(parsing::sam::parse_header_text(&buffer)?);
+    }
+
+    let format = identify::detect_format(&args.input);
+    let cache = match format {
+        InputFormat::Fasta => args.digest_cache.open()?,
+        _ => None,
+    };
+    identify::parse_file(&args.input, format, cache.as_deref())
+}
+
+fn print_text_report(args: &AssertArgs, best: Option<&MatchResult>, violations: &[Violation]) {
+    if violations.is_empty() {
+        println!("PASS: {}", args.input.display());
+    } else {
+        println!(
+            "FAIL: {} ({} violations)",
+            args.input.display(),
+            violations.len()
+        );
+    }
+    if let Some(best) = best {
+        println!(
+            "   Match: {} ({}), {:?}, {:?} confidence ({:.1}%)",
+            best.reference.id,
+            best.reference.display_name,
+            best.diagnosis.match_type,
+            best.score.confidence,
+            best.score.composite * 100.0
+        );
+    }
+    for violation in violations {
+        println!("   - {}: {}", violation.rule, violation.message);
+    }
+}
+
+fn print_json_report(
+    args: &AssertArgs,
+    policy: &Policy,
+    best: Option<&MatchResult>,
+    violations: &[Violation],
+) -> anyhow::Result<()> {
+    let matched = best.map(|best| {
+        serde_json::json!({
+            "reference": {
+                "id": best.reference.id.0,
+                "display_name": best.reference.display_name,
+                "assembly": format!("{}", best.reference.assembly),
+            },
+            "match_type": format!("{:?}", best.diagnosis.match_type),
+            "confidence": format!("{:?}", best.score.confidence),
+            "score": best.score.composite,
+            "ambiguity": best.ambiguity,
+            "tiers": best.tiers,
+        })
+    });
+    let output = serde_json::json!({
+        "input": args.input.display().to_string(),
+        "passed": violations.is_empty(),
+        "match": matched,
+        "policy": policy,
+        "violations": violations,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_tsv_report(violations: &[Violation]) {
+    println!("rule\tmessage");
+    for violation in violations {
+        println!("{}\t{}", violation.rule, violation.message);
+    }
+}
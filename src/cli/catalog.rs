@@ -5,7 +5,7 @@ use clap::{Args, Subcommand};
 use crate::catalog::builder::{InputFormat, ReferenceBuilder};
 use crate::catalog::hierarchical::HierarchicalCatalog;
 use crate::catalog::store::ReferenceCatalog;
-use crate::cli::OutputFormat;
+use crate::cli::{DigestCacheArgs, OutputFormat};
 use crate::core::types::{Assembly, ReferenceSource};
 
 /// Helper function to convert usize count to f64 with explicit precision loss allowance
@@ -143,6 +143,9 @@ pub enum CatalogCommands {
         /// See: <https://genome.ucsc.edu/FAQ/FAQdownloads.html>
         #[arg(long)]
         no_generate_ucsc_names: bool,
+
+        #[command(flatten)]
+        digest_cache: DigestCacheArgs,
     },
 
     /// Build a new reference entry from input files
@@ -220,6 +223,9 @@ pub enum CatalogCommands {
         /// See: <https://genome.ucsc.edu/FAQ/FAQdownloads.html>
         #[arg(long)]
         no_generate_ucsc_names: bool,
+
+        #[command(flatten)]
+        digest_cache: DigestCacheArgs,
     },
 }
 
@@ -295,6 +301,7 @@ pub fn run(args: CatalogArgs, format: OutputFormat, verbose: bool) -> anyhow::Re
             require_md5,
             infer_assembly,
             no_generate_ucsc_names,
+            digest_cache,
         } => run_build_hierarchical(
             id,
             name,
@@ -310,6 +317,7 @@ pub fn run(args: CatalogArgs, format: OutputFormat, verbose: bool) -> anyhow::Re
             require_md5,
             infer_assembly,
             !no_generate_ucsc_names, // Convert opt-out flag to opt-in parameter
+            &digest_cache,
             format,
             verbose,
         ),
@@ -330,6 +338,7 @@ pub fn run(args: CatalogArgs, format: OutputFormat, verbose: bool) -> anyhow::Re
             input_format,
             require_md5,
             no_generate_ucsc_names,
+            digest_cache,
         } => run_build(
             id,
             name,
@@ -347,6 +356,7 @@ pub fn run(args: CatalogArgs, format: OutputFormat, verbose: bool) -> anyhow::Re
             input_format,
             require_md5,
             !no_generate_ucsc_names, // Convert opt-out flag to opt-in parameter
+            &digest_cache,
             format,
             verbose,
         ),
@@ -824,6 +834,7 @@ fn run_build_hierarchical(
     require_md5: bool,
     infer_assembly: Option<Option<PathBuf>>,
     generate_ucsc_names: bool,
+    digest_cache: &DigestCacheArgs,
     format: OutputFormat,
     verbose: bool,
 ) -> anyhow::Result<()> {
@@ -844,6 +855,9 @@ fn run_build_hierarchical(
         .with_display_name(&name)
         .with_source(ref_source)
         .with_generate_ucsc_names(generate_ucsc_names);
+    if let Some(cache) = digest_cache.open()? {
+        builder = builder.with_digest_cache(cache);
+    }
 
     if let Some(url) = download_url {
         builder = builder.with_download_url(url);
@@ -1113,6 +1127,7 @@ fn run_build(
     input_format: Option<InputFormatArg>,
     require_md5: bool,
     generate_ucsc_names: bool,
+    digest_cache: &DigestCacheArgs,
     format: OutputFormat,
     verbose: bool,
 ) -> anyhow::Result<()> {
@@ -1129,6 +1144,9 @@ fn run_build(
 
     // Create builder with UCSC name generation option
     let mut builder = ReferenceBuilder::new(&id, &name).generate_ucsc_names(generate_ucsc_names);
+    if let Some(cache) = digest_cache.open()? {
+        builder = builder.digest_cache(cache);
+    }
 
     if let Some(assembly) = assembly {
         builder = builder.assembly(assembly);
@@ -0,0 +1,219 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::Args;
+
+use crate::cli::identify::{self, InputFormat};
+use crate::cli::{DigestCacheArgs, OutputFormat};
+use crate::matching::compat::{CompatFile, CompatReport, FileContigs, FileRole};
+use crate::parsing;
+use crate::parsing::digest_cache::DigestCache;
+use crate::parsing::features::FeatureFormat;
+
+#[derive(Args)]
+pub struct CheckCompatArgs {
+    /// Files to check: BAM, SAM, CRAM, VCF, .dict, FASTA, FAI, interval list,
+    /// BED or GTF/GFF
+    #[arg(required = true, num_args = 2..)]
+    pub inputs: Vec<PathBuf>,
+
+    #[command(flatten)]
+    pub digest_cache: DigestCacheArgs,
+}
+
+/// Execute check-compat subcommand
+///
+/// # Errors
+///
+/// Returns an error if any input cannot be read, or if any two inputs are not
+/// compatible.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: CheckCompatArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let needs_cache = args
+        .inputs
+        .iter()
+        .any(|path| matches!(identify::detect_format(path), InputFormat::Fasta));
+    let cache = if needs_cache {
+        args.digest_cache.open()?
+    } else {
+        None
+    };
+
+    let files = args
+        .inputs
+        .iter()
+        .map(|path| {
+            read_input(path, cache.as_deref())
+                .with_context(|| format!("Failed to read {}", path.display()))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if verbose {
+        for file in &files {
+            eprintln!("{}: {} contigs", file.source, file.contig_count());
+        }
+    }
+
+    let report = CompatReport::check(&files);
+
+    match format {
+        OutputFormat::Text => print_text_report(&files, &report),
+        OutputFormat::Json => print_json_report(&files, &report)?,
+        OutputFormat::Tsv => print_tsv_report(&files, &report),
+    }
+
+    if !report.is_compatible() {
+        anyhow::bail!(
+            "{} of {} pairs of files are not compatible",
+            report.incompatible().count(),
+            report.pairs.len()
+        );
+    }
+    Ok(())
+}
+
+/// Read a feature file's contigs, or any other file's sequence dictionary.
+/// FASTA, FAI and `.dict` files are references; everything else is data.
+fn read_input(path: &Path, cache: Option<&DigestCache>) -> anyhow::Result<CompatFile> {
+    if let Some(format) = FeatureFormat::from_path(path) {
+        let features = parsing::features::parse_feature_file(path, format)?;
+        return Ok(CompatFile::new(
+            path.display().to_string(),
+            FileContigs::Features(features),
+        ));
+    }
+
+    let format = identify::detect_format(path);
+    let role = match format {
+        InputFormat::Fasta | InputFormat::Fai | InputFormat::Dict => FileRole::Reference,
+        _ => FileRole::Data,
+    };
+    let header = identify::parse_file(path, format, cache)?;
+    Ok(
+        CompatFile::new(path.display().to_string(), FileContigs::Dictionary(header))
+            .with_role(role),
+    )
+}
+
+fn kind(file: &CompatFile) -> &'static str {
+    if file.has_dictionary() {
+        "dictionary"
+    } else {
+        "features"
+    }
+}
+
+fn print_text_report(files: &[CompatFile], report: &CompatReport) {
+    println!("Files:");
+    for (i, file) in files.iter().enumerate() {
+        println!(
+            "   [{}] {} ({} {}, {} contigs)",
+            i + 1,
+            file.source,
+            file.role,
+            kind(file),
+            file.contig_count()
+        );
+    }
+
+    let labels: Vec<String> = (1..=files.len()).map(|i| format!("[{i}]")).collect();
+    let width = labels
+        .iter()
+        .map(String::len)
+        .chain(["conflicting".len()])
+        .max()
+        .unwrap_or_default();
+    println!("\nPairwise verdicts (row vs column):");
+    print!("   {:width$}", "");
+    for label in &labels {
+        print!("  {label:width$}");
+    }
+    println!();
+    for (label, row) in labels.iter().zip(report.matrix()) {
+        print!("   {label:width$}");
+        for verdict in row {
+            let cell = verdict.map_or_else(|| "-".to_string(), |v| v.to_string());
+            print!("  {cell:width$}");
+        }
+        println!();
+    }
+
+    let incompatible: Vec<_> = report.incompatible().collect();
+    if !incompatible.is_empty() {
+        println!("\nIncompatible pairs:");
+        for pair in &incompatible {
+            println!(
+                "   [{}] {} vs [{}] {}: {}",
+                pair.first + 1,
+                files[pair.first].source,
+                pair.second + 1,
+                files[pair.second].source,
+                pair.verdict
+            );
+            for detail in &pair.details {
+                println!("      - {detail}");
+            }
+        }
+    }
+
+    println!(
+        "\n{}",
+        if incompatible.is_empty() {
+            "PASS: all files are compatible".to_string()
+        } else {
+            format!(
+                "FAIL: {} of {} pairs of files are not compatible",
+                incompatible.len(),
+                report.pairs.len()
+            )
+        }
+    );
+}
+
+fn print_json_report(files: &[CompatFile], report: &CompatReport) -> anyhow::Result<()> {
+    let files: Vec<_> = files
+        .iter()
+        .map(|file| {
+            serde_json::json!({
+                "path": file.source,
+                "kind": kind(file),
+                "role": file.role,
+                "contigs": file.contig_count(),
+            })
+        })
+        .collect();
+    let pairs: Vec<_> = report
+        .pairs
+        .iter()
+        .map(|pair| {
+            serde_json::json!({
+                "first": pair.first,
+                "second": pair.second,
+                "verdict": pair.verdict,
+                "compatible": pair.compatible,
+                "details": pair.details,
+            })
+        })
+        .collect();
+    let output = serde_json::json!({
+        "files": files,
+        "pairs": pairs,
+        "matrix": report.matrix(),
+        "compatible": report.is_compatible(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_tsv_report(files: &[CompatFile], report: &CompatReport) {
+    println!("first\tsecond\tverdict\tcompatible\tdetails");
+    for pair in &report.pairs {
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            files[pair.first].source,
+            files[pair.second].source,
+            pair.verdict,
+            pair.compatible,
+            pair.details.join("; ")
+        );
+    }
+}
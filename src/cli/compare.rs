@@ -9,6 +9,7 @@ use crate::core::reference::KnownReference;
 use crate::core::types::{Assembly, ReferenceSource};
 use crate::matching::scoring::MatchScore;
 use crate::parsing;
+use crate::seqcol::{SeqCol, SeqColComparison};
 
 #[derive(Args)]
 pub struct CompareArgs {
@@ -152,6 +153,18 @@ fn print_text_comparison(
     println!("  Order Score: {:.2}%", score.order_score * 100.0);
     println!("  Composite Score: {:.2}%", score.composite * 100.0);
     println!("  Confidence: {:?}", score.confidence);
+
+    let seqcol = SeqColComparison::compare(&SeqCol::from(query_a), &SeqCol::from(query_b));
+    println!("\nSequence Collections:");
+    println!(
+        "  Digest A: {}",
+        seqcol.digests.a.as_deref().unwrap_or("unavailable")
+    );
+    println!(
+        "  Digest B: {}",
+        seqcol.digests.b.as_deref().unwrap_or("unavailable")
+    );
+    println!("  Identical: {}", seqcol.is_identical());
 }
 
 fn print_json_comparison(
@@ -182,7 +195,8 @@ fn print_json_comparison(
             "order_score": score.order_score,
             "composite": score.composite,
             "confidence": format!("{:?}", score.confidence),
-        }
+        },
+        "seqcol": SeqColComparison::compare(&SeqCol::from(query_a), &SeqCol::from(query_b)),
     });
 
     println!("{}", serde_json::to_string_pretty(&output)?);
@@ -0,0 +1,321 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use flate2::read::MultiGzDecoder;
+use noodles::bgzf;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::cli::OutputFormat;
+use crate::core::naming::{NameMap, NameStyle};
+use crate::core::reference::KnownReference;
+use crate::core::types::{Confidence, ReferenceId};
+use crate::matching::engine::{MatchingConfig, MatchingEngine};
+use crate::parsing;
+use crate::parsing::rename::{RenameFormat, RenameReport};
+
+#[derive(Args)]
+pub struct ConvertNamesArgs {
+    /// Input file (VCF, BED, GTF/GFF or interval list, optionally gzipped)
+    /// Use '-' for stdin (requires --input-type)
+    #[arg(required = true)]
+    pub input: PathBuf,
+
+    /// Rename contigs to their names in this catalog reference
+    #[arg(
+        long,
+        value_name = "REFERENCE_ID",
+        conflicts_with = "to",
+        required_unless_present = "to"
+    )]
+    pub to_reference: Option<String>,
+
+    /// Rename contigs to this naming convention
+    #[arg(long, value_name = "STYLE")]
+    pub to: Option<NameStyle>,
+
+    /// Catalog reference whose aliases define the names for --to (default:
+    /// identified from the ##contig lines of a VCF)
+    #[arg(long, requires = "to", conflicts_with = "ncbi_report")]
+    pub reference: Option<String>,
+
+    /// NCBI assembly report whose columns define the names for --to
+    #[arg(long, requires = "to")]
+    pub ncbi_report: Option<PathBuf>,
+
+    /// Input format (default: from the file extension)
+    #[arg(long, value_name = "FORMAT")]
+    pub input_type: Option<RenameFormat>,
+
+    /// Output file (default: stdout); compressed with BGZF if it ends in .gz
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Remove lines naming contigs that have no name in the target convention
+    /// (default: keep them unchanged)
+    #[arg(long)]
+    pub drop_unmapped: bool,
+
+    /// Path to custom catalog file
+    #[arg(long)]
+    pub catalog: Option<PathBuf>,
+}
+
+/// Execute convert-names subcommand
+///
+/// # Errors
+///
+/// Returns an error if the input, catalog or NCBI report cannot be read, the
+/// reference cannot be determined, or the output cannot be written.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: ConvertNamesArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let stdin = args.input.to_string_lossy() == "-";
+    let input_format = match args.input_type {
+        Some(input_format) => input_format,
+        None if stdin => anyhow::bail!("--input-type is required when reading from stdin"),
+        None => RenameFormat::from_path(&args.input).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cannot tell the format of {}; use --input-type",
+                args.input.display()
+            )
+        })?,
+    };
+    if let Some(output) = &args.output {
+        if !stdin
+            && output.exists()
+            && std::fs::canonicalize(output)? == std::fs::canonicalize(&args.input)?
+        {
+            anyhow::bail!("Output must differ from the input");
+        }
+    }
+
+    let (names, target) = build_name_map(&args, input_format, stdin)?;
+    if verbose {
+        eprintln!("Renaming contigs to {target}");
+    }
+
+    let reader: Box<dyn BufRead> = if stdin {
+        Box::new(io::stdin().lock())
+    } else {
+        open_input(&args.input)?
+    };
+    let report = match &args.output {
+        Some(path) if is_gzipped(path) => {
+            let mut writer = bgzf::Writer::new(File::create(path)?);
+            let report = parsing::rename::rename_contigs(
+                reader,
+                &mut writer,
+                input_format,
+                &names,
+                args.drop_unmapped,
+            )?;
+            writer.finish()?;
+            report
+        }
+        Some(path) => parsing::rename::rename_contigs(
+            reader,
+            BufWriter::new(File::create(path)?),
+            input_format,
+            &names,
+            args.drop_unmapped,
+        )?,
+        None => parsing::rename::rename_contigs(
+            reader,
+            io::stdout().lock(),
+            input_format,
+            &names,
+            args.drop_unmapped,
+        )?,
+    };
+
+    if !report.unmapped.is_empty() {
+        tracing::warn!(
+            "{} contigs have no name in {target}{}: {}",
+            report.unmapped.len(),
+            if args.drop_unmapped {
+                " and were dropped"
+            } else {
+                " and were left unchanged"
+            },
+            report
+                .unmapped
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    if report.unmapped_mates > 0 {
+        tracing::warn!(
+            "{} records name a mate contig (breakend ALT or CHR2) with no name in {target}; \
+             those mates were left unchanged",
+            report.unmapped_mates
+        );
+    }
+
+    // The converted file goes to stdout unless written to a file, so the
+    // report only goes there in the latter case
+    if args.output.is_some() {
+        print_report(&args, &target, &report, format)?;
+    } else if verbose {
+        eprintln!(
+            "Renamed {} of {} records ({} dropped)",
+            report.renamed, report.records, report.dropped
+        );
+    }
+
+    Ok(())
+}
+
+/// The name map for the requested target, and a description of the target
+fn build_name_map(
+    args: &ConvertNamesArgs,
+    input_format: RenameFormat,
+    stdin: bool,
+) -> anyhow::Result<(NameMap, String)> {
+    if let (Some(style), Some(path)) = (args.to, &args.ncbi_report) {
+        let entries =
+            parsing::ncbi_report::parse_ncbi_report_text(&std::fs::read_to_string(path)?)?;
+        return Ok((
+            NameMap::from_ncbi_report(&entries, style),
+            format!("{style} names"),
+        ));
+    }
+
+    let catalog = if let Some(path) = &args.catalog {
+        ReferenceCatalog::load_from_file(path)?
+    } else {
+        ReferenceCatalog::load_embedded()?
+    };
+
+    if let Some(id) = &args.to_reference {
+        let reference = find_in_catalog(&catalog, id)?;
+        return Ok((NameMap::to_reference(reference), reference.id.to_string()));
+    }
+
+    let style = args
+        .to
+        .ok_or_else(|| anyhow::anyhow!("Either --to-reference or --to is required"))?;
+    let reference = match &args.reference {
+        Some(id) => find_in_catalog(&catalog, id)?,
+        None if input_format == RenameFormat::Vcf && !stdin => {
+            identify_vcf_reference(&catalog, &args.input)?
+        }
+        None => anyhow::bail!(
+            "--to needs --reference or --ncbi-report to know the assembly of the contigs"
+        ),
+    };
+    Ok((
+        NameMap::to_style(reference, style),
+        format!("{style} names of {}", reference.id),
+    ))
+}
+
+fn find_in_catalog<'a>(
+    catalog: &'a ReferenceCatalog,
+    id: &str,
+) -> anyhow::Result<&'a KnownReference> {
+    catalog
+        .get(&ReferenceId::new(id))
+        .ok_or_else(|| anyhow::anyhow!("Reference '{id}' not found in catalog"))
+}
+
+/// The best catalog match for the `##contig` lines of a VCF. Accessions
+/// differ between builds, so a low-confidence match is refused.
+fn identify_vcf_reference<'a>(
+    catalog: &'a ReferenceCatalog,
+    path: &Path,
+) -> anyhow::Result<&'a KnownReference> {
+    let mut header = String::new();
+    for line in open_input(path)?.lines() {
+        let line = line?;
+        if !line.starts_with('#') {
+            break;
+        }
+        header.push_str(&line);
+        header.push('\n');
+    }
+    let query = parsing::vcf::parse_vcf_header_text(&header)
+        .map_err(|e| anyhow::anyhow!("Cannot identify the reference of {}: {e}", path.display()))?;
+
+    let engine = MatchingEngine::new(catalog, MatchingConfig::default());
+    let Some(best) = engine.find_matches(&query, 1).into_iter().next() else {
+        anyhow::bail!("No catalog reference matches the VCF's contigs; use --reference");
+    };
+    if best.score.confidence == Confidence::Low {
+        anyhow::bail!(
+            "Best match {} has low confidence ({:.1}%); use --reference",
+            best.reference.id,
+            best.score.composite * 100.0
+        );
+    }
+    tracing::info!("Using names from {}", best.reference.id);
+    catalog
+        .get(&best.reference.id)
+        .ok_or_else(|| anyhow::anyhow!("Reference '{}' not found in catalog", best.reference.id))
+}
+
+fn open_input(path: &Path) -> anyhow::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    if is_gzipped(path) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+fn is_gzipped(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz") || ext.eq_ignore_ascii_case("bgz"))
+}
+
+fn print_report(
+    args: &ConvertNamesArgs,
+    target: &str,
+    report: &RenameReport,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Text => {
+            println!(
+                "Wrote {} with {target}",
+                args.output.as_deref().unwrap_or(Path::new("-")).display()
+            );
+            println!("   Records: {}", report.records);
+            println!("   Renamed: {}", report.renamed);
+            if !report.unmapped.is_empty() {
+                println!("   Unmapped contigs: {}", report.unmapped.len());
+                for (name, count) in &report.unmapped {
+                    println!("      {name}: {count} records");
+                }
+                if args.drop_unmapped {
+                    println!("   Dropped: {}", report.dropped);
+                }
+            }
+            if report.unmapped_mates > 0 {
+                println!("   Records with unmapped mates: {}", report.unmapped_mates);
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "input": args.input,
+                "output": args.output,
+                "target": target,
+                "records": report.records,
+                "renamed": report.renamed,
+                "dropped": report.dropped,
+                "unmapped": report.unmapped,
+                "unmapped_mates": report.unmapped_mates,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Tsv => {
+            println!("contig\trecords");
+            for (name, count) in &report.unmapped {
+                println!("{name}\t{count}");
+            }
+        }
+    }
+    Ok(())
+}
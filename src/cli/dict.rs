@@ -0,0 +1,211 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::cli::{DigestCacheArgs, OutputFormat};
+use crate::core::reference::KnownReference;
+use crate::core::types::ReferenceId;
+use crate::matching::annotation::Annotation;
+use crate::matching::engine::{MatchingConfig, MatchingEngine};
+use crate::parsing;
+
+/// FASTA extensions replaced by `.dict` when deriving the output path
+const FASTA_SUFFIXES: [&str; 9] = [
+    ".fa.gz",
+    ".fasta.gz",
+    ".fna.gz",
+    ".fa.bgz",
+    ".fasta.bgz",
+    ".fna.bgz",
+    ".fa",
+    ".fasta",
+    ".fna",
+];
+
+#[derive(Args)]
+pub struct DictArgs {
+    /// FASTA file to create a dictionary for (uncompressed, gzip or BGZF)
+    #[arg(required = true)]
+    pub fasta: PathBuf,
+
+    /// Output .dict file (default: the FASTA path with its extension replaced by .dict)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite the output file if it exists
+    #[arg(long)]
+    pub force: bool,
+
+    /// URI for the UR tags (default: file: URI of the absolute FASTA path)
+    #[arg(long)]
+    pub uri: Option<String>,
+
+    /// ID of the catalog reference to take AS, SP and AN tags from
+    /// (default: the best match for the FASTA)
+    #[arg(long)]
+    pub reference: Option<String>,
+
+    /// Only write SN, LN, M5 and UR, like Picard's CreateSequenceDictionary
+    #[arg(long)]
+    pub no_catalog: bool,
+
+    /// Path to custom catalog file
+    #[arg(long)]
+    pub catalog: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub digest_cache: DigestCacheArgs,
+}
+
+/// Execute dict subcommand
+///
+/// # Errors
+///
+/// Returns an error if the FASTA or catalog cannot be read, the requested
+/// reference is not in the catalog, or the dictionary cannot be written.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: DictArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let output = match &args.output {
+        Some(path) => path.clone(),
+        None => default_output(&args.fasta),
+    };
+    if output.exists() && !args.force {
+        anyhow::bail!(
+            "{} already exists (use --force to overwrite)",
+            output.display()
+        );
+    }
+
+    let cache = args.digest_cache.open()?;
+    let fasta = parsing::fasta::parse_fasta_file_with_md5_cached(&args.fasta, cache.as_deref())?;
+    if verbose {
+        eprintln!(
+            "Hashed {} contigs from {}",
+            fasta.contigs.len(),
+            args.fasta.display()
+        );
+    }
+
+    let catalog = if args.no_catalog {
+        None
+    } else if let Some(path) = &args.catalog {
+        Some(ReferenceCatalog::load_from_file(path)?)
+    } else {
+        Some(ReferenceCatalog::load_embedded()?)
+    };
+    let reference = match &catalog {
+        Some(catalog) => find_reference(&args, catalog, &fasta)?,
+        None => None,
+    };
+
+    let annotation = reference.map(|reference| Annotation::annotate(&fasta, reference));
+    let mut contigs = annotation
+        .as_ref()
+        .map_or_else(|| fasta.contigs.clone(), |a| a.contigs.clone());
+    let uri = match &args.uri {
+        Some(uri) => uri.clone(),
+        None => format!("file:{}", std::fs::canonicalize(&args.fasta)?.display()),
+    };
+    for contig in &mut contigs {
+        // The dictionary describes the FASTA; sha512t24u has no SAM tag
+        contig.uri = Some(uri.clone());
+        contig.sha512t24u = None;
+    }
+
+    let file = std::fs::File::create(&output)?;
+    parsing::dict::write_dict(std::io::BufWriter::new(file), &contigs)?;
+
+    match format {
+        OutputFormat::Text => {
+            println!("Wrote {} ({} contigs)", output.display(), contigs.len());
+            match (reference, &annotation) {
+                (Some(reference), Some(annotation)) => {
+                    println!(
+                        "   Annotated from: {} ({})",
+                        reference.id, reference.display_name
+                    );
+                    println!(
+                        "   Contigs annotated: {}/{}",
+                        annotation.annotated,
+                        contigs.len()
+                    );
+                    if !annotation.skipped.is_empty() {
+                        println!("   Not annotated: {}", annotation.skipped.len());
+                        if verbose {
+                            for skipped in &annotation.skipped {
+                                println!("      {}: {}", skipped.name, skipped.reason);
+                            }
+                        }
+                    }
+                }
+                _ => println!("   No catalog reference matched; wrote SN, LN, M5 and UR only"),
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "fasta": args.fasta,
+                "output": output,
+                "contigs": contigs.len(),
+                "reference": reference.map(|r| &r.id.0),
+                "annotated": annotation.as_ref().map_or(0, |a| a.annotated),
+                "skipped": annotation.as_ref().map(|a| &a.skipped),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Tsv => {
+            println!("output\tcontigs\treference\tannotated\tskipped");
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                output.display(),
+                contigs.len(),
+                reference.map_or("", |r| r.id.0.as_str()),
+                annotation.as_ref().map_or(0, |a| a.annotated),
+                annotation.as_ref().map_or(0, |a| a.skipped.len()),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The requested catalog reference, or the best match for the FASTA
+fn find_reference<'a>(
+    args: &DictArgs,
+    catalog: &'a ReferenceCatalog,
+    fasta: &crate::core::header::QueryHeader,
+) -> anyhow::Result<Option<&'a KnownReference>> {
+    if let Some(id) = &args.reference {
+        return catalog
+            .get(&ReferenceId::new(id))
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("Reference '{id}' not found in catalog"));
+    }
+
+    let engine = MatchingEngine::new(catalog, MatchingConfig::default());
+    let Some(best) = engine.find_matches(fasta, 1).into_iter().next() else {
+        return Ok(None);
+    };
+    if best.ambiguity.ambiguous {
+        tracing::warn!(
+            "Best match {} is tied with {} other references; use --reference to choose one",
+            best.reference.id,
+            best.ambiguity.tied_with.len()
+        );
+    }
+    Ok(catalog.get(&best.reference.id))
+}
+
+/// The FASTA path with its extension replaced by `.dict`, as Picard does
+fn default_output(fasta: &Path) -> PathBuf {
+    let name = fasta
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let lower = name.to_lowercase();
+    let stem = FASTA_SUFFIXES
+        .iter()
+        .find(|suffix| lower.ends_with(*suffix))
+        .map_or(name.as_str(), |suffix| &name[..name.len() - suffix.len()]);
+    fasta.with_file_name(format!("{stem}.dict"))
+}
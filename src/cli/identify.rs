@@ -1,17 +1,33 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use anyhow::Context;
 use clap::Args;
 
 use crate::catalog::hierarchical::HierarchicalCatalog;
 use crate::catalog::store::ReferenceCatalog;
-use crate::cli::OutputFormat;
+use crate::cli::refget::RefgetCacheArgs;
+use crate::cli::{identify_batch, identify_joint};
+use crate::cli::{DigestCacheArgs, OutputFormat};
+use crate::core::contig::Contig;
 use crate::core::header::QueryHeader;
-use crate::core::types::Confidence;
-use crate::matching::engine::{MatchResult, MatchingConfig, MatchingEngine, ScoringWeights};
+use crate::core::types::{Confidence, ReferenceId};
+use crate::matching::decomposition::Decomposition;
+use crate::matching::disambiguation::DiscriminatorEvidence;
+use crate::matching::engine::{
+    MatchResult, MatchingConfig, MatchingEngine, ScoringMode, ScoringWeights,
+    DEFAULT_AMBIGUITY_TOLERANCE,
+};
+use crate::matching::fix_plan::{FixInput, FixPlan, FixPlanArtifacts};
 use crate::matching::hierarchical_engine::{HierarchicalMatchResult, HierarchicalMatchingEngine};
+use crate::matching::tiers::{AssemblyTier, TierVerdict};
 use crate::matching::Suggestion;
 use crate::parsing;
+use crate::parsing::digest_cache::DigestCache;
+use crate::parsing::fai::FaiEntry;
 use crate::refget::{EnrichedContig, RefgetConfig, RefgetLookupResult};
+use crate::seqcol::{SeqCol, SeqColComparison};
 
 /// How to handle references that have contigs missing from their FASTA
 /// (e.g., CHM13 where MT is in assembly report but uses standard rCRS mitochondria)
@@ -28,10 +44,33 @@ pub enum MissingContigHandling {
 
 #[derive(Args)]
 pub struct IdentifyArgs {
-    /// Input file (BAM, SAM, CRAM, FASTA, FAI, VCF, .dict, TSV, or CSV)
-    /// Use '-' for stdin (expects header text)
-    #[arg(required = true)]
-    pub input: PathBuf,
+    /// Input files (BAM, SAM, CRAM, FASTA, FAI, VCF, .dict, interval list, TSV,
+    /// or CSV), directories or glob patterns. Use '-' for stdin (expects header text).
+    /// More than one file is identified in batch, one summary row per file.
+    #[arg(required = true, num_args = 1..)]
+    pub inputs: Vec<PathBuf>,
+
+    /// Scan directories recursively for BAM, SAM, CRAM, VCF and .dict files
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// Number of files to identify in parallel in batch mode
+    /// (default: number of CPUs)
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub threads: Option<u16>,
+
+    /// Write one JSON object per file per line, as in batch mode, instead of
+    /// the --format output
+    #[arg(long)]
+    pub jsonl: bool,
+
+    /// Treat the inputs as files of one dataset (e.g. a BAM, its VCF and a
+    /// .dict) and identify a single reference from their combined evidence
+    #[arg(
+        long,
+        conflicts_with_all = ["recursive", "jsonl", "input_format", "hierarchical", "fasta", "emit_fix_plan", "refget_server"]
+    )]
+    pub joint: bool,
 
     /// Input format (auto-detected by default)
     #[arg(long)]
@@ -74,11 +113,64 @@ pub struct IdentifyArgs {
     #[arg(long, default_value = "10", value_parser = clap::value_parser!(u32).range(0..=100))]
     pub weight_order: u32,
 
+    /// Whether match and coverage scores count contigs or weight them by length.
+    /// With length-weighted, primary chromosomes dominate over small decoys.
+    #[arg(long, value_enum, default_value = "count")]
+    pub scoring_mode: ScoringMode,
+
+    /// Score difference (0-1) within which candidates are reported as tied
+    #[arg(long, default_value_t = DEFAULT_AMBIGUITY_TOLERANCE)]
+    pub ambiguity_tolerance: f64,
+
     /// Refget server URL for looking up unknown contigs.
-    /// When set, unmatched contigs with MD5 digests are queried against this
-    /// server to retrieve aliases and other metadata.
+    /// When set, unmatched contigs with MD5 (or, failing that, sha512t24u)
+    /// digests are queried against this server to retrieve aliases and other metadata.
     #[arg(long)]
     pub refget_server: Option<String>,
+
+    #[command(flatten)]
+    pub refget_cache: RefgetCacheArgs,
+
+    /// Indexed FASTA (with .fai, plus .gzi if BGZF-compressed) of the reference
+    /// the input was aligned to. When the top match is ambiguous, only the contigs
+    /// that discriminate between the tied candidates are hashed and matching is re-run.
+    #[arg(long)]
+    pub fasta: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub digest_cache: DigestCacheArgs,
+
+    /// Write a fix plan for the top match into this directory: a rename map,
+    /// the target .dict and, for BAM/SAM/CRAM/VCF input, a script applying them
+    #[arg(long, value_name = "DIR")]
+    pub emit_fix_plan: Option<PathBuf>,
+}
+
+impl IdentifyArgs {
+    /// The single input of a non-batch run
+    fn input(&self) -> &Path {
+        &self.inputs[0]
+    }
+
+    /// Scoring weights from the command line
+    pub(crate) fn scoring_weights(&self) -> ScoringWeights {
+        ScoringWeights {
+            contig_match: f64::from(self.weight_match) / 100.0,
+            coverage: f64::from(self.weight_coverage) / 100.0,
+            order: f64::from(self.weight_order) / 100.0,
+            conflict_penalty: 0.1, // Default: 10% credit for MD5 conflicts
+            scoring_mode: self.scoring_mode,
+        }
+    }
+
+    /// Matching configuration from the command line
+    pub(crate) fn matching_config(&self) -> MatchingConfig {
+        MatchingConfig {
+            min_score: 0.1,
+            scoring_weights: self.scoring_weights(),
+            ambiguity_tolerance: self.ambiguity_tolerance,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, clap::ValueEnum)]
@@ -92,6 +184,7 @@ pub enum InputFormat {
     Vcf,
     Tsv,
     Csv,
+    IntervalList,
 }
 
 /// Execute identify subcommand
@@ -101,6 +194,13 @@ pub enum InputFormat {
 /// Returns an error if the input cannot be parsed or identification fails.
 #[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
 pub fn run(args: IdentifyArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    if args.joint {
+        return identify_joint::run(&args, format, verbose);
+    }
+    if identify_batch::is_batch(&args) {
+        return identify_batch::run(&args, format, verbose);
+    }
+
     // Parse input first (needed for both catalog types)
     let query = parse_input(&args)?;
 
@@ -113,6 +213,13 @@ pub fn run(args: IdentifyArgs, format: OutputFormat, verbose: bool) -> anyhow::R
         );
     }
 
+    if args.hierarchical && args.fasta.is_some() {
+        anyhow::bail!("--fasta is not supported with --hierarchical");
+    }
+    if args.hierarchical && args.emit_fix_plan.is_some() {
+        anyhow::bail!("--emit-fix-plan is not supported with --hierarchical");
+    }
+
     // Use hierarchical or flat catalog based on flag
     if args.hierarchical {
         run_hierarchical(&args, &query, format, verbose)
@@ -143,13 +250,7 @@ fn run_flat(
         return Ok(());
     }
 
-    // Build scoring weights from command line args
-    let scoring_weights = ScoringWeights {
-        contig_match: f64::from(args.weight_match) / 100.0,
-        coverage: f64::from(args.weight_coverage) / 100.0,
-        order: f64::from(args.weight_order) / 100.0,
-        conflict_penalty: 0.1, // Default: 10% credit for MD5 conflicts
-    };
+    let scoring_weights = args.scoring_weights();
 
     if verbose {
         eprintln!(
@@ -161,45 +262,46 @@ fn run_flat(
     }
 
     // Find matches with custom config
-    let config = MatchingConfig {
-        min_score: 0.1,
-        scoring_weights: scoring_weights.clone(),
+    let engine = MatchingEngine::new(&catalog, args.matching_config());
+    let mut query = Cow::Borrowed(query);
+    let matches = match &args.fasta {
+        Some(fasta) => match_with_fasta(
+            fasta,
+            &catalog,
+            &engine,
+            query.to_mut(),
+            args.max_matches,
+            verbose,
+        )?,
+        None => engine.find_matches(&query, args.max_matches),
     };
-    let engine = MatchingEngine::new(&catalog, config);
-    let matches = engine.find_matches(query, args.max_matches);
+    let query = query.as_ref();
 
     if matches.is_empty() {
         eprintln!("No matching references found.");
         return Ok(());
     }
 
+    // When the best match leaves contigs unexplained, check whether the query was
+    // assembled from several references
+    let decomposition = matches
+        .first()
+        .filter(|m| !m.diagnosis.query_only.is_empty() || !m.diagnosis.conflicts.is_empty())
+        .map(|_| Decomposition::analyze(query, &catalog))
+        .filter(Decomposition::is_mixed);
+
     // Optionally enrich unmatched contigs via refget
-    let enriched = if let Some(ref server_url) = args.refget_server {
-        let config = RefgetConfig::new(server_url);
-        // Collect all query_only contigs from the top match
-        let unmatched_contigs: Vec<_> = matches
-            .first()
-            .map(|m| m.diagnosis.query_only.clone())
-            .unwrap_or_default();
-
-        if unmatched_contigs.is_empty() {
-            None
-        } else {
-            if verbose {
-                eprintln!(
-                    "Querying refget server for {} unmatched contigs...",
-                    unmatched_contigs.len()
-                );
-            }
-            let rt = tokio::runtime::Runtime::new()?;
-            let results = rt.block_on(crate::refget::enrichment::enrich_contigs(
-                &unmatched_contigs,
-                &config,
-            ));
-            Some(results)
-        }
-    } else {
-        None
+    let enriched = enrich_unmatched(args, &matches, verbose)?;
+
+    let fix_plan = match &args.emit_fix_plan {
+        Some(directory) => Some(emit_fix_plan(
+            args,
+            query,
+            &catalog,
+            &matches[0],
+            directory,
+        )?),
+        None => None,
     };
 
     // Output results
@@ -212,29 +314,304 @@ fn run_flat(
                 args.missing_contig_handling,
                 &scoring_weights,
             );
+            if let Some(ref decomposition) = decomposition {
+                print_decomposition_text(decomposition);
+            }
             if let Some(ref enriched) = enriched {
                 print_refget_text_results(enriched);
             }
+            if let Some(ref fix_plan) = fix_plan {
+                print_fix_plan_text(fix_plan);
+            }
         }
         OutputFormat::Json => {
             print_json_results(
                 &matches,
+                query,
                 args.missing_contig_handling,
                 &scoring_weights,
+                decomposition.as_ref(),
                 enriched.as_deref(),
+                fix_plan.as_ref(),
             )?;
         }
         OutputFormat::Tsv => {
             print_tsv_results(&matches, &scoring_weights);
+            if let Some(ref decomposition) = decomposition {
+                print_decomposition_tsv(decomposition);
+            }
             if let Some(ref enriched) = enriched {
                 print_refget_tsv_results(enriched);
             }
+            if let Some(ref fix_plan) = fix_plan {
+                eprintln!("Fix plan written to {}", fix_plan.directory.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rounds of targeted hashing before giving up on an ambiguous match
+const MAX_FASTA_ROUNDS: usize = 3;
+
+/// Match the query, hashing the contigs that discriminate between tied
+/// candidates from the indexed FASTA and re-matching with their digests.
+///
+/// Hashed contigs already in the query, under the FASTA's name or a name the
+/// tied candidates give the same contig, gain their digests; contigs the query
+/// does not list are added to it.
+fn match_with_fasta(
+    fasta: &Path,
+    catalog: &ReferenceCatalog,
+    engine: &MatchingEngine,
+    query: &mut QueryHeader,
+    limit: usize,
+    verbose: bool,
+) -> anyhow::Result<Vec<MatchResult>> {
+    let mut fai_path = fasta.as_os_str().to_owned();
+    fai_path.push(".fai");
+    let fai_path = PathBuf::from(fai_path);
+    let entries = parsing::fai::parse_fai_entries_file(&fai_path).with_context(|| {
+        format!(
+            "--fasta requires an index at {} (create it with `samtools faidx`)",
+            fai_path.display()
+        )
+    })?;
+    let by_name: HashMap<&str, &FaiEntry> = entries.iter().map(|e| (e.name.as_str(), e)).collect();
+
+    let mut hashed: HashSet<String> = HashSet::new();
+    let mut matches = engine.find_matches(query, limit);
+    for _ in 0..MAX_FASTA_ROUNDS {
+        let Some(plan) = matches.first().and_then(|m| {
+            m.diagnosis.suggestions.iter().find_map(|s| match s {
+                Suggestion::Disambiguate { plan } => Some(plan),
+                _ => None,
+            })
+        }) else {
+            break;
+        };
+
+        let mut targets: Vec<&FaiEntry> = Vec::new();
+        let mut target_names: Vec<HashSet<String>> = Vec::new();
+        for discriminator in &plan.discriminators {
+            if discriminator.evidence != DiscriminatorEvidence::Md5 {
+                continue;
+            }
+            let names = equivalent_names(&discriminator.contig, &plan.candidates, catalog);
+            if let Some(entry) = fasta_entry(&discriminator.contig, &names, &by_name) {
+                if hashed.insert(entry.name.clone()) {
+                    targets.push(entry);
+                    target_names.push(names);
+                }
+            }
         }
+        if targets.is_empty() {
+            break;
+        }
+
+        if verbose {
+            let names: Vec<&str> = targets.iter().map(|e| e.name.as_str()).collect();
+            eprintln!("Hashing {} from {}", names.join(", "), fasta.display());
+        }
+        let contigs = parsing::fasta::hash_indexed_contigs(fasta, &targets)?;
+        for (contig, names) in contigs.into_iter().zip(&target_names) {
+            merge_hashed_contig(query, contig, names)
+                .with_context(|| format!("Cannot use contigs hashed from {}", fasta.display()))?;
+        }
+        query.rebuild_indexes();
+        matches = engine.find_matches(query, limit);
     }
 
+    Ok(matches)
+}
+
+/// `contig` and every name or alias the tied candidates give the contig
+/// they call `contig`
+fn equivalent_names(
+    contig: &str,
+    candidates: &[ReferenceId],
+    catalog: &ReferenceCatalog,
+) -> HashSet<String> {
+    candidates
+        .iter()
+        .filter_map(|id| catalog.get(id))
+        .flat_map(|reference| &reference.contigs)
+        .filter(|c| c.name == contig || c.aliases.iter().any(|a| a == contig))
+        .flat_map(|c| std::iter::once(&c.name).chain(&c.aliases))
+        .chain(std::iter::once(&contig.to_string()))
+        .cloned()
+        .collect()
+}
+
+/// Find a discriminating contig in the FASTA index under its own name, or
+/// failing that under one of its equivalent `names`
+fn fasta_entry<'a>(
+    contig: &str,
+    names: &HashSet<String>,
+    by_name: &HashMap<&str, &'a FaiEntry>,
+) -> Option<&'a FaiEntry> {
+    by_name.get(contig).copied().or_else(|| {
+        let mut names: Vec<&String> = names.iter().collect();
+        names.sort();
+        names
+            .into_iter()
+            .find_map(|name| by_name.get(name.as_str()).copied())
+    })
+}
+
+/// Give the query contig that `contig`, hashed from the FASTA, stands for its
+/// digests, or add `contig` to the query if it lists no such contig.
+///
+/// The query contig is found by the FASTA's name, or by one of the equivalent
+/// `names` or its own aliases. Either way the lengths must agree.
+fn merge_hashed_contig(
+    query: &mut QueryHeader,
+    contig: Contig,
+    names: &HashSet<String>,
+) -> anyhow::Result<()> {
+    let existing = query
+        .contigs
+        .iter()
+        .position(|c| c.name == contig.name)
+        .or_else(|| {
+            query
+                .contigs
+                .iter()
+                .position(|c| names.contains(&c.name) || c.aliases.contains(&contig.name))
+        });
+    let Some(index) = existing else {
+        query.contigs.push(contig);
+        return Ok(());
+    };
+
+    let existing = &mut query.contigs[index];
+    if existing.length != contig.length {
+        anyhow::bail!(
+            "{} has length {} in the input but {} has length {} in the FASTA",
+            existing.name,
+            existing.length,
+            contig.name,
+            contig.length
+        );
+    }
+    existing.md5 = contig.md5;
+    existing.sha512t24u = contig.sha512t24u;
     Ok(())
 }
 
+/// Write the fix plan for `best` into `directory`
+fn emit_fix_plan(
+    args: &IdentifyArgs,
+    query: &QueryHeader,
+    catalog: &ReferenceCatalog,
+    best: &MatchResult,
+    directory: &Path,
+) -> anyhow::Result<FixPlanArtifacts> {
+    let reference = catalog
+        .get(&best.reference.id)
+        .ok_or_else(|| anyhow::anyhow!("Reference '{}' not found in catalog", best.reference.id))?;
+    if best.ambiguity.ambiguous {
+        tracing::warn!(
+            "Top match {} is ambiguous; the fix plan targets it, not the references it is tied with",
+            reference.id
+        );
+    }
+
+    // The script needs the input's path, so there is none for stdin
+    let path = args.input();
+    let input = if path.to_string_lossy() == "-" {
+        None
+    } else {
+        let kind = match args.input_format.unwrap_or_else(|| detect_format(path)) {
+            InputFormat::Sam => Some(FixInput::Sam),
+            InputFormat::Bam => Some(FixInput::Bam),
+            InputFormat::Cram => Some(FixInput::Cram),
+            InputFormat::Vcf => Some(FixInput::Vcf),
+            _ => None,
+        };
+        kind.map(|kind| anyhow::Ok((std::fs::canonicalize(path)?, kind)))
+            .transpose()?
+    };
+
+    let mut plan = FixPlan::new(query, reference, &best.diagnosis);
+    // The script may run from another directory
+    plan.catalog = args
+        .catalog
+        .as_deref()
+        .map(std::fs::canonicalize)
+        .transpose()?;
+    let artifacts = plan
+        .write(
+            directory,
+            input.as_ref().map(|(path, kind)| (path.as_path(), *kind)),
+        )
+        .with_context(|| format!("Failed to write fix plan to {}", directory.display()))?;
+    if let Some(reason) = &plan.blocked {
+        tracing::warn!("No fix script written: {reason}");
+    }
+    Ok(artifacts)
+}
+
+fn print_fix_plan_text(artifacts: &FixPlanArtifacts) {
+    println!("Fix plan: {}", artifacts.directory.display());
+    if let Some(path) = &artifacts.rename_map {
+        println!(
+            "   Rename map ({} contigs): {}",
+            artifacts.renamed,
+            path.display()
+        );
+    }
+    println!("   Target dictionary: {}", artifacts.dict.display());
+    match (&artifacts.script, &artifacts.blocked) {
+        (Some(path), _) => println!("   Script: {}", path.display()),
+        (None, Some(reason)) => println!("   No script: {reason}"),
+        (None, None) if artifacts.renamed == 0 && !artifacts.reordered => {
+            println!("   No script: nothing to rename or reorder");
+        }
+        (None, None) => println!("   No script: only written for BAM, SAM, CRAM and VCF files"),
+    }
+    println!();
+}
+
+/// Query the refget server (if configured) for the top match's unmatched contigs
+fn enrich_unmatched(
+    args: &IdentifyArgs,
+    matches: &[MatchResult],
+    verbose: bool,
+) -> anyhow::Result<Option<Vec<EnrichedContig>>> {
+    let Some(server_url) = &args.refget_server else {
+        return Ok(None);
+    };
+
+    // Collect all query_only contigs from the top match
+    let unmatched_contigs: Vec<_> = matches
+        .first()
+        .map(|m| m.diagnosis.query_only.clone())
+        .unwrap_or_default();
+    if unmatched_contigs.is_empty() {
+        return Ok(None);
+    }
+
+    let mut config = RefgetConfig::new(server_url);
+    if let Some(cache) = args.refget_cache.open()? {
+        config = config.with_cache(cache);
+    }
+
+    if verbose {
+        eprintln!(
+            "Querying refget server for {} unmatched contigs...",
+            unmatched_contigs.len()
+        );
+    }
+    let rt = tokio::runtime::Runtime::new()?;
+    let results = rt.block_on(crate::refget::enrichment::enrich_contigs(
+        &unmatched_contigs,
+        &config,
+    ));
+    Ok(Some(results))
+}
+
 fn run_hierarchical(
     args: &IdentifyArgs,
     query: &QueryHeader,
@@ -280,32 +657,46 @@ fn parse_input(args: &IdentifyArgs) -> anyhow::Result<QueryHeader> {
     use std::io::{self, Read};
 
     // Handle stdin
-    if args.input.to_string_lossy() == "-" {
+    let path = args.input();
+    if path.to_string_lossy() == "-" {
         let mut buffer = String::new();
         io::stdin().read_to_string(&mut buffer)?;
         return Ok(parsing::sam::parse_header_text(&buffer)?);
     }
 
     // Auto-detect or use specified format
-    let format = args
-        .input_format
-        .unwrap_or_else(|| detect_format(&args.input));
+    let format = args.input_format.unwrap_or_else(|| detect_format(path));
+    let cache = match format {
+        InputFormat::Fasta => args.digest_cache.open()?,
+        _ => None,
+    };
+    parse_file(path, format, cache.as_deref())
+}
 
+/// Parse the header of a file in the given format
+pub(crate) fn parse_file(
+    path: &Path,
+    format: InputFormat,
+    cache: Option<&DigestCache>,
+) -> anyhow::Result<QueryHeader> {
     match format {
         InputFormat::Sam | InputFormat::Bam | InputFormat::Cram => {
-            Ok(parsing::sam::parse_file(&args.input)?)
+            Ok(parsing::sam::parse_file(path)?)
         }
-        InputFormat::Dict => Ok(parsing::dict::parse_dict_file(&args.input)?),
-        InputFormat::Fai => Ok(parsing::fai::parse_fai_file(&args.input)?),
-        InputFormat::Fasta => Ok(parsing::fasta::parse_fasta_file(&args.input)?),
-        InputFormat::Vcf => Ok(parsing::vcf::parse_vcf_file(&args.input)?),
-        InputFormat::Tsv => Ok(parsing::tsv::parse_tsv_file(&args.input, '\t')?),
-        InputFormat::Csv => Ok(parsing::tsv::parse_tsv_file(&args.input, ',')?),
+        InputFormat::Dict => Ok(parsing::dict::parse_dict_file(path)?),
+        InputFormat::Fai => Ok(parsing::fai::parse_fai_file(path)?),
+        InputFormat::Fasta => Ok(parsing::fasta::parse_fasta_file_with_md5_cached(
+            path, cache,
+        )?),
+        InputFormat::Vcf => Ok(parsing::vcf::parse_vcf_file(path)?),
+        InputFormat::Tsv => Ok(parsing::tsv::parse_tsv_file(path, '\t')?),
+        InputFormat::Csv => Ok(parsing::tsv::parse_tsv_file(path, ',')?),
+        InputFormat::IntervalList => Ok(parsing::sam::parse_interval_list_file(path)?),
     }
 }
 
 /// Detect input format from file extension
-fn detect_format(path: &Path) -> InputFormat {
+pub(crate) fn detect_format(path: &Path) -> InputFormat {
     let path_str = path.to_string_lossy().to_lowercase();
 
     // Check for FASTA files (including gzipped)
@@ -332,6 +723,7 @@ fn detect_format(path: &Path) -> InputFormat {
         Some("vcf") => InputFormat::Vcf,
         Some("tsv") => InputFormat::Tsv,
         Some("csv") => InputFormat::Csv,
+        Some("interval_list") => InputFormat::IntervalList,
         _ => InputFormat::Sam, // Default to SAM for unknown extensions
     }
 }
@@ -367,6 +759,19 @@ fn print_text_results(
         println!("   Assembly: {}", result.reference.assembly);
         println!("   Source: {}", result.reference.source);
         println!("   Match Type: {:?}", result.diagnosis.match_type);
+        if result.ambiguity.ambiguous {
+            println!(
+                "   Ambiguous: indistinguishable from {} (margin {:+.1}%)",
+                join_ids(&result.ambiguity.tied_with),
+                result.ambiguity.margin.unwrap_or(0.0) * 100.0
+            );
+        }
+        for verdict in [&result.tiers.primary, &result.tiers.auxiliary]
+            .into_iter()
+            .flatten()
+        {
+            print_tier_verdict(verdict);
+        }
 
         // Score breakdown: show component scores and final composite
         // Normalize weights for display
@@ -374,8 +779,8 @@ fn print_text_results(
         println!(
             "\n   Score: {:.1}% = {:.0}%×match + {:.0}%×coverage + {:.0}%×order",
             result.score.composite * 100.0,
-            result.score.match_quality * 100.0,
-            result.score.coverage_score * 100.0,
+            result.score.active_match_quality() * 100.0,
+            result.score.active_coverage_score() * 100.0,
             result.score.order_score * 100.0,
         );
         println!(
@@ -443,6 +848,15 @@ fn print_text_results(
         println!(
             "\n   Query contigs: {total_query} total → {exact} exact, {name_len} name+length, {conflicts} conflicts, {unmatched} unmatched"
         );
+        let lw = &result.score.length_weighted;
+        println!(
+            "   Query bases: {} total → {:.1}% exact, {:.1}% name+length, {:.1}% conflicts, {:.1}% unmatched",
+            lw.query_bases(),
+            lw.exact_fraction * 100.0,
+            lw.name_length_fraction * 100.0,
+            lw.conflict_fraction * 100.0,
+            lw.unmatched_fraction * 100.0,
+        );
 
         // Reference coverage info
         let total_ref = result.reference.contigs.len();
@@ -505,6 +919,15 @@ fn print_text_results(
                         println!("   - Realignment needed: {reason}");
                         println!("     Suggested reference: {suggested_reference}");
                     }
+                    Suggestion::Disambiguate { plan } => {
+                        println!("   - Disambiguate {}:", join_ids(&plan.candidates));
+                        for discriminator in &plan.discriminators {
+                            println!("     {}", discriminator.description());
+                        }
+                        for group in &plan.unresolved {
+                            println!("     No contig distinguishes {}", join_ids(group));
+                        }
+                    }
                 }
             }
         }
@@ -526,13 +949,39 @@ fn print_text_results(
     println!();
 }
 
+fn print_tier_verdict(verdict: &TierVerdict) {
+    let label = match verdict.tier {
+        AssemblyTier::Primary => "Primary assembly",
+        AssemblyTier::Auxiliary => "Auxiliary contigs",
+    };
+    let state = if verdict.identical {
+        "identical"
+    } else {
+        "differs"
+    };
+    println!(
+        "   {label}: {state} - {:?} ({:?}, {:.1}%), {} query / {} reference contigs, {} exact, {} conflicts",
+        verdict.match_type,
+        verdict.confidence,
+        verdict.composite * 100.0,
+        verdict.query_contigs,
+        verdict.reference_contigs,
+        verdict.score.exact_matches,
+        verdict.diagnosis.conflicts.len(),
+    );
+}
+
 fn print_json_results(
     matches: &[MatchResult],
+    query: &QueryHeader,
     missing_handling: MissingContigHandling,
     weights: &ScoringWeights,
+    decomposition: Option<&Decomposition>,
     enriched: Option<&[EnrichedContig]>,
+    fix_plan: Option<&FixPlanArtifacts>,
 ) -> anyhow::Result<()> {
     let norm = weights.normalized();
+    let query_seqcol = SeqCol::from(query);
     // Create serializable output
     let results: Vec<serde_json::Value> = matches
         .iter()
@@ -555,9 +1004,16 @@ fn print_json_results(
                     "composite": m.score.composite,
                     "confidence": format!("{:?}", m.score.confidence),
                     // Component scores (these make up the composite)
-                    "match_quality": m.score.match_quality,
-                    "coverage_score": m.score.coverage_score,
+                    "match_quality": m.score.active_match_quality(),
+                    "coverage_score": m.score.active_coverage_score(),
                     "order_score": m.score.order_score,
+                    // Both scoring modes, whichever was selected
+                    "scoring_mode": m.score.scoring_mode,
+                    "count_based": {
+                        "match_quality": m.score.match_quality,
+                        "coverage_score": m.score.coverage_score,
+                    },
+                    "length_weighted": m.score.length_weighted,
                     // Weights used
                     "weights": {
                         "match": norm.contig_match,
@@ -577,7 +1033,21 @@ fn print_json_results(
                     "not_in_query": ref_uncovered,
                 },
                 "match_type": format!("{:?}", m.diagnosis.match_type),
+                "ambiguity": m.ambiguity,
+                "disambiguation": m.diagnosis.suggestions.iter().find_map(|s| match s {
+                    Suggestion::Disambiguate { plan } => Some(plan),
+                    _ => None,
+                }),
+                "tiers": {
+                    "summary": m.tiers.summary(),
+                    "primary": m.tiers.primary,
+                    "auxiliary": m.tiers.auxiliary,
+                },
                 "reordered": m.diagnosis.reordered,
+                "seqcol_comparison": SeqColComparison::compare(
+                    &query_seqcol,
+                    &SeqCol::from(&m.reference),
+                ),
             });
 
             // Add missing contig info unless silent
@@ -592,11 +1062,27 @@ fn print_json_results(
         })
         .collect();
 
-    let mut output = serde_json::json!({ "matches": results });
-
+    let query_level1 = query_seqcol.level1();
+    let mut output = serde_json::json!({
+        "query": {
+            "seqcol": {
+                "digest": query_level1.digest(),
+                "level1": query_level1,
+            },
+        },
+        "matches": results,
+        "ambiguous": matches.first().is_some_and(|m| m.ambiguity.ambiguous),
+    });
+
+    if let Some(decomposition) = decomposition {
+        output["decomposition"] = serde_json::json!(decomposition);
+    }
     if let Some(enriched) = enriched {
         output["refget_enrichment"] = serde_json::json!(enriched);
     }
+    if let Some(fix_plan) = fix_plan {
+        output["fix_plan"] = serde_json::json!(fix_plan);
+    }
 
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
@@ -606,7 +1092,7 @@ fn print_tsv_results(matches: &[MatchResult], weights: &ScoringWeights) {
     let norm = weights.normalized();
     // Header with all fields
     println!(
-        "rank\tid\tdisplay_name\tassembly\tsource\tmatch_type\tscore\tmatch_score\tcoverage_score\torder_score\tweight_match\tweight_coverage\tweight_order\tconfidence\texact\tname_length\tconflicts\tunmatched\tref_total\tref_matched\tref_uncovered"
+        "rank\tid\tdisplay_name\tassembly\tsource\tmatch_type\tscore\tmatch_score\tcoverage_score\torder_score\tweight_match\tweight_coverage\tweight_order\tconfidence\texact\tname_length\tconflicts\tunmatched\tref_total\tref_matched\tref_uncovered\tprimary_match_type\tprimary_confidence\tauxiliary_match_type\tauxiliary_confidence\tambiguous\tmargin\ttied_with"
     );
     for (i, m) in matches.iter().enumerate() {
         let ref_total = m.reference.contigs.len();
@@ -614,7 +1100,7 @@ fn print_tsv_results(matches: &[MatchResult], weights: &ScoringWeights) {
         let ref_uncovered = ref_total.saturating_sub(ref_matched);
 
         println!(
-            "{}\t{}\t{}\t{}\t{}\t{:?}\t{:.4}\t{:.4}\t{:.4}\t{:.4}\t{:.2}\t{:.2}\t{:.2}\t{:?}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            "{}\t{}\t{}\t{}\t{}\t{:?}\t{:.4}\t{:.4}\t{:.4}\t{:.4}\t{:.2}\t{:.2}\t{:.2}\t{:?}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
             i + 1,
             m.reference.id,
             m.reference.display_name,
@@ -622,8 +1108,8 @@ fn print_tsv_results(matches: &[MatchResult], weights: &ScoringWeights) {
             m.reference.source,
             m.diagnosis.match_type,
             m.score.composite,
-            m.score.match_quality,
-            m.score.coverage_score,
+            m.score.active_match_quality(),
+            m.score.active_coverage_score(),
             m.score.order_score,
             norm.contig_match,
             norm.coverage,
@@ -636,10 +1122,98 @@ fn print_tsv_results(matches: &[MatchResult], weights: &ScoringWeights) {
             ref_total,
             ref_matched,
             ref_uncovered,
+            tier_tsv(m.tiers.primary.as_ref().map(|t| &t.match_type)),
+            tier_tsv(m.tiers.primary.as_ref().map(|t| t.confidence)),
+            tier_tsv(m.tiers.auxiliary.as_ref().map(|t| &t.match_type)),
+            tier_tsv(m.tiers.auxiliary.as_ref().map(|t| t.confidence)),
+            m.ambiguity.ambiguous,
+            m.ambiguity
+                .margin
+                .map(|margin| format!("{margin:.4}"))
+                .unwrap_or_default(),
+            m.ambiguity
+                .tied_with
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
         );
     }
 }
 
+/// Debug-format a tier field for TSV, or an empty cell if the tier is absent
+fn tier_tsv(value: Option<impl std::fmt::Debug>) -> String {
+    value.map(|v| format!("{v:?}")).unwrap_or_default()
+}
+
+// ============================================================================
+// Source decomposition output functions
+// ============================================================================
+
+fn join_ids(ids: &[ReferenceId]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_decomposition_text(decomposition: &Decomposition) {
+    println!("Mixed Sources:");
+    println!("{}", "─".repeat(60));
+    println!("  {}\n", decomposition.describe());
+    for (i, component) in decomposition.components.iter().enumerate() {
+        println!(
+            "  {}. {} ({})",
+            i + 1,
+            component.display_name,
+            component.reference_id
+        );
+        println!(
+            "     {} contig(s): {}",
+            component.contigs.len(),
+            component.summary
+        );
+        if component.name_length_only > 0 {
+            println!(
+                "     ({} matched by name and length only)",
+                component.name_length_only
+            );
+        }
+    }
+    if !decomposition.unexplained.is_empty() {
+        println!(
+            "  Unexplained: {} contig(s) not in any catalog reference",
+            decomposition.unexplained.len()
+        );
+        let shown: Vec<&str> = decomposition
+            .unexplained
+            .iter()
+            .take(10)
+            .map(String::as_str)
+            .collect();
+        let more = decomposition.unexplained.len().saturating_sub(shown.len());
+        if more > 0 {
+            println!("     {}, ... and {more} more", shown.join(", "));
+        } else {
+            println!("     {}", shown.join(", "));
+        }
+    }
+    println!();
+}
+
+fn print_decomposition_tsv(decomposition: &Decomposition) {
+    println!("\n# Source decomposition");
+    println!("contig\tsource_reference");
+    for component in &decomposition.components {
+        for contig in &component.contigs {
+            println!("{contig}\t{}", component.reference_id);
+        }
+    }
+    for contig in &decomposition.unexplained {
+        println!("{contig}\t");
+    }
+}
+
 // ============================================================================
 // Refget enrichment output functions
 // ============================================================================
@@ -662,6 +1236,7 @@ fn print_refget_text_results(enriched: &[EnrichedContig]) {
             aliases,
             sha512t24u,
             circular,
+            ..
         } = &entry.refget_metadata
         {
             print!("  {} ", entry.name);
@@ -690,6 +1265,7 @@ fn print_refget_tsv_results(enriched: &[EnrichedContig]) {
                 aliases,
                 sha512t24u,
                 circular,
+                ..
             } => {
                 let alias_str: Vec<String> = aliases
                     .iter()
@@ -861,3 +1437,94 @@ fn print_hierarchical_tsv_results(matches: &[HierarchicalMatchResult]) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| (*n).to_string()).collect()
+    }
+
+    fn hashed(name: &str, length: u64) -> Contig {
+        Contig::new(name, length)
+            .with_md5("6aef897c3d6ff0c78aff06ac189178dd")
+            .with_sha512t24u("2YnepKM7OkBoOrKmvHbGqguVfF9amCST")
+    }
+
+    #[test]
+    fn test_equivalent_names_follow_candidate_aliases() {
+        let catalog = ReferenceCatalog::load_embedded().unwrap();
+        let candidates = [ReferenceId::new("hs38DH")];
+
+        let equivalent = equivalent_names("Y", &candidates, &catalog);
+        assert!(equivalent.contains("chrY"), "{equivalent:?}");
+        assert!(equivalent.contains("Y"));
+        assert!(!equivalent.contains("chrX"));
+    }
+
+    #[test]
+    fn test_fasta_entry_prefers_own_name() {
+        let entry = |name: &str, offset| FaiEntry {
+            name: name.to_string(),
+            length: 1000,
+            offset,
+            line_bases: 60,
+            line_width: 61,
+        };
+        let entries = [entry("chrY", 6), entry("Y", 1100)];
+        let by_name: HashMap<&str, &FaiEntry> =
+            entries.iter().map(|e| (e.name.as_str(), e)).collect();
+
+        let entry = fasta_entry("Y", &names(&["Y", "chrY"]), &by_name).unwrap();
+        assert_eq!(entry.name, "Y");
+        let entry = fasta_entry("24", &names(&["24", "chrY"]), &by_name).unwrap();
+        assert_eq!(entry.name, "chrY");
+        assert!(fasta_entry("chrX", &names(&["chrX"]), &by_name).is_none());
+    }
+
+    #[test]
+    fn test_merge_hashed_contig_through_alias() {
+        let mut query = QueryHeader::new(vec![Contig::new("1", 2000), Contig::new("Y", 1000)]);
+
+        merge_hashed_contig(&mut query, hashed("chrY", 1000), &names(&["Y", "chrY"])).unwrap();
+
+        // The query's Y gains the digests instead of a second chrY being added
+        assert_eq!(query.contigs.len(), 2);
+        assert_eq!(query.contigs[1].name, "Y");
+        assert!(query.contigs[1].md5.is_some());
+        assert!(query.contigs[1].sha512t24u.is_some());
+    }
+
+    #[test]
+    fn test_merge_hashed_contig_through_query_alias() {
+        let mut contig = Contig::new("Y", 1000);
+        contig.aliases = vec!["chrY".to_string()];
+        let mut query = QueryHeader::new(vec![contig]);
+
+        merge_hashed_contig(&mut query, hashed("chrY", 1000), &names(&["chrY"])).unwrap();
+
+        assert_eq!(query.contigs.len(), 1);
+        assert!(query.contigs[0].md5.is_some());
+    }
+
+    #[test]
+    fn test_merge_hashed_contig_adds_unlisted_contig() {
+        let mut query = QueryHeader::new(vec![Contig::new("1", 2000)]);
+
+        merge_hashed_contig(&mut query, hashed("chrY", 1000), &names(&["Y", "chrY"])).unwrap();
+
+        assert_eq!(query.contigs.len(), 2);
+        assert_eq!(query.contigs[1].name, "chrY");
+    }
+
+    #[test]
+    fn test_merge_hashed_contig_rejects_length_mismatch() {
+        let mut query = QueryHeader::new(vec![Contig::new("Y", 999)]);
+
+        let error = merge_hashed_contig(&mut query, hashed("chrY", 1000), &names(&["Y", "chrY"]))
+            .unwrap_err();
+        assert!(error.to_string().contains("length 999"), "{error}");
+        assert!(query.contigs[0].md5.is_none());
+    }
+}
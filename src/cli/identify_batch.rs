@@ -0,0 +1,627 @@
+//! Batch mode of the identify command.
+//!
+//! Identifies many files against a catalog loaded once, in parallel, and
+//! reports one row per file with the best match plus a summary of how many
+//! files map to each reference. Inputs may be files, glob patterns, or
+//! directories, which are scanned (recursively with `--recursive`) for BAM,
+//! SAM, CRAM, VCF and `.dict` files.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::Serialize;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::cli::identify::{self, IdentifyArgs, InputFormat};
+use crate::cli::OutputFormat;
+use crate::core::types::ReferenceId;
+use crate::matching::engine::MatchingEngine;
+use crate::parsing::digest_cache::DigestCache;
+use crate::utils::glob;
+
+/// File name suffixes picked up when scanning a directory
+const HEADER_SUFFIXES: &[&str] = &[
+    ".bam", ".sam", ".cram", ".dict", ".vcf", ".vcf.gz", ".vcf.bgz",
+];
+
+/// Whether the arguments ask for more than a single file, or for a row per file
+pub(crate) fn is_batch(args: &IdentifyArgs) -> bool {
+    args.inputs.len() > 1
+        || args.recursive
+        || args.jsonl
+        || args
+            .inputs
+            .iter()
+            .any(|path| path.is_dir() || (glob::is_pattern(path) && !path.exists()))
+}
+
+/// Outcome of identifying one file
+#[derive(Debug, Serialize)]
+struct FileRow {
+    path: PathBuf,
+    reference: Option<ReferenceId>,
+    display_name: Option<String>,
+    match_type: Option<String>,
+    confidence: Option<String>,
+    score: Option<f64>,
+    ambiguous: bool,
+    tied_with: Vec<ReferenceId>,
+    error: Option<String>,
+}
+
+impl FileRow {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            reference: None,
+            display_name: None,
+            match_type: None,
+            confidence: None,
+            score: None,
+            ambiguous: false,
+            tied_with: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn failed(path: PathBuf, error: String) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::new(path)
+        }
+    }
+}
+
+/// A file to identify, or an input that names no readable file
+enum BatchInput {
+    File(PathBuf),
+    Invalid { path: PathBuf, error: String },
+}
+
+/// Number of files identified as one reference
+#[derive(Debug, Serialize)]
+struct ReferenceCount {
+    reference: ReferenceId,
+    display_name: String,
+    files: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchSummary {
+    files: usize,
+    identified: usize,
+    ambiguous: usize,
+    no_match: usize,
+    errors: usize,
+    by_reference: Vec<ReferenceCount>,
+}
+
+impl BatchSummary {
+    fn new(rows: &[FileRow]) -> Self {
+        let mut counts: HashMap<&ReferenceId, ReferenceCount> = HashMap::new();
+        for row in rows {
+            if let (Some(reference), Some(display_name)) = (&row.reference, &row.display_name) {
+                counts
+                    .entry(reference)
+                    .or_insert_with(|| ReferenceCount {
+                        reference: reference.clone(),
+                        display_name: display_name.clone(),
+                        files: 0,
+                    })
+                    .files += 1;
+            }
+        }
+        let mut by_reference: Vec<ReferenceCount> = counts.into_values().collect();
+        by_reference.sort_by(|a, b| {
+            b.files
+                .cmp(&a.files)
+                .then_with(|| a.reference.0.cmp(&b.reference.0))
+        });
+
+        let errors = rows.iter().filter(|r| r.error.is_some()).count();
+        let identified = rows.iter().filter(|r| r.reference.is_some()).count();
+        Self {
+            files: rows.len(),
+            identified,
+            ambiguous: rows.iter().filter(|r| r.ambiguous).count(),
+            no_match: rows.len() - identified - errors,
+            errors,
+            by_reference,
+        }
+    }
+}
+
+/// Identify every file named by the arguments
+///
+/// # Errors
+///
+/// Returns an error if an option that only applies to a single file is given,
+/// if the catalog cannot be loaded, or, after the report is printed, if any
+/// input could not be identified.
+pub(crate) fn run(args: &IdentifyArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    if args.inputs.iter().any(|path| path.to_string_lossy() == "-") {
+        anyhow::bail!("stdin ('-') cannot be combined with other inputs");
+    }
+    for (set, option) in [
+        (args.hierarchical, "--hierarchical"),
+        (args.fasta.is_some(), "--fasta"),
+        (args.emit_fix_plan.is_some(), "--emit-fix-plan"),
+        (args.refget_server.is_some(), "--refget-server"),
+    ] {
+        if set {
+            anyhow::bail!("{option} is not supported when identifying several files");
+        }
+    }
+
+    let files = collect_files(&args.inputs, args.recursive);
+    if verbose {
+        eprintln!("Identifying {} files", files.len());
+    }
+
+    let catalog = if let Some(path) = &args.catalog {
+        ReferenceCatalog::load_from_file(path)?
+    } else {
+        ReferenceCatalog::load_embedded()?
+    };
+    let engine = MatchingEngine::new(&catalog, args.matching_config());
+    let needs_cache = files.iter().any(|file| {
+        matches!(file, BatchInput::File(path) if matches!(input_format(args, path), InputFormat::Fasta))
+    });
+    let cache = if needs_cache {
+        args.digest_cache.open()?
+    } else {
+        None
+    };
+
+    let threads = args.threads.map_or_else(
+        || std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get),
+        usize::from,
+    );
+    let rows = identify_all(args, &engine, cache.as_deref(), files, threads, verbose);
+    let summary = BatchSummary::new(&rows);
+
+    if args.jsonl {
+        for row in &rows {
+            println!("{}", serde_json::to_string(row)?);
+        }
+        // Keep stdout to one object per file
+        eprint!("{}", summary_text(&summary));
+    } else {
+        match format {
+            OutputFormat::Text => {
+                print_text(&rows);
+                print!("{}", summary_text(&summary));
+            }
+            OutputFormat::Json => {
+                let output = serde_json::json!({
+                    "files": rows,
+                    "summary": summary,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+            OutputFormat::Tsv => print_tsv(&rows, &summary),
+        }
+    }
+
+    if summary.errors > 0 {
+        anyhow::bail!(
+            "{} of {} inputs could not be identified",
+            summary.errors,
+            summary.files
+        );
+    }
+    Ok(())
+}
+
+/// Expand the inputs into files, in the order given and without duplicates.
+/// Inputs that name nothing are kept as errors so they show up in the report.
+fn collect_files(inputs: &[PathBuf], recursive: bool) -> Vec<BatchInput> {
+    let mut files = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            let mut found = Vec::new();
+            scan_dir(input, recursive, &mut found, &mut files);
+            found.sort();
+            files.extend(found.into_iter().map(BatchInput::File));
+        } else if input.exists() {
+            files.push(BatchInput::File(input.clone()));
+        } else if glob::is_pattern(input) {
+            match glob::expand(input) {
+                Ok(matched) if matched.is_empty() => files.push(BatchInput::Invalid {
+                    path: input.clone(),
+                    error: "No files match this pattern".to_string(),
+                }),
+                Ok(matched) => files.extend(matched.into_iter().map(BatchInput::File)),
+                Err(e) => files.push(BatchInput::Invalid {
+                    path: input.clone(),
+                    error: format!("Invalid glob pattern: {e}"),
+                }),
+            }
+        } else {
+            files.push(BatchInput::Invalid {
+                path: input.clone(),
+                error: "No such file or directory".to_string(),
+            });
+        }
+    }
+
+    let mut seen = HashSet::new();
+    files.retain(|file| match file {
+        BatchInput::File(path) => {
+            seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+        }
+        BatchInput::Invalid { .. } => true,
+    });
+    files
+}
+
+/// Header files in `dir`, descending into subdirectories if `recursive`.
+/// Like the local index scan, symlinked files are followed but symlinked
+/// directories are not.
+fn scan_dir(dir: &Path, recursive: bool, found: &mut Vec<PathBuf>, errors: &mut Vec<BatchInput>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(BatchInput::Invalid {
+                path: dir.to_path_buf(),
+                error: e.to_string(),
+            });
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if recursive {
+                scan_dir(&path, recursive, found, errors);
+            }
+        } else if (file_type.is_file() || (file_type.is_symlink() && path.is_file()))
+            && is_header_file(&path)
+        {
+            found.push(path);
+        }
+    }
+}
+
+fn is_header_file(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| {
+        let name = name.to_string_lossy().to_lowercase();
+        HEADER_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+    })
+}
+
+fn input_format(args: &IdentifyArgs, path: &Path) -> InputFormat {
+    args.input_format
+        .unwrap_or_else(|| identify::detect_format(path))
+}
+
+/// Identify the files on `threads` worker threads, keeping their order
+fn identify_all(
+    args: &IdentifyArgs,
+    engine: &MatchingEngine,
+    cache: Option<&DigestCache>,
+    files: Vec<BatchInput>,
+    threads: usize,
+    verbose: bool,
+) -> Vec<FileRow> {
+    let total = files.len();
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+
+    let mut rows: Vec<(usize, FileRow)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(total).max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut rows = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(file) = files.get(index) else {
+                            break;
+                        };
+                        let row = match file {
+                            BatchInput::File(path) => identify_file(args, engine, cache, path),
+                            BatchInput::Invalid { path, error } => {
+                                FileRow::failed(path.clone(), error.clone())
+                            }
+                        };
+                        if verbose {
+                            let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                            eprintln!("[{done}/{total}] {}", row.path.display());
+                        }
+                        rows.push((index, row));
+                    }
+                    rows
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    rows.sort_by_key(|(index, _)| *index);
+    rows.into_iter().map(|(_, row)| row).collect()
+}
+
+fn identify_file(
+    args: &IdentifyArgs,
+    engine: &MatchingEngine,
+    cache: Option<&DigestCache>,
+    path: &Path,
+) -> FileRow {
+    let query = match identify::parse_file(path, input_format(args, path), cache) {
+        Ok(query) => query,
+        Err(e) => return FileRow::failed(path.to_path_buf(), format!("{e:#}")),
+    };
+    let mut row = FileRow::new(path.to_path_buf());
+    if let Some(best) = engine
+        .find_matches(&query, args.max_matches.max(1))
+        .into_iter()
+        .next()
+    {
+        row.reference = Some(best.reference.id.clone());
+        row.display_name = Some(best.reference.display_name.clone());
+        row.match_type = Some(format!("{:?}", best.diagnosis.match_type));
+        row.confidence = Some(format!("{:?}", best.score.confidence));
+        row.score = Some(best.score.composite);
+        row.ambiguous = best.ambiguity.ambiguous;
+        row.tied_with = best.ambiguity.tied_with;
+    }
+    row
+}
+
+fn print_text(rows: &[FileRow]) {
+    let cells: Vec<[String; 6]> = rows
+        .iter()
+        .map(|row| {
+            let note = match (&row.error, row.reference.is_some(), row.ambiguous) {
+                (Some(error), _, _) => format!("error: {error}"),
+                (None, false, _) => "no match".to_string(),
+                (None, true, true) => format!(
+                    "ambiguous with {}",
+                    row.tied_with
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                (None, true, false) => String::new(),
+            };
+            [
+                row.path.display().to_string(),
+                row.reference
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                row.match_type.clone().unwrap_or_default(),
+                row.confidence.clone().unwrap_or_default(),
+                row.score
+                    .map(|score| format!("{:.1}%", score * 100.0))
+                    .unwrap_or_default(),
+                note,
+            ]
+        })
+        .collect();
+
+    let header = [
+        "FILE",
+        "REFERENCE",
+        "MATCH TYPE",
+        "CONFIDENCE",
+        "SCORE",
+        "NOTE",
+    ]
+    .map(String::from);
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&cells) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
+
+fn summary_text(summary: &BatchSummary) -> String {
+    let mut text = format!(
+        "\nSummary: {} files, {} identified ({} ambiguous), {} no match, {} errors\n",
+        summary.files, summary.identified, summary.ambiguous, summary.no_match, summary.errors
+    );
+    for count in &summary.by_reference {
+        text.push_str(&format!(
+            "   {} ({}): {}\n",
+            count.reference, count.display_name, count.files
+        ));
+    }
+    text
+}
+
+fn print_tsv(rows: &[FileRow], summary: &BatchSummary) {
+    println!(
+        "path\treference\tdisplay_name\tmatch_type\tconfidence\tscore\tambiguous\ttied_with\terror"
+    );
+    for row in rows {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            row.path.display(),
+            row.reference
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            row.display_name.as_deref().unwrap_or_default(),
+            row.match_type.as_deref().unwrap_or_default(),
+            row.confidence.as_deref().unwrap_or_default(),
+            row.score
+                .map(|score| format!("{score:.4}"))
+                .unwrap_or_default(),
+            row.ambiguous,
+            row.tied_with
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            row.error.as_deref().unwrap_or_default(),
+        );
+    }
+
+    println!("\n# Summary by reference");
+    println!("reference\tdisplay_name\tfiles");
+    for count in &summary.by_reference {
+        println!(
+            "{}\t{}\t{}",
+            count.reference, count.display_name, count.files
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tree(files: &[&str]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for file in files {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, "").unwrap();
+        }
+        dir
+    }
+
+    /// The collected paths, with invalid inputs as `!path`
+    fn collected(inputs: &[PathBuf], recursive: bool) -> Vec<String> {
+        collect_files(inputs, recursive)
+            .into_iter()
+            .map(|file| match file {
+                BatchInput::File(path) => path.display().to_string(),
+                BatchInput::Invalid { path, .. } => format!("!{}", path.display()),
+            })
+            .collect()
+    }
+
+    fn row(reference: Option<&str>, ambiguous: bool, error: Option<&str>) -> FileRow {
+        let mut row = FileRow::new(PathBuf::from("file.bam"));
+        row.reference = reference.map(ReferenceId::new);
+        row.display_name = reference.map(|r| r.to_uppercase());
+        row.ambiguous = ambiguous;
+        row.error = error.map(String::from);
+        row
+    }
+
+    #[test]
+    fn test_collect_files_scans_directories() {
+        let dir = tree(&[
+            "b.bam",
+            "a.cram",
+            "notes.txt",
+            "sample.VCF.GZ",
+            "nested/c.dict",
+        ]);
+        let root = dir.path();
+        let path = |name: &str| root.join(name).display().to_string();
+
+        // Sorted by path, header files only, top level unless recursive
+        assert_eq!(
+            collected(&[root.to_path_buf()], false),
+            vec![path("a.cram"), path("b.bam"), path("sample.VCF.GZ")]
+        );
+        assert_eq!(
+            collected(&[root.to_path_buf()], true),
+            vec![
+                path("a.cram"),
+                path("b.bam"),
+                path("nested/c.dict"),
+                path("sample.VCF.GZ"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_files_keeps_input_order_without_duplicates() {
+        let dir = tree(&["a.bam", "b.bam", "notes.txt"]);
+        let root = dir.path();
+        let path = |name: &str| root.join(name).display().to_string();
+
+        // Files named explicitly are kept whatever their extension, and a
+        // file reached again through the directory is reported once
+        assert_eq!(
+            collected(
+                &[
+                    root.join("notes.txt"),
+                    root.join("b.bam"),
+                    root.to_path_buf(),
+                    root.join("missing.bam"),
+                    root.join("*.cram"),
+                    root.join("*.bam"),
+                ],
+                false,
+            ),
+            vec![
+                path("notes.txt"),
+                path("b.bam"),
+                path("a.bam"),
+                format!("!{}", path("missing.bam")),
+                format!("!{}", path("*.cram")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_files_reports_invalid_patterns() {
+        let dir = tree(&["a.bam"]);
+        let pattern = dir.path().join("[*.bam");
+
+        let files = collect_files(std::slice::from_ref(&pattern), false);
+        let [BatchInput::Invalid { path, error }] = files.as_slice() else {
+            panic!("expected one invalid input");
+        };
+        assert_eq!(path, &pattern);
+        assert!(error.starts_with("Invalid glob pattern: "), "{error}");
+    }
+
+    #[test]
+    fn test_summary_counts() {
+        let rows = vec![
+            row(Some("hg38"), false, None),
+            row(Some("b37"), true, None),
+            row(Some("hg38"), false, None),
+            row(None, false, None),
+            row(None, false, Some("unreadable")),
+        ];
+        let summary = BatchSummary::new(&rows);
+
+        assert_eq!(summary.files, 5);
+        assert_eq!(summary.identified, 3);
+        assert_eq!(summary.ambiguous, 1);
+        assert_eq!(summary.no_match, 1);
+        assert_eq!(summary.errors, 1);
+        // Most files first
+        let counts: Vec<(String, usize)> = summary
+            .by_reference
+            .iter()
+            .map(|count| (count.reference.to_string(), count.files))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("hg38".to_string(), 2), ("b37".to_string(), 1)]
+        );
+        assert!(summary_text(&summary).contains("5 files, 3 identified (1 ambiguous)"));
+    }
+}
@@ -0,0 +1,314 @@
+//! Joint mode of the identify command.
+//!
+//! Merges the headers of several files of one dataset into a single query,
+//! makes one reference call from it and checks every file against that call.
+
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::cli::identify::{self, IdentifyArgs, InputFormat};
+use crate::cli::OutputFormat;
+use crate::core::types::ReferenceId;
+use crate::matching::engine::{MatchResult, MatchingEngine};
+use crate::matching::joint::{self, FileConsistency, JointInput, JointQuery, TieBreak};
+use crate::parsing;
+use crate::parsing::digest_cache::DigestCache;
+
+/// A file's consistency with the joint call, and its own best match
+#[derive(Debug, Serialize)]
+struct FileReport {
+    #[serde(flatten)]
+    consistency: FileConsistency,
+    alone: Option<AloneMatch>,
+}
+
+/// The best match of a file identified on its own
+#[derive(Debug, Serialize)]
+struct AloneMatch {
+    reference: ReferenceId,
+    ambiguous: bool,
+    tied_with: Vec<ReferenceId>,
+}
+
+/// Identify the reference of the files in the arguments together
+///
+/// # Errors
+///
+/// Returns an error if any input cannot be read, or if the catalog cannot be
+/// loaded.
+pub(crate) fn run(args: &IdentifyArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    if args.inputs.iter().any(|path| path.to_string_lossy() == "-") {
+        anyhow::bail!("stdin ('-') is not supported with --joint");
+    }
+
+    let needs_cache = args
+        .inputs
+        .iter()
+        .any(|path| matches!(identify::detect_format(path), InputFormat::Fasta));
+    let cache = if needs_cache {
+        args.digest_cache.open()?
+    } else {
+        None
+    };
+    let inputs = args
+        .inputs
+        .iter()
+        .map(|path| {
+            read_input(path, cache.as_deref())
+                .with_context(|| format!("Failed to read {}", path.display()))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let joint = JointQuery::merge(&inputs);
+    if verbose {
+        eprintln!(
+            "Merged {} files into {} contigs ({} disagreements)",
+            inputs.len(),
+            joint.query.contigs.len(),
+            joint.disagreements.len()
+        );
+    }
+
+    let catalog = if let Some(path) = &args.catalog {
+        ReferenceCatalog::load_from_file(path)?
+    } else {
+        ReferenceCatalog::load_embedded()?
+    };
+    let engine = MatchingEngine::new(&catalog, args.matching_config());
+    let mut matches = engine.find_matches(&joint.query, args.max_matches.max(1));
+    let tie_break = joint::break_tie(&mut matches, &inputs);
+    let Some(best) = matches.first() else {
+        eprintln!("No matching references found.");
+        return Ok(());
+    };
+
+    let files: Vec<FileReport> = inputs
+        .iter()
+        .map(|input| FileReport {
+            consistency: FileConsistency::check(input, &best.reference, &catalog),
+            alone: engine
+                .find_matches(&input.query, args.max_matches.max(1))
+                .into_iter()
+                .next()
+                .map(|m| AloneMatch {
+                    reference: m.reference.id,
+                    ambiguous: m.ambiguity.ambiguous,
+                    tied_with: m.ambiguity.tied_with,
+                }),
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Text => print_text(best, &joint, tie_break.as_ref(), &files),
+        OutputFormat::Json => print_json(best, &joint, tie_break.as_ref(), &files)?,
+        OutputFormat::Tsv => print_tsv(best, &joint, tie_break.as_ref(), &files),
+    }
+    Ok(())
+}
+
+/// Read a file's header, and for VCFs the FASTA named by `##reference=`
+fn read_input(path: &Path, cache: Option<&DigestCache>) -> anyhow::Result<JointInput> {
+    let source = path.display().to_string();
+    match identify::detect_format(path) {
+        InputFormat::Vcf => {
+            let header = parsing::vcf::read_vcf_header(path)?;
+            let query = parsing::vcf::parse_vcf_header_text(&header)?;
+            Ok(JointInput::new(source, query)
+                .with_reference_line(parsing::vcf::reference_line(&header).map(String::from)))
+        }
+        format => Ok(JointInput::new(
+            source,
+            identify::parse_file(path, format, cache)?,
+        )),
+    }
+}
+
+fn join_ids(ids: &[ReferenceId]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_text(
+    best: &MatchResult,
+    joint: &JointQuery,
+    tie_break: Option<&TieBreak>,
+    files: &[FileReport],
+) {
+    println!(
+        "\nJoint call: {} ({})",
+        best.reference.display_name,
+        format!("{:?}", best.score.confidence).to_uppercase()
+    );
+    println!("   ID: {}", best.reference.id);
+    println!("   Match Type: {:?}", best.diagnosis.match_type);
+    println!("   Score: {:.1}%", best.score.composite * 100.0);
+    println!(
+        "   Evidence: {} files, {} contigs, {} with MD5",
+        files.len(),
+        joint.query.contigs.len(),
+        joint.query.md5_set.len()
+    );
+    if let Some(tie_break) = tie_break {
+        println!(
+            "   Tie with {} broken by {}, which names {}",
+            join_ids(&tie_break.over),
+            tie_break.source,
+            tie_break.reference_name
+        );
+    }
+    if best.ambiguity.ambiguous {
+        println!(
+            "   Ambiguous: indistinguishable from {} (margin {:+.1}%)",
+            join_ids(&best.ambiguity.tied_with),
+            best.ambiguity.margin.unwrap_or(0.0) * 100.0
+        );
+    }
+
+    println!("\nFiles:");
+    for file in files {
+        let check = &file.consistency;
+        let alone = match &file.alone {
+            Some(alone) if alone.ambiguous => format!(
+                "{} (tied with {})",
+                alone.reference,
+                join_ids(&alone.tied_with)
+            ),
+            Some(alone) => alone.reference.to_string(),
+            None => "no match".to_string(),
+        };
+        println!(
+            "   {}: {} ({:?}, {} contigs); alone: {alone}",
+            check.source,
+            if check.consistent {
+                "consistent"
+            } else {
+                "INCONSISTENT"
+            },
+            check.match_type,
+            check.contigs,
+        );
+        for conflict in &check.conflicts {
+            println!("      - {conflict}");
+        }
+        if !check.not_in_reference.is_empty() {
+            println!(
+                "      - {} contigs not in {}: {}",
+                check.not_in_reference.len(),
+                best.reference.id,
+                check.not_in_reference.join(", ")
+            );
+        }
+        if let Some(other) = &check.names_other_reference {
+            println!("      - Names another reference: {other}");
+        }
+    }
+
+    if !joint.disagreements.is_empty() {
+        println!("\nDisagreements between files:");
+        for disagreement in &joint.disagreements {
+            println!("   - {}", disagreement.description);
+        }
+    }
+    println!();
+}
+
+fn print_json(
+    best: &MatchResult,
+    joint: &JointQuery,
+    tie_break: Option<&TieBreak>,
+    files: &[FileReport],
+) -> anyhow::Result<()> {
+    let output = serde_json::json!({
+        "call": {
+            "reference": {
+                "id": best.reference.id.0,
+                "display_name": best.reference.display_name,
+                "assembly": format!("{}", best.reference.assembly),
+                "source": format!("{}", best.reference.source),
+            },
+            "match_type": format!("{:?}", best.diagnosis.match_type),
+            "confidence": format!("{:?}", best.score.confidence),
+            "score": best.score.composite,
+            "ambiguity": best.ambiguity,
+            "tie_break": tie_break,
+        },
+        "evidence": {
+            "files": files.len(),
+            "contigs": joint.query.contigs.len(),
+            "with_md5": joint.query.md5_set.len(),
+        },
+        "files": files,
+        "disagreements": joint.disagreements,
+        "consistent": joint.disagreements.is_empty()
+            && files.iter().all(|f| f.consistency.consistent),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_tsv(
+    best: &MatchResult,
+    joint: &JointQuery,
+    tie_break: Option<&TieBreak>,
+    files: &[FileReport],
+) {
+    println!(
+        "reference\tdisplay_name\tmatch_type\tconfidence\tscore\tambiguous\ttied_with\ttie_broken_by"
+    );
+    println!(
+        "{}\t{}\t{:?}\t{:?}\t{:.4}\t{}\t{}\t{}",
+        best.reference.id,
+        best.reference.display_name,
+        best.diagnosis.match_type,
+        best.score.confidence,
+        best.score.composite,
+        best.ambiguity.ambiguous,
+        best.ambiguity
+            .tied_with
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+        tie_break.map_or("", |t| t.source.as_str()),
+    );
+
+    println!("\n# Files");
+    println!("file\tcontigs\tmatch_type\tconsistent\tconflicts\tnot_in_reference\tnames_other_reference\talone_reference\talone_ambiguous");
+    for file in files {
+        let check = &file.consistency;
+        println!(
+            "{}\t{}\t{:?}\t{}\t{}\t{}\t{}\t{}\t{}",
+            check.source,
+            check.contigs,
+            check.match_type,
+            check.consistent,
+            check.conflicts.len(),
+            check.not_in_reference.join(","),
+            check.names_other_reference.as_deref().unwrap_or_default(),
+            file.alone
+                .as_ref()
+                .map(|a| a.reference.to_string())
+                .unwrap_or_default(),
+            file.alone.as_ref().is_some_and(|a| a.ambiguous),
+        );
+    }
+
+    if !joint.disagreements.is_empty() {
+        println!("\n# Disagreements between files");
+        println!("contig\tfirst_file\tfile\tdescription");
+        for disagreement in &joint.disagreements {
+            println!(
+                "{}\t{}\t{}\t{}",
+                disagreement.contig,
+                disagreement.first_source,
+                disagreement.source,
+                disagreement.description
+            );
+        }
+    }
+}
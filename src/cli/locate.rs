@@ -0,0 +1,220 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+use crate::catalog::local::{self, LocalIndex, LocalReference, ScanResult};
+use crate::cli::identify::{self, detect_format, InputFormat};
+use crate::cli::{DigestCacheArgs, OutputFormat};
+use crate::core::header::QueryHeader;
+use crate::core::types::Confidence;
+use crate::matching::engine::{MatchResult, MatchingConfig, MatchingEngine};
+use crate::parsing;
+use crate::parsing::digest_cache::DigestCache;
+
+#[derive(Args)]
+pub struct LocateArgs {
+    /// Input file (BAM, SAM, CRAM, FASTA, FAI, VCF, .dict, TSV, or CSV)
+    /// Use '-' for stdin (expects header text)
+    #[arg(required = true)]
+    pub input: PathBuf,
+
+    /// Directory to search for reference files (.fa/.fasta/.fna, optionally
+    /// gzipped, .dict, .fai and .2bit), recursively. May be repeated.
+    #[arg(long, value_name = "DIR", required = true, num_args = 1..)]
+    pub search: Vec<PathBuf>,
+
+    /// Number of matching files to show
+    #[arg(short = 'n', long, default_value = "5")]
+    pub max_matches: usize,
+
+    /// Input format (auto-detected by default)
+    #[arg(long)]
+    pub input_format: Option<InputFormat>,
+
+    /// Path to the local reference index (default: ~/.cache/ref-solver/local-index.json)
+    #[arg(long)]
+    pub index_cache: Option<PathBuf>,
+
+    /// Do not read or write the local reference index
+    #[arg(long)]
+    pub no_index_cache: bool,
+
+    #[command(flatten)]
+    pub digest_cache: DigestCacheArgs,
+}
+
+/// Execute locate subcommand
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be parsed or the index cannot be read.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: LocateArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let digest_cache = args.digest_cache.open()?;
+    let query = parse_input(&args, digest_cache.as_deref())?;
+
+    let mut index = open_index(&args)?;
+    let scan = index.scan(&args.search, digest_cache.as_deref());
+    if let Err(e) = index.save() {
+        tracing::warn!("Failed to save local reference index: {e}");
+    }
+
+    if verbose {
+        eprintln!(
+            "Found {} reference files ({} from the index or digest cache, {} read)",
+            scan.references.len(),
+            scan.reused,
+            scan.indexed
+        );
+        for (path, reason) in &scan.skipped {
+            eprintln!("Skipped {}: {reason}", path.display());
+        }
+    }
+
+    let catalog = local::to_catalog(&scan.references);
+    let engine = MatchingEngine::new(&catalog, MatchingConfig::default());
+    let matches = engine.find_matches(&query, args.max_matches);
+
+    match format {
+        OutputFormat::Text => print_text_results(&matches, &scan),
+        OutputFormat::Json => print_json_results(&matches, &scan, &args.search)?,
+        OutputFormat::Tsv => print_tsv_results(&matches, &scan),
+    }
+
+    Ok(())
+}
+
+fn open_index(args: &LocateArgs) -> anyhow::Result<LocalIndex> {
+    if args.no_index_cache {
+        return Ok(LocalIndex::in_memory());
+    }
+    let Some(path) = args.index_cache.clone().or_else(LocalIndex::default_path) else {
+        tracing::debug!("No cache directory available, local reference index disabled");
+        return Ok(LocalIndex::in_memory());
+    };
+    Ok(LocalIndex::open(path)?)
+}
+
+fn parse_input(args: &LocateArgs, cache: Option<&DigestCache>) -> anyhow::Result<QueryHeader> {
+    use std::io::{self, Read};
+
+    // Handle stdin
+    if args.input.to_string_lossy() == "-" {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        return Ok(parsing::sam::parse_header_text(&buffer)?);
+    }
+
+    let path = &args.input;
+    let format = args.input_format.unwrap_or_else(|| detect_format(path));
+    identify::parse_file(path, format, cache)
+}
+
+/// The scanned file a match refers to (match IDs are file paths)
+fn local_reference<'a>(result: &MatchResult, scan: &'a ScanResult) -> Option<&'a LocalReference> {
+    scan.references
+        .iter()
+        .find(|reference| reference.path == Path::new(&result.reference.id.0))
+}
+
+fn print_text_results(matches: &[MatchResult], scan: &ScanResult) {
+    if matches.is_empty() {
+        println!(
+            "No matching reference files among {} found.",
+            scan.references.len()
+        );
+        return;
+    }
+
+    for (i, result) in matches.iter().enumerate() {
+        let confidence_str = match result.score.confidence {
+            Confidence::Exact => "EXACT",
+            Confidence::High => "HIGH",
+            Confidence::Medium => "MEDIUM",
+            Confidence::Low => "LOW",
+        };
+        println!("#{} {} ({confidence_str})", i + 1, result.reference.id);
+        println!("   Match Type: {:?}", result.diagnosis.match_type);
+        println!("   Score: {:.1}%", result.score.composite * 100.0);
+        let reference = local_reference(result, scan);
+        if let Some(reference) = reference {
+            if reference.source == reference.path {
+                println!("   Read from: {}", reference.kind);
+            } else {
+                println!("   Read from: {}", reference.source.display());
+            }
+        }
+        if result.ambiguity.ambiguous {
+            let tied = result
+                .ambiguity
+                .tied_with
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            if reference.is_some_and(|r| r.md5_coverage() < 1.0) {
+                println!(
+                    "   Ambiguous: indistinguishable from {tied} by names and lengths; \
+                     MD5s (e.g. from `samtools dict`) would tell them apart"
+                );
+            } else {
+                println!("   Ambiguous: same sequences as {tied}");
+            }
+        }
+        println!();
+    }
+}
+
+fn print_json_results(
+    matches: &[MatchResult],
+    scan: &ScanResult,
+    searched: &[PathBuf],
+) -> anyhow::Result<()> {
+    let results: Vec<serde_json::Value> = matches
+        .iter()
+        .map(|m| {
+            let reference = local_reference(m, scan);
+            serde_json::json!({
+                "path": m.reference.id.0,
+                "kind": reference.map(|r| r.kind),
+                "source": reference.map(|r| &r.source),
+                "md5_coverage": reference.map(LocalReference::md5_coverage),
+                "match_type": format!("{:?}", m.diagnosis.match_type),
+                "confidence": format!("{:?}", m.score.confidence),
+                "score": m.score.composite,
+                "ambiguity": m.ambiguity,
+            })
+        })
+        .collect();
+
+    let output = serde_json::json!({
+        "searched": searched,
+        "files_found": scan.references.len(),
+        "skipped": scan
+            .skipped
+            .iter()
+            .map(|(path, reason)| serde_json::json!({"path": path, "reason": reason}))
+            .collect::<Vec<_>>(),
+        "matches": results,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_tsv_results(matches: &[MatchResult], scan: &ScanResult) {
+    println!("rank\tpath\tkind\tsource\tmatch_type\tconfidence\tscore\tambiguous");
+    for (i, m) in matches.iter().enumerate() {
+        let reference = local_reference(m, scan);
+        println!(
+            "{}\t{}\t{}\t{}\t{:?}\t{:?}\t{:.4}\t{}",
+            i + 1,
+            m.reference.id,
+            reference.map_or_else(String::new, |r| r.kind.to_string()),
+            reference.map_or_else(String::new, |r| r.source.display().to_string()),
+            m.diagnosis.match_type,
+            m.score.confidence,
+            m.score.composite,
+            m.ambiguity.ambiguous,
+        );
+    }
+}
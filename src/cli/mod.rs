@@ -4,7 +4,18 @@
 //!
 //! - **identify**: Identify the reference genome from a BAM/SAM/CRAM file
 //! - **compare**: Compare two headers or a header against a known reference
+//! - **check-compat**: Check that a pipeline's input files have compatible contigs
+//! - **assert**: Check an input's reference against an expectation or policy file
+//! - **tool-compat**: Predict whether GATK, Picard, bcftools and DeepVariant accept a file pair
+//! - **locate**: Find the matching reference files among local directories
 //! - **catalog**: List, show, or export references from the catalog
+//! - **annotate-header**: Fill in missing `@SQ` tags from the identified reference
+//! - **convert-names**: Rename contigs in VCF, BED, GTF/GFF and interval list files
+//! - **dict**: Create a sequence dictionary for a FASTA, annotated from the catalog
+//! - **reheader**: Rewrite an alignment file's header to match a catalog reference
+//! - **verify-fasta**: Verify a FASTA contig-by-contig against a catalog reference
+//! - **seqcol**: Compute GA4GH sequence collection digests
+//! - **refget**: Manage the persistent refget lookup cache
 //! - **serve**: Start the interactive web interface
 //!
 //! ## Usage
@@ -26,12 +37,30 @@
 //! ref-solver serve --port 8080 --open
 //! ```
 
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use clap::{Parser, Subcommand};
 
+use crate::parsing::digest_cache::DigestCache;
+
+pub mod annotate_header;
+pub mod assert;
 pub mod catalog;
+pub mod check_compat;
 pub mod compare;
+pub mod convert_names;
+pub mod dict;
 pub mod identify;
+pub mod identify_batch;
+pub mod identify_joint;
+pub mod locate;
+pub mod refget;
+pub mod reheader;
 pub mod score;
+pub mod seqcol;
+pub mod tool_compat;
+pub mod verify_fasta;
 
 #[derive(Parser)]
 #[command(name = "ref-solver")]
@@ -63,6 +92,20 @@ pub enum Commands {
     /// Compare two headers or references
     Compare(compare::CompareArgs),
 
+    /// Check that files have mutually compatible contigs (exits non-zero if not)
+    CheckCompat(check_compat::CheckCompatArgs),
+
+    /// Check that an input's reference meets an expectation or policy
+    /// (exits with status 2 if not)
+    Assert(assert::AssertArgs),
+
+    /// Predict whether GATK, Picard, bcftools and DeepVariant will accept an
+    /// input with a reference, by each tool's dictionary checks
+    ToolCompat(tool_compat::ToolCompatArgs),
+
+    /// Find which local reference files match a BAM/SAM/VCF header
+    Locate(locate::LocateArgs),
+
     /// Score a query file against a reference file directly (no catalog).
     /// By default, scoring is asymmetric: it measures how well the query
     /// matches the reference. Use --symmetric to compute both directions.
@@ -71,6 +114,31 @@ pub enum Commands {
     /// Manage the reference catalog
     Catalog(catalog::CatalogArgs),
 
+    /// Fill in missing M5, AS, SP and AN tags of a header's @SQ lines from the
+    /// identified catalog reference
+    AnnotateHeader(annotate_header::AnnotateHeaderArgs),
+
+    /// Rename the contigs of a VCF, BED, GTF/GFF or interval list file to a
+    /// catalog reference's names or a naming convention
+    ConvertNames(convert_names::ConvertNamesArgs),
+
+    /// Create a sequence dictionary (.dict) for a FASTA, with AS, SP and AN
+    /// tags from the matching catalog reference
+    Dict(dict::DictArgs),
+
+    /// Rename and reorder the contigs of a BAM/SAM/CRAM file to match a catalog
+    /// reference, remapping every record
+    Reheader(reheader::ReheaderArgs),
+
+    /// Verify that a FASTA is identical to a catalog reference (exits non-zero if not)
+    VerifyFasta(verify_fasta::VerifyFastaArgs),
+
+    /// GA4GH sequence collection (seqcol) tools
+    Seqcol(seqcol::SeqcolArgs),
+
+    /// Refget tools (lookup cache management)
+    Refget(refget::RefgetArgs),
+
     /// Start the web server
     Serve(ServeArgs),
 }
@@ -97,6 +165,50 @@ pub struct ServeArgs {
     /// Disable refget lookups for unmatched contigs
     #[arg(long)]
     pub no_refget: bool,
+
+    #[command(flatten)]
+    pub refget_cache: refget::RefgetCacheArgs,
+
+    /// Directory of indexed FASTA files (.fa/.fasta/.fna with .fai) to serve via the
+    /// refget sequences API at /sequence/. May be repeated.
+    #[arg(long, value_name = "DIR")]
+    pub refget_fasta: Vec<PathBuf>,
+
+    /// Cache for the digests of the --refget-fasta sequences, which are hashed
+    /// at startup
+    #[command(flatten)]
+    pub digest_cache: DigestCacheArgs,
+}
+
+/// FASTA digest cache options shared by commands that hash FASTA files
+#[derive(clap::Args, Clone, Debug)]
+pub struct DigestCacheArgs {
+    /// Path to the FASTA digest cache (default: ~/.cache/ref-solver/fasta-digests.json)
+    #[arg(long)]
+    pub digest_cache: Option<PathBuf>,
+
+    /// Do not read or write the FASTA digest cache
+    #[arg(long)]
+    pub no_digest_cache: bool,
+}
+
+impl DigestCacheArgs {
+    /// Open the configured cache, or `None` if caching is disabled or no
+    /// default location is available.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file exists but cannot be read.
+    pub fn open(&self) -> anyhow::Result<Option<Arc<DigestCache>>> {
+        if self.no_digest_cache {
+            return Ok(None);
+        }
+        let Some(path) = self.digest_cache.clone().or_else(DigestCache::default_path) else {
+            tracing::debug!("No cache directory available, FASTA digest cache disabled");
+            return Ok(None);
+        };
+        Ok(Some(Arc::new(DigestCache::open(path)?)))
+    }
 }
 
 #[derive(Clone, Copy, Debug, clap::ValueEnum)]
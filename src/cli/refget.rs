@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+use clap::{Args, Subcommand};
+
+use crate::cli::OutputFormat;
+use crate::refget::cache::{CacheEntry, RefgetCache, DEFAULT_CACHE_TTL_DAYS};
+use crate::refget::RefgetLookupResult;
+
+#[derive(Args)]
+pub struct RefgetArgs {
+    #[command(subcommand)]
+    pub command: RefgetCommands,
+}
+
+#[derive(Subcommand)]
+#[non_exhaustive]
+pub enum RefgetCommands {
+    /// Manage the persistent refget lookup cache
+    Cache(CacheArgs),
+}
+
+#[derive(Args)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommands,
+
+    /// Path to the cache file (default: ~/.cache/ref-solver/refget-cache.json)
+    #[arg(long, global = true)]
+    pub path: Option<PathBuf>,
+
+    /// Days before a cached lookup expires
+    #[arg(long, global = true, default_value_t = DEFAULT_CACHE_TTL_DAYS)]
+    pub ttl_days: u64,
+}
+
+#[derive(Subcommand)]
+#[non_exhaustive]
+pub enum CacheCommands {
+    /// List cached lookups
+    List,
+
+    /// Remove expired lookups from the cache
+    Prune,
+
+    /// Merge lookups from another cache file (newer entries win)
+    Import {
+        /// Cache file to import
+        #[arg(required = true)]
+        input: PathBuf,
+    },
+
+    /// Write unexpired lookups to a file, e.g. to seed an offline environment
+    Export {
+        /// Output cache file
+        #[arg(required = true)]
+        output: PathBuf,
+    },
+}
+
+/// Refget cache options shared by commands that query a refget server
+#[derive(Args, Clone, Debug)]
+pub struct RefgetCacheArgs {
+    /// Path to the refget lookup cache (default: ~/.cache/ref-solver/refget-cache.json)
+    #[arg(long)]
+    pub refget_cache: Option<PathBuf>,
+
+    /// Days before a cached refget lookup expires
+    #[arg(long, default_value_t = DEFAULT_CACHE_TTL_DAYS)]
+    pub refget_cache_ttl_days: u64,
+
+    /// Do not read or write the refget lookup cache
+    #[arg(long)]
+    pub no_refget_cache: bool,
+}
+
+impl RefgetCacheArgs {
+    /// Open the configured cache, or `None` if caching is disabled or no
+    /// default location is available.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file exists but cannot be read.
+    pub fn open(&self) -> anyhow::Result<Option<Arc<RefgetCache>>> {
+        if self.no_refget_cache {
+            return Ok(None);
+        }
+        let Some(path) = self.refget_cache.clone().or_else(RefgetCache::default_path) else {
+            tracing::debug!("No cache directory available, refget cache disabled");
+            return Ok(None);
+        };
+        let cache = RefgetCache::open(path, self.refget_cache_ttl_days)?;
+        Ok(Some(Arc::new(cache)))
+    }
+}
+
+/// Execute refget subcommand
+///
+/// # Errors
+///
+/// Returns an error if the cache cannot be read or written.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: RefgetArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    match args.command {
+        RefgetCommands::Cache(cache_args) => run_cache(&cache_args, format, verbose),
+    }
+}
+
+fn run_cache(args: &CacheArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let path = args
+        .path
+        .clone()
+        .or_else(RefgetCache::default_path)
+        .context("No cache location available; set --path")?;
+    let cache = RefgetCache::open(&path, args.ttl_days)?;
+
+    if verbose {
+        eprintln!("Using refget cache at {}", cache.path().display());
+    }
+
+    match &args.command {
+        CacheCommands::List => print_entries(&cache, format)?,
+        CacheCommands::Prune => {
+            let removed = cache.prune();
+            cache.save()?;
+            println!("Removed {removed} expired entries");
+        }
+        CacheCommands::Import { input } => {
+            let merged = cache.import(input)?;
+            cache.save()?;
+            println!("Imported {merged} entries from {}", input.display());
+        }
+        CacheCommands::Export { output } => {
+            let exported = cache.export(output)?;
+            println!("Exported {exported} entries to {}", output.display());
+        }
+    }
+
+    Ok(())
+}
+
+fn print_entries(cache: &RefgetCache, format: OutputFormat) -> anyhow::Result<()> {
+    let entries = cache.entries();
+
+    match format {
+        OutputFormat::Text => {
+            println!("Refget cache: {}", cache.path().display());
+            println!("{} entries\n", entries.len());
+            for entry in &entries {
+                let expired = if cache.is_expired(entry) {
+                    " (expired)"
+                } else {
+                    ""
+                };
+                println!(
+                    "  {}  {:<9}  {}{expired}  {}  {}",
+                    entry.digest,
+                    status(entry),
+                    entry.fetched_at.format("%Y-%m-%d %H:%M"),
+                    entry.server,
+                    aliases(entry)
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let output: Vec<_> = entries
+                .iter()
+                .map(|entry| {
+                    serde_json::json!({
+                        "digest": entry.digest,
+                        "fetched_at": entry.fetched_at,
+                        "server": entry.server,
+                        "expired": cache.is_expired(entry),
+                        "result": entry.result,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Tsv => {
+            println!("digest\tstatus\tfetched_at\texpired\tserver\taliases");
+            for entry in &entries {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    entry.digest,
+                    status(entry),
+                    entry.fetched_at.to_rfc3339(),
+                    cache.is_expired(entry),
+                    entry.server,
+                    aliases(entry)
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn status(entry: &CacheEntry) -> &'static str {
+    match entry.result {
+        RefgetLookupResult::Found { .. } => "found",
+        RefgetLookupResult::NotFound => "not_found",
+        RefgetLookupResult::Error { .. } => "error",
+    }
+}
+
+fn aliases(entry: &CacheEntry) -> String {
+    match &entry.result {
+        RefgetLookupResult::Found { aliases, .. } => aliases
+            .iter()
+            .map(|a| a.value.as_str())
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use noodles::sam::alignment::record::data::field::Tag;
+use noodles::sam::alignment::record_buf::data::field::Value;
+use noodles::sam::alignment::record_buf::Data;
+use noodles::sam::alignment::{Record, RecordBuf};
+use noodles::sam::header::record::value::map::{self, Program, ReferenceSequence};
+use noodles::sam::header::record::value::Map;
+use noodles::{bam, cram, fasta, sam};
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::cli::OutputFormat;
+use crate::core::types::ReferenceId;
+use crate::matching::diagnosis::MatchDiagnosis;
+use crate::matching::reheader::{self, ReheaderPlan};
+use crate::parsing;
+
+#[derive(Args)]
+pub struct ReheaderArgs {
+    /// Input alignment file (BAM, SAM or CRAM)
+    #[arg(required = true)]
+    pub input: PathBuf,
+
+    /// ID of the catalog reference whose dictionary the output should have
+    #[arg(long = "to", value_name = "REFERENCE_ID", required = true)]
+    pub reference_id: String,
+
+    /// Output alignment file; the format follows the extension (.bam, .sam or .cram)
+    #[arg(short, long, required = true)]
+    pub output: PathBuf,
+
+    /// Indexed FASTA of the target reference, required to read or write CRAM
+    #[arg(long)]
+    pub reference_fasta: Option<PathBuf>,
+
+    /// Keep contigs that are not in the reference, after the reference's contigs,
+    /// instead of refusing to reheader
+    #[arg(long)]
+    pub append_unmatched: bool,
+
+    /// Path to custom catalog file
+    #[arg(long)]
+    pub catalog: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AlignmentFormat {
+    Sam,
+    Bam,
+    Cram,
+}
+
+impl AlignmentFormat {
+    fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("sam") => Ok(Self::Sam),
+            Some("bam") => Ok(Self::Bam),
+            Some("cram") => Ok(Self::Cram),
+            _ => anyhow::bail!(
+                "Cannot tell the format of {} (expected .bam, .sam or .cram)",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Execute reheader subcommand
+///
+/// # Errors
+///
+/// Returns an error if the input or catalog cannot be read, the header
+/// conflicts with the target reference, or the output cannot be written.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: ReheaderArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let input_format = AlignmentFormat::from_path(&args.input)?;
+    let output_format = AlignmentFormat::from_path(&args.output)?;
+    if args.output.exists()
+        && std::fs::canonicalize(&args.output)? == std::fs::canonicalize(&args.input)?
+    {
+        anyhow::bail!("Output must differ from the input");
+    }
+    if (input_format == AlignmentFormat::Cram || output_format == AlignmentFormat::Cram)
+        && args.reference_fasta.is_none()
+    {
+        anyhow::bail!("--reference-fasta is required to read or write CRAM");
+    }
+
+    let catalog = if let Some(path) = &args.catalog {
+        ReferenceCatalog::load_from_file(path)?
+    } else {
+        ReferenceCatalog::load_embedded()?
+    };
+    let reference = catalog
+        .get(&ReferenceId::new(&args.reference_id))
+        .ok_or_else(|| anyhow::anyhow!("Reference '{}' not found in catalog", args.reference_id))?;
+
+    let header = parsing::sam::read_header(&args.input)?;
+    let query = parsing::sam::header_to_query(&header, Some(&args.input))?;
+    let diagnosis = MatchDiagnosis::analyze(&query, reference);
+    let plan = ReheaderPlan::build(&query, reference, &diagnosis, args.append_unmatched)
+        .map_err(|e| anyhow::anyhow!("Refusing to reheader to {}: {e}", reference.id))?;
+    if verbose {
+        for (old, new) in &plan.renamed {
+            eprintln!("Renaming {old} -> {new}");
+        }
+    }
+
+    let new_header = build_header(&header, &plan)?;
+
+    // Write next to the output and move it into place once complete, so a
+    // failed run leaves no partial file
+    let dir = match args.output.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let temp = tempfile::NamedTempFile::new_in(dir)?;
+    let mut writer = open_writer(&args, output_format, temp.as_file().try_clone()?)?;
+    writer.write_alignment_header(&new_header)?;
+
+    let mut records = 0u64;
+    let renames = plan.renames();
+    let remap = |id: &mut Option<usize>| {
+        if let Some(old) = *id {
+            *id = Some(plan.mapping[old]);
+        }
+    };
+    read_records(&args, input_format, &plan, |mut record| {
+        remap(record.reference_sequence_id_mut());
+        remap(record.mate_reference_sequence_id_mut());
+        if !renames.is_empty() {
+            rename_tags(record.data_mut(), &renames);
+        }
+        writer.write_alignment_record(&new_header, &record)?;
+        records += 1;
+        Ok(())
+    })?;
+    writer.finish(&new_header)?;
+    drop(writer);
+    // Temporary files are created private to the user; give the output the
+    // input's permissions instead
+    std::fs::set_permissions(temp.path(), std::fs::metadata(&args.input)?.permissions())?;
+    temp.persist(&args.output).map_err(|e| e.error)?;
+
+    let resorted = plan.reordered && is_coordinate_sorted(&header);
+    if resorted {
+        tracing::warn!(
+            "Contigs were reordered, so {} is no longer coordinate-sorted; sort it before indexing",
+            args.output.display()
+        );
+    }
+
+    match format {
+        OutputFormat::Text => {
+            println!(
+                "Wrote {} with the dictionary of {} ({})",
+                args.output.display(),
+                reference.id,
+                reference.display_name
+            );
+            println!("   Records: {records}");
+            println!("   Contigs renamed: {}", plan.renamed.len());
+            println!(
+                "   Contigs reordered: {}",
+                if plan.reordered { "yes" } else { "no" }
+            );
+            if !plan.appended.is_empty() {
+                println!(
+                    "   Appended (not in reference): {}",
+                    plan.appended.join(", ")
+                );
+            }
+            if resorted {
+                println!("   Output is unsorted; run `samtools sort` before indexing");
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "input": args.input,
+                "output": args.output,
+                "reference": reference.id.0,
+                "records": records,
+                "renamed": plan
+                    .renamed
+                    .iter()
+                    .map(|(from, to)| serde_json::json!({"from": from, "to": to}))
+                    .collect::<Vec<_>>(),
+                "reordered": plan.reordered,
+                "appended": plan.appended,
+                "needs_sort": resorted,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Tsv => {
+            println!("from\tto");
+            for (from, to) in &plan.renamed {
+                println!("{from}\t{to}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The input header with its dictionary replaced by the plan's, marked
+/// unsorted if reordering broke coordinate order, and a `@PG` line added
+fn build_header(header: &sam::Header, plan: &ReheaderPlan) -> anyhow::Result<sam::Header> {
+    let mut new_header = header.clone();
+
+    let reference_sequences = new_header.reference_sequences_mut();
+    reference_sequences.clear();
+    for contig in &plan.contigs {
+        let length = usize::try_from(contig.length)
+            .ok()
+            .and_then(NonZeroUsize::new)
+            .ok_or_else(|| anyhow::anyhow!("Contig {} has invalid length", contig.name))?;
+        let mut map = Map::<ReferenceSequence>::new(length);
+        parsing::sam::fill_reference_sequence_tags(&mut map, contig);
+        reference_sequences.insert(contig.name.as_str().into(), map);
+    }
+
+    if plan.reordered && is_coordinate_sorted(header) {
+        if let Some(hd) = new_header.header_mut() {
+            hd.other_fields_mut()
+                .insert(map::header::tag::SORT_ORDER, "unsorted".into());
+        }
+    }
+
+    let program = Map::<Program>::builder()
+        .insert(map::program::tag::NAME, "ref-solver")
+        .insert(map::program::tag::VERSION, env!("CARGO_PKG_VERSION"))
+        .insert(
+            map::program::tag::COMMAND_LINE,
+            std::env::args().collect::<Vec<_>>().join(" "),
+        )
+        .build()?;
+    new_header.programs_mut().add("ref-solver", program)?;
+
+    Ok(new_header)
+}
+
+/// Rename the contigs of a record's `SA` and `XA` tags
+fn rename_tags(data: &mut Data, renames: &HashMap<&str, &str>) {
+    for [a, b] in reheader::ALIGNMENT_LIST_TAGS {
+        if let Some(Value::String(value)) = data.get_mut(&Tag::new(a, b)) {
+            let renamed = std::str::from_utf8(value)
+                .ok()
+                .and_then(|list| reheader::rename_alignment_list(list, renames));
+            if let Some(renamed) = renamed {
+                *value = renamed.into();
+            }
+        }
+    }
+}
+
+fn is_coordinate_sorted(header: &sam::Header) -> bool {
+    header.header().is_some_and(|hd| {
+        hd.other_fields()
+            .get(&map::header::tag::SORT_ORDER)
+            .is_some_and(|so| so == "coordinate")
+    })
+}
+
+fn open_writer(
+    args: &ReheaderArgs,
+    format: AlignmentFormat,
+    file: File,
+) -> anyhow::Result<Box<dyn sam::alignment::io::Write>> {
+    Ok(match format {
+        AlignmentFormat::Sam => Box::new(SamWriter(sam::io::Writer::new(BufWriter::new(file)))),
+        AlignmentFormat::Bam => Box::new(bam::io::Writer::new(file)),
+        AlignmentFormat::Cram => {
+            // The FASTA has the target reference's names, as does the new header
+            let repository = fasta::Repository::new(indexed_fasta(args)?);
+            Box::new(
+                cram::io::writer::Builder::default()
+                    .set_reference_sequence_repository(repository)
+                    .build_from_writer(BufWriter::new(file)),
+            )
+        }
+    })
+}
+
+/// SAM writer whose `finish` flushes its buffer, so write errors surface
+/// before the output is moved into place
+struct SamWriter(sam::io::Writer<BufWriter<File>>);
+
+impl sam::alignment::io::Write for SamWriter {
+    fn write_alignment_header(&mut self, header: &sam::Header) -> io::Result<()> {
+        self.0.write_alignment_header(header)
+    }
+
+    fn write_alignment_record(
+        &mut self,
+        header: &sam::Header,
+        record: &dyn Record,
+    ) -> io::Result<()> {
+        self.0.write_alignment_record(header, record)
+    }
+
+    fn finish(&mut self, _: &sam::Header) -> io::Result<()> {
+        io::Write::flush(self.0.get_mut())
+    }
+}
+
+/// Read every record of the input, passing each to `process`
+fn read_records(
+    args: &ReheaderArgs,
+    format: AlignmentFormat,
+    plan: &ReheaderPlan,
+    mut process: impl FnMut(RecordBuf) -> io::Result<()>,
+) -> anyhow::Result<()> {
+    match format {
+        AlignmentFormat::Sam => {
+            let mut reader = sam::io::Reader::new(BufReader::new(File::open(&args.input)?));
+            let header = reader.read_header()?;
+            for record in reader.record_bufs(&header) {
+                process(record?)?;
+            }
+        }
+        AlignmentFormat::Bam => {
+            let mut reader = bam::io::Reader::new(File::open(&args.input)?);
+            let header = reader.read_header()?;
+            for record in reader.record_bufs(&header) {
+                process(record?)?;
+            }
+        }
+        AlignmentFormat::Cram => {
+            // The input names its sequences by its own contig names; look them
+            // up in the target FASTA under their new names
+            let header = parsing::sam::read_header(&args.input)?;
+            let names = header
+                .reference_sequences()
+                .keys()
+                .zip(&plan.mapping)
+                .map(|(old, &index)| (old.to_vec(), plan.contigs[index].name.clone().into_bytes()))
+                .collect();
+            let repository = fasta::Repository::new(RenamingAdapter {
+                inner: indexed_fasta(args)?,
+                names,
+            });
+            let mut reader = cram::io::reader::Builder::default()
+                .set_reference_sequence_repository(repository)
+                .build_from_path(&args.input)?;
+            let header = reader.read_header()?;
+            for record in reader.records(&header) {
+                let record = record?;
+                process(RecordBuf::try_from_alignment_record(&header, &record)?)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+type IndexedFasta = fasta::repository::adapters::IndexedReader<fasta::io::BufReader<File>>;
+
+fn indexed_fasta(args: &ReheaderArgs) -> anyhow::Result<IndexedFasta> {
+    let path = args
+        .reference_fasta
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("--reference-fasta is required to read or write CRAM"))?;
+    let reader = fasta::io::indexed_reader::Builder::default().build_from_path(path)?;
+    Ok(fasta::repository::adapters::IndexedReader::new(reader))
+}
+
+/// Looks up sequences under their names in the target reference
+struct RenamingAdapter {
+    inner: IndexedFasta,
+    names: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl fasta::repository::Adapter for RenamingAdapter {
+    fn get(&mut self, name: &[u8]) -> Option<io::Result<fasta::Record>> {
+        let name = self.names.get(name).map_or(name, Vec::as_slice).to_vec();
+        self.inner.get(&name)
+    }
+}
@@ -7,13 +7,14 @@ use std::path::{Path, PathBuf};
 
 use clap::Args;
 
-use crate::cli::OutputFormat;
+use crate::cli::{DigestCacheArgs, OutputFormat};
 use crate::core::header::QueryHeader;
 use crate::core::reference::KnownReference;
 use crate::core::types::{Assembly, ReferenceSource};
-use crate::matching::engine::ScoringWeights;
-use crate::matching::scoring::MatchScore;
+use crate::matching::engine::{ScoringMode, ScoringWeights};
+use crate::matching::scoring::{LengthWeightedScore, MatchScore};
 use crate::parsing;
+use crate::parsing::digest_cache::DigestCache;
 
 /// Arguments for the score command
 #[derive(Args)]
@@ -49,6 +50,14 @@ pub struct ScoreArgs {
     /// Whether contigs appear in the same order
     #[arg(long, default_value = "10", value_parser = clap::value_parser!(u32).range(0..=100))]
     pub weight_order: u32,
+
+    /// Whether match and coverage scores count contigs or weight them by length.
+    /// With length-weighted, primary chromosomes dominate over small decoys.
+    #[arg(long, value_enum, default_value = "count")]
+    pub scoring_mode: ScoringMode,
+
+    #[command(flatten)]
+    pub digest_cache: DigestCacheArgs,
 }
 
 /// Result of scoring in one direction
@@ -73,6 +82,7 @@ pub fn run(args: ScoreArgs, format: OutputFormat, verbose: bool) -> anyhow::Resu
         coverage: f64::from(args.weight_coverage) / 100.0,
         order: f64::from(args.weight_order) / 100.0,
         conflict_penalty: 0.1,
+        scoring_mode: args.scoring_mode,
     };
 
     if verbose {
@@ -84,8 +94,10 @@ pub fn run(args: ScoreArgs, format: OutputFormat, verbose: bool) -> anyhow::Resu
         );
     }
 
+    let cache = args.digest_cache.open()?;
+
     // Parse query file
-    let query_header = parse_input(&args.query)?;
+    let query_header = parse_input(&args.query, cache.as_deref())?;
     if verbose {
         eprintln!(
             "Query: {} contigs ({:.0}% have MD5)",
@@ -95,7 +107,7 @@ pub fn run(args: ScoreArgs, format: OutputFormat, verbose: bool) -> anyhow::Resu
     }
 
     // Parse reference file
-    let reference_header = parse_input(&args.reference)?;
+    let reference_header = parse_input(&args.reference, cache.as_deref())?;
     if verbose {
         eprintln!(
             "Reference: {} contigs ({:.0}% have MD5)",
@@ -173,7 +185,7 @@ fn compute_score(
     }
 }
 
-fn parse_input(path: &Path) -> anyhow::Result<QueryHeader> {
+fn parse_input(path: &Path, cache: Option<&DigestCache>) -> anyhow::Result<QueryHeader> {
     let ext = path
         .extension()
         .and_then(|e| e.to_str())
@@ -182,7 +194,9 @@ fn parse_input(path: &Path) -> anyhow::Result<QueryHeader> {
     match ext.as_deref() {
         Some("dict") => Ok(parsing::dict::parse_dict_file(path)?),
         Some("fai") => Ok(parsing::fai::parse_fai_file(path)?),
-        Some("fa" | "fasta" | "fna") => Ok(parsing::fasta::parse_fasta_file(path)?),
+        Some("fa" | "fasta" | "fna") => Ok(parsing::fasta::parse_fasta_file_with_md5_cached(
+            path, cache,
+        )?),
         Some("vcf" | "vcf.gz") => Ok(parsing::vcf::parse_vcf_file(path)?),
         Some("tsv") => Ok(parsing::tsv::parse_tsv_file(path, '\t')?),
         Some("csv") => Ok(parsing::tsv::parse_tsv_file(path, ',')?),
@@ -205,8 +219,8 @@ fn print_text_result(result: &ScoreResult, weights: &ScoringWeights, suffix: &st
     println!(
         "\n   Score: {:.1}% = {:.0}%×match + {:.0}%×coverage + {:.0}%×order",
         result.score.composite * 100.0,
-        result.score.match_quality * 100.0,
-        result.score.coverage_score * 100.0,
+        result.score.active_match_quality() * 100.0,
+        result.score.active_coverage_score() * 100.0,
         result.score.order_score * 100.0,
     );
     println!(
@@ -226,6 +240,7 @@ fn print_text_result(result: &ScoreResult, weights: &ScoringWeights, suffix: &st
     println!(
         "\n   Query contigs: {total_query} total → {exact} exact, {name_len} name+length, {conflicts} conflicts, {unmatched} unmatched"
     );
+    print_bases_line(&result.score.length_weighted);
 
     // Reference coverage
     let total_ref = result.reference_header.contigs.len();
@@ -244,6 +259,17 @@ fn print_text_result(result: &ScoreResult, weights: &ScoringWeights, suffix: &st
     println!("   Confidence: {:?}", result.score.confidence);
 }
 
+fn print_bases_line(lw: &LengthWeightedScore) {
+    println!(
+        "   Query bases: {} total → {:.1}% exact, {:.1}% name+length, {:.1}% conflicts, {:.1}% unmatched",
+        lw.query_bases(),
+        lw.exact_fraction * 100.0,
+        lw.name_length_fraction * 100.0,
+        lw.conflict_fraction * 100.0,
+        lw.unmatched_fraction * 100.0,
+    );
+}
+
 fn print_json_results(
     forward: &ScoreResult,
     reverse: Option<&ScoreResult>,
@@ -268,9 +294,15 @@ fn print_json_results(
             "score": {
                 "composite": result.score.composite,
                 "confidence": format!("{:?}", result.score.confidence),
-                "match_quality": result.score.match_quality,
-                "coverage_score": result.score.coverage_score,
+                "match_quality": result.score.active_match_quality(),
+                "coverage_score": result.score.active_coverage_score(),
                 "order_score": result.score.order_score,
+                "scoring_mode": result.score.scoring_mode,
+                "count_based": {
+                    "match_quality": result.score.match_quality,
+                    "coverage_score": result.score.coverage_score,
+                },
+                "length_weighted": result.score.length_weighted,
                 "weights": {
                     "match": norm.contig_match,
                     "coverage": norm.coverage,
@@ -328,8 +360,8 @@ fn print_tsv_results(
             result.query_path.display(),
             result.reference_path.display(),
             result.score.composite,
-            result.score.match_quality,
-            result.score.coverage_score,
+            result.score.active_match_quality(),
+            result.score.active_coverage_score(),
             result.score.order_score,
             norm.contig_match,
             norm.coverage,
@@ -375,7 +407,7 @@ mod tests {
         let valid_md5 = "6aef897c3d6ff0c78aff06ac189178dd";
         let file = create_temp_dict_file(&[("chr1", 1000, Some(valid_md5)), ("chr2", 2000, None)]);
 
-        let header = parse_input(file.path()).unwrap();
+        let header = parse_input(file.path(), None).unwrap();
         assert_eq!(header.contigs.len(), 2);
         assert_eq!(header.contigs[0].name, "chr1");
         assert_eq!(header.contigs[0].length, 1000);
@@ -507,6 +539,7 @@ mod tests {
             coverage: 0.7,
             order: 0.1,
             conflict_penalty: 0.1,
+            scoring_mode: ScoringMode::Count,
         };
 
         // Emphasize match over coverage
@@ -515,6 +548,7 @@ mod tests {
             coverage: 0.1,
             order: 0.1,
             conflict_penalty: 0.1,
+            scoring_mode: ScoringMode::Count,
         };
 
         let result_high_cov = compute_score(
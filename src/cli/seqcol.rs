@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand};
+
+use crate::cli::identify::{self, detect_format, InputFormat};
+use crate::cli::{DigestCacheArgs, OutputFormat};
+use crate::seqcol::SeqCol;
+
+#[derive(Args)]
+pub struct SeqcolArgs {
+    #[command(subcommand)]
+    pub command: SeqcolCommands,
+}
+
+#[derive(Subcommand)]
+#[non_exhaustive]
+pub enum SeqcolCommands {
+    /// Compute the seqcol digest of an input file.
+    /// FASTA inputs are hashed; other inputs must carry sha512t24u digests
+    /// for the level-0 digest to be available.
+    Digest {
+        /// Input file (FASTA, BAM, SAM, CRAM, FAI, VCF, .dict, TSV, or CSV)
+        #[arg(required = true)]
+        input: PathBuf,
+
+        /// Input format (auto-detected by default)
+        #[arg(long)]
+        input_format: Option<InputFormat>,
+
+        /// Level of detail to output (0 = digest, 1 = attribute digests, 2 = arrays)
+        #[arg(long, default_value = "1", value_parser = clap::value_parser!(u8).range(0..=2))]
+        level: u8,
+
+        #[command(flatten)]
+        digest_cache: DigestCacheArgs,
+    },
+}
+
+/// Execute seqcol subcommand
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be parsed.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: SeqcolArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    match args.command {
+        SeqcolCommands::Digest {
+            input,
+            input_format,
+            level,
+            digest_cache,
+        } => run_digest(&input, input_format, level, &digest_cache, format, verbose),
+    }
+}
+
+fn run_digest(
+    input: &Path,
+    input_format: Option<InputFormat>,
+    level: u8,
+    digest_cache: &DigestCacheArgs,
+    format: OutputFormat,
+    verbose: bool,
+) -> anyhow::Result<()> {
+    let format_in = input_format.unwrap_or_else(|| detect_format(input));
+    // Sequence digests are required for the level-0 digest, so FASTAs are hashed
+    let cache = match format_in {
+        InputFormat::Fasta => digest_cache.open()?,
+        _ => None,
+    };
+    let query = identify::parse_file(input, format_in, cache.as_deref())?;
+
+    if verbose {
+        let with_digest = query
+            .contigs
+            .iter()
+            .filter(|c| c.sha512t24u.is_some())
+            .count();
+        eprintln!(
+            "Parsed {} contigs from input ({with_digest} have sha512t24u)",
+            query.contigs.len(),
+        );
+    }
+
+    let seqcol = SeqCol::from(&query);
+    let level1 = seqcol.level1();
+    let digest = level1.digest();
+
+    match format {
+        OutputFormat::Text => {
+            println!("Sequence collection: {}", input.display());
+            match &digest {
+                Some(d) => println!("  Digest: {d}"),
+                None => println!("  Digest: unavailable (not all contigs have sha512t24u)"),
+            }
+            if level >= 1 {
+                println!("  Attributes:");
+                println!("    names:     {}", level1.names);
+                println!("    lengths:   {}", level1.lengths);
+                if let Some(sequences) = &level1.sequences {
+                    println!("    sequences: {sequences}");
+                }
+            }
+            if level >= 2 {
+                println!("  Collection:");
+                for (i, (name, length)) in seqcol.names.iter().zip(&seqcol.lengths).enumerate() {
+                    let sequence = seqcol.sequences.as_ref().map_or("-", |s| s[i].as_str());
+                    println!("    {name}\t{length}\t{sequence}");
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let mut output = serde_json::json!({
+                "input": input.display().to_string(),
+                "digest": digest,
+            });
+            if level >= 1 {
+                output["level1"] = serde_json::json!(level1);
+            }
+            if level >= 2 {
+                output["level2"] = serde_json::json!(seqcol);
+            }
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Tsv => {
+            println!("attribute\tdigest");
+            println!("collection\t{}", digest.as_deref().unwrap_or(""));
+            if level >= 1 {
+                println!("names\t{}", level1.names);
+                println!("lengths\t{}", level1.lengths);
+                println!("sequences\t{}", level1.sequences.as_deref().unwrap_or(""));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::Args;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::cli::identify::{self, InputFormat};
+use crate::cli::{DigestCacheArgs, OutputFormat};
+use crate::core::contig::Contig;
+use crate::core::types::ReferenceId;
+use crate::matching::tool_compat::{self, Tool, ToolVerdict};
+
+#[derive(Args)]
+pub struct ToolCompatArgs {
+    /// Input file (BAM, SAM, CRAM, VCF, .dict, FASTA, interval list, or TSV)
+    #[arg(required = true)]
+    pub input: PathBuf,
+
+    /// Reference file the input will be used with, or a reference ID from the
+    /// catalog with --reference
+    #[arg(required = true)]
+    pub other: String,
+
+    /// Treat the second argument as a reference ID from the catalog
+    #[arg(long)]
+    pub reference: bool,
+
+    /// Path to custom catalog file (only used with --reference)
+    #[arg(long)]
+    pub catalog: Option<PathBuf>,
+
+    /// Only predict these tools' verdicts (default: all). May be repeated.
+    #[arg(long = "tool", value_enum)]
+    pub tools: Vec<Tool>,
+
+    #[command(flatten)]
+    pub digest_cache: DigestCacheArgs,
+}
+
+/// Execute tool-compat subcommand
+///
+/// # Errors
+///
+/// Returns an error if an input cannot be read or the reference is not in the
+/// catalog.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: ToolCompatArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let query = read_contigs(&args.input, &args.digest_cache)?;
+
+    let reference = if args.reference {
+        let catalog = if let Some(path) = &args.catalog {
+            ReferenceCatalog::load_from_file(path)?
+        } else {
+            ReferenceCatalog::load_embedded()?
+        };
+        catalog
+            .get(&ReferenceId::new(&args.other))
+            .ok_or_else(|| anyhow::anyhow!("Reference '{}' not found in catalog", args.other))?
+            .contigs
+            .clone()
+    } else {
+        read_contigs(Path::new(&args.other), &args.digest_cache)?
+    };
+
+    if verbose {
+        eprintln!(
+            "Input: {} contigs, reference: {} contigs",
+            query.len(),
+            reference.len()
+        );
+    }
+
+    let verdicts: Vec<ToolVerdict> = if args.tools.is_empty() {
+        tool_compat::predict(&query, &reference)
+    } else {
+        args.tools
+            .iter()
+            .map(|&tool| tool_compat::predict_tool(tool, &query, &reference))
+            .collect()
+    };
+
+    match format {
+        OutputFormat::Text => print_text_report(&args, &verdicts),
+        OutputFormat::Json => print_json_report(&args, &verdicts)?,
+        OutputFormat::Tsv => print_tsv_report(&verdicts),
+    }
+    Ok(())
+}
+
+fn read_contigs(path: &Path, digest_cache: &DigestCacheArgs) -> anyhow::Result<Vec<Contig>> {
+    let format = identify::detect_format(path);
+    let cache = match format {
+        InputFormat::Fasta => digest_cache.open()?,
+        _ => None,
+    };
+    let header = identify::parse_file(path, format, cache.as_deref())
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(header.contigs)
+}
+
+fn print_text_report(args: &ToolCompatArgs, verdicts: &[ToolVerdict]) {
+    println!(
+        "{} with {}{}",
+        args.input.display(),
+        args.other,
+        if args.reference { " (catalog)" } else { "" }
+    );
+    println!();
+
+    let width = verdicts
+        .iter()
+        .map(|v| v.tool.to_string().len())
+        .max()
+        .unwrap_or_default();
+    for verdict in verdicts {
+        println!(
+            "   {:width$}  {:5}  {}",
+            verdict.tool.to_string(),
+            if verdict.works { "works" } else { "FAILS" },
+            verdict.check
+        );
+        for message in &verdict.messages {
+            println!("   {:width$}         - {message}", "");
+        }
+    }
+}
+
+fn print_json_report(args: &ToolCompatArgs, verdicts: &[ToolVerdict]) -> anyhow::Result<()> {
+    let output = serde_json::json!({
+        "input": args.input.display().to_string(),
+        "other": args.other,
+        "other_is_catalog_reference": args.reference,
+        "tools": verdicts,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_tsv_report(verdicts: &[ToolVerdict]) {
+    println!("tool\tworks\tcheck\tmessages");
+    for verdict in verdicts {
+        println!(
+            "{}\t{}\t{}\t{}",
+            verdict.tool,
+            verdict.works,
+            verdict.check,
+            verdict.messages.join("; ")
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::cli::{DigestCacheArgs, OutputFormat};
+use crate::core::reference::KnownReference;
+use crate::core::types::ReferenceId;
+use crate::matching::verification::{ContigCheck, ContigStatus, FastaVerification};
+use crate::parsing;
+
+#[derive(Args)]
+pub struct VerifyFastaArgs {
+    /// FASTA file to verify (uncompressed, gzip or BGZF)
+    #[arg(required = true)]
+    pub fasta: PathBuf,
+
+    /// ID of the catalog reference the FASTA should be identical to
+    #[arg(required = true)]
+    pub reference_id: String,
+
+    /// Path to custom catalog file
+    #[arg(long)]
+    pub catalog: Option<PathBuf>,
+
+    /// Reuse digests from the FASTA digest cache instead of hashing every
+    /// base. Off by default: the cache trusts the file's path, size and
+    /// modification time, which a verification should not.
+    #[arg(long)]
+    pub cached: bool,
+
+    /// Path to the FASTA digest cache (with --cached; default: ~/.cache/ref-solver/fasta-digests.json)
+    #[arg(long, requires = "cached")]
+    pub digest_cache: Option<PathBuf>,
+}
+
+/// Execute verify-fasta subcommand
+///
+/// # Errors
+///
+/// Returns an error if the FASTA or catalog cannot be read, the reference is
+/// not in the catalog, or the FASTA is not identical to the reference.
+#[allow(clippy::needless_pass_by_value)] // CLI entry point, values from clap
+pub fn run(args: VerifyFastaArgs, format: OutputFormat, verbose: bool) -> anyhow::Result<()> {
+    let catalog = if let Some(path) = &args.catalog {
+        ReferenceCatalog::load_from_file(path)?
+    } else {
+        ReferenceCatalog::load_embedded()?
+    };
+    let reference = catalog
+        .get(&ReferenceId::new(&args.reference_id))
+        .ok_or_else(|| anyhow::anyhow!("Reference '{}' not found in catalog", args.reference_id))?;
+
+    let cache = if args.cached {
+        DigestCacheArgs {
+            digest_cache: args.digest_cache.clone(),
+            no_digest_cache: false,
+        }
+        .open()?
+    } else {
+        None
+    };
+    let fasta = parsing::fasta::parse_fasta_file_with_md5_cached(&args.fasta, cache.as_deref())?;
+    if verbose {
+        eprintln!(
+            "Hashed {} contigs from {}",
+            fasta.contigs.len(),
+            args.fasta.display()
+        );
+    }
+
+    let verification = FastaVerification::verify(&fasta, reference);
+
+    match format {
+        OutputFormat::Text => print_text_report(&verification, &args, reference, verbose),
+        OutputFormat::Json => print_json_report(&verification, &args, reference)?,
+        OutputFormat::Tsv => print_tsv_report(&verification),
+    }
+
+    if !verification.passed {
+        anyhow::bail!(
+            "{} is not identical to {}",
+            args.fasta.display(),
+            reference.id
+        );
+    }
+    Ok(())
+}
+
+fn print_text_report(
+    verification: &FastaVerification,
+    args: &VerifyFastaArgs,
+    reference: &KnownReference,
+    verbose: bool,
+) {
+    println!(
+        "Verifying {} against {} ({})",
+        args.fasta.display(),
+        reference.id,
+        reference.display_name
+    );
+    if let Some(url) = &reference.download_url {
+        println!("   Download URL: {url}");
+    }
+
+    println!("\nContigs:");
+    for status in [
+        ContigStatus::Identical,
+        ContigStatus::Renamed,
+        ContigStatus::LengthMismatch,
+        ContigStatus::DigestMismatch,
+        ContigStatus::Unverifiable,
+        ContigStatus::Missing,
+        ContigStatus::ExpectedAbsent,
+        ContigStatus::Extra,
+    ] {
+        let count = verification.count(status);
+        if count > 0 {
+            println!("   {status}: {count}");
+        }
+    }
+    println!(
+        "   Order: {}",
+        if verification.order_matches {
+            "matches reference".to_string()
+        } else {
+            format!(
+                "differs from reference (first at {})",
+                verification.first_out_of_order.as_deref().unwrap_or("?")
+            )
+        }
+    );
+
+    let problems: Vec<&ContigCheck> = verification.problems().collect();
+    if !problems.is_empty() {
+        println!("\nProblems:");
+        let shown = if verbose { problems.len() } else { 20 };
+        for check in problems.iter().take(shown) {
+            println!("   {}", describe(check));
+        }
+        if problems.len() > shown {
+            println!(
+                "   ... and {} more (use --verbose to see all)",
+                problems.len() - shown
+            );
+        }
+    }
+
+    println!(
+        "\n{}",
+        if verification.passed {
+            "PASS: FASTA is identical to the catalog reference"
+        } else {
+            "FAIL: FASTA differs from the catalog reference"
+        }
+    );
+}
+
+/// One-line description of a failed check
+fn describe(check: &ContigCheck) -> String {
+    let fasta_name = check.fasta_name.as_deref().unwrap_or("?");
+    let reference_name = check.reference_name.as_deref().unwrap_or("?");
+    let md5 = |md5: &Option<String>| md5.clone().unwrap_or_else(|| "none".to_string());
+    match check.status {
+        ContigStatus::Renamed => format!("{fasta_name}: named {reference_name} in the reference"),
+        ContigStatus::LengthMismatch => format!(
+            "{fasta_name}: length {} but reference {reference_name} has {}",
+            check.fasta_length.unwrap_or(0),
+            check.reference_length.unwrap_or(0)
+        ),
+        ContigStatus::DigestMismatch => format!(
+            "{fasta_name}: MD5 {} but reference {reference_name} has {}",
+            md5(&check.fasta_md5),
+            md5(&check.reference_md5)
+        ),
+        ContigStatus::Unverifiable => {
+            format!("{fasta_name}: reference {reference_name} has no digest to check against")
+        }
+        ContigStatus::Missing => format!("{reference_name}: missing from FASTA"),
+        ContigStatus::Extra => format!("{fasta_name}: not in reference"),
+        ContigStatus::Identical | ContigStatus::ExpectedAbsent => {
+            format!("{fasta_name}: {}", check.status)
+        }
+    }
+}
+
+fn print_json_report(
+    verification: &FastaVerification,
+    args: &VerifyFastaArgs,
+    reference: &KnownReference,
+) -> anyhow::Result<()> {
+    let output = serde_json::json!({
+        "fasta": args.fasta,
+        "reference": {
+            "id": reference.id.0,
+            "display_name": reference.display_name,
+            "download_url": reference.download_url,
+        },
+        "passed": verification.passed,
+        "order_matches": verification.order_matches,
+        "first_out_of_order": verification.first_out_of_order,
+        "contigs": verification.contigs,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_tsv_report(verification: &FastaVerification) {
+    println!("status\tfasta_name\treference_name\tfasta_length\treference_length\tfasta_md5\treference_md5");
+    let field = |value: Option<String>| value.unwrap_or_default();
+    for check in &verification.contigs {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            check.status.as_str(),
+            field(check.fasta_name.clone()),
+            field(check.reference_name.clone()),
+            field(check.fasta_length.map(|l| l.to_string())),
+            field(check.reference_length.map(|l| l.to_string())),
+            field(check.fasta_md5.clone()),
+            field(check.reference_md5.clone()),
+        );
+    }
+}
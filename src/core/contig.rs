@@ -100,7 +100,6 @@ impl Contig {
         }
     }
 
-    #[cfg(test)]
     #[must_use]
     pub fn with_md5(mut self, md5: impl Into<String>) -> Self {
         self.md5 = Some(md5.into());
@@ -114,6 +113,38 @@ impl Contig {
         self
     }
 
+    #[must_use]
+    pub fn with_sha512t24u(mut self, digest: impl Into<String>) -> Self {
+        self.sha512t24u = Some(digest.into());
+        self
+    }
+
+    /// Compare the sequence digests of two contigs.
+    ///
+    /// MD5 and sha512t24u are treated as equivalent evidence of sequence identity:
+    /// - `Some(true)` if at least one digest type is present on both sides and all
+    ///   shared digest types agree
+    /// - `Some(false)` if any digest type present on both sides differs
+    /// - `None` if the contigs share no digest type (cannot be compared)
+    #[must_use]
+    pub fn digests_match(&self, other: &Contig) -> Option<bool> {
+        let md5 = match (&self.md5, &other.md5) {
+            (Some(a), Some(b)) => Some(a.eq_ignore_ascii_case(b)),
+            _ => None,
+        };
+        // sha512t24u is base64url and therefore case-sensitive
+        let sha512t24u = match (&self.sha512t24u, &other.sha512t24u) {
+            (Some(a), Some(b)) => Some(a == b),
+            _ => None,
+        };
+
+        match (md5, sha512t24u) {
+            (None, None) => None,
+            (Some(a), None) | (None, Some(a)) => Some(a),
+            (Some(a), Some(b)) => Some(a && b),
+        }
+    }
+
     /// Check if this contig is a primary chromosome (1-22, X, Y)
     /// Matches both UCSC (chr1) and NCBI (1) naming conventions exactly
     #[must_use]
@@ -183,6 +214,19 @@ impl Contig {
         ) || name_lower.contains("mitochon")
     }
 
+    /// Check if this contig is part of the primary assembly (assembled molecules
+    /// and the mitochondrion).
+    ///
+    /// Uses the NCBI sequence role when known, otherwise falls back to the name.
+    #[must_use]
+    pub fn is_primary_assembly(&self) -> bool {
+        match self.sequence_role {
+            SequenceRole::AssembledMolecule => true,
+            SequenceRole::Unknown => self.is_primary_chromosome() || self.is_mitochondrial(),
+            _ => false,
+        }
+    }
+
     /// Check if this is an ALT contig (`GRCh38`)
     #[must_use]
     pub fn is_alt(&self) -> bool {
@@ -258,4 +302,55 @@ mod tests {
         assert!(!Contig::new("chr1", 100).is_mitochondrial());
         assert!(!Contig::new("chrX", 100).is_mitochondrial());
     }
+
+    #[test]
+    fn test_is_primary_assembly() {
+        // Falls back to the name when the role is unknown
+        assert!(Contig::new("chr1", 100).is_primary_assembly());
+        assert!(Contig::new("MT", 100).is_primary_assembly());
+        assert!(!Contig::new("chrUn_KI270302v1", 100).is_primary_assembly());
+
+        // The sequence role takes precedence over the name
+        let mut contig = Contig::new("NC_000001.11", 100);
+        assert!(!contig.is_primary_assembly());
+        contig.sequence_role = SequenceRole::AssembledMolecule;
+        assert!(contig.is_primary_assembly());
+        let mut contig = Contig::new("chr1", 100);
+        contig.sequence_role = SequenceRole::AltScaffold;
+        assert!(!contig.is_primary_assembly());
+    }
+
+    #[test]
+    fn test_digests_match() {
+        let md5_a = Contig::new("chr1", 100).with_md5("6aef897c3d6ff0c78aff06ac189178dd");
+        let md5_b = Contig::new("1", 100).with_md5("6AEF897C3D6FF0C78AFF06AC189178DD");
+        let md5_other = Contig::new("chr1", 100).with_md5("f98db672eb0993dcfdabafe2a882905c");
+        let sha_a = Contig::new("chr1", 100).with_sha512t24u("2648ae1bacce4ec4b6cf337dcae37816");
+        let sha_other =
+            Contig::new("chr1", 100).with_sha512t24u("0123456789abcdef0123456789abcdef");
+        let none = Contig::new("chr1", 100);
+
+        // MD5 comparison is case-insensitive
+        assert_eq!(md5_a.digests_match(&md5_b), Some(true));
+        assert_eq!(md5_a.digests_match(&md5_other), Some(false));
+
+        // sha512t24u alone is sufficient evidence
+        assert_eq!(sha_a.digests_match(&sha_a.clone()), Some(true));
+        assert_eq!(sha_a.digests_match(&sha_other), Some(false));
+
+        // No shared digest type
+        assert_eq!(md5_a.digests_match(&sha_a), None);
+        assert_eq!(md5_a.digests_match(&none), None);
+
+        // Any disagreeing digest type is a mismatch
+        let both = md5_a
+            .clone()
+            .with_sha512t24u("2648ae1bacce4ec4b6cf337dcae37816");
+        let both_bad_sha = md5_a
+            .clone()
+            .with_sha512t24u("0123456789abcdef0123456789abcdef");
+        assert_eq!(both.digests_match(&both.clone()), Some(true));
+        assert_eq!(both.digests_match(&both_bad_sha), Some(false));
+        assert_eq!(both.digests_match(&sha_a), Some(true));
+    }
 }
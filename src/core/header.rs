@@ -48,6 +48,10 @@ pub struct QueryHeader {
     /// Signature for exact matching
     #[serde(skip)]
     pub signature: Option<String>,
+
+    /// Signature for exact matching computed from sha512t24u digests
+    #[serde(skip)]
+    pub sha512t24u_signature: Option<String>,
 }
 
 impl QueryHeader {
@@ -64,6 +68,7 @@ impl QueryHeader {
             name_length_set: HashSet::new(),
             alias_length_set: HashSet::new(),
             signature: None,
+            sha512t24u_signature: None,
         };
 
         header.rebuild_indexes();
@@ -99,11 +104,11 @@ impl QueryHeader {
             }
         }
 
-        // Compute signature using centralized helper
+        // Compute signatures using centralized helper
         let sig = compute_signature(&self.md5_set);
-        if !sig.is_empty() {
-            self.signature = Some(sig);
-        }
+        self.signature = (!sig.is_empty()).then_some(sig);
+        let sig = compute_signature(&self.sha512t24u_set);
+        self.sha512t24u_signature = (!sig.is_empty()).then_some(sig);
     }
 
     /// Check if header has MD5 information
@@ -240,6 +245,18 @@ mod tests {
         assert_eq!(header1.signature, header2.signature);
     }
 
+    #[test]
+    fn test_sha512t24u_signature() {
+        let header = QueryHeader::new(vec![
+            Contig::new("chr1", 100).with_sha512t24u("2648ae1bacce4ec4b6cf337dcae37816"),
+            Contig::new("chr2", 200).with_sha512t24u("0123456789abcdef0123456789abcdef"),
+        ]);
+
+        assert!(!header.has_md5s());
+        assert!(header.signature.is_none());
+        assert!(header.sha512t24u_signature.is_some());
+    }
+
     #[test]
     fn test_with_source() {
         let contigs = vec![Contig::new("chr1", 100)];
@@ -3,6 +3,7 @@
 //! This module provides the fundamental types used throughout the library:
 //!
 //! - [`contig::Contig`]: Represents a single sequence/chromosome with name, length, and optional MD5
+//! - [`naming::NameMap`]: Translates contig names between naming conventions
 //! - [`header::QueryHeader`]: A sequence dictionary extracted from a BAM/SAM/CRAM file
 //! - [`reference::KnownReference`]: A reference genome definition from the catalog
 //! - [`types::ReferenceId`], [`types::Assembly`], [`types::ReferenceSource`]: Reference metadata types
@@ -24,5 +25,6 @@
 pub mod assembly;
 pub mod contig;
 pub mod header;
+pub mod naming;
 pub mod reference;
 pub mod types;
@@ -0,0 +1,301 @@
+//! Translation of contig names between naming conventions.
+//!
+//! The same sequence goes by several names: `chr1` (UCSC), `1` (NCBI
+//! sequence name), `NC_000001.11` (`RefSeq`) and `CM000663.2` (`GenBank`).
+//! A [`NameMap`] maps every known name of a contig to a single target name,
+//! built either from a catalog reference's names and aliases or from the
+//! columns of an NCBI assembly report.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::core::reference::KnownReference;
+use crate::parsing::ncbi_report::NcbiContigEntry;
+
+/// Naming style of a contig name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum NameStyle {
+    /// UCSC style: chr1, chrM, `chr1_KI270706v1_random`
+    Ucsc,
+    /// NCBI sequence names: 1, MT, `HSCHR1_CTG1_UNLOCALIZED`
+    Ncbi,
+    /// `RefSeq` accessions: `NC_000001.11`
+    Refseq,
+    /// `GenBank` accessions: CM000663.2, KI270706.1
+    Genbank,
+}
+
+impl NameStyle {
+    /// Guess the style of a name from its shape
+    #[must_use]
+    pub fn classify(name: &str) -> Self {
+        if name.starts_with("chr") {
+            Self::Ucsc
+        } else if is_refseq_accession(name) {
+            Self::Refseq
+        } else if is_genbank_accession(name) {
+            Self::Genbank
+        } else {
+            Self::Ncbi
+        }
+    }
+
+    /// The name of this style in an NCBI assembly report entry. Patches
+    /// without a UCSC-style name get the name UCSC gives them.
+    fn ncbi_entry_name(self, entry: &NcbiContigEntry) -> Option<String> {
+        let name = match self {
+            Self::Ucsc => entry.ucsc_name.as_ref(),
+            Self::Ncbi => Some(&entry.sequence_name),
+            Self::Refseq => entry.refseq_accn.as_ref(),
+            Self::Genbank => entry.genbank_accn.as_ref(),
+        };
+        match name.filter(|n| !n.is_empty() && *n != "na") {
+            Some(name) => Some(name.clone()),
+            None if self == Self::Ucsc => entry
+                .all_names_with_options(true)
+                .into_iter()
+                .find(|n| Self::classify(n) == Self::Ucsc),
+            None => None,
+        }
+    }
+}
+
+impl std::fmt::Display for NameStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ucsc => write!(f, "UCSC"),
+            Self::Ncbi => write!(f, "NCBI"),
+            Self::Refseq => write!(f, "RefSeq"),
+            Self::Genbank => write!(f, "GenBank"),
+        }
+    }
+}
+
+/// `NC_000001.11`, `NT_187361.1`, `NW_025791756.1`; the version may be absent
+fn is_refseq_accession(name: &str) -> bool {
+    let Some((prefix, rest)) = name.split_once('_') else {
+        return false;
+    };
+    let number = rest.split_once('.').map_or(rest, |(number, version)| {
+        if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+            ""
+        } else {
+            number
+        }
+    });
+    prefix.len() == 2
+        && prefix.bytes().all(|b| b.is_ascii_uppercase())
+        && !number.is_empty()
+        && number.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// One or two letters, at least five digits and a version: CM000663.2, J01415.2
+fn is_genbank_accession(name: &str) -> bool {
+    let Some((accession, version)) = name.split_once('.') else {
+        return false;
+    };
+    let letters = accession.bytes().take_while(u8::is_ascii_uppercase).count();
+    let digits = &accession[letters..];
+    (1..=2).contains(&letters)
+        && digits.len() >= 5
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && !version.is_empty()
+        && version.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Maps contig names to their names in a target convention
+#[derive(Debug, Clone, Default)]
+pub struct NameMap {
+    names: HashMap<String, String>,
+}
+
+impl NameMap {
+    /// Map every name and alias of the reference's contigs to the contig's
+    /// name in the reference
+    #[must_use]
+    pub fn to_reference(reference: &KnownReference) -> Self {
+        let mut builder = NameMapBuilder::default();
+        for contig in &reference.contigs {
+            builder.add(
+                std::iter::once(&contig.name).chain(&contig.aliases),
+                &contig.name,
+            );
+        }
+        builder.build()
+    }
+
+    /// Map every name and alias of the reference's contigs to the contig's
+    /// name in `style`. Contigs with no name of that style are left out.
+    #[must_use]
+    pub fn to_style(reference: &KnownReference, style: NameStyle) -> Self {
+        let mut builder = NameMapBuilder::default();
+        for contig in &reference.contigs {
+            let names = || std::iter::once(&contig.name).chain(&contig.aliases);
+            if let Some(target) = names().find(|n| NameStyle::classify(n) == style) {
+                builder.add(names(), target);
+            }
+        }
+        builder.build()
+    }
+
+    /// Map every name of the report's entries to the entry's name in `style`,
+    /// using the report's columns rather than guessing from the names.
+    /// Entries with no name in that column are left out.
+    #[must_use]
+    pub fn from_ncbi_report(entries: &[NcbiContigEntry], style: NameStyle) -> Self {
+        let mut builder = NameMapBuilder::default();
+        for entry in entries {
+            if let Some(target) = style.ncbi_entry_name(entry) {
+                builder.add(&entry.all_names_with_options(true), &target);
+            }
+        }
+        builder.build()
+    }
+
+    /// The target name for `name`, if it is known
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.names.get(name).map(String::as_str)
+    }
+}
+
+/// Key under which two names count as the same contig: without a `chr`
+/// prefix, with `M` for the mitochondrion as `MT`
+#[must_use]
+pub fn same_name_key(name: &str) -> String {
+    let name = name.strip_prefix("chr").unwrap_or(name);
+    if name == "M" {
+        "MT".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Collects name mappings, dropping names claimed by two different targets
+#[derive(Default)]
+struct NameMapBuilder {
+    names: HashMap<String, String>,
+    conflicting: HashSet<String>,
+}
+
+impl NameMapBuilder {
+    fn add<'a>(&mut self, names: impl IntoIterator<Item = &'a String>, target: &str) {
+        for name in names {
+            match self.names.get(name) {
+                Some(existing) if existing != target => {
+                    self.conflicting.insert(name.clone());
+                }
+                Some(_) => {}
+                None => {
+                    self.names.insert(name.clone(), target.to_string());
+                }
+            }
+        }
+    }
+
+    fn build(mut self) -> NameMap {
+        for name in &self.conflicting {
+            self.names.remove(name);
+        }
+        NameMap { names: self.names }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::contig::Contig;
+    use crate::core::types::{Assembly, ReferenceSource};
+    use crate::parsing::ncbi_report::parse_ncbi_report_text;
+
+    fn make_reference() -> KnownReference {
+        KnownReference::new(
+            "test_ref",
+            "Test Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 1000).with_aliases(vec![
+                "1".to_string(),
+                "CM000663.2".to_string(),
+                "NC_000001.11".to_string(),
+            ]),
+            Contig::new("chrM", 16569).with_aliases(vec![
+                "NC_012920.1".to_string(),
+                "MT".to_string(),
+                "J01415.2".to_string(),
+            ]),
+            Contig::new("chrEBV", 171_823),
+        ])
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(NameStyle::classify("chr1"), NameStyle::Ucsc);
+        assert_eq!(
+            NameStyle::classify("chr1_KI270706v1_random"),
+            NameStyle::Ucsc
+        );
+        assert_eq!(NameStyle::classify("MT"), NameStyle::Ncbi);
+        assert_eq!(
+            NameStyle::classify("HSCHR1_CTG1_UNLOCALIZED"),
+            NameStyle::Ncbi
+        );
+        assert_eq!(NameStyle::classify("NC_000001.11"), NameStyle::Refseq);
+        assert_eq!(NameStyle::classify("NC_007605"), NameStyle::Refseq);
+        assert_eq!(NameStyle::classify("CM000663.2"), NameStyle::Genbank);
+        assert_eq!(NameStyle::classify("J01415.2"), NameStyle::Genbank);
+        assert_eq!(NameStyle::classify("hs37d5"), NameStyle::Ncbi);
+    }
+
+    #[test]
+    fn test_to_reference() {
+        let map = NameMap::to_reference(&make_reference());
+
+        assert_eq!(map.get("1"), Some("chr1"));
+        assert_eq!(map.get("NC_012920.1"), Some("chrM"));
+        assert_eq!(map.get("chrEBV"), Some("chrEBV"));
+        assert_eq!(map.get("2"), None);
+    }
+
+    #[test]
+    fn test_to_style() {
+        let map = NameMap::to_style(&make_reference(), NameStyle::Refseq);
+
+        assert_eq!(map.get("chr1"), Some("NC_000001.11"));
+        assert_eq!(map.get("MT"), Some("NC_012920.1"));
+        assert_eq!(map.get("chrEBV"), None);
+    }
+
+    #[test]
+    fn test_conflicting_aliases_are_dropped() {
+        let reference = make_reference().with_contigs(vec![
+            Contig::new("chr1", 1000).with_aliases(vec!["x".to_string()]),
+            Contig::new("chr2", 2000).with_aliases(vec!["x".to_string()]),
+        ]);
+
+        let map = NameMap::to_reference(&reference);
+
+        assert_eq!(map.get("x"), None);
+        assert_eq!(map.get("chr2"), Some("chr2"));
+    }
+
+    #[test]
+    fn test_from_ncbi_report() {
+        let report = "# Sequence-Name\tSequence-Role\tAssigned-Molecule\tAssigned-Molecule-Location/Type\tGenBank-Accn\tRelationship\tRefSeq-Accn\tAssembly-Unit\tSequence-Length\tUCSC-style-name
+1\tassembled-molecule\t1\tChromosome\tCM000663.2\t=\tNC_000001.11\tPrimary Assembly\t248956422\tchr1
+HSCHR1_CTG1_UNLOCALIZED\tunlocalized-scaffold\t1\tChromosome\tKI270706.1\t=\tNT_187361.1\tPrimary Assembly\t175055\tna
+";
+        let entries = parse_ncbi_report_text(report).unwrap();
+
+        let ucsc = NameMap::from_ncbi_report(&entries, NameStyle::Ucsc);
+        assert_eq!(ucsc.get("NC_000001.11"), Some("chr1"));
+        assert_eq!(ucsc.get("KI270706.1"), None);
+
+        let ncbi = NameMap::from_ncbi_report(&entries, NameStyle::Ncbi);
+        assert_eq!(ncbi.get("NT_187361.1"), Some("HSCHR1_CTG1_UNLOCALIZED"));
+    }
+}
@@ -64,6 +64,10 @@ pub struct KnownReference {
     /// Signature for exact matching (hash of sorted MD5s)
     #[serde(skip)]
     pub signature: Option<String>,
+
+    /// Signature for exact matching (hash of sorted sha512t24u digests)
+    #[serde(skip)]
+    pub sha512t24u_signature: Option<String>,
 }
 
 impl KnownReference {
@@ -89,6 +93,7 @@ impl KnownReference {
             sha512t24u_set: HashSet::new(),
             name_length_set: HashSet::new(),
             signature: None,
+            sha512t24u_signature: None,
         }
     }
 
@@ -123,14 +128,15 @@ impl KnownReference {
             }
         }
 
-        // Compute signature from sorted MD5s
-        self.signature = self.compute_signature();
+        // Compute signatures from sorted MD5s and sorted sha512t24u digests
+        self.signature = Self::compute_signature(&self.md5_set);
+        self.sha512t24u_signature = Self::compute_signature(&self.sha512t24u_set);
     }
 
     /// Compute a signature for exact matching
-    /// Uses sorted MD5s concatenated and hashed
-    fn compute_signature(&self) -> Option<String> {
-        let sig = compute_sig(&self.md5_set);
+    /// Uses sorted digests concatenated and hashed
+    fn compute_signature(digests: &HashSet<String>) -> Option<String> {
+        let sig = compute_sig(digests);
         if sig.is_empty() {
             None
         } else {
@@ -48,6 +48,7 @@
 //! - [`matching`]: Matching engine and scoring algorithms
 //! - [`parsing`]: Parsers for SAM/BAM/CRAM, dict, and TSV files
 //! - [`refget`]: Refget server integration for unknown contig lookup
+//! - [`seqcol`]: GA4GH sequence collection digests and comparison
 //! - [`cli`]: Command-line interface implementation
 //! - [`web`]: Web server for browser-based identification
 
@@ -57,6 +58,7 @@ pub mod core;
 pub mod matching;
 pub mod parsing;
 pub mod refget;
+pub mod seqcol;
 pub mod utils;
 pub mod web;
 
@@ -7,6 +7,7 @@ mod core;
 mod matching;
 mod parsing;
 mod refget;
+mod seqcol;
 mod utils;
 mod web;
 
@@ -20,7 +21,9 @@ fn main() -> anyhow::Result<()> {
         EnvFilter::new("ref_solver=warn")
     };
 
+    // Log to stderr so warnings never mix with data or reports on stdout
     tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
         .with_env_filter(filter)
         .with_target(false)
         .without_time()
@@ -33,12 +36,45 @@ fn main() -> anyhow::Result<()> {
         cli::Commands::Compare(args) => {
             cli::compare::run(args, cli.format, cli.verbose)?;
         }
+        cli::Commands::CheckCompat(args) => {
+            cli::check_compat::run(args, cli.format, cli.verbose)?;
+        }
+        cli::Commands::Assert(args) => {
+            cli::assert::run(args, cli.format, cli.verbose)?;
+        }
+        cli::Commands::ToolCompat(args) => {
+            cli::tool_compat::run(args, cli.format, cli.verbose)?;
+        }
+        cli::Commands::Locate(args) => {
+            cli::locate::run(args, cli.format, cli.verbose)?;
+        }
         cli::Commands::Score(args) => {
             cli::score::run(args, cli.format, cli.verbose)?;
         }
         cli::Commands::Catalog(args) => {
             cli::catalog::run(args, cli.format, cli.verbose)?;
         }
+        cli::Commands::AnnotateHeader(args) => {
+            cli::annotate_header::run(args, cli.format, cli.verbose)?;
+        }
+        cli::Commands::ConvertNames(args) => {
+            cli::convert_names::run(args, cli.format, cli.verbose)?;
+        }
+        cli::Commands::Dict(args) => {
+            cli::dict::run(args, cli.format, cli.verbose)?;
+        }
+        cli::Commands::Reheader(args) => {
+            cli::reheader::run(args, cli.format, cli.verbose)?;
+        }
+        cli::Commands::VerifyFasta(args) => {
+            cli::verify_fasta::run(args, cli.format, cli.verbose)?;
+        }
+        cli::Commands::Seqcol(args) => {
+            cli::seqcol::run(args, cli.format, cli.verbose)?;
+        }
+        cli::Commands::Refget(args) => {
+            cli::refget::run(args, cli.format, cli.verbose)?;
+        }
         cli::Commands::Serve(args) => {
             web::server::run(args)?;
         }
@@ -0,0 +1,433 @@
+//! Annotation of query contigs with metadata from a catalog reference.
+//!
+//! Headers and dictionaries often carry only names and lengths. Once the
+//! reference is known, its contigs supply the MD5, assembly, species and
+//! alternate names. [`Annotation`] pairs each query contig with at most one
+//! reference contig and fills in whatever the query is missing, leaving
+//! contigs that cannot be paired with certainty untouched.
+//!
+//! When several references match the query equally well, a contig is only
+//! annotated with what all of them agree on.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::reference::KnownReference;
+
+/// Why a query contig was left unannotated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// No reference contig has the same sequence, or the same name and length
+    NotInReference,
+    /// Several reference contigs fit equally well
+    Ambiguous,
+    /// References tied for the best match pair the contig with different
+    /// sequences, or not all of them have it
+    Disagreement,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInReference => write!(f, "not in reference"),
+            Self::Ambiguous => write!(f, "ambiguous"),
+            Self::Disagreement => write!(f, "tied references disagree"),
+        }
+    }
+}
+
+/// A query contig left unannotated
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedContig {
+    /// Name in the query
+    pub name: String,
+
+    /// Why it was skipped
+    pub reason: SkipReason,
+}
+
+/// Query contigs annotated from a reference
+#[derive(Debug, Clone, Serialize)]
+pub struct Annotation {
+    /// Query contigs in their original order, annotated where paired
+    pub contigs: Vec<Contig>,
+
+    /// Number of contigs that gained at least one field
+    pub annotated: usize,
+
+    /// Contigs that could not be paired with a reference contig
+    pub skipped: Vec<SkippedContig>,
+}
+
+impl Annotation {
+    /// Annotate the contigs of a query from a reference.
+    ///
+    /// A query contig with a digest is paired with the reference contig of the
+    /// same sequence, preferring one whose name or alias matches. A contig
+    /// without digests is paired only if exactly one reference contig has its
+    /// name (or alias) and length. Existing values are kept; the missing `md5`,
+    /// `assembly` and `species` are copied and the reference's name and aliases
+    /// are added to the contig's aliases.
+    #[must_use]
+    pub fn annotate(query: &QueryHeader, reference: &KnownReference) -> Self {
+        Self::annotate_consensus(query, &[reference])
+    }
+
+    /// Annotate the contigs of a query from references that match it equally
+    /// well, using the rules of [`Annotation::annotate`] for each.
+    ///
+    /// A contig is annotated only if every reference pairs it with a contig of
+    /// the same sequence (no digests that differ), and only with the values
+    /// and names all those contigs share: a value some of them lack is left out. Otherwise it is skipped with
+    /// [`SkipReason::Disagreement`], or with the reason every reference gives.
+    #[must_use]
+    pub fn annotate_consensus(query: &QueryHeader, references: &[&KnownReference]) -> Self {
+        let pairings: Vec<Vec<Result<usize, SkipReason>>> = references
+            .iter()
+            .map(|reference| pair_contigs(query, reference))
+            .collect();
+
+        let mut contigs = Vec::with_capacity(query.contigs.len());
+        let mut annotated = 0;
+        let mut skipped = Vec::new();
+        for (i, contig) in query.contigs.iter().enumerate() {
+            let mut contig = contig.clone();
+            let paired: Result<Vec<&Contig>, SkipReason> = references
+                .iter()
+                .zip(&pairings)
+                .map(|(reference, pairs)| pairs[i].map(|index| &reference.contigs[index]))
+                .collect();
+            let consensus = match paired {
+                Ok(expected) => consensus(&expected).ok_or(SkipReason::Disagreement),
+                Err(reason) if pairings.iter().all(|pairs| pairs[i] == Err(reason)) => Err(reason),
+                Err(_) => Err(SkipReason::Disagreement),
+            };
+            match consensus {
+                Ok(expected) => {
+                    if fill(&mut contig, &expected) {
+                        annotated += 1;
+                    }
+                }
+                Err(reason) => skipped.push(SkippedContig {
+                    name: contig.name.clone(),
+                    reason,
+                }),
+            }
+            contigs.push(contig);
+        }
+
+        Self {
+            contigs,
+            annotated,
+            skipped,
+        }
+    }
+}
+
+/// A contig holding what all of `expected` agree on, or `None` if their
+/// digests show different sequences
+fn consensus(expected: &[&Contig]) -> Option<Contig> {
+    let (first, rest) = expected.split_first()?;
+    if rest.is_empty() {
+        return Some((*first).clone());
+    }
+    for (i, a) in expected.iter().enumerate() {
+        for b in &expected[i + 1..] {
+            if a.length != b.length || a.digests_match(b) == Some(false) {
+                return None;
+            }
+        }
+    }
+
+    // A value every contig has, and has the same
+    let shared = |field: fn(&Contig) -> &Option<String>| {
+        let value = field(first).as_ref()?;
+        rest.iter()
+            .all(|c| {
+                field(c)
+                    .as_ref()
+                    .is_some_and(|other| other.eq_ignore_ascii_case(value))
+            })
+            .then(|| value.clone())
+    };
+    let names = |c: &Contig| -> HashSet<String> {
+        std::iter::once(&c.name)
+            .chain(&c.aliases)
+            .cloned()
+            .collect()
+    };
+    let common = rest.iter().fold(names(first), |common, c| {
+        common.intersection(&names(c)).cloned().collect()
+    });
+
+    // Keep the first contig's name order
+    let mut shared_names = std::iter::once(&first.name)
+        .chain(&first.aliases)
+        .filter(|name| common.contains(*name));
+    let mut contig = Contig::new(
+        shared_names.next().cloned().unwrap_or_default(),
+        first.length,
+    );
+    contig.aliases = shared_names.cloned().collect();
+    contig.md5 = shared(|c| &c.md5);
+    contig.sha512t24u = shared(|c| &c.sha512t24u);
+    contig.assembly = shared(|c| &c.assembly);
+    contig.species = shared(|c| &c.species);
+    Some(contig)
+}
+
+/// Pair each query contig with the index of its reference contig, using the
+/// rules of [`Annotation::annotate`]
+#[must_use]
+pub fn pair_contigs(
+    query: &QueryHeader,
+    reference: &KnownReference,
+) -> Vec<Result<usize, SkipReason>> {
+    let mut by_name: HashMap<(&str, u64), Vec<usize>> = HashMap::new();
+    for (i, contig) in reference.contigs.iter().enumerate() {
+        for name in std::iter::once(&contig.name).chain(&contig.aliases) {
+            let indices = by_name.entry((name.as_str(), contig.length)).or_default();
+            if !indices.contains(&i) {
+                indices.push(i);
+            }
+        }
+    }
+
+    query
+        .contigs
+        .iter()
+        .map(|contig| {
+            let named = by_name
+                .get(&(contig.name.as_str(), contig.length))
+                .map_or(&[][..], Vec::as_slice);
+            if contig.md5.is_some() || contig.sha512t24u.is_some() {
+                pair_by_digest(contig, reference, named)
+            } else {
+                match named {
+                    [] => Err(SkipReason::NotInReference),
+                    [index] => Ok(*index),
+                    _ => Err(SkipReason::Ambiguous),
+                }
+            }
+        })
+        .collect()
+}
+
+/// Pair a contig with digests to the reference contig of the same sequence
+fn pair_by_digest(
+    contig: &Contig,
+    reference: &KnownReference,
+    named: &[usize],
+) -> Result<usize, SkipReason> {
+    let same_sequence = |i: &usize| {
+        let candidate = &reference.contigs[*i];
+        candidate.length == contig.length && contig.digests_match(candidate) == Some(true)
+    };
+
+    if let Some(&index) = named.iter().find(|i| same_sequence(i)) {
+        return Ok(index);
+    }
+    let matches: Vec<usize> = (0..reference.contigs.len())
+        .filter(|i| same_sequence(i))
+        .collect();
+    match matches.as_slice() {
+        [] => Err(SkipReason::NotInReference),
+        [index] => Ok(*index),
+        _ => Err(SkipReason::Ambiguous),
+    }
+}
+
+/// Copy the fields the contig lacks from its reference contig, returning
+/// whether anything was added
+fn fill(contig: &mut Contig, expected: &Contig) -> bool {
+    let mut changed = false;
+    for (field, value) in [
+        (&mut contig.md5, &expected.md5),
+        (&mut contig.sha512t24u, &expected.sha512t24u),
+        (&mut contig.assembly, &expected.assembly),
+        (&mut contig.species, &expected.species),
+    ] {
+        if field.is_none() && value.is_some() {
+            field.clone_from(value);
+            changed = true;
+        }
+    }
+
+    for alias in std::iter::once(&expected.name).chain(&expected.aliases) {
+        if !alias.is_empty() && *alias != contig.name && !contig.aliases.contains(alias) {
+            contig.aliases.push(alias.clone());
+            changed = true;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{Assembly, ReferenceSource};
+
+    fn make_reference() -> KnownReference {
+        let mut chr1 = Contig::new("chr1", 1000)
+            .with_md5("aaaa")
+            .with_aliases(vec!["1".to_string(), "NC_000001.11".to_string()]);
+        chr1.assembly = Some("GRCh38".to_string());
+        chr1.species = Some("Homo sapiens".to_string());
+        KnownReference::new(
+            "test_ref",
+            "Test Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            chr1,
+            Contig::new("chr2", 2000).with_md5("bbbb"),
+            Contig::new("chrUn_a", 500).with_md5("cccc"),
+            Contig::new("chrUn_b", 500).with_md5("cccc"),
+        ])
+    }
+
+    #[test]
+    fn test_annotates_by_name_and_length() {
+        let query = QueryHeader::new(vec![Contig::new("1", 1000), Contig::new("chr2", 2000)]);
+
+        let annotation = Annotation::annotate(&query, &make_reference());
+
+        assert_eq!(annotation.annotated, 2);
+        let chr1 = &annotation.contigs[0];
+        assert_eq!(chr1.name, "1");
+        assert_eq!(chr1.md5.as_deref(), Some("aaaa"));
+        assert_eq!(chr1.assembly.as_deref(), Some("GRCh38"));
+        assert_eq!(chr1.species.as_deref(), Some("Homo sapiens"));
+        assert_eq!(chr1.aliases, vec!["chr1", "NC_000001.11"]);
+    }
+
+    #[test]
+    fn test_annotates_by_digest() {
+        let query = QueryHeader::new(vec![
+            // Renamed, paired by MD5
+            Contig::new("contig_1", 1000).with_md5("aaaa"),
+            // Same name and length but a different sequence
+            Contig::new("chr2", 2000).with_md5("ffff"),
+            // Same sequence as two reference contigs, paired by name
+            Contig::new("chrUn_b", 500).with_md5("cccc"),
+        ]);
+
+        let annotation = Annotation::annotate(&query, &make_reference());
+
+        assert_eq!(annotation.annotated, 1);
+        assert_eq!(annotation.contigs[0].assembly.as_deref(), Some("GRCh38"));
+        assert!(annotation.contigs[0].aliases.contains(&"chr1".to_string()));
+        assert_eq!(annotation.contigs[1].md5.as_deref(), Some("ffff"));
+        assert_eq!(annotation.skipped.len(), 1);
+        assert_eq!(annotation.skipped[0].name, "chr2");
+        assert_eq!(annotation.skipped[0].reason, SkipReason::NotInReference);
+    }
+
+    #[test]
+    fn test_skips_ambiguous_contigs() {
+        let query = QueryHeader::new(vec![Contig::new("chrUn", 500).with_md5("cccc")]);
+
+        let annotation = Annotation::annotate(&query, &make_reference());
+
+        assert_eq!(annotation.annotated, 0);
+        assert_eq!(annotation.skipped[0].reason, SkipReason::Ambiguous);
+    }
+
+    #[test]
+    fn test_consensus_of_tied_references() {
+        let reference = make_reference();
+        // Same chr1 sequence under fewer names and without metadata; chr2 is
+        // another sequence; no chrUn contigs
+        let other = KnownReference::new(
+            "other_ref",
+            "Other Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 1000)
+                .with_md5("AAAA")
+                .with_aliases(vec!["1".to_string()]),
+            Contig::new("chr2", 2000).with_md5("eeee"),
+        ]);
+        let query = QueryHeader::new(vec![
+            Contig::new("1", 1000),
+            Contig::new("chr2", 2000),
+            Contig::new("chrUn_a", 500),
+            Contig::new("chrZ", 10),
+        ]);
+
+        let annotation = Annotation::annotate_consensus(&query, &[&reference, &other]);
+
+        assert_eq!(annotation.annotated, 1);
+        let chr1 = &annotation.contigs[0];
+        assert_eq!(chr1.md5.as_deref(), Some("aaaa"));
+        assert_eq!(chr1.aliases, vec!["chr1"]);
+        // Only one reference knows these, so they are not shared
+        assert_eq!(chr1.assembly, None);
+        assert_eq!(chr1.species, None);
+
+        let reasons: Vec<(&str, SkipReason)> = annotation
+            .skipped
+            .iter()
+            .map(|s| (s.name.as_str(), s.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("chr2", SkipReason::Disagreement),
+                ("chrUn_a", SkipReason::Disagreement),
+                ("chrZ", SkipReason::NotInReference),
+            ]
+        );
+        assert!(annotation.contigs[1].md5.is_none());
+    }
+
+    #[test]
+    fn test_consensus_leaves_out_values_a_tied_reference_lacks() {
+        let reference = make_reference();
+        // chr1 has no MD5 here, so only the names and length decide the pairing
+        let other = KnownReference::new(
+            "other_ref",
+            "Other Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 1000).with_aliases(vec!["1".to_string()])
+        ]);
+        let query = QueryHeader::new(vec![Contig::new("1", 1000)]);
+
+        let annotation = Annotation::annotate_consensus(&query, &[&reference, &other]);
+
+        assert!(annotation.skipped.is_empty());
+        let chr1 = &annotation.contigs[0];
+        assert_eq!(chr1.md5, None);
+        assert_eq!(chr1.aliases, vec!["chr1"]);
+
+        // Either way round
+        let annotation = Annotation::annotate_consensus(&query, &[&other, &reference]);
+        assert_eq!(annotation.contigs[0].md5, None);
+    }
+
+    #[test]
+    fn test_consensus_of_one_reference_is_annotate() {
+        let reference = make_reference();
+        let query = QueryHeader::new(vec![
+            Contig::new("1", 1000),
+            Contig::new("chrUn", 500).with_md5("cccc"),
+        ]);
+
+        let single = Annotation::annotate(&query, &reference);
+        let consensus = Annotation::annotate_consensus(&query, &[&reference, &reference]);
+
+        assert_eq!(consensus.contigs, single.contigs);
+        assert_eq!(consensus.annotated, single.annotated);
+        assert_eq!(consensus.skipped[0].reason, SkipReason::Ambiguous);
+    }
+}
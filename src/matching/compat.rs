@@ -0,0 +1,733 @@
+//! Mutual compatibility of the contigs of a pipeline's input files.
+//!
+//! Tools such as GATK refuse inputs whose sequence dictionaries disagree, often
+//! hours into a run. [`CompatReport`] compares every pair of input files up
+//! front and gives each pair a [`Verdict`]:
+//!
+//! - files with a sequence dictionary (alignments, VCFs, `.dict`, FASTA,
+//!   interval lists) are compared with [`MatchDiagnosis`] in both directions,
+//!   so names, lengths, order and, where both have digests, sequences count
+//! - feature files (BED, GTF/GFF) only name contigs; each must be in the other
+//!   file's dictionary and long enough to hold the features
+//! - two feature files can only be checked for naming the same contig
+//!   differently
+//!
+//! A file with fewer contigs than another is usually fine (a VCF of the
+//! autosomes against a whole-genome BAM), except when the smaller file is the
+//! reference: tools cannot use data on contigs the reference lacks, so each
+//! file carries a [`FileRole`].
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::naming::same_name_key;
+use crate::core::reference::KnownReference;
+use crate::core::types::{Assembly, ReferenceSource};
+use crate::matching::diagnosis::MatchDiagnosis;
+use crate::parsing::features::FeatureContig;
+
+/// Number of contig names listed in a detail before the rest are counted
+const MAX_LISTED_NAMES: usize = 5;
+
+/// The contigs of one input file
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum FileContigs {
+    /// A sequence dictionary with names, lengths and possibly digests
+    Dictionary(QueryHeader),
+    /// Contigs named by features, with the extent of the features on each
+    Features(Vec<FeatureContig>),
+}
+
+/// What an input file is to the pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileRole {
+    /// The reference sequences (FASTA, FAI, `.dict`), which must hold every
+    /// contig of the other files
+    Reference,
+    /// Data aligned or called against the reference
+    Data,
+}
+
+impl std::fmt::Display for FileRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Reference => write!(f, "reference"),
+            Self::Data => write!(f, "data"),
+        }
+    }
+}
+
+/// An input file to check
+#[derive(Debug, Clone)]
+pub struct CompatFile {
+    /// Where the contigs came from (e.g. the file path)
+    pub source: String,
+
+    /// The file's contigs
+    pub contigs: FileContigs,
+
+    /// Whether the file is a reference or data
+    pub role: FileRole,
+}
+
+impl CompatFile {
+    /// A data file; see [`CompatFile::with_role`] for references
+    #[must_use]
+    pub fn new(source: impl Into<String>, contigs: FileContigs) -> Self {
+        Self {
+            source: source.into(),
+            contigs,
+            role: FileRole::Data,
+        }
+    }
+
+    #[must_use]
+    pub fn with_role(mut self, role: FileRole) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Number of contigs in the dictionary or named by features
+    #[must_use]
+    pub fn contig_count(&self) -> usize {
+        match &self.contigs {
+            FileContigs::Dictionary(header) => header.contigs.len(),
+            FileContigs::Features(features) => features.len(),
+        }
+    }
+
+    /// Whether the file has a sequence dictionary
+    #[must_use]
+    pub fn has_dictionary(&self) -> bool {
+        matches!(self.contigs, FileContigs::Dictionary(_))
+    }
+}
+
+/// How the contigs of a first file relate to those of a second
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// Same contigs, lengths and (where known) sequences, in the same order
+    Identical,
+    /// Every contig of the first file is in the second, in the same order
+    Subset,
+    /// Every contig of the second file is in the first, in the same order
+    Superset,
+    /// Two feature files that name no contig differently
+    Compatible,
+    /// Same contigs in a different order
+    Reordered,
+    /// Some contigs go by different names in the two files
+    Renamed,
+    /// Some contigs with the same name differ in length or sequence
+    Conflicting,
+    /// Each file has contigs the other lacks
+    Overlapping,
+    /// No contigs in common
+    Disjoint,
+}
+
+impl Verdict {
+    /// Whether tools can use a first file with role `first` and a second with
+    /// role `second` together, given this verdict. A subset is compatible
+    /// unless the file with fewer contigs is a reference.
+    #[must_use]
+    pub fn is_compatible(self, first: FileRole, second: FileRole) -> bool {
+        match self {
+            Self::Identical | Self::Compatible => true,
+            Self::Subset => first != FileRole::Reference,
+            Self::Superset => second != FileRole::Reference,
+            _ => false,
+        }
+    }
+
+    /// The verdict with the two files swapped
+    #[must_use]
+    pub fn flipped(self) -> Self {
+        match self {
+            Self::Subset => Self::Superset,
+            Self::Superset => Self::Subset,
+            other => other,
+        }
+    }
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Identical => write!(f, "identical"),
+            Self::Subset => write!(f, "subset"),
+            Self::Superset => write!(f, "superset"),
+            Self::Compatible => write!(f, "compatible"),
+            Self::Reordered => write!(f, "reordered"),
+            Self::Renamed => write!(f, "renamed"),
+            Self::Conflicting => write!(f, "conflicting"),
+            Self::Overlapping => write!(f, "overlapping"),
+            Self::Disjoint => write!(f, "disjoint"),
+        }
+    }
+}
+
+/// The verdict for one pair of files
+#[derive(Debug, Clone, Serialize)]
+pub struct PairCheck {
+    /// Index of the first file
+    pub first: usize,
+
+    /// Index of the second file
+    pub second: usize,
+
+    /// How the first file relates to the second
+    pub verdict: Verdict,
+
+    /// Whether tools can use the two files together
+    pub compatible: bool,
+
+    /// What makes the files incompatible, or what one has that the other lacks
+    pub details: Vec<String>,
+}
+
+/// Pairwise compatibility of a set of files
+#[derive(Debug, Clone, Serialize)]
+pub struct CompatReport {
+    /// Number of files checked
+    pub files: usize,
+
+    /// One check per unordered pair of files
+    pub pairs: Vec<PairCheck>,
+}
+
+impl CompatReport {
+    /// Compare every pair of `files`
+    #[must_use]
+    pub fn check(files: &[CompatFile]) -> Self {
+        let mut pairs = Vec::new();
+        for (i, first) in files.iter().enumerate() {
+            for (j, second) in files.iter().enumerate().skip(i + 1) {
+                let (verdict, mut details) = compare(first, second);
+                let compatible = verdict.is_compatible(first.role, second.role);
+                if !compatible && matches!(verdict, Verdict::Subset | Verdict::Superset) {
+                    let (reference, other) = if verdict == Verdict::Subset {
+                        (first, second)
+                    } else {
+                        (second, first)
+                    };
+                    details.insert(
+                        0,
+                        format!(
+                            "Reference {} lacks contigs of {}",
+                            reference.source, other.source
+                        ),
+                    );
+                }
+                pairs.push(PairCheck {
+                    first: i,
+                    second: j,
+                    verdict,
+                    compatible,
+                    details,
+                });
+            }
+        }
+        Self {
+            files: files.len(),
+            pairs,
+        }
+    }
+
+    /// How file `first` relates to file `second`, `None` for a file with itself
+    #[must_use]
+    pub fn verdict(&self, first: usize, second: usize) -> Option<Verdict> {
+        self.pairs.iter().find_map(|pair| {
+            if (pair.first, pair.second) == (first, second) {
+                Some(pair.verdict)
+            } else if (pair.second, pair.first) == (first, second) {
+                Some(pair.verdict.flipped())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Verdicts of every file against every other, row against column
+    #[must_use]
+    pub fn matrix(&self) -> Vec<Vec<Option<Verdict>>> {
+        (0..self.files)
+            .map(|i| (0..self.files).map(|j| self.verdict(i, j)).collect())
+            .collect()
+    }
+
+    /// Pairs of files that cannot be used together
+    pub fn incompatible(&self) -> impl Iterator<Item = &PairCheck> {
+        self.pairs.iter().filter(|pair| !pair.compatible)
+    }
+
+    /// Whether all files can be used together
+    #[must_use]
+    pub fn is_compatible(&self) -> bool {
+        self.incompatible().next().is_none()
+    }
+}
+
+/// How `first` relates to `second`, with details
+fn compare(first: &CompatFile, second: &CompatFile) -> (Verdict, Vec<String>) {
+    match (&first.contigs, &second.contigs) {
+        (FileContigs::Dictionary(a), FileContigs::Dictionary(b)) => {
+            compare_dictionaries(a, b, &first.source, &second.source)
+        }
+        (FileContigs::Features(features), FileContigs::Dictionary(dictionary)) => {
+            compare_features(features, dictionary, &first.source, &second.source)
+        }
+        (FileContigs::Dictionary(dictionary), FileContigs::Features(features)) => {
+            let (verdict, details) =
+                compare_features(features, dictionary, &second.source, &first.source);
+            (verdict.flipped(), details)
+        }
+        (FileContigs::Features(a), FileContigs::Features(b)) => {
+            compare_feature_names(a, b, &first.source, &second.source)
+        }
+    }
+}
+
+/// Contig lookups by name (including aliases) and by name key
+struct NameIndex<'a> {
+    by_name: HashMap<&'a str, &'a Contig>,
+    by_key: HashMap<String, &'a Contig>,
+}
+
+impl<'a> NameIndex<'a> {
+    fn new(contigs: &'a [Contig]) -> Self {
+        let mut by_name = HashMap::new();
+        let mut by_key = HashMap::new();
+        for contig in contigs {
+            for name in std::iter::once(&contig.name).chain(&contig.aliases) {
+                by_name.entry(name.as_str()).or_insert(contig);
+                by_key.entry(same_name_key(name)).or_insert(contig);
+            }
+        }
+        Self { by_name, by_key }
+    }
+
+    fn get(&self, name: &str) -> Option<&'a Contig> {
+        self.by_name.get(name).copied()
+    }
+
+    /// A contig with another name that counts as the same (`chr1`/`1`)
+    fn get_renamed(&self, name: &str) -> Option<&'a Contig> {
+        self.by_key
+            .get(&same_name_key(name))
+            .copied()
+            .filter(|c| c.name != name)
+    }
+}
+
+fn as_reference(source: &str, header: &QueryHeader) -> KnownReference {
+    KnownReference::new(
+        source,
+        source,
+        Assembly::Other("unknown".to_string()),
+        ReferenceSource::Custom("file".to_string()),
+    )
+    .with_contigs(header.contigs.clone())
+}
+
+/// Contigs of the query of `diagnosis` that have no counterpart in its reference
+fn unmatched(diagnosis: &MatchDiagnosis) -> impl Iterator<Item = &Contig> {
+    diagnosis.query_only.iter().chain(
+        diagnosis
+            .conflicts
+            .iter()
+            .filter(|c| c.expected.is_none())
+            .map(|c| &c.query_contig),
+    )
+}
+
+fn compare_dictionaries(
+    a: &QueryHeader,
+    b: &QueryHeader,
+    a_source: &str,
+    b_source: &str,
+) -> (Verdict, Vec<String>) {
+    let forward = MatchDiagnosis::analyze(a, &as_reference(b_source, b));
+    let backward = MatchDiagnosis::analyze(b, &as_reference(a_source, a));
+    let a_names = NameIndex::new(&a.contigs);
+    let b_names = NameIndex::new(&b.contigs);
+
+    let mut conflicts: Vec<String> = forward
+        .conflicts
+        .iter()
+        .filter(|c| c.expected.is_some())
+        .map(|c| c.description.clone())
+        .collect();
+    let mut renames: Vec<String> = forward
+        .renamed_matches
+        .iter()
+        .map(|r| format!("{} is {} in {b_source}", r.query_name, r.reference_name))
+        .collect();
+
+    // Contigs the diagnosis could not pair: same name with another length,
+    // another spelling of the name, or missing from the other file
+    let mut only_in_a = Vec::new();
+    for contig in unmatched(&forward) {
+        if let Some(other) = b_names.get(&contig.name) {
+            conflicts.push(format!(
+                "{} is {} bp in {a_source} but {} bp in {b_source}",
+                contig.name, contig.length, other.length
+            ));
+        } else if let Some(other) = b_names
+            .get_renamed(&contig.name)
+            .filter(|c| c.length == contig.length)
+        {
+            renames.push(format!("{} is {} in {b_source}", contig.name, other.name));
+        } else {
+            only_in_a.push(contig.name.as_str());
+        }
+    }
+    let only_in_b: Vec<&str> = unmatched(&backward)
+        .filter(|c| a_names.get(&c.name).is_none())
+        .filter(|c| {
+            !a_names
+                .get_renamed(&c.name)
+                .is_some_and(|other| other.length == c.length)
+        })
+        .map(|c| c.name.as_str())
+        .collect();
+
+    let mut details = Vec::new();
+    let verdict = if !conflicts.is_empty() {
+        details = conflicts;
+        Verdict::Conflicting
+    } else if !renames.is_empty() {
+        details = renames;
+        Verdict::Renamed
+    } else if only_in_a.len() == a.contigs.len() {
+        Verdict::Disjoint
+    } else if !only_in_a.is_empty() && !only_in_b.is_empty() {
+        Verdict::Overlapping
+    } else if forward.reordered {
+        details.push(format!("Contigs are in a different order in {b_source}"));
+        Verdict::Reordered
+    } else if !only_in_a.is_empty() {
+        Verdict::Superset
+    } else if !only_in_b.is_empty() {
+        Verdict::Subset
+    } else {
+        Verdict::Identical
+    };
+    if verdict != Verdict::Disjoint {
+        details.extend(list_names(&only_in_a, a_source));
+        details.extend(list_names(&only_in_b, b_source));
+    }
+    (verdict, details)
+}
+
+/// How a feature file relates to a file with a dictionary
+fn compare_features(
+    features: &[FeatureContig],
+    dictionary: &QueryHeader,
+    features_source: &str,
+    dictionary_source: &str,
+) -> (Verdict, Vec<String>) {
+    let names = NameIndex::new(&dictionary.contigs);
+    let mut conflicts = Vec::new();
+    let mut renames = Vec::new();
+    let mut missing = Vec::new();
+    for feature in features {
+        if let Some(contig) = names.get(&feature.name) {
+            if feature.max_end > contig.length {
+                conflicts.push(format!(
+                    "Features on {} in {features_source} end at {}, past its length of {} bp in {dictionary_source}",
+                    feature.name, feature.max_end, contig.length
+                ));
+            }
+        } else if let Some(contig) = names.get_renamed(&feature.name) {
+            renames.push(format!(
+                "{} is {} in {dictionary_source}",
+                feature.name, contig.name
+            ));
+        } else {
+            missing.push(feature.name.as_str());
+        }
+    }
+
+    let details = list_names(&missing, features_source).into_iter();
+    if !conflicts.is_empty() {
+        (
+            Verdict::Conflicting,
+            conflicts.into_iter().chain(details).collect(),
+        )
+    } else if !renames.is_empty() {
+        (
+            Verdict::Renamed,
+            renames.into_iter().chain(details).collect(),
+        )
+    } else if !features.is_empty() && missing.len() == features.len() {
+        (Verdict::Disjoint, Vec::new())
+    } else if !missing.is_empty() {
+        (Verdict::Overlapping, details.collect())
+    } else {
+        (Verdict::Subset, Vec::new())
+    }
+}
+
+/// How two feature files relate, by name alone
+fn compare_feature_names(
+    a: &[FeatureContig],
+    b: &[FeatureContig],
+    a_source: &str,
+    b_source: &str,
+) -> (Verdict, Vec<String>) {
+    let mut b_by_key: HashMap<String, &str> = HashMap::new();
+    for feature in b {
+        b_by_key
+            .entry(same_name_key(&feature.name))
+            .or_insert(&feature.name);
+    }
+    let b_names: std::collections::HashSet<&str> = b.iter().map(|f| f.name.as_str()).collect();
+
+    let renames: Vec<String> = a
+        .iter()
+        .filter(|feature| !b_names.contains(feature.name.as_str()))
+        .filter_map(|feature| {
+            b_by_key
+                .get(&same_name_key(&feature.name))
+                .map(|other| format!("{} in {a_source} is {other} in {b_source}", feature.name))
+        })
+        .collect();
+    if renames.is_empty() {
+        (Verdict::Compatible, Vec::new())
+    } else {
+        (Verdict::Renamed, renames)
+    }
+}
+
+/// "N contigs only in source: a, b, ...", or nothing for no names
+fn list_names(names: &[&str], source: &str) -> Option<String> {
+    if names.is_empty() {
+        return None;
+    }
+    let mut listed = names
+        .iter()
+        .take(MAX_LISTED_NAMES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if names.len() > MAX_LISTED_NAMES {
+        listed.push_str(&format!(" and {} more", names.len() - MAX_LISTED_NAMES));
+    }
+    Some(format!(
+        "{} contig{} only in {source}: {listed}",
+        names.len(),
+        if names.len() == 1 { "" } else { "s" }
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MD5_1: &str = "6aef897c3d6ff0c78aff06ac189178dd";
+    const MD5_2: &str = "f98db672eb0993dcfdabafe2a882905c";
+    const MD5_OTHER: &str = "0123456789abcdef0123456789abcdef";
+
+    fn dictionary(source: &str, contigs: Vec<Contig>) -> CompatFile {
+        CompatFile::new(source, FileContigs::Dictionary(QueryHeader::new(contigs)))
+    }
+
+    fn reference(source: &str, contigs: Vec<Contig>) -> CompatFile {
+        dictionary(source, contigs).with_role(FileRole::Reference)
+    }
+
+    fn features(source: &str, contigs: &[(&str, u64)]) -> CompatFile {
+        CompatFile::new(
+            source,
+            FileContigs::Features(
+                contigs
+                    .iter()
+                    .map(|(name, max_end)| FeatureContig {
+                        name: (*name).to_string(),
+                        max_end: *max_end,
+                        records: 1,
+                    })
+                    .collect(),
+            ),
+        )
+    }
+
+    fn ucsc() -> Vec<Contig> {
+        vec![
+            Contig::new("chr1", 1000).with_md5(MD5_1),
+            Contig::new("chr2", 500).with_md5(MD5_2),
+        ]
+    }
+
+    fn check(first: &CompatFile, second: &CompatFile) -> Verdict {
+        CompatReport::check(&[first.clone(), second.clone()])
+            .verdict(0, 1)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dictionaries_identical_subset_reordered() {
+        let full = dictionary("a.dict", ucsc());
+        assert_eq!(
+            check(&full, &dictionary("b.bam", ucsc())),
+            Verdict::Identical
+        );
+
+        let partial = dictionary("b.vcf", vec![Contig::new("chr2", 500)]);
+        assert_eq!(check(&partial, &full), Verdict::Subset);
+        assert_eq!(check(&full, &partial), Verdict::Superset);
+
+        let mut reversed = ucsc();
+        reversed.reverse();
+        assert_eq!(
+            check(&full, &dictionary("c.bam", reversed)),
+            Verdict::Reordered
+        );
+    }
+
+    #[test]
+    fn test_dictionaries_conflicting() {
+        let full = dictionary("a.dict", ucsc());
+        let other_sequence = dictionary(
+            "b.dict",
+            vec![
+                Contig::new("chr1", 1000).with_md5(MD5_OTHER),
+                Contig::new("chr2", 500).with_md5(MD5_2),
+            ],
+        );
+        assert_eq!(check(&full, &other_sequence), Verdict::Conflicting);
+
+        let other_length = dictionary(
+            "c.bam",
+            vec![Contig::new("chr1", 999), Contig::new("chr2", 500)],
+        );
+        let report = CompatReport::check(&[full, other_length]);
+        assert_eq!(report.verdict(0, 1), Some(Verdict::Conflicting));
+        assert!(report.pairs[0].details[0].contains("1000 bp"));
+        assert!(!report.is_compatible());
+    }
+
+    #[test]
+    fn test_dictionaries_renamed_overlapping_disjoint() {
+        let full = dictionary("a.dict", ucsc());
+        let ncbi = dictionary("b.bam", vec![Contig::new("1", 1000), Contig::new("2", 500)]);
+        assert_eq!(check(&full, &ncbi), Verdict::Renamed);
+
+        let overlapping = dictionary(
+            "c.bam",
+            vec![Contig::new("chr1", 1000), Contig::new("chrX", 2000)],
+        );
+        let report = CompatReport::check(&[full.clone(), overlapping]);
+        assert_eq!(report.verdict(0, 1), Some(Verdict::Overlapping));
+        assert_eq!(report.pairs[0].details.len(), 2);
+
+        let disjoint = dictionary("d.bam", vec![Contig::new("chrX", 2000)]);
+        assert_eq!(check(&full, &disjoint), Verdict::Disjoint);
+    }
+
+    #[test]
+    fn test_features_against_dictionary() {
+        let full = dictionary("ref.dict", ucsc());
+        assert_eq!(
+            check(&features("t.bed", &[("chr1", 900)]), &full),
+            Verdict::Subset
+        );
+        assert_eq!(
+            check(&full, &features("t.bed", &[("chr1", 900)])),
+            Verdict::Superset
+        );
+        assert_eq!(
+            check(&features("t.bed", &[("chr1", 1001)]), &full),
+            Verdict::Conflicting
+        );
+        assert_eq!(
+            check(&features("t.bed", &[("1", 900)]), &full),
+            Verdict::Renamed
+        );
+        assert_eq!(
+            check(&features("t.bed", &[("chr1", 900), ("chrUn", 10)]), &full),
+            Verdict::Overlapping
+        );
+        assert_eq!(
+            check(&features("t.bed", &[("chrUn", 10)]), &full),
+            Verdict::Disjoint
+        );
+    }
+
+    #[test]
+    fn test_feature_files_by_name() {
+        let ucsc_bed = features("a.bed", &[("chr1", 10), ("chrM", 10)]);
+        assert_eq!(
+            check(&ucsc_bed, &features("b.bed", &[("chr2", 10)])),
+            Verdict::Compatible
+        );
+        assert_eq!(
+            check(&ucsc_bed, &features("b.gtf", &[("MT", 10)])),
+            Verdict::Renamed
+        );
+    }
+
+    #[test]
+    fn test_matrix() {
+        let report = CompatReport::check(&[
+            dictionary("a.dict", ucsc()),
+            dictionary("b.vcf", vec![Contig::new("chr1", 1000)]),
+            features("c.bed", &[("chr2", 100)]),
+        ]);
+        let matrix = report.matrix();
+        assert_eq!(matrix[0][0], None);
+        assert_eq!(matrix[0][1], Some(Verdict::Superset));
+        assert_eq!(matrix[1][0], Some(Verdict::Subset));
+        assert_eq!(matrix[2][0], Some(Verdict::Subset));
+        assert_eq!(matrix[1][2], Some(Verdict::Disjoint));
+        assert!(!report.is_compatible());
+        assert_eq!(report.incompatible().count(), 1);
+    }
+
+    #[test]
+    fn test_reference_must_hold_every_contig() {
+        let reference_dict = reference("ref.dict", ucsc());
+        let mut with_decoy = ucsc();
+        with_decoy.push(Contig::new("chrUn_decoy", 100));
+        let reads = dictionary("reads.sam", with_decoy.clone());
+
+        // Data with a contig the reference lacks
+        let report = CompatReport::check(&[reads.clone(), reference_dict.clone()]);
+        assert_eq!(report.verdict(0, 1), Some(Verdict::Superset));
+        assert!(!report.is_compatible());
+        let details = &report.pairs[0].details;
+        assert!(details[0].contains("Reference ref.dict"), "{details:?}");
+        assert!(details[1].contains("chrUn_decoy"), "{details:?}");
+
+        // Either order
+        let report = CompatReport::check(&[reference_dict.clone(), reads.clone()]);
+        assert_eq!(report.verdict(0, 1), Some(Verdict::Subset));
+        assert!(!report.is_compatible());
+
+        // Data on fewer contigs than the reference is fine
+        let partial = dictionary("calls.vcf", vec![Contig::new("chr1", 1000)]);
+        assert!(CompatReport::check(&[partial.clone(), reference_dict]).is_compatible());
+
+        // Two data files may differ in coverage
+        assert!(CompatReport::check(&[partial, reads]).is_compatible());
+
+        // A reference that is itself a subset of another reference is not
+        let full_reference = reference("full.fa", with_decoy);
+        let report = CompatReport::check(&[reference("ref.fa", ucsc()), full_reference]);
+        assert!(!report.is_compatible());
+
+        // Features outside the reference are caught as before, features inside pass
+        assert!(CompatReport::check(&[
+            features("t.bed", &[("chr1", 10)]),
+            reference("ref.fa", ucsc())
+        ])
+        .is_compatible());
+    }
+}
@@ -0,0 +1,381 @@
+//! Decomposition of mixed-build headers into their source references.
+//!
+//! A header assembled by hand from several references (e.g. hg38 primary
+//! chromosomes, decoys from another build and chrM from hg19) matches no single
+//! catalog reference well. [`Decomposition`] explains such a query as a small set
+//! of catalog references using a greedy set cover over per-contig provenance:
+//!
+//! 1. Each query contig is attributed to every catalog reference containing its
+//!    sequence, by MD5 or sha512t24u. Contigs without any digest fall back to
+//!    name and length.
+//! 2. References are chosen one at a time by how many remaining query contigs they
+//!    explain. Ties prefer references that use the query's contig names, then
+//!    smaller references.
+//! 3. Contigs that no reference contains are reported as unexplained.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::types::ReferenceId;
+
+/// A query explained as a combination of catalog references
+#[derive(Debug, Clone, Serialize)]
+pub struct Decomposition {
+    /// Source references in the order they were chosen (largest contribution first)
+    pub components: Vec<SourceComponent>,
+
+    /// Names of query contigs not found in any catalog reference
+    pub unexplained: Vec<String>,
+}
+
+/// The query contigs attributed to one catalog reference
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceComponent {
+    /// Catalog reference ID
+    pub reference_id: ReferenceId,
+
+    /// Human-readable name of the reference
+    pub display_name: String,
+
+    /// Names of query contigs attributed to this reference, in query order
+    pub contigs: Vec<String>,
+
+    /// How many of those contigs were attributed by name and length only (no digest)
+    pub name_length_only: usize,
+
+    /// Short description of the attributed contigs (e.g. "24 primary chromosomes, chrM")
+    pub summary: String,
+}
+
+/// How a query contig was attributed to catalog references
+struct Provenance {
+    refs: HashSet<usize>,
+    by_digest: bool,
+}
+
+impl Decomposition {
+    /// Decompose a query into the catalog references its contigs come from
+    #[must_use]
+    pub fn analyze(query: &QueryHeader, catalog: &ReferenceCatalog) -> Self {
+        let provenance: Vec<Provenance> = query
+            .contigs
+            .iter()
+            .map(|contig| contig_provenance(contig, catalog))
+            .collect();
+
+        // Query contig names present in each candidate reference, for tie-breaking
+        let mut names_in_ref: HashMap<usize, HashSet<&str>> = HashMap::new();
+        for &ref_idx in provenance.iter().flat_map(|p| &p.refs) {
+            names_in_ref.entry(ref_idx).or_insert_with(|| {
+                catalog.references[ref_idx]
+                    .contigs
+                    .iter()
+                    .map(|c| c.name.as_str())
+                    .collect()
+            });
+        }
+
+        let mut uncovered: HashSet<usize> = provenance
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !p.refs.is_empty())
+            .map(|(i, _)| i)
+            .collect();
+        let mut components = Vec::new();
+
+        while !uncovered.is_empty() {
+            // (explained, same-name, -size, -index) so ties go to smaller, earlier refs
+            let mut best: Option<(usize, (usize, usize, isize, isize))> = None;
+            for (&ref_idx, names) in &names_in_ref {
+                let explained: Vec<usize> = uncovered
+                    .iter()
+                    .copied()
+                    .filter(|&i| provenance[i].refs.contains(&ref_idx))
+                    .collect();
+                if explained.is_empty() {
+                    continue;
+                }
+                let same_name = explained
+                    .iter()
+                    .filter(|&&i| names.contains(query.contigs[i].name.as_str()))
+                    .count();
+                let size = catalog.references[ref_idx].contigs.len();
+                let key = (
+                    explained.len(),
+                    same_name,
+                    -isize::try_from(size).unwrap_or(isize::MAX),
+                    -isize::try_from(ref_idx).unwrap_or(isize::MAX),
+                );
+                if best.as_ref().map_or(true, |(_, best_key)| key > *best_key) {
+                    best = Some((ref_idx, key));
+                }
+            }
+
+            let Some((ref_idx, _)) = best else {
+                break;
+            };
+
+            let mut indices: Vec<usize> = uncovered
+                .iter()
+                .copied()
+                .filter(|&i| provenance[i].refs.contains(&ref_idx))
+                .collect();
+            indices.sort_unstable();
+            for i in &indices {
+                uncovered.remove(i);
+            }
+
+            let contigs: Vec<&Contig> = indices.iter().map(|&i| &query.contigs[i]).collect();
+            let reference = &catalog.references[ref_idx];
+            components.push(SourceComponent {
+                reference_id: reference.id.clone(),
+                display_name: reference.display_name.clone(),
+                contigs: contigs.iter().map(|c| c.name.clone()).collect(),
+                name_length_only: indices
+                    .iter()
+                    .filter(|&&i| !provenance[i].by_digest)
+                    .count(),
+                summary: summarize_contigs(&contigs),
+            });
+        }
+
+        let unexplained = query
+            .contigs
+            .iter()
+            .zip(&provenance)
+            .filter(|(_, p)| p.refs.is_empty())
+            .map(|(c, _)| c.name.clone())
+            .collect();
+
+        Self {
+            components,
+            unexplained,
+        }
+    }
+
+    /// Whether the query draws on more than one catalog reference
+    #[must_use]
+    pub fn is_mixed(&self) -> bool {
+        self.components.len() > 1
+    }
+
+    /// One-line description, e.g. "24 primary chromosomes from `hg38_ucsc` + chrM from `hg19_ucsc`"
+    #[must_use]
+    pub fn describe(&self) -> String {
+        self.components
+            .iter()
+            .map(|c| format!("{} from {}", c.summary, c.reference_id))
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
+/// Catalog references containing a query contig's sequence
+fn contig_provenance(contig: &Contig, catalog: &ReferenceCatalog) -> Provenance {
+    let mut refs: HashSet<usize> = HashSet::new();
+
+    if let Some(md5) = &contig.md5 {
+        if let Some(indices) = catalog.md5_to_refs.get(&md5.to_ascii_lowercase()) {
+            refs.extend(indices);
+        }
+    }
+    if let Some(digest) = &contig.sha512t24u {
+        if let Some(indices) = catalog.sha512t24u_to_refs.get(digest) {
+            refs.extend(indices);
+        }
+    }
+
+    // A digest that is not in the catalog means the sequence is unknown, even if
+    // a contig with the same name and length exists
+    let by_digest = contig.md5.is_some() || contig.sha512t24u.is_some();
+    if !by_digest {
+        let key = (contig.name.clone(), contig.length);
+        for map in [&catalog.name_length_to_refs, &catalog.alias_length_to_refs] {
+            if let Some(indices) = map.get(&key) {
+                refs.extend(indices);
+            }
+        }
+    }
+
+    Provenance { refs, by_digest }
+}
+
+/// Summarize contigs by kind, e.g. "24 primary chromosomes, 2 alt contigs, chrM"
+fn summarize_contigs(contigs: &[&Contig]) -> String {
+    let mut primary = 0usize;
+    let mut alt = 0usize;
+    let mut decoy = 0usize;
+    let mut hla = 0usize;
+    let mut other = 0usize;
+    let mut mito = Vec::new();
+
+    for contig in contigs {
+        if contig.is_mitochondrial() {
+            mito.push(contig.name.as_str());
+        } else if contig.is_primary_chromosome() {
+            primary += 1;
+        } else if contig.is_alt() {
+            alt += 1;
+        } else if contig.name.starts_with("HLA-") {
+            hla += 1;
+        } else if contig.is_decoy() {
+            decoy += 1;
+        } else {
+            other += 1;
+        }
+    }
+
+    let plural = |n: usize, singular: &str, plural: &str| {
+        if n == 1 {
+            format!("1 {singular}")
+        } else {
+            format!("{n} {plural}")
+        }
+    };
+
+    let mut parts = Vec::new();
+    if primary > 0 {
+        parts.push(plural(primary, "primary chromosome", "primary chromosomes"));
+    }
+    if alt > 0 {
+        parts.push(plural(alt, "alt contig", "alt contigs"));
+    }
+    if decoy > 0 {
+        parts.push(plural(
+            decoy,
+            "decoy/unplaced contig",
+            "decoy/unplaced contigs",
+        ));
+    }
+    if hla > 0 {
+        parts.push(plural(hla, "HLA contig", "HLA contigs"));
+    }
+    if other > 0 {
+        parts.push(plural(other, "other contig", "other contigs"));
+    }
+    parts.extend(mito.into_iter().map(ToString::to_string));
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::reference::KnownReference;
+    use crate::core::types::{Assembly, ReferenceSource};
+
+    fn reference(id: &str, contigs: Vec<Contig>) -> KnownReference {
+        KnownReference::new(
+            id,
+            id,
+            Assembly::Other("test".to_string()),
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(contigs)
+    }
+
+    fn md5(n: u8) -> String {
+        format!("{n:032x}")
+    }
+
+    fn make_catalog() -> ReferenceCatalog {
+        let mut catalog = ReferenceCatalog::new();
+        // Primary chromosomes + mito (build A)
+        catalog.add_reference(reference(
+            "build_a",
+            vec![
+                Contig::new("chr1", 100).with_md5(md5(1)),
+                Contig::new("chr2", 90).with_md5(md5(2)),
+                Contig::new("chrM", 16569).with_md5(md5(3)),
+            ],
+        ));
+        // Same primaries with decoys (build A + decoys)
+        catalog.add_reference(reference(
+            "build_a_decoy",
+            vec![
+                Contig::new("chr1", 100).with_md5(md5(1)),
+                Contig::new("chr2", 90).with_md5(md5(2)),
+                Contig::new("chrM", 16569).with_md5(md5(3)),
+                Contig::new("chrUn_decoy1", 10).with_md5(md5(4)),
+            ],
+        ));
+        // A different build with a different mito
+        catalog.add_reference(reference(
+            "build_b",
+            vec![
+                Contig::new("chr1", 110).with_md5(md5(5)),
+                Contig::new("chrM", 16571).with_md5(md5(6)),
+            ],
+        ));
+        catalog
+    }
+
+    #[test]
+    fn test_single_source() {
+        let catalog = make_catalog();
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 100).with_md5(md5(1)),
+            Contig::new("chr2", 90).with_md5(md5(2)),
+        ]);
+
+        let decomposition = Decomposition::analyze(&query, &catalog);
+        assert!(!decomposition.is_mixed());
+        // Ties go to the smaller reference
+        assert_eq!(decomposition.components[0].reference_id.0, "build_a");
+        assert!(decomposition.unexplained.is_empty());
+    }
+
+    #[test]
+    fn test_mixed_sources() {
+        let catalog = make_catalog();
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 100).with_md5(md5(1)),
+            Contig::new("chr2", 90).with_md5(md5(2)),
+            Contig::new("chrUn_decoy1", 10).with_md5(md5(4)),
+            Contig::new("chrM", 16571).with_md5(md5(6)),
+            Contig::new("chrUn_custom", 5).with_md5(md5(7)),
+        ]);
+
+        let decomposition = Decomposition::analyze(&query, &catalog);
+        assert!(decomposition.is_mixed());
+        assert_eq!(decomposition.components.len(), 2);
+
+        let first = &decomposition.components[0];
+        assert_eq!(first.reference_id.0, "build_a_decoy");
+        assert_eq!(first.contigs, vec!["chr1", "chr2", "chrUn_decoy1"]);
+        assert_eq!(
+            first.summary,
+            "2 primary chromosomes, 1 decoy/unplaced contig"
+        );
+
+        let second = &decomposition.components[1];
+        assert_eq!(second.reference_id.0, "build_b");
+        assert_eq!(second.contigs, vec!["chrM"]);
+
+        assert_eq!(decomposition.unexplained, vec!["chrUn_custom"]);
+        assert_eq!(
+            decomposition.describe(),
+            "2 primary chromosomes, 1 decoy/unplaced contig from build_a_decoy + chrM from build_b"
+        );
+    }
+
+    #[test]
+    fn test_name_length_fallback_without_digests() {
+        let catalog = make_catalog();
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 110),
+            Contig::new("chrM", 16571),
+            // Digest not in the catalog: unexplained despite matching name and length
+            Contig::new("chr2", 90).with_md5(md5(9)),
+        ]);
+
+        let decomposition = Decomposition::analyze(&query, &catalog);
+        assert_eq!(decomposition.components.len(), 1);
+        assert_eq!(decomposition.components[0].reference_id.0, "build_b");
+        assert_eq!(decomposition.components[0].name_length_only, 2);
+        assert_eq!(decomposition.unexplained, vec!["chr2"]);
+    }
+}
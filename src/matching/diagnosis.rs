@@ -1,9 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
+use serde::{Deserialize, Serialize};
+
 use crate::core::contig::Contig;
 use crate::core::header::QueryHeader;
 use crate::core::reference::KnownReference;
 use crate::core::types::MatchType;
+use crate::matching::disambiguation::DisambiguationPlan;
 
 /// Detailed diagnosis of differences between query and reference
 #[derive(Debug, Clone)]
@@ -14,7 +17,7 @@ pub struct MatchDiagnosis {
     /// Query contigs that match reference exactly
     pub exact_matches: Vec<ContigMatch>,
 
-    /// Query contigs that match by MD5 but have different names
+    /// Query contigs that match by sequence digest but have different names
     pub renamed_matches: Vec<RenamedContig>,
 
     /// Query contigs with no match in reference
@@ -47,9 +50,9 @@ pub struct ContigConflict {
     pub description: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConflictType {
-    /// Same name, different sequence (different MD5/length)
+    /// Same name, different sequence (different MD5/sha512t24u/length)
     SequenceMismatch,
     /// Mitochondrial from different build
     MitochondrialMismatch,
@@ -76,6 +79,8 @@ pub enum Suggestion {
         reason: String,
         suggested_reference: String,
     },
+    /// Several references tie; check these contigs to tell them apart
+    Disambiguate { plan: DisambiguationPlan },
 }
 
 impl MatchDiagnosis {
@@ -94,6 +99,11 @@ impl MatchDiagnosis {
             .iter()
             .filter_map(|c| c.md5.as_ref().map(|m| (m.as_str(), c)))
             .collect();
+        let ref_by_sha512t24u: HashMap<&str, &Contig> = reference
+            .contigs
+            .iter()
+            .filter_map(|c| c.sha512t24u.as_ref().map(|d| (d.as_str(), c)))
+            .collect();
 
         // Use exact names for matching (no normalization)
         // Index by both primary name AND aliases for proper alias-based matching
@@ -107,30 +117,38 @@ impl MatchDiagnosis {
             }
         }
 
-        let mut matched_ref_md5s: HashSet<&str> = HashSet::new();
+        let mut matched_ref_digests: HashSet<&str> = HashSet::new();
         let mut matched_ref_name_lengths: HashSet<(String, u64)> = HashSet::new();
 
         // Analyze each query contig using exact names
         for q_contig in &query.contigs {
             let q_key = (q_contig.name.clone(), q_contig.length);
 
-            // Try MD5 match first
-            if let Some(q_md5) = &q_contig.md5 {
-                if let Some(r_contig) = ref_by_md5.get(q_md5.as_str()) {
-                    matched_ref_md5s.insert(q_md5.as_str());
+            // Try digest match first (MD5, then sha512t24u)
+            let digest_match = q_contig
+                .md5
+                .as_deref()
+                .and_then(|d| ref_by_md5.get_key_value(d))
+                .or_else(|| {
+                    q_contig
+                        .sha512t24u
+                        .as_deref()
+                        .and_then(|d| ref_by_sha512t24u.get_key_value(d))
+                });
+            if let Some((digest, r_contig)) = digest_match {
+                matched_ref_digests.insert(digest);
 
-                    if q_contig.name == r_contig.name {
-                        // Exact match
-                        exact_matches.push(ContigMatch);
-                    } else {
-                        // Same sequence, different name
-                        renamed_matches.push(RenamedContig {
-                            query_name: q_contig.name.clone(),
-                            reference_name: r_contig.name.clone(),
-                        });
-                    }
-                    continue;
+                if q_contig.name == r_contig.name {
+                    // Exact match
+                    exact_matches.push(ContigMatch);
+                } else {
+                    // Same sequence, different name
+                    renamed_matches.push(RenamedContig {
+                        query_name: q_contig.name.clone(),
+                        reference_name: r_contig.name.clone(),
+                    });
                 }
+                continue;
             }
 
             // Try name+length match (direct name or via alias)
@@ -147,26 +165,21 @@ impl MatchDiagnosis {
                 matched_ref_name_lengths.insert(matched_key);
 
                 // Check if this might be a conflict (same position but different sequence)
-                if let (Some(q_md5), Some(r_md5)) = (&q_contig.md5, &r_contig.md5) {
-                    if q_md5 != r_md5 {
-                        // Different sequence!
-                        let conflict_type = if q_contig.is_mitochondrial() {
-                            ConflictType::MitochondrialMismatch
-                        } else {
-                            ConflictType::SequenceMismatch
-                        };
-
-                        conflicts.push(ContigConflict {
-                            query_contig: q_contig.clone(),
-                            expected: Some(r_contig.clone()),
-                            conflict_type,
-                            description: format!(
-                                "Contig {} has same name/length but different MD5 (query: {}, ref: {})",
-                                q_contig.name, q_md5, r_md5
-                            ),
-                        });
-                        continue;
-                    }
+                if q_contig.digests_match(r_contig) == Some(false) {
+                    // Different sequence!
+                    let conflict_type = if q_contig.is_mitochondrial() {
+                        ConflictType::MitochondrialMismatch
+                    } else {
+                        ConflictType::SequenceMismatch
+                    };
+
+                    conflicts.push(ContigConflict {
+                        query_contig: q_contig.clone(),
+                        expected: Some(r_contig.clone()),
+                        conflict_type,
+                        description: describe_digest_mismatch(q_contig, r_contig),
+                    });
+                    continue;
                 }
 
                 // Check all 4 combinations - any match via name or alias should be treated equally:
@@ -219,7 +232,7 @@ impl MatchDiagnosis {
         }
 
         // Determine match type and reordering
-        let reordered = !super::scoring::MatchScore::calculate(query, reference).order_preserved;
+        let reordered = !super::scoring::order_preserved(query, reference);
 
         let match_type = determine_match_type(
             &exact_matches,
@@ -251,6 +264,30 @@ impl MatchDiagnosis {
     }
 }
 
+/// Describe a same-name/length contig whose sequence digest differs from the reference
+fn describe_digest_mismatch(q_contig: &Contig, r_contig: &Contig) -> String {
+    let digests = match (&q_contig.md5, &r_contig.md5) {
+        (Some(q_md5), Some(r_md5)) if !q_md5.eq_ignore_ascii_case(r_md5) => {
+            Some(("MD5", q_md5, r_md5))
+        }
+        _ => match (&q_contig.sha512t24u, &r_contig.sha512t24u) {
+            (Some(q_sha), Some(r_sha)) if q_sha != r_sha => Some(("sha512t24u", q_sha, r_sha)),
+            _ => None,
+        },
+    };
+
+    match digests {
+        Some((kind, q_digest, r_digest)) => format!(
+            "Contig {} has same name/length but different {kind} (query: {q_digest}, ref: {r_digest})",
+            q_contig.name
+        ),
+        None => format!(
+            "Contig {} has same name/length but different sequence",
+            q_contig.name
+        ),
+    }
+}
+
 fn determine_match_type(
     exact_matches: &[ContigMatch],
     renamed_matches: &[RenamedContig],
@@ -0,0 +1,565 @@
+//! Plans for telling apart catalog references that tie for a query.
+//!
+//! A header without MD5s often matches several references equally well: the
+//! `GRCh38` analysis sets differ only in masked bases, and hg19 and b37 agree on
+//! every name once aliases are taken into account. [`DisambiguationPlan`] picks
+//! the few contigs whose digest or length splits the tied candidates, using the
+//! catalog's `md5_to_refs` and `name_length_to_refs` indexes to skip the
+//! contigs that every candidate shares.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::types::ReferenceId;
+
+/// Fixed cost of a step, in bases, so that a handful of small MD5s is not
+/// preferred over a single length check
+const STEP_OVERHEAD_BASES: u64 = 1_000_000;
+
+/// Kind of evidence a discriminator asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscriminatorEvidence {
+    /// MD5 of a contig, computed from the FASTA
+    Md5,
+    /// Length of a contig the query does not list
+    Length,
+}
+
+impl std::fmt::Display for DiscriminatorEvidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Md5 => write!(f, "MD5"),
+            Self::Length => write!(f, "length"),
+        }
+    }
+}
+
+/// Candidates that expect the same value for a discriminating contig
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscriminatorGroup {
+    /// Expected MD5 or length, `None` if these candidates lack the contig
+    pub expected: Option<String>,
+
+    /// Candidates expecting this value
+    pub references: Vec<ReferenceId>,
+}
+
+/// A contig whose MD5 or length splits the tied candidates
+#[derive(Debug, Clone, Serialize)]
+pub struct Discriminator {
+    /// Contig to check
+    pub contig: String,
+
+    /// Whether to compute the contig's MD5 or look up its length
+    pub evidence: DiscriminatorEvidence,
+
+    /// Candidates grouped by the value they expect
+    pub groups: Vec<DiscriminatorGroup>,
+
+    /// Bases that must be hashed for this check (zero for length checks)
+    pub bases: u64,
+}
+
+impl Discriminator {
+    /// Human-readable description, e.g. "chrM length distinguishes hg19 (16571) vs b37 (16569)"
+    #[must_use]
+    pub fn description(&self) -> String {
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| {
+                let references = group
+                    .references
+                    .iter()
+                    .map(|id| id.0.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match &group.expected {
+                    Some(value) => format!("{references} ({})", short_value(value)),
+                    None => format!("{references} (absent)"),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" vs ");
+        format!("{} {} distinguishes {groups}", self.contig, self.evidence)
+    }
+}
+
+/// Shorten MD5s for display; lengths are shown in full
+fn short_value(value: &str) -> &str {
+    if value.len() == 32 {
+        &value[..8]
+    } else {
+        value
+    }
+}
+
+/// The checks that tell apart a set of tied catalog references
+#[derive(Debug, Clone, Serialize)]
+pub struct DisambiguationPlan {
+    /// References that tie for the query
+    pub candidates: Vec<ReferenceId>,
+
+    /// Contigs to check, cheapest and most informative first
+    pub discriminators: Vec<Discriminator>,
+
+    /// Groups of candidates that no contig can tell apart
+    pub unresolved: Vec<Vec<ReferenceId>>,
+}
+
+impl DisambiguationPlan {
+    /// Build a plan for the given tied candidates (indices into the catalog).
+    ///
+    /// Two kinds of check are considered: the MD5 of any contig the query does
+    /// not already carry a digest for, and the length of a contig the query
+    /// does not list. Checks are chosen greedily by the number of newly
+    /// separated candidate pairs per base hashed, so free length checks come
+    /// first. Returns `None` for fewer than two candidates.
+    #[must_use]
+    pub fn build(
+        query: &QueryHeader,
+        catalog: &ReferenceCatalog,
+        candidates: &[usize],
+    ) -> Option<Self> {
+        if candidates.len() < 2 {
+            return None;
+        }
+
+        // Each candidate's contigs by name and alias
+        let by_name: Vec<HashMap<&str, &Contig>> = candidates
+            .iter()
+            .map(|&idx| {
+                let mut names = HashMap::new();
+                for contig in &catalog.references[idx].contigs {
+                    for name in std::iter::once(&contig.name).chain(&contig.aliases) {
+                        names.entry(name.as_str()).or_insert(contig);
+                    }
+                }
+                names
+            })
+            .collect();
+
+        let mut options = md5_options(query, catalog, candidates, &by_name);
+        options.extend(absent_options(query, catalog, candidates, &by_name));
+
+        let chosen = choose_options(&options, candidates.len());
+
+        // Candidates are unresolved if they share a group in every chosen check
+        let mut unresolved_groups: Vec<(Vec<usize>, Vec<ReferenceId>)> = Vec::new();
+        for (position, &idx) in candidates.iter().enumerate() {
+            let key: Vec<usize> = chosen
+                .iter()
+                .map(|&option| options[option].partition[position])
+                .collect();
+            let id = catalog.references[idx].id.clone();
+            match unresolved_groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, ids)) => ids.push(id),
+                None => unresolved_groups.push((key, vec![id])),
+            }
+        }
+
+        Some(Self {
+            candidates: candidates
+                .iter()
+                .map(|&idx| catalog.references[idx].id.clone())
+                .collect(),
+            discriminators: chosen
+                .into_iter()
+                .map(|option| options[option].to_discriminator(catalog, candidates))
+                .collect(),
+            unresolved: unresolved_groups
+                .into_iter()
+                .map(|(_, ids)| ids)
+                .filter(|ids| ids.len() > 1)
+                .collect(),
+        })
+    }
+}
+
+/// A possible check and how it partitions the candidates
+struct CheckOption {
+    contig: String,
+    evidence: DiscriminatorEvidence,
+    bases: u64,
+    /// Distinct expected values, in order of first appearance
+    values: Vec<Option<String>>,
+    /// Index into `values` for each candidate
+    partition: Vec<usize>,
+}
+
+impl CheckOption {
+    /// Returns `None` unless the expected values split the candidates
+    fn new(
+        contig: String,
+        evidence: DiscriminatorEvidence,
+        bases: u64,
+        expected: Vec<Option<String>>,
+    ) -> Option<Self> {
+        let mut values: Vec<Option<String>> = Vec::new();
+        let partition = expected
+            .into_iter()
+            .map(|value| {
+                values.iter().position(|v| *v == value).unwrap_or_else(|| {
+                    values.push(value);
+                    values.len() - 1
+                })
+            })
+            .collect();
+        (values.len() > 1).then_some(Self {
+            contig,
+            evidence,
+            bases,
+            values,
+            partition,
+        })
+    }
+
+    fn separates(&self, i: usize, j: usize) -> bool {
+        self.partition[i] != self.partition[j]
+    }
+
+    fn to_discriminator(&self, catalog: &ReferenceCatalog, candidates: &[usize]) -> Discriminator {
+        // Present values first, candidates lacking the contig last
+        let mut order: Vec<usize> = (0..self.values.len()).collect();
+        order.sort_by_key(|&v| self.values[v].is_none());
+
+        Discriminator {
+            contig: self.contig.clone(),
+            evidence: self.evidence,
+            groups: order
+                .into_iter()
+                .map(|v| DiscriminatorGroup {
+                    expected: self.values[v].clone(),
+                    references: candidates
+                        .iter()
+                        .zip(&self.partition)
+                        .filter(|(_, &group)| group == v)
+                        .map(|(&idx, _)| catalog.references[idx].id.clone())
+                        .collect(),
+                })
+                .collect(),
+            bases: self.bases,
+        }
+    }
+}
+
+/// True if every candidate appears in the index entry
+fn shared_by_all(indices: Option<&Vec<usize>>, candidates: &[usize]) -> bool {
+    indices.is_some_and(|indices| candidates.iter().all(|idx| indices.contains(idx)))
+}
+
+/// MD5 checks for query contigs that lack a digest
+fn md5_options(
+    query: &QueryHeader,
+    catalog: &ReferenceCatalog,
+    candidates: &[usize],
+    by_name: &[HashMap<&str, &Contig>],
+) -> Vec<CheckOption> {
+    query
+        .contigs
+        .iter()
+        .filter(|contig| contig.md5.is_none() && contig.sha512t24u.is_none())
+        .filter_map(|query_contig| {
+            let expected: Vec<Option<String>> = by_name
+                .iter()
+                .map(|names| {
+                    std::iter::once(&query_contig.name)
+                        .chain(&query_contig.aliases)
+                        .find_map(|name| names.get(name.as_str()))
+                        .filter(|contig| contig.length == query_contig.length)
+                        .and_then(|contig| contig.md5.as_ref())
+                        .map(|md5| md5.to_lowercase())
+                })
+                .collect();
+
+            // Skip sequences every candidate contains
+            if expected[0]
+                .as_ref()
+                .is_some_and(|md5| shared_by_all(catalog.md5_to_refs.get(md5), candidates))
+            {
+                return None;
+            }
+
+            CheckOption::new(
+                query_contig.name.clone(),
+                DiscriminatorEvidence::Md5,
+                query_contig.length,
+                expected,
+            )
+        })
+        .collect()
+}
+
+/// Length and MD5 checks for candidate contigs the query does not list
+fn absent_options(
+    query: &QueryHeader,
+    catalog: &ReferenceCatalog,
+    candidates: &[usize],
+    by_name: &[HashMap<&str, &Contig>],
+) -> Vec<CheckOption> {
+    let query_names: HashSet<&str> = query
+        .contigs
+        .iter()
+        .flat_map(|contig| std::iter::once(&contig.name).chain(&contig.aliases))
+        .map(String::as_str)
+        .collect();
+
+    let absent: BTreeSet<&str> = candidates
+        .iter()
+        .flat_map(|&idx| &catalog.references[idx].contigs)
+        .filter(|contig| {
+            !std::iter::once(&contig.name)
+                .chain(&contig.aliases)
+                .any(|name| query_names.contains(name.as_str()))
+        })
+        .map(|contig| contig.name.as_str())
+        .collect();
+
+    let mut options = Vec::new();
+    for name in absent {
+        let contigs: Vec<Option<&Contig>> = by_name
+            .iter()
+            .map(|names| names.get(name).copied())
+            .collect();
+        let Some(first) = contigs.iter().flatten().next() else {
+            continue;
+        };
+
+        // Skip contigs every candidate has at the same length
+        if !shared_by_all(
+            catalog
+                .name_length_to_refs
+                .get(&(name.to_string(), first.length)),
+            candidates,
+        ) {
+            options.extend(CheckOption::new(
+                name.to_string(),
+                DiscriminatorEvidence::Length,
+                0,
+                contigs
+                    .iter()
+                    .map(|contig| contig.map(|c| c.length.to_string()))
+                    .collect(),
+            ));
+        }
+
+        // Skip sequences every candidate contains
+        if !first
+            .md5
+            .as_ref()
+            .is_some_and(|md5| shared_by_all(catalog.md5_to_refs.get(md5), candidates))
+        {
+            options.extend(CheckOption::new(
+                name.to_string(),
+                DiscriminatorEvidence::Md5,
+                contigs
+                    .iter()
+                    .flatten()
+                    .map(|c| c.length)
+                    .max()
+                    .unwrap_or(0),
+                contigs
+                    .iter()
+                    .map(|contig| {
+                        contig
+                            .and_then(|c| c.md5.as_ref())
+                            .map(|m| m.to_lowercase())
+                    })
+                    .collect(),
+            ));
+        }
+    }
+    options
+}
+
+/// Greedily choose checks until no remaining check separates another pair
+fn choose_options(options: &[CheckOption], candidates: usize) -> Vec<usize> {
+    let pairs: Vec<(usize, usize)> = (0..candidates)
+        .flat_map(|i| (i + 1..candidates).map(move |j| (i, j)))
+        .collect();
+    let mut separated = vec![false; pairs.len()];
+    let mut chosen = Vec::new();
+
+    loop {
+        let mut best: Option<(usize, f64)> = None;
+        for (index, option) in options.iter().enumerate() {
+            let new_pairs = pairs
+                .iter()
+                .zip(&separated)
+                .filter(|&(&(i, j), &done)| !done && option.separates(i, j))
+                .count();
+            if new_pairs == 0 {
+                continue;
+            }
+            let value = count_to_f64(new_pairs) / bases_to_f64(option.bases + STEP_OVERHEAD_BASES);
+            if best.map_or(true, |(_, best_value)| value > best_value) {
+                best = Some((index, value));
+            }
+        }
+
+        let Some((index, _)) = best else {
+            return chosen;
+        };
+        for (&(i, j), done) in pairs.iter().zip(separated.iter_mut()) {
+            *done |= options[index].separates(i, j);
+        }
+        chosen.push(index);
+    }
+}
+
+#[allow(clippy::cast_precision_loss)] // Pair counts are small
+fn count_to_f64(count: usize) -> f64 {
+    count as f64
+}
+
+#[allow(clippy::cast_precision_loss)] // Genome sizes are well below 2^52
+fn bases_to_f64(bases: u64) -> f64 {
+    bases as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::reference::KnownReference;
+    use crate::core::types::{Assembly, ReferenceSource};
+
+    fn make_reference(id: &str, chr_y_md5: &str, chr_m_length: u64) -> KnownReference {
+        KnownReference::new(
+            id,
+            id,
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 248_956_422).with_md5("6aef897c3d6ff0c78aff06ac189178dd"),
+            Contig::new("chrY", 57_227_415).with_md5(chr_y_md5),
+            Contig::new("chrM", chr_m_length).with_md5("c68f52674c9fb33aef52dcf399755519"),
+        ])
+    }
+
+    fn make_catalog() -> ReferenceCatalog {
+        let mut catalog = ReferenceCatalog::new();
+        catalog.add_reference(make_reference(
+            "unmasked",
+            "ce3e31103314a704255f3cd90369ecce",
+            16569,
+        ));
+        catalog.add_reference(make_reference(
+            "par_masked",
+            "1e97f2aebd3bfe8b3e2e0a3b6ad59d91",
+            16569,
+        ));
+        catalog.add_reference(make_reference(
+            "old_mito",
+            "ce3e31103314a704255f3cd90369ecce",
+            16571,
+        ));
+        catalog
+    }
+
+    #[test]
+    fn test_plan_separates_all_candidates() {
+        let catalog = make_catalog();
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 248_956_422),
+            Contig::new("chrY", 57_227_415),
+        ]);
+
+        let plan = DisambiguationPlan::build(&query, &catalog, &[0, 1, 2]).unwrap();
+
+        // The free chrM length check is taken first, then the chrY MD5;
+        // chr1 is shared by all candidates and never considered
+        assert_eq!(plan.discriminators.len(), 2);
+        let chr_m = &plan.discriminators[0];
+        assert_eq!(chr_m.contig, "chrM");
+        assert_eq!(chr_m.evidence, DiscriminatorEvidence::Length);
+        assert_eq!(chr_m.bases, 0);
+        assert_eq!(
+            chr_m.description(),
+            "chrM length distinguishes unmasked, par_masked (16569) vs old_mito (16571)"
+        );
+
+        let chr_y = &plan.discriminators[1];
+        assert_eq!(chr_y.contig, "chrY");
+        assert_eq!(chr_y.evidence, DiscriminatorEvidence::Md5);
+        assert_eq!(chr_y.groups.len(), 2);
+        assert_eq!(
+            chr_y.groups[1].references,
+            vec![ReferenceId::new("par_masked")]
+        );
+
+        assert!(plan.unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_plan_hashes_contigs_missing_from_header() {
+        let catalog = make_catalog();
+        let query = QueryHeader::new(vec![Contig::new("chr1", 248_956_422)]);
+
+        let plan = DisambiguationPlan::build(&query, &catalog, &[0, 1]).unwrap();
+
+        assert_eq!(plan.discriminators.len(), 1);
+        let chr_y = &plan.discriminators[0];
+        assert_eq!(chr_y.contig, "chrY");
+        assert_eq!(chr_y.evidence, DiscriminatorEvidence::Md5);
+        assert_eq!(chr_y.bases, 57_227_415);
+        assert_eq!(
+            chr_y.description(),
+            "chrY MD5 distinguishes unmasked (ce3e3110) vs par_masked (1e97f2ae)"
+        );
+        assert!(plan.unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_plan_skips_contigs_with_any_digest() {
+        let catalog = make_catalog();
+        // chrY already carries a sha512t24u digest, so it is comparable as is
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 248_956_422),
+            Contig::new("chrY", 57_227_415).with_sha512t24u("2648ae1bacce4ec4b6cf337dcae37816"),
+        ]);
+
+        let plan = DisambiguationPlan::build(&query, &catalog, &[0, 1]).unwrap();
+
+        assert!(plan.discriminators.is_empty());
+        assert_eq!(
+            plan.unresolved,
+            vec![vec![
+                ReferenceId::new("unmasked"),
+                ReferenceId::new("par_masked")
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_plan_reports_indistinguishable_candidates() {
+        let mut catalog = make_catalog();
+        catalog.add_reference(make_reference(
+            "unmasked_copy",
+            "ce3e31103314a704255f3cd90369ecce",
+            16569,
+        ));
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 248_956_422),
+            Contig::new("chrY", 57_227_415),
+            Contig::new("chrM", 16569),
+        ]);
+
+        let plan = DisambiguationPlan::build(&query, &catalog, &[0, 3]).unwrap();
+        assert!(plan.discriminators.is_empty());
+        assert_eq!(
+            plan.unresolved,
+            vec![vec![
+                ReferenceId::new("unmasked"),
+                ReferenceId::new("unmasked_copy")
+            ]]
+        );
+
+        assert!(DisambiguationPlan::build(&query, &catalog, &[0]).is_none());
+    }
+}
@@ -2,8 +2,11 @@ use crate::catalog::index::CandidateFinder;
 use crate::catalog::store::ReferenceCatalog;
 use crate::core::header::QueryHeader;
 use crate::core::reference::KnownReference;
-use crate::matching::diagnosis::MatchDiagnosis;
-use crate::matching::scoring::MatchScore;
+use crate::core::types::ReferenceId;
+use crate::matching::diagnosis::{MatchDiagnosis, Suggestion};
+use crate::matching::disambiguation::DisambiguationPlan;
+use crate::matching::scoring::{classify_contigs, MatchScore};
+use crate::matching::tiers::TieredVerdict;
 
 /// Result of matching a query against the catalog
 #[derive(Debug, Clone)]
@@ -16,18 +19,46 @@ pub struct MatchResult {
 
     /// Detailed diagnosis
     pub diagnosis: MatchDiagnosis,
+
+    /// Separate verdicts for the primary assembly and auxiliary contigs
+    pub tiers: TieredVerdict,
+
+    /// How clearly this match stands out from the other candidates
+    pub ambiguity: Ambiguity,
+}
+
+/// How clearly a match stands out from the other candidates.
+///
+/// Automation should refuse to auto-select a reference when the top match is
+/// ambiguous: the evidence in the query cannot tell the tied candidates apart.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct Ambiguity {
+    /// True if another candidate scored within the tolerance of this one
+    pub ambiguous: bool,
+
+    /// Score difference to the best other candidate (for the top match, its lead
+    /// over the runner-up), `None` if there are no other candidates
+    pub margin: Option<f64>,
+
+    /// Candidates whose scores are within the tolerance of this one
+    pub tied_with: Vec<ReferenceId>,
 }
 
 impl MatchResult {
     #[must_use]
     pub fn new(reference: &KnownReference, query: &QueryHeader, weights: &ScoringWeights) -> Self {
-        let score = MatchScore::calculate_with_weights(query, reference, weights);
+        // Classify each contig once for the overall score and the tiers
+        let classes = classify_contigs(query, reference);
+        let score = MatchScore::calculate_classified(query, reference, &classes, weights);
         let diagnosis = MatchDiagnosis::analyze(query, reference);
+        let tiers = TieredVerdict::analyze(query, reference, &classes, weights);
 
         Self {
             reference: reference.clone(),
             score,
             diagnosis,
+            tiers,
+            ambiguity: Ambiguity::default(),
         }
     }
 }
@@ -35,6 +66,9 @@ impl MatchResult {
 /// Default minimum score threshold for matches
 pub const DEFAULT_MIN_SCORE: f64 = 0.1;
 
+/// Default score difference within which two candidates are considered tied
+pub const DEFAULT_AMBIGUITY_TOLERANCE: f64 = 0.01;
+
 /// Configuration for the matching engine
 #[derive(Debug, Clone)]
 pub struct MatchingConfig {
@@ -42,6 +76,8 @@ pub struct MatchingConfig {
     pub min_score: f64,
     /// Custom scoring weights
     pub scoring_weights: ScoringWeights,
+    /// Score difference within which candidates are reported as tied
+    pub ambiguity_tolerance: f64,
 }
 
 impl Default for MatchingConfig {
@@ -49,10 +85,33 @@ impl Default for MatchingConfig {
         Self {
             min_score: DEFAULT_MIN_SCORE,
             scoring_weights: ScoringWeights::default(),
+            ambiguity_tolerance: DEFAULT_AMBIGUITY_TOLERANCE,
         }
     }
 }
 
+/// How per-contig results are aggregated into the match and coverage scores
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScoringMode {
+    /// Every contig counts equally
+    #[default]
+    Count,
+    /// Contigs are weighted by their length in base pairs, so primary
+    /// chromosomes dominate over small decoy and unplaced contigs
+    LengthWeighted,
+}
+
 /// Configurable weights for the scoring algorithm
 ///
 /// The scoring algorithm uses three main components:
@@ -61,7 +120,8 @@ impl Default for MatchingConfig {
 /// - order: Whether contigs appear in the same order
 ///
 /// Additionally, `conflict_penalty` controls how much credit MD5 conflicts receive
-/// (name+length matches but MD5 differs, indicating different sequences).
+/// (name+length matches but MD5 differs, indicating different sequences), and
+/// `scoring_mode` selects whether contigs are counted or weighted by length.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ScoringWeights {
     /// Weight for per-contig match score (default 0.70 = 70%)
@@ -80,6 +140,10 @@ pub struct ScoringWeights {
     /// Set to 0.0 for zero credit on conflicts, 1.0 to treat conflicts as matches
     #[serde(default = "default_conflict_penalty")]
     pub conflict_penalty: f64,
+
+    /// Whether match and coverage scores count contigs or base pairs (default: count)
+    #[serde(default)]
+    pub scoring_mode: ScoringMode,
 }
 
 fn default_contig_match() -> f64 {
@@ -102,6 +166,7 @@ impl Default for ScoringWeights {
             coverage: 0.20,        // 20%
             order: 0.10,           // 10%
             conflict_penalty: 0.1, // 10% credit for conflicts
+            scoring_mode: ScoringMode::Count,
         }
     }
 }
@@ -122,6 +187,7 @@ impl ScoringWeights {
             coverage: self.coverage / total,
             order: self.order / total,
             conflict_penalty: self.conflict_penalty.clamp(0.0, 1.0),
+            scoring_mode: self.scoring_mode,
         }
     }
 }
@@ -143,20 +209,30 @@ impl<'a> MatchingEngine<'a> {
     /// Find the best matching references for a query
     #[must_use]
     pub fn find_matches(&self, query: &QueryHeader, limit: usize) -> Vec<MatchResult> {
-        // Step 1: Try exact signature match
-        if let Some(sig) = &query.signature {
-            if let Some(reference) = self.catalog.find_by_signature(sig) {
-                return vec![MatchResult::new(
-                    reference,
-                    query,
-                    &self.config.scoring_weights,
-                )];
-            }
-        }
+        // Step 1: References with the query's exact signature (MD5, then
+        // sha512t24u). Several can share one, so they are scored and checked
+        // for ties like any other candidate rather than returned directly.
+        let mut candidate_indices: Vec<usize> = query
+            .signature
+            .as_deref()
+            .map(|sig| self.catalog.indices_by_signature(sig))
+            .filter(|indices| !indices.is_empty())
+            .or_else(|| {
+                query
+                    .sha512t24u_signature
+                    .as_deref()
+                    .map(|sig| self.catalog.indices_by_sha512t24u_signature(sig))
+            })
+            .unwrap_or_default()
+            .to_vec();
 
-        // Step 2: Find candidates via index
+        // Step 2: Find further candidates via index
         let finder = CandidateFinder::new(self.catalog);
-        let candidate_indices = finder.find_top_candidates(query, limit * 2);
+        for idx in finder.find_top_candidates(query, limit * 2) {
+            if !candidate_indices.contains(&idx) {
+                candidate_indices.push(idx);
+            }
+        }
 
         // Step 3: Score and rank candidates with custom weights
         let mut results: Vec<MatchResult> = candidate_indices
@@ -175,12 +251,30 @@ impl<'a> MatchingEngine<'a> {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        // Filter to meaningful matches and limit
+        // Filter to meaningful matches, then flag ties before limiting so that
+        // candidates cut off by the limit still count
+        results.retain(|r| r.score.composite > self.config.min_score);
+        analyze_ambiguity(&mut results, self.config.ambiguity_tolerance);
+        results.truncate(limit);
+        self.suggest_disambiguation(query, &mut results);
         results
-            .into_iter()
-            .filter(|r| r.score.composite > self.config.min_score)
-            .take(limit)
-            .collect()
+    }
+
+    /// Suggest the contigs that would tell apart each ambiguous result and
+    /// the candidates it ties with
+    fn suggest_disambiguation(&self, query: &QueryHeader, results: &mut [MatchResult]) {
+        for result in results.iter_mut().filter(|r| r.ambiguity.ambiguous) {
+            let candidates: Vec<usize> = std::iter::once(&result.reference.id)
+                .chain(&result.ambiguity.tied_with)
+                .filter_map(|id| self.catalog.index_of(id))
+                .collect();
+            if let Some(plan) = DisambiguationPlan::build(query, self.catalog, &candidates) {
+                result
+                    .diagnosis
+                    .suggestions
+                    .push(Suggestion::Disambiguate { plan });
+            }
+        }
     }
 
     /// Find the single best match
@@ -191,10 +285,39 @@ impl<'a> MatchingEngine<'a> {
     }
 }
 
+/// Fill in the ambiguity of each result relative to the others
+fn analyze_ambiguity(results: &mut [MatchResult], tolerance: f64) {
+    let scores: Vec<(ReferenceId, f64)> = results
+        .iter()
+        .map(|r| (r.reference.id.clone(), r.score.composite))
+        .collect();
+
+    for (i, result) in results.iter_mut().enumerate() {
+        let composite = result.score.composite;
+        let others = scores
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, other)| other);
+
+        result.ambiguity.tied_with = others
+            .clone()
+            .filter(|(_, score)| (composite - score).abs() <= tolerance)
+            .map(|(id, _)| id.clone())
+            .collect();
+        result.ambiguity.ambiguous = !result.ambiguity.tied_with.is_empty();
+        result.ambiguity.margin = others
+            .map(|(_, score)| *score)
+            .reduce(f64::max)
+            .map(|best_other| composite - best_other);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::core::contig::Contig;
+    use crate::core::types::MatchType;
 
     fn make_test_catalog() -> ReferenceCatalog {
         ReferenceCatalog::load_embedded().unwrap()
@@ -296,6 +419,72 @@ mod tests {
         assert!(!matches.is_empty());
     }
 
+    #[test]
+    fn test_find_matches_sha512t24u_only() {
+        let catalog = make_test_catalog();
+        let engine = MatchingEngine::new(&catalog, MatchingConfig::default());
+
+        // Build a query carrying only sha512t24u digests (e.g. a refget-native dictionary)
+        let reference = catalog
+            .references
+            .iter()
+            .find(|r| r.sha512t24u_signature.is_some())
+            .expect("catalog should have sha512t24u digests");
+        let contigs: Vec<Contig> = reference
+            .contigs
+            .iter()
+            .map(|c| {
+                let mut contig = c.clone();
+                contig.md5 = None;
+                contig
+            })
+            .collect();
+        let query = QueryHeader::new(contigs);
+        assert!(query.signature.is_none());
+
+        let matches = engine.find_matches(&query, 5);
+        assert!(!matches.is_empty());
+        assert_eq!(
+            matches[0].reference.sha512t24u_signature,
+            query.sha512t24u_signature
+        );
+        let with_digest = query
+            .contigs
+            .iter()
+            .filter(|c| c.sha512t24u.is_some())
+            .count();
+        assert_eq!(matches[0].score.exact_matches, with_digest);
+        assert_eq!(matches[0].score.md5_conflicts, 0);
+    }
+
+    #[test]
+    fn test_renamed_detected_via_sha512t24u() {
+        let catalog = make_test_catalog();
+
+        let reference = catalog
+            .references
+            .iter()
+            .find(|r| r.sha512t24u_signature.is_some())
+            .expect("catalog should have sha512t24u digests");
+        let contigs: Vec<Contig> = reference
+            .contigs
+            .iter()
+            .filter(|c| c.sha512t24u.is_some())
+            .take(3)
+            .enumerate()
+            .map(|(i, c)| {
+                let mut contig = Contig::new(format!("seq{i}"), c.length);
+                contig.sha512t24u.clone_from(&c.sha512t24u);
+                contig
+            })
+            .collect();
+        let query = QueryHeader::new(contigs);
+
+        let diagnosis = MatchDiagnosis::analyze(&query, reference);
+        assert_eq!(diagnosis.renamed_matches.len(), 3);
+        assert_eq!(diagnosis.match_type, MatchType::Renamed);
+    }
+
     #[test]
     fn test_find_best_match() {
         let catalog = make_test_catalog();
@@ -462,4 +651,144 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_ambiguity_reports_tied_candidates() {
+        use crate::core::types::{Assembly, ReferenceSource};
+
+        let make_reference = |id: &str, chr2_length: u64| {
+            KnownReference::new(
+                id,
+                id,
+                Assembly::Grch38,
+                ReferenceSource::Custom("test".to_string()),
+            )
+            .with_contigs(vec![
+                Contig::new("chr1", 248_956_422),
+                Contig::new("chr2", chr2_length),
+                Contig::new("chr3", 198_295_559),
+            ])
+        };
+        let mut catalog = ReferenceCatalog::new();
+        catalog.add_reference(make_reference("ref_a", 242_193_529));
+        catalog.add_reference(make_reference("ref_b", 242_193_529));
+        catalog.add_reference(make_reference("ref_c", 243_199_373));
+        let engine = MatchingEngine::new(&catalog, MatchingConfig::default());
+
+        // Name+length-only header: ref_a and ref_b are indistinguishable
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 248_956_422),
+            Contig::new("chr2", 242_193_529),
+            Contig::new("chr3", 198_295_559),
+        ]);
+        let matches = engine.find_matches(&query, 5);
+        assert_eq!(matches.len(), 3);
+
+        let top = &matches[0];
+        assert!(top.ambiguity.ambiguous);
+        assert_eq!(top.ambiguity.tied_with.len(), 1);
+        assert!((top.ambiguity.margin.unwrap()).abs() < 1e-9);
+
+        let last = &matches[2];
+        assert_eq!(last.reference.id.0, "ref_c");
+        assert!(!last.ambiguity.ambiguous);
+        assert!(last.ambiguity.margin.unwrap() < 0.0);
+
+        // Ties are still reported when the limit cuts off the runner-up
+        let matches = engine.find_matches(&query, 1);
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].ambiguity.ambiguous);
+
+        // Nothing outside the header distinguishes the tied pair
+        let plan = matches[0]
+            .diagnosis
+            .suggestions
+            .iter()
+            .find_map(|s| match s {
+                Suggestion::Disambiguate { plan } => Some(plan),
+                _ => None,
+            })
+            .expect("ambiguous match should suggest disambiguation");
+        assert_eq!(plan.candidates.len(), 2);
+        assert!(plan.discriminators.is_empty());
+        assert_eq!(plan.unresolved.len(), 1);
+    }
+
+    #[test]
+    fn test_shared_signature_is_ambiguous() {
+        use crate::core::types::{Assembly, ReferenceSource};
+
+        let mut catalog = ReferenceCatalog::new();
+        for id in ["ref_a", "ref_b", "ref_c"] {
+            catalog.add_reference(
+                KnownReference::new(
+                    id,
+                    id,
+                    Assembly::Grch38,
+                    ReferenceSource::Custom("test".to_string()),
+                )
+                .with_contigs(vec![
+                    Contig::new("chr1", 248_956_422).with_md5("6aef897c3d6ff0c78aff06ac189178dd"),
+                    Contig::new("chr2", 242_193_529).with_md5("f98db672eb0993dcfdabafe2a882905c"),
+                ]),
+            );
+        }
+        let engine = MatchingEngine::new(&catalog, MatchingConfig::default());
+
+        // The query's signature matches all three references
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 248_956_422).with_md5("6aef897c3d6ff0c78aff06ac189178dd"),
+            Contig::new("chr2", 242_193_529).with_md5("f98db672eb0993dcfdabafe2a882905c"),
+        ]);
+        assert_eq!(
+            catalog
+                .indices_by_signature(query.signature.as_deref().unwrap())
+                .len(),
+            3
+        );
+
+        let matches = engine.find_matches(&query, 1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].reference.id.0, "ref_a");
+        assert!(matches[0].ambiguity.ambiguous);
+        assert_eq!(matches[0].ambiguity.tied_with.len(), 2);
+        assert!(matches[0]
+            .diagnosis
+            .suggestions
+            .iter()
+            .any(|s| matches!(s, Suggestion::Disambiguate { .. })));
+    }
+
+    #[test]
+    fn test_ambiguity_clear_winner() {
+        use crate::core::types::{Assembly, ReferenceSource};
+
+        let mut catalog = ReferenceCatalog::new();
+        for (id, chr2_length) in [("ref_a", 242_193_529), ("ref_b", 243_199_373)] {
+            catalog.add_reference(
+                KnownReference::new(
+                    id,
+                    id,
+                    Assembly::Grch38,
+                    ReferenceSource::Custom("test".to_string()),
+                )
+                .with_contigs(vec![
+                    Contig::new("chr1", 248_956_422),
+                    Contig::new("chr2", chr2_length),
+                ]),
+            );
+        }
+        let engine = MatchingEngine::new(&catalog, MatchingConfig::default());
+
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 248_956_422),
+            Contig::new("chr2", 242_193_529),
+        ]);
+        let matches = engine.find_matches(&query, 5);
+
+        assert_eq!(matches[0].reference.id.0, "ref_a");
+        assert!(!matches[0].ambiguity.ambiguous);
+        assert!(matches[0].ambiguity.tied_with.is_empty());
+        assert!(matches[0].ambiguity.margin.unwrap() > DEFAULT_AMBIGUITY_TOLERANCE);
+    }
 }
@@ -0,0 +1,537 @@
+//! Fix plans written out as files that can be reviewed and run.
+//!
+//! A [`FixPlan`] turns the [`ReheaderPlan`] for a query and its matching
+//! reference into artifacts in a directory:
+//!
+//! - `rename_map.tsv`: two columns, old and new name, as read by
+//!   `bcftools annotate --rename-chrs` and fgbio
+//! - `<reference>.dict`: the target sequence dictionary, for Picard `SortVcf`
+//! - `fix.sh`: a shell script fixing the input file with `ref-solver reheader`
+//!   or `convert-names`, which also rename the contigs named in `SA`/`XA` tags
+//!   and VCF breakend mates, then sorting it with samtools or Picard
+//!
+//! The script is only written for alignment and VCF inputs, and not at all
+//! when the query has sequences that differ from the reference.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::reference::KnownReference;
+use crate::matching::diagnosis::MatchDiagnosis;
+use crate::matching::reheader::ReheaderPlan;
+use crate::parsing::sam::ParseError;
+
+/// File name of the rename map within the plan directory
+pub const RENAME_MAP_FILE: &str = "rename_map.tsv";
+
+/// File name of the script within the plan directory
+pub const SCRIPT_FILE: &str = "fix.sh";
+
+/// Kind of input file a fix script can be written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixInput {
+    Sam,
+    Bam,
+    Cram,
+    Vcf,
+}
+
+/// The steps that make a query conform to a reference
+#[derive(Debug, Clone)]
+pub struct FixPlan {
+    /// ID of the target reference
+    pub reference_id: String,
+
+    /// Display name of the target reference
+    pub reference_name: String,
+
+    /// Contigs whose name changes, as (old name, new name)
+    pub renamed: Vec<(String, String)>,
+
+    /// True if contigs must be reordered to match the reference
+    pub reordered: bool,
+
+    /// Contigs not in the reference, kept at the end of the dictionary
+    pub appended: Vec<String>,
+
+    /// Target sequence dictionary
+    pub dictionary: Vec<Contig>,
+
+    /// Why the input cannot be fixed by renaming and reordering, if it cannot
+    pub blocked: Option<String>,
+
+    /// Custom catalog holding the reference, passed on to ref-solver by the script
+    pub catalog: Option<PathBuf>,
+}
+
+/// Paths of the files written for a plan
+#[derive(Debug, Clone, Serialize)]
+pub struct FixPlanArtifacts {
+    pub directory: PathBuf,
+    pub rename_map: Option<PathBuf>,
+    pub dict: PathBuf,
+    pub script: Option<PathBuf>,
+    pub renamed: usize,
+    pub reordered: bool,
+    pub blocked: Option<String>,
+}
+
+impl FixPlan {
+    /// Plan the fix of `query` to conform to `reference`.
+    ///
+    /// Contigs not in the reference are kept, after the reference's contigs.
+    /// Sequence conflicts block the plan; the dictionary is still the
+    /// reference's so that it can be used for realignment.
+    #[must_use]
+    pub fn new(
+        query: &QueryHeader,
+        reference: &KnownReference,
+        diagnosis: &MatchDiagnosis,
+    ) -> Self {
+        let mut plan = Self {
+            reference_id: reference.id.to_string(),
+            reference_name: reference.display_name.clone(),
+            renamed: Vec::new(),
+            reordered: false,
+            appended: Vec::new(),
+            dictionary: Vec::new(),
+            blocked: None,
+            catalog: None,
+        };
+        match ReheaderPlan::build(query, reference, diagnosis, true) {
+            Ok(reheader) => {
+                plan.renamed = reheader.renamed;
+                plan.reordered = reheader.reordered;
+                plan.appended = reheader.appended;
+                plan.dictionary = reheader.contigs;
+            }
+            Err(e) => {
+                plan.blocked = Some(e.to_string());
+                plan.dictionary = reference
+                    .contigs
+                    .iter()
+                    .filter(|c| !reference.contigs_missing_from_fasta.contains(&c.name))
+                    .cloned()
+                    .collect();
+            }
+        }
+        plan
+    }
+
+    /// True if the input needs renaming or reordering
+    #[must_use]
+    pub fn has_changes(&self) -> bool {
+        !self.renamed.is_empty() || self.reordered
+    }
+
+    /// File name of the target dictionary within the plan directory
+    #[must_use]
+    pub fn dict_file(&self) -> String {
+        format!("{}.dict", self.reference_id)
+    }
+
+    /// The rename map: one `old<TAB>new` line per renamed contig
+    #[must_use]
+    pub fn rename_map(&self) -> String {
+        self.renamed
+            .iter()
+            .map(|(old, new)| format!("{old}\t{new}\n"))
+            .collect()
+    }
+
+    /// A bash script that renames and reorders `input` (an absolute path)
+    /// into `output`, reading the plan's files from the script's directory.
+    /// Returns `None` if the plan is blocked or there is nothing to do.
+    #[must_use]
+    pub fn script(&self, input: &Path, kind: FixInput, output: &Path) -> Option<String> {
+        if self.blocked.is_some() || !self.has_changes() {
+            return None;
+        }
+
+        let mut lines = vec![
+            "#!/usr/bin/env bash".to_string(),
+            format!(
+                "# Fix plan generated by ref-solver {}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            "#".to_string(),
+            format!("# Input:     {}", input.display()),
+            format!(
+                "# Reference: {} ({})",
+                self.reference_id, self.reference_name
+            ),
+        ];
+        if !self.renamed.is_empty() {
+            lines.push(format!(
+                "# Renames {} contigs (see {RENAME_MAP_FILE})",
+                self.renamed.len()
+            ));
+        }
+        if self.reordered {
+            lines.push(format!(
+                "# Reorders contigs to match {} and re-sorts the records",
+                self.dict_file()
+            ));
+        }
+        if !self.appended.is_empty() {
+            lines.push(format!(
+                "# Keeps {} contigs not in the reference at the end of the dictionary",
+                self.appended.len()
+            ));
+        }
+        let sorter = match (self.reordered, kind) {
+            (false, _) => "",
+            (true, FixInput::Vcf) => " and picard",
+            (true, _) => " and samtools",
+        };
+        lines.push("#".to_string());
+        lines.push(format!(
+            "# Review before running. Requires ref-solver (or REF_SOLVER set to it){sorter}."
+        ));
+        lines.push(format!("# Usage: {SCRIPT_FILE} [OUTPUT]"));
+        lines.push("set -euo pipefail".to_string());
+        lines.push(String::new());
+        lines.push(format!("INPUT={}", shell_quote(&input.to_string_lossy())));
+        lines.push(format!(
+            "DEFAULT_OUTPUT={}",
+            shell_quote(&output.to_string_lossy())
+        ));
+        lines.push("OUTPUT=\"${1:-$DEFAULT_OUTPUT}\"".to_string());
+        if kind == FixInput::Cram {
+            lines.push(format!(
+                "REFERENCE_FASTA=\"${{REFERENCE_FASTA:?Set REFERENCE_FASTA to the FASTA of {}}}\"",
+                self.reference_id
+            ));
+        }
+        lines.push("REF_SOLVER=\"${REF_SOLVER:-ref-solver}\"".to_string());
+        lines.push("PLAN_DIR=\"$(cd \"$(dirname \"${BASH_SOURCE[0]}\")\" && pwd)\"".to_string());
+        lines.push("WORK_DIR=\"$(mktemp -d)\"".to_string());
+        lines.push("trap 'rm -rf \"$WORK_DIR\"' EXIT".to_string());
+
+        match kind {
+            FixInput::Vcf => self.vcf_steps(&mut lines),
+            FixInput::Sam | FixInput::Bam | FixInput::Cram => {
+                self.alignment_steps(&mut lines, kind);
+            }
+        }
+
+        let mut script = lines.join("\n");
+        script.push('\n');
+        Some(script)
+    }
+
+    /// Options naming the target reference, for `reheader` and `convert-names`
+    fn reference_options(&self, option: &str) -> String {
+        let mut options = format!("{option} {}", shell_quote(&self.reference_id));
+        if let Some(catalog) = &self.catalog {
+            options.push_str(&format!(
+                " --catalog {}",
+                shell_quote(&catalog.to_string_lossy())
+            ));
+        }
+        options
+    }
+
+    fn vcf_steps(&self, lines: &mut Vec<String>) {
+        let mut current = "\"$INPUT\"";
+        if !self.renamed.is_empty() {
+            let target = if self.reordered {
+                "\"$WORK_DIR/renamed.vcf.gz\""
+            } else {
+                "\"$OUTPUT\""
+            };
+            lines.push(String::new());
+            lines.push(
+                "# Rename contigs in ##contig lines, records, breakend mates and CHR2".to_string(),
+            );
+            lines.push(format!(
+                "\"$REF_SOLVER\" convert-names {current} {} -o {target}",
+                self.reference_options("--to-reference")
+            ));
+            current = target;
+        }
+        if self.reordered {
+            lines.push(String::new());
+            lines.push("# Reorder contigs and sort records to match the reference".to_string());
+            lines.push(format!(
+                "picard SortVcf I={current} O=\"$OUTPUT\" SEQUENCE_DICTIONARY=\"$PLAN_DIR/{}\"",
+                self.dict_file()
+            ));
+        }
+    }
+
+    fn alignment_steps(&self, lines: &mut Vec<String>, kind: FixInput) {
+        let target = match (self.reordered, kind) {
+            (false, _) => "\"$OUTPUT\"",
+            (true, FixInput::Cram) => "\"$WORK_DIR/reheadered.cram\"",
+            (true, _) => "\"$WORK_DIR/reheadered.bam\"",
+        };
+        let mut options = self.reference_options("--to");
+        if !self.appended.is_empty() {
+            options.push_str(" --append-unmatched");
+        }
+        if kind == FixInput::Cram {
+            options.push_str(" --reference-fasta \"$REFERENCE_FASTA\"");
+        }
+        lines.push(String::new());
+        lines.push(
+            "# Rename and reorder the dictionary, remapping records and SA/XA tags".to_string(),
+        );
+        lines.push(format!(
+            "\"$REF_SOLVER\" reheader \"$INPUT\" {options} --output {target}"
+        ));
+
+        if self.reordered {
+            let reference = if kind == FixInput::Cram {
+                " --reference \"$REFERENCE_FASTA\""
+            } else {
+                ""
+            };
+            lines.push(String::new());
+            lines.push("# Sort records into the reference's contig order".to_string());
+            lines.push(format!("samtools sort{reference} -o \"$OUTPUT\" {target}"));
+        }
+    }
+
+    /// Write the plan's files into `directory`, creating it if needed. The
+    /// script is written if `input` gives the input file and its kind.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::Io` if a file cannot be written, or
+    /// `ParseError::InvalidFormat` if the dictionary cannot be written.
+    pub fn write(
+        &self,
+        directory: &Path,
+        input: Option<(&Path, FixInput)>,
+    ) -> Result<FixPlanArtifacts, ParseError> {
+        std::fs::create_dir_all(directory)?;
+
+        let dict = directory.join(self.dict_file());
+        let mut writer = BufWriter::new(File::create(&dict)?);
+        crate::parsing::dict::write_dict(&mut writer, &self.dictionary)?;
+        writer.flush()?;
+
+        let rename_map = if self.renamed.is_empty() {
+            None
+        } else {
+            let path = directory.join(RENAME_MAP_FILE);
+            std::fs::write(&path, self.rename_map())?;
+            Some(path)
+        };
+
+        let script = match input.and_then(|(path, kind)| {
+            self.script(path, kind, &default_output(path, kind, &self.reference_id))
+        }) {
+            Some(script) => {
+                let path = directory.join(SCRIPT_FILE);
+                std::fs::write(&path, script)?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+                }
+                Some(path)
+            }
+            None => None,
+        };
+
+        Ok(FixPlanArtifacts {
+            directory: directory.to_path_buf(),
+            rename_map,
+            dict,
+            script,
+            renamed: self.renamed.len(),
+            reordered: self.reordered,
+            blocked: self.blocked.clone(),
+        })
+    }
+}
+
+/// `<input stem>.<reference>.<ext>` next to the input; SAM becomes BAM
+fn default_output(input: &Path, kind: FixInput, reference_id: &str) -> PathBuf {
+    let name = input
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (stem, extension) = match kind {
+        FixInput::Vcf => {
+            let uncompressed = name
+                .strip_suffix(".gz")
+                .or_else(|| name.strip_suffix(".bgz"))
+                .unwrap_or(&name);
+            let stem = uncompressed.strip_suffix(".vcf").unwrap_or(uncompressed);
+            (stem.to_string(), "vcf.gz")
+        }
+        FixInput::Sam | FixInput::Bam | FixInput::Cram => {
+            let stem = Path::new(&name)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            (
+                stem,
+                if kind == FixInput::Cram {
+                    "cram"
+                } else {
+                    "bam"
+                },
+            )
+        }
+    };
+    input.with_file_name(format!("{stem}.{reference_id}.{extension}"))
+}
+
+/// Quote a string for a POSIX shell
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{Assembly, ReferenceSource};
+
+    fn make_reference() -> KnownReference {
+        KnownReference::new(
+            "test_ref",
+            "Test Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 1000)
+                .with_md5("aaaa")
+                .with_aliases(vec!["1".to_string()]),
+            Contig::new("chr2", 2000)
+                .with_md5("bbbb")
+                .with_aliases(vec!["2".to_string()]),
+        ])
+    }
+
+    fn plan(query: &QueryHeader) -> FixPlan {
+        let reference = make_reference();
+        let diagnosis = MatchDiagnosis::analyze(query, &reference);
+        FixPlan::new(query, &reference, &diagnosis)
+    }
+
+    #[test]
+    fn test_rename_and_reorder_script() {
+        let query = QueryHeader::new(vec![Contig::new("2", 2000), Contig::new("1", 1000)]);
+
+        let plan = plan(&query);
+        assert_eq!(plan.rename_map(), "2\tchr2\n1\tchr1\n");
+        assert!(plan.reordered);
+
+        let script = plan
+            .script(
+                Path::new("/data/in.bam"),
+                FixInput::Bam,
+                Path::new("/data/out.bam"),
+            )
+            .unwrap();
+        assert!(script.contains("INPUT='/data/in.bam'"));
+        assert!(script.contains(
+            "\"$REF_SOLVER\" reheader \"$INPUT\" --to 'test_ref' --output \"$WORK_DIR/reheadered.bam\""
+        ));
+        assert!(script.contains("samtools sort -o \"$OUTPUT\" \"$WORK_DIR/reheadered.bam\""));
+
+        let script = plan
+            .script(
+                Path::new("/data/in.vcf.gz"),
+                FixInput::Vcf,
+                Path::new("/data/out.vcf.gz"),
+            )
+            .unwrap();
+        assert!(script.contains(
+            "\"$REF_SOLVER\" convert-names \"$INPUT\" --to-reference 'test_ref' -o \"$WORK_DIR/renamed.vcf.gz\""
+        ));
+        assert!(script.contains(
+            "picard SortVcf I=\"$WORK_DIR/renamed.vcf.gz\" O=\"$OUTPUT\" SEQUENCE_DICTIONARY=\"$PLAN_DIR/test_ref.dict\""
+        ));
+    }
+
+    #[test]
+    fn test_cram_script_passes_catalog_and_reference() {
+        let query = QueryHeader::new(vec![
+            Contig::new("2", 2000),
+            Contig::new("1", 1000),
+            Contig::new("decoy", 500),
+        ]);
+        let mut plan = plan(&query);
+        plan.catalog = Some(PathBuf::from("/catalogs/custom.json"));
+        assert_eq!(plan.appended, vec!["decoy".to_string()]);
+
+        let script = plan
+            .script(
+                Path::new("/data/in.cram"),
+                FixInput::Cram,
+                Path::new("/data/out.cram"),
+            )
+            .unwrap();
+        assert!(script.contains(
+            "reheader \"$INPUT\" --to 'test_ref' --catalog '/catalogs/custom.json' \
+--append-unmatched --reference-fasta \"$REFERENCE_FASTA\" --output \"$WORK_DIR/reheadered.cram\""
+        ));
+        assert!(script.contains(
+            "samtools sort --reference \"$REFERENCE_FASTA\" -o \"$OUTPUT\" \"$WORK_DIR/reheadered.cram\""
+        ));
+    }
+
+    #[test]
+    fn test_blocked_plan_has_no_script() {
+        let query = QueryHeader::new(vec![
+            Contig::new("1", 1000).with_md5("ffff"),
+            Contig::new("chr2", 2000),
+        ]);
+
+        let plan = plan(&query);
+
+        assert!(plan.blocked.is_some());
+        assert_eq!(plan.dictionary.len(), 2);
+        assert!(plan
+            .script(
+                Path::new("/data/in.bam"),
+                FixInput::Bam,
+                Path::new("/data/out.bam")
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_write_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let query = QueryHeader::new(vec![Contig::new("1", 1000), Contig::new("2", 2000)]);
+
+        let artifacts = plan(&query)
+            .write(dir.path(), Some((Path::new("/data/in.sam"), FixInput::Sam)))
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(artifacts.rename_map.unwrap()).unwrap(),
+            "1\tchr1\n2\tchr2\n"
+        );
+        let dict = std::fs::read_to_string(&artifacts.dict).unwrap();
+        assert!(dict.contains("SN:chr1\tLN:1000\tM5:aaaa"));
+        let script = std::fs::read_to_string(artifacts.script.unwrap()).unwrap();
+        assert!(script.contains("DEFAULT_OUTPUT='/data/in.test_ref.bam'"));
+        assert!(script.contains("reheader \"$INPUT\" --to 'test_ref' --output \"$OUTPUT\""));
+        assert!(!script.contains("samtools"));
+    }
+
+    #[test]
+    fn test_default_output() {
+        assert_eq!(
+            default_output(Path::new("/d/calls.vcf.gz"), FixInput::Vcf, "hg38"),
+            PathBuf::from("/d/calls.hg38.vcf.gz")
+        );
+        assert_eq!(
+            default_output(Path::new("/d/reads.cram"), FixInput::Cram, "hg38"),
+            PathBuf::from("/d/reads.hg38.cram")
+        );
+    }
+}
@@ -0,0 +1,537 @@
+//! Joint identification from several files of one dataset.
+//!
+//! Files of the same dataset carry complementary evidence: a BAM lists names
+//! and lengths, a `.dict` has MD5s, a VCF names its FASTA in a `##reference=`
+//! line. Each may be ambiguous on its own while their union is decisive.
+//! [`JointQuery`] merges the files' contigs into a single query:
+//!
+//! - contigs with the same MD5, or the same name and length, are one contig;
+//!   `chr1`/`1` and `chrM`/`MT` count as the same name, and names from other
+//!   files are kept as aliases
+//! - digests and tags missing in one file are taken from another
+//! - contigs the files disagree on (length or MD5) are kept as first seen and
+//!   reported as [`ContigDisagreement`]s
+//!
+//! Reference FASTAs the files name (`##reference=`, `@SQ` `UR` tags) break
+//! ties the contigs cannot ([`break_tie`]), and every file is then checked
+//! against the joint call ([`FileConsistency`]).
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::naming::same_name_key;
+use crate::core::reference::KnownReference;
+use crate::core::types::{MatchType, ReferenceId};
+use crate::matching::diagnosis::MatchDiagnosis;
+use crate::matching::engine::MatchResult;
+use crate::matching::Suggestion;
+
+/// The header of one file of the dataset
+#[derive(Debug, Clone)]
+pub struct JointInput {
+    /// File the header was read from
+    pub source: String,
+
+    /// Contigs of the file
+    pub query: QueryHeader,
+
+    /// Reference FASTA the file names, e.g. a VCF's `##reference=` line
+    pub reference_line: Option<String>,
+}
+
+impl JointInput {
+    #[must_use]
+    pub fn new(source: impl Into<String>, query: QueryHeader) -> Self {
+        Self {
+            source: source.into(),
+            query,
+            reference_line: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_reference_line(mut self, reference_line: Option<String>) -> Self {
+        self.reference_line = reference_line;
+        self
+    }
+
+    /// The reference FASTAs this file names: its reference line and the
+    /// distinct `UR` tags of its contigs
+    fn reference_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.reference_line.iter().map(String::as_str).collect();
+        for uri in self.query.contigs.iter().filter_map(|c| c.uri.as_deref()) {
+            if !names.contains(&uri) {
+                names.push(uri);
+            }
+        }
+        names
+    }
+}
+
+/// Two files that disagree about a contig
+#[derive(Debug, Clone, Serialize)]
+pub struct ContigDisagreement {
+    /// Contig name in the later file
+    pub contig: String,
+
+    /// File the merged contig was first taken from
+    pub first_source: String,
+
+    /// File that disagrees with it
+    pub source: String,
+
+    /// What differs (e.g. "chrM is 16571 bp in a.bam but MT is 16569 bp in b.vcf")
+    pub description: String,
+}
+
+/// The merged evidence of several files
+#[derive(Debug, Clone)]
+pub struct JointQuery {
+    /// Union of the files' contigs
+    pub query: QueryHeader,
+
+    /// Contigs the files disagree on
+    pub disagreements: Vec<ContigDisagreement>,
+}
+
+impl JointQuery {
+    /// Merge the contigs of `inputs`, in file order
+    #[must_use]
+    pub fn merge(inputs: &[JointInput]) -> Self {
+        let mut contigs: Vec<Contig> = Vec::new();
+        let mut origins: Vec<&str> = Vec::new();
+        let mut by_md5: HashMap<String, usize> = HashMap::new();
+        let mut by_name: HashMap<String, usize> = HashMap::new();
+        let mut disagreements = Vec::new();
+
+        for input in inputs {
+            for contig in &input.query.contigs {
+                let existing = contig
+                    .md5
+                    .as_ref()
+                    .and_then(|md5| by_md5.get(md5))
+                    .or_else(|| by_name.get(&same_name_key(&contig.name)))
+                    .copied();
+
+                let Some(index) = existing else {
+                    let index = contigs.len();
+                    if let Some(md5) = &contig.md5 {
+                        by_md5.insert(md5.clone(), index);
+                    }
+                    by_name.insert(same_name_key(&contig.name), index);
+                    contigs.push(contig.clone());
+                    origins.push(&input.source);
+                    continue;
+                };
+
+                let merged = &mut contigs[index];
+                if let Some(description) = disagreement(merged, contig, origins[index], input) {
+                    disagreements.push(ContigDisagreement {
+                        contig: contig.name.clone(),
+                        first_source: origins[index].to_string(),
+                        source: input.source.clone(),
+                        description,
+                    });
+                    continue;
+                }
+
+                if let Some(md5) = &contig.md5 {
+                    by_md5.entry(md5.clone()).or_insert(index);
+                }
+                by_name.entry(same_name_key(&contig.name)).or_insert(index);
+                fill_in(merged, contig);
+            }
+        }
+
+        Self {
+            query: QueryHeader::new(contigs),
+            disagreements,
+        }
+    }
+}
+
+/// Why `contig` from `input` cannot be the same sequence as `merged`, if it cannot
+fn disagreement(
+    merged: &Contig,
+    contig: &Contig,
+    first_source: &str,
+    input: &JointInput,
+) -> Option<String> {
+    if merged.length != contig.length {
+        return Some(format!(
+            "{} is {} bp in {first_source} but {} is {} bp in {}",
+            merged.name, merged.length, contig.name, contig.length, input.source
+        ));
+    }
+    if merged.digests_match(contig) == Some(false) {
+        return Some(format!(
+            "{} in {first_source} and {} in {} have different sequences",
+            merged.name, contig.name, input.source
+        ));
+    }
+    None
+}
+
+/// Copy the names, digests and tags `merged` lacks from `contig`
+fn fill_in(merged: &mut Contig, contig: &Contig) {
+    for name in std::iter::once(&contig.name).chain(&contig.aliases) {
+        if *name != merged.name && !merged.aliases.contains(name) {
+            merged.aliases.push(name.clone());
+        }
+    }
+    if merged.md5.is_none() {
+        merged.md5.clone_from(&contig.md5);
+    }
+    if merged.sha512t24u.is_none() {
+        merged.sha512t24u.clone_from(&contig.sha512t24u);
+    }
+    if merged.assembly.is_none() {
+        merged.assembly.clone_from(&contig.assembly);
+    }
+    if merged.uri.is_none() {
+        merged.uri.clone_from(&contig.uri);
+    }
+    if merged.species.is_none() {
+        merged.species.clone_from(&contig.species);
+    }
+}
+
+/// A tie between candidates broken by a reference FASTA named by a file
+#[derive(Debug, Clone, Serialize)]
+pub struct TieBreak {
+    /// The candidate the file names
+    pub reference_id: ReferenceId,
+
+    /// The candidates it was tied with
+    pub over: Vec<ReferenceId>,
+
+    /// The FASTA path or URL as the file gives it
+    pub reference_name: String,
+
+    /// File naming it
+    pub source: String,
+}
+
+/// If the top match is tied and the inputs name the FASTA of exactly one of
+/// the tied candidates, move that candidate to the top and mark it as no
+/// longer ambiguous: it ties with nothing, its margin is its lead over the
+/// best other match, and it no longer suggests hashing contigs to tell it apart.
+pub fn break_tie(matches: &mut Vec<MatchResult>, inputs: &[JointInput]) -> Option<TieBreak> {
+    let top = matches.first().filter(|m| m.ambiguity.ambiguous)?;
+    let tied: Vec<usize> = matches
+        .iter()
+        .enumerate()
+        .filter(|(i, m)| *i == 0 || top.ambiguity.tied_with.contains(&m.reference.id))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut named: Vec<(usize, &str, &str)> = Vec::new();
+    for input in inputs {
+        for name in input.reference_names() {
+            for &i in &tied {
+                if names_reference(name, &matches[i].reference)
+                    && !named.iter().any(|(named_i, _, _)| *named_i == i)
+                {
+                    named.push((i, name, &input.source));
+                }
+            }
+        }
+    }
+    let [(index, reference_name, source)] = named[..] else {
+        return None;
+    };
+
+    let tie_break = TieBreak {
+        reference_id: matches[index].reference.id.clone(),
+        over: tied
+            .iter()
+            .filter(|&&i| i != index)
+            .map(|&i| matches[i].reference.id.clone())
+            .collect(),
+        reference_name: reference_name.to_string(),
+        source: source.to_string(),
+    };
+    let mut chosen = matches.remove(index);
+    chosen.ambiguity.ambiguous = false;
+    chosen.ambiguity.tied_with.clear();
+    chosen.ambiguity.margin = matches
+        .iter()
+        .map(|m| m.score.composite)
+        .reduce(f64::max)
+        .map(|best_other| chosen.score.composite - best_other);
+    chosen
+        .diagnosis
+        .suggestions
+        .retain(|s| !matches!(s, Suggestion::Disambiguate { .. }));
+    matches.insert(0, chosen);
+    Some(tie_break)
+}
+
+/// Whether a FASTA path or URL is the FASTA of `reference`: its file name,
+/// without directories and extensions, is the reference's ID or the file
+/// name of its download URL
+fn names_reference(name: &str, reference: &KnownReference) -> bool {
+    let stem = fasta_stem(name);
+    !stem.is_empty()
+        && (stem == reference.id.0.to_lowercase()
+            || reference
+                .download_url
+                .as_deref()
+                .is_some_and(|url| fasta_stem(url) == stem))
+}
+
+/// Lowercase file name of a FASTA path or URL without compression and FASTA
+/// extensions
+fn fasta_stem(name: &str) -> String {
+    let name = name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(name)
+        .to_lowercase();
+    let name = name
+        .strip_suffix(".gz")
+        .or_else(|| name.strip_suffix(".bgz"))
+        .unwrap_or(&name);
+    [".fasta", ".fa", ".fna", ".2bit"]
+        .iter()
+        .find_map(|extension| name.strip_suffix(extension))
+        .unwrap_or(name)
+        .to_string()
+}
+
+/// How one file of the dataset agrees with the joint call
+#[derive(Debug, Clone, Serialize)]
+pub struct FileConsistency {
+    /// File checked
+    pub source: String,
+
+    /// Number of contigs in the file
+    pub contigs: usize,
+
+    /// How the file alone matches the called reference
+    pub match_type: MatchType,
+
+    /// Contigs whose sequence differs from the reference's, or primary
+    /// chromosomes the reference lacks
+    pub conflicts: Vec<String>,
+
+    /// Other contigs the reference does not have
+    pub not_in_reference: Vec<String>,
+
+    /// Set if the file names the FASTA of another catalog reference
+    pub names_other_reference: Option<String>,
+
+    /// True if none of the above were found
+    pub consistent: bool,
+}
+
+impl FileConsistency {
+    /// Check `input` against the called `reference`
+    #[must_use]
+    pub fn check(
+        input: &JointInput,
+        reference: &KnownReference,
+        catalog: &ReferenceCatalog,
+    ) -> Self {
+        let diagnosis = MatchDiagnosis::analyze(&input.query, reference);
+        let conflicts: Vec<String> = diagnosis
+            .conflicts
+            .iter()
+            .map(|c| c.description.clone())
+            .collect();
+        let not_in_reference: Vec<String> = diagnosis
+            .query_only
+            .iter()
+            .map(|c| c.name.clone())
+            .collect();
+
+        let names = input.reference_names();
+        let names_other_reference = if names.iter().any(|n| names_reference(n, reference)) {
+            None
+        } else {
+            names.iter().find_map(|name| {
+                let others: Vec<&str> = catalog
+                    .references
+                    .iter()
+                    .filter(|r| names_reference(name, r))
+                    .map(|r| r.id.0.as_str())
+                    .collect();
+                (!others.is_empty()).then(|| format!("{name} is {}", others.join(" or ")))
+            })
+        };
+
+        Self {
+            source: input.source.clone(),
+            contigs: input.query.contigs.len(),
+            match_type: diagnosis.match_type,
+            consistent: conflicts.is_empty()
+                && not_in_reference.is_empty()
+                && names_other_reference.is_none(),
+            conflicts,
+            not_in_reference,
+            names_other_reference,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{Assembly, ReferenceSource};
+    use crate::matching::engine::{MatchingConfig, MatchingEngine};
+
+    fn md5(n: u8) -> String {
+        format!("{n:032x}")
+    }
+
+    fn reference(id: &str, url: &str, contigs: Vec<Contig>) -> KnownReference {
+        let mut reference = KnownReference::new(
+            id,
+            id,
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(contigs);
+        reference.download_url = Some(url.to_string());
+        reference
+    }
+
+    /// Two references that differ only in chrY, which neither test query lists
+    fn make_catalog() -> ReferenceCatalog {
+        let mut catalog = ReferenceCatalog::new();
+        for (id, url, y) in [
+            ("masked", "https://example.org/masked.fa.gz", 10),
+            ("unmasked", "https://example.org/unmasked.fasta", 11),
+        ] {
+            catalog.add_reference(reference(
+                id,
+                url,
+                vec![
+                    Contig::new("chr1", 1000)
+                        .with_md5(md5(1))
+                        .with_aliases(vec!["1".to_string()]),
+                    Contig::new("chrM", 500)
+                        .with_md5(md5(2))
+                        .with_aliases(vec!["MT".to_string()]),
+                    Contig::new("chrY", 300).with_md5(md5(y)),
+                ],
+            ));
+        }
+        catalog
+    }
+
+    #[test]
+    fn test_merge_fills_in_digests_and_aliases() {
+        let bam = JointInput::new(
+            "a.bam",
+            QueryHeader::new(vec![Contig::new("chr1", 1000), Contig::new("chrM", 500)]),
+        );
+        let dict = JointInput::new(
+            "b.dict",
+            QueryHeader::new(vec![
+                Contig::new("1", 1000).with_md5(md5(1)),
+                Contig::new("MT", 500).with_md5(md5(2)),
+                Contig::new("2", 900),
+            ]),
+        );
+
+        let joint = JointQuery::merge(&[bam, dict]);
+
+        let names: Vec<&str> = joint
+            .query
+            .contigs
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["chr1", "chrM", "2"]);
+        assert_eq!(joint.query.contigs[0].md5, Some(md5(1)));
+        assert_eq!(joint.query.contigs[1].aliases, vec!["MT".to_string()]);
+        assert!(joint.disagreements.is_empty());
+    }
+
+    #[test]
+    fn test_merge_reports_disagreements() {
+        let a = JointInput::new("a.bam", QueryHeader::new(vec![Contig::new("chrM", 16571)]));
+        let b = JointInput::new("b.vcf", QueryHeader::new(vec![Contig::new("MT", 16569)]));
+
+        let joint = JointQuery::merge(&[a, b]);
+
+        assert_eq!(joint.query.contigs.len(), 1);
+        assert_eq!(joint.query.contigs[0].length, 16571);
+        assert_eq!(joint.disagreements.len(), 1);
+        assert_eq!(joint.disagreements[0].source, "b.vcf");
+    }
+
+    #[test]
+    fn test_break_tie_with_reference_line() {
+        let catalog = make_catalog();
+        let engine = MatchingEngine::new(&catalog, MatchingConfig::default());
+        let bam = JointInput::new(
+            "a.bam",
+            QueryHeader::new(vec![
+                Contig::new("chr1", 1000).with_md5(md5(1)),
+                Contig::new("chrM", 500).with_md5(md5(2)),
+            ]),
+        );
+        let vcf = JointInput::new("b.vcf", QueryHeader::new(vec![Contig::new("chr1", 1000)]))
+            .with_reference_line(Some("file:///refs/unmasked.fasta".to_string()));
+        let inputs = [bam, vcf];
+        let joint = JointQuery::merge(&inputs);
+
+        let mut matches = engine.find_matches(&joint.query, 5);
+        assert!(matches[0].ambiguity.ambiguous);
+        let is_disambiguation = |s: &Suggestion| matches!(s, Suggestion::Disambiguate { .. });
+        let unmasked = matches
+            .iter()
+            .find(|m| m.reference.id.0 == "unmasked")
+            .unwrap();
+        assert!(unmasked.diagnosis.suggestions.iter().any(is_disambiguation));
+
+        let tie_break = break_tie(&mut matches, &inputs).unwrap();
+
+        assert_eq!(tie_break.reference_id.0, "unmasked");
+        assert_eq!(tie_break.over, vec![ReferenceId::new("masked")]);
+        let chosen = &matches[0];
+        assert_eq!(chosen.reference.id.0, "unmasked");
+        assert!(!chosen.ambiguity.ambiguous);
+        assert!(chosen.ambiguity.tied_with.is_empty());
+        assert_eq!(
+            chosen.ambiguity.margin,
+            Some(chosen.score.composite - matches[1].score.composite)
+        );
+        assert!(!chosen.diagnosis.suggestions.iter().any(is_disambiguation));
+    }
+
+    #[test]
+    fn test_file_consistency() {
+        let catalog = make_catalog();
+        let reference = &catalog.references[1];
+
+        let good = JointInput::new(
+            "a.bam",
+            QueryHeader::new(vec![Contig::new("chr1", 1000).with_md5(md5(1))]),
+        );
+        let check = FileConsistency::check(&good, reference, &catalog);
+        assert!(check.consistent);
+
+        let bad = JointInput::new(
+            "b.vcf",
+            QueryHeader::new(vec![
+                Contig::new("chr1", 1000),
+                Contig::new("chrUn_decoy", 50),
+            ]),
+        )
+        .with_reference_line(Some("/refs/masked.fa".to_string()));
+        let check = FileConsistency::check(&bad, reference, &catalog);
+        assert!(!check.consistent);
+        assert_eq!(check.not_in_reference, vec!["chrUn_decoy".to_string()]);
+        assert_eq!(
+            check.names_other_reference.as_deref(),
+            Some("/refs/masked.fa is masked")
+        );
+    }
+}
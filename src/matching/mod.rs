@@ -5,6 +5,17 @@
 //! - [`engine::MatchingEngine`]: Main entry point for finding reference matches
 //! - [`scoring::MatchScore`]: Detailed similarity scores between a query and reference
 //! - [`diagnosis::MatchDiagnosis`]: Detailed analysis of differences and suggestions
+//! - [`decomposition::Decomposition`]: Explains mixed-build headers as a set of source references
+//! - [`tiers::TieredVerdict`]: Separate verdicts for the primary assembly and auxiliary contigs
+//! - [`disambiguation::DisambiguationPlan`]: Contigs that tell apart references tied for a query
+//! - [`annotation::Annotation`]: Query contigs with metadata filled in from a reference
+//! - [`reheader::ReheaderPlan`]: Mapping of a header's contigs onto a reference's dictionary
+//! - [`fix_plan::FixPlan`]: Rename maps, target dictionaries and scripts that fix a query
+//! - [`joint::JointQuery`]: The merged evidence of several files from one dataset
+//! - [`compat::CompatReport`]: Pairwise compatibility of the contigs of several files
+//! - [`policy::Policy`]: Acceptance rules for an input's reference, checked by `assert`
+//! - [`tool_compat::predict`]: Whether GATK, Picard, bcftools and DeepVariant accept a dictionary pair
+//! - [`verification::FastaVerification`]: Contig-by-contig check that a FASTA is a given reference
 //!
 //! ## Matching Algorithm
 //!
@@ -45,9 +56,20 @@
 //! }
 //! ```
 
+pub mod annotation;
+pub mod compat;
+pub mod decomposition;
 pub mod diagnosis;
+pub mod disambiguation;
 pub mod engine;
+pub mod fix_plan;
 pub mod hierarchical_engine;
+pub mod joint;
+pub mod policy;
+pub mod reheader;
 pub mod scoring;
+pub mod tiers;
+pub mod tool_compat;
+pub mod verification;
 
 pub use diagnosis::Suggestion;
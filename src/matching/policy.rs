@@ -0,0 +1,561 @@
+//! Acceptance policies for gating pipeline steps on an input's reference.
+//!
+//! A [`Policy`] states once, in a JSON file, what a QA team accepts: which
+//! references, whether renamed or reordered contigs are fine, whether the
+//! primary assembly must be exact, which conflicts fail an input and how
+//! confident the identification must be. [`Policy::evaluate`] checks the best
+//! match of an input against it and lists every [`Violation`].
+//!
+//! ```json
+//! {
+//!   "allowed_references": ["GRCh38"],
+//!   "allow_renames": false,
+//!   "allow_reorders": true,
+//!   "require_primary_exact": true,
+//!   "forbidden_conflicts": ["MitochondrialMismatch", "SequenceMismatch"],
+//!   "min_confidence": "high"
+//! }
+//! ```
+//!
+//! Omitted fields take their defaults, which are strict: renames and
+//! reorders are refused, every conflict type is forbidden and the
+//! confidence must be at least high.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::core::reference::KnownReference;
+use crate::core::types::{Confidence, MatchType, ReferenceId};
+use crate::matching::diagnosis::ConflictType;
+use crate::matching::engine::MatchResult;
+
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    #[error("Failed to read policy: {0}")]
+    ReadError(#[from] std::io::Error),
+
+    #[error("Failed to parse policy: {0}")]
+    ParseError(#[from] serde_json::Error),
+}
+
+/// Rules an input's reference must satisfy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    /// Catalog reference IDs, assemblies (e.g. `GRCh38`) or catalog tags, one
+    /// of which the match must have; empty allows any reference
+    pub allowed_references: Vec<String>,
+
+    /// Accept contigs that match the reference under other names
+    pub allow_renames: bool,
+
+    /// Accept contigs in a different order than in the reference
+    pub allow_reorders: bool,
+
+    /// Require every primary-assembly contig of the reference to be present
+    /// with the same sequence; auxiliary contigs may differ
+    pub require_primary_exact: bool,
+
+    /// Conflict types that violate the policy
+    pub forbidden_conflicts: Vec<ConflictType>,
+
+    /// Lowest acceptable confidence of a match that differs from the reference
+    /// in more than names and order
+    pub min_confidence: Confidence,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            allowed_references: Vec::new(),
+            allow_renames: false,
+            allow_reorders: false,
+            require_primary_exact: false,
+            forbidden_conflicts: vec![
+                ConflictType::SequenceMismatch,
+                ConflictType::MitochondrialMismatch,
+                ConflictType::UnknownContig,
+            ],
+            min_confidence: Confidence::High,
+        }
+    }
+}
+
+/// The rule a violation breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// No reference matched the input
+    NoMatch,
+    /// The match is not one of the allowed references
+    UnexpectedReference,
+    /// The match is tied with a reference that is not allowed
+    Ambiguous,
+    /// The match is less confident than required
+    LowConfidence,
+    /// Contigs match under other names
+    Renamed,
+    /// Contigs are in a different order
+    Reordered,
+    /// The primary assembly is not exactly the reference's
+    PrimaryNotExact,
+    /// A forbidden conflict was found
+    ForbiddenConflict,
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoMatch => write!(f, "no_match"),
+            Self::UnexpectedReference => write!(f, "unexpected_reference"),
+            Self::Ambiguous => write!(f, "ambiguous"),
+            Self::LowConfidence => write!(f, "low_confidence"),
+            Self::Renamed => write!(f, "renamed"),
+            Self::Reordered => write!(f, "reordered"),
+            Self::PrimaryNotExact => write!(f, "primary_not_exact"),
+            Self::ForbiddenConflict => write!(f, "forbidden_conflict"),
+        }
+    }
+}
+
+/// One way in which a match breaks a policy
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    /// The rule that is broken
+    pub rule: Rule,
+
+    /// What breaks it
+    pub message: String,
+}
+
+impl Violation {
+    fn new(rule: Rule, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            message: message.into(),
+        }
+    }
+}
+
+impl Policy {
+    /// Load a policy from a JSON file
+    ///
+    /// # Errors
+    ///
+    /// Returns `PolicyError::ReadError` if the file cannot be read, or
+    /// `PolicyError::ParseError` if it is not a valid policy.
+    pub fn load_from_file(path: &Path) -> Result<Self, PolicyError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_json(&content)
+    }
+
+    /// Parse a policy from JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns `PolicyError::ParseError` if the JSON is invalid or has unknown
+    /// fields.
+    pub fn from_json(json: &str) -> Result<Self, PolicyError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Whether `reference` is one of the allowed references, by ID, assembly
+    /// or tag (case-insensitive)
+    #[must_use]
+    pub fn allows(&self, reference: &KnownReference) -> bool {
+        self.allowed_references.is_empty()
+            || self.allowed_references.iter().any(|allowed| {
+                reference.id.0.eq_ignore_ascii_case(allowed)
+                    || reference.assembly.to_string().eq_ignore_ascii_case(allowed)
+                    || reference
+                        .tags
+                        .iter()
+                        .any(|tag| tag.eq_ignore_ascii_case(allowed))
+            })
+    }
+
+    /// The match to check: the first of the top match and the references
+    /// tied with it that the policy allows, or the top match if none is.
+    ///
+    /// `matches` are ranked, as returned by
+    /// [`MatchingEngine::find_matches`](crate::matching::engine::MatchingEngine::find_matches)
+    /// with a limit that covers the top match's ties.
+    #[must_use]
+    pub fn select<'a>(&self, matches: &'a [MatchResult]) -> Option<&'a MatchResult> {
+        let top = matches.first()?;
+        matches
+            .iter()
+            .filter(|m| {
+                m.reference.id == top.reference.id
+                    || top.ambiguity.tied_with.contains(&m.reference.id)
+            })
+            .find(|m| self.allows(&m.reference))
+            .or(Some(top))
+    }
+
+    /// Check the matches of an input against the policy.
+    ///
+    /// The match checked is the one [`Self::select`] picks. References tied
+    /// with it must be allowed too, unless both it and they hold the same
+    /// sequences as every query contig, by digest. Tied references missing
+    /// from `matches` are looked up in `catalog`.
+    #[must_use]
+    pub fn evaluate(&self, matches: &[MatchResult], catalog: &ReferenceCatalog) -> Vec<Violation> {
+        let Some(best) = self.select(matches) else {
+            return vec![Violation::new(
+                Rule::NoMatch,
+                "No reference matched the input",
+            )];
+        };
+        let reference = &best.reference;
+        let diagnosis = &best.diagnosis;
+        let mut violations = Vec::new();
+
+        if !self.allows(reference) {
+            violations.push(Violation::new(
+                Rule::UnexpectedReference,
+                format!(
+                    "Matched {} ({}), which is not one of: {}",
+                    reference.id,
+                    reference.assembly,
+                    self.allowed_references.join(", ")
+                ),
+            ));
+        }
+
+        if best.ambiguity.ambiguous {
+            let interchangeable = |id: &ReferenceId| {
+                digest_verified(best)
+                    && matches
+                        .iter()
+                        .find(|m| m.reference.id == *id)
+                        .is_some_and(digest_verified)
+            };
+            let disallowed: Vec<String> = best
+                .ambiguity
+                .tied_with
+                .iter()
+                .filter(|id| catalog.get(id).map_or(true, |r| !self.allows(r)))
+                .filter(|id| !interchangeable(id))
+                .map(ToString::to_string)
+                .collect();
+            if !disallowed.is_empty() {
+                violations.push(Violation::new(
+                    Rule::Ambiguous,
+                    format!(
+                        "{} cannot be told apart from {}",
+                        reference.id,
+                        disallowed.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        // Renaming and reordering lower the score of matches whose sequences
+        // are all identical; those are judged by the rename and reorder rules
+        let same_sequences = matches!(
+            diagnosis.match_type,
+            MatchType::Exact
+                | MatchType::Reordered
+                | MatchType::Renamed
+                | MatchType::ReorderedAndRenamed
+        );
+        if !same_sequences && best.score.confidence < self.min_confidence {
+            violations.push(Violation::new(
+                Rule::LowConfidence,
+                format!(
+                    "Confidence is {:?} ({:.1}%), below {:?}",
+                    best.score.confidence,
+                    best.score.composite * 100.0,
+                    self.min_confidence
+                ),
+            ));
+        }
+
+        if !self.allow_renames {
+            if let Some(first) = diagnosis.renamed_matches.first() {
+                violations.push(Violation::new(
+                    Rule::Renamed,
+                    format!(
+                        "{} contigs have other names in {} (e.g. {} is {})",
+                        diagnosis.renamed_matches.len(),
+                        reference.id,
+                        first.query_name,
+                        first.reference_name
+                    ),
+                ));
+            }
+        }
+
+        if !self.allow_reorders && diagnosis.reordered {
+            violations.push(Violation::new(
+                Rule::Reordered,
+                format!("Contigs are in a different order than in {}", reference.id),
+            ));
+        }
+
+        if self.require_primary_exact {
+            match &best.tiers.primary {
+                None => violations.push(Violation::new(
+                    Rule::PrimaryNotExact,
+                    "The input has no primary-assembly contigs",
+                )),
+                Some(primary) if primary.identical => {}
+                Some(primary) if primary.missing_contigs > 0 => {
+                    violations.push(Violation::new(
+                        Rule::PrimaryNotExact,
+                        format!(
+                            "Primary assembly lacks {} of the {} contigs of {}",
+                            primary.missing_contigs, primary.reference_contigs, reference.id
+                        ),
+                    ));
+                }
+                Some(primary) => violations.push(Violation::new(
+                    Rule::PrimaryNotExact,
+                    format!(
+                        "Primary assembly differs from {} ({:?})",
+                        reference.id, primary.match_type
+                    ),
+                )),
+            }
+        }
+
+        for conflict in &diagnosis.conflicts {
+            if self.forbidden_conflicts.contains(&conflict.conflict_type) {
+                violations.push(Violation::new(
+                    Rule::ForbiddenConflict,
+                    format!("{:?}: {}", conflict.conflict_type, conflict.description),
+                ));
+            }
+        }
+
+        violations
+    }
+}
+
+/// True if every query contig matched the reference by digest, so the query
+/// holds the reference's sequences whatever the names
+fn digest_verified(result: &MatchResult) -> bool {
+    let score = &result.score;
+    score.exact_matches > 0
+        && score.name_length_matches == 0
+        && score.md5_conflicts == 0
+        && score.unmatched == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::contig::Contig;
+    use crate::core::header::QueryHeader;
+    use crate::core::types::{Assembly, ReferenceSource};
+    use crate::matching::engine::{MatchingConfig, MatchingEngine};
+
+    fn md5(n: u8) -> String {
+        format!("{n:032x}")
+    }
+
+    fn make_catalog() -> ReferenceCatalog {
+        let mut catalog = ReferenceCatalog::new();
+        let mut reference = KnownReference::new(
+            "test_grch38",
+            "Test GRCh38",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 1000)
+                .with_md5(md5(1))
+                .with_aliases(vec!["1".to_string()]),
+            Contig::new("chr2", 900)
+                .with_md5(md5(2))
+                .with_aliases(vec!["2".to_string()]),
+            Contig::new("chrM", 500)
+                .with_md5(md5(3))
+                .with_aliases(vec!["MT".to_string()]),
+        ]);
+        reference.tags = vec!["production".to_string()];
+        catalog.add_reference(reference);
+        catalog
+    }
+
+    fn evaluate(policy: &Policy, contigs: Vec<Contig>) -> Vec<Rule> {
+        let catalog = make_catalog();
+        let engine = MatchingEngine::new(&catalog, MatchingConfig::default());
+        let matches = engine.find_matches(&QueryHeader::new(contigs), 1);
+        policy
+            .evaluate(&matches, &catalog)
+            .into_iter()
+            .map(|v| v.rule)
+            .collect()
+    }
+
+    fn exact_contigs() -> Vec<Contig> {
+        vec![
+            Contig::new("chr1", 1000).with_md5(md5(1)),
+            Contig::new("chr2", 900).with_md5(md5(2)),
+            Contig::new("chrM", 500).with_md5(md5(3)),
+        ]
+    }
+
+    #[test]
+    fn test_policy_from_json_defaults() {
+        let policy =
+            Policy::from_json(r#"{"allowed_references": ["GRCh38"], "allow_reorders": true}"#)
+                .unwrap();
+        assert_eq!(policy.allowed_references, vec!["GRCh38"]);
+        assert!(policy.allow_reorders);
+        assert!(!policy.allow_renames);
+        assert_eq!(policy.min_confidence, Confidence::High);
+        assert_eq!(policy.forbidden_conflicts.len(), 3);
+
+        let policy =
+            Policy::from_json(r#"{"forbidden_conflicts": ["MitochondrialMismatch"]}"#).unwrap();
+        assert_eq!(
+            policy.forbidden_conflicts,
+            vec![ConflictType::MitochondrialMismatch]
+        );
+        assert!(Policy::from_json(r#"{"allow_everything": true}"#).is_err());
+    }
+
+    #[test]
+    fn test_allowed_references() {
+        let catalog = make_catalog();
+        let reference = catalog.get(&ReferenceId::new("test_grch38")).unwrap();
+        for allowed in ["test_grch38", "grch38", "Production"] {
+            let policy = Policy {
+                allowed_references: vec![allowed.to_string()],
+                ..Policy::default()
+            };
+            assert!(policy.allows(reference), "{allowed}");
+        }
+        let policy = Policy {
+            allowed_references: vec!["GRCh37".to_string()],
+            ..Policy::default()
+        };
+        assert!(!policy.allows(reference));
+        assert_eq!(
+            evaluate(&policy, exact_contigs()),
+            vec![Rule::UnexpectedReference]
+        );
+    }
+
+    #[test]
+    fn test_exact_match_passes() {
+        let policy = Policy {
+            allowed_references: vec!["GRCh38".to_string()],
+            require_primary_exact: true,
+            ..Policy::default()
+        };
+        assert!(evaluate(&policy, exact_contigs()).is_empty());
+
+        // Dropping a primary contig is no longer exact
+        let mut partial = exact_contigs();
+        partial.pop();
+        let policy = Policy {
+            min_confidence: Confidence::Low,
+            ..policy
+        };
+        assert!(evaluate(&policy, partial).contains(&Rule::PrimaryNotExact));
+    }
+
+    #[test]
+    fn test_renames_and_reorders() {
+        let renamed = vec![
+            Contig::new("1", 1000).with_md5(md5(1)),
+            Contig::new("2", 900).with_md5(md5(2)),
+            Contig::new("MT", 500).with_md5(md5(3)),
+        ];
+        assert!(evaluate(&Policy::default(), renamed.clone()).contains(&Rule::Renamed));
+        let lenient = Policy {
+            allow_renames: true,
+            ..Policy::default()
+        };
+        assert!(evaluate(&lenient, renamed).is_empty());
+
+        let mut reordered = exact_contigs();
+        reordered.swap(0, 1);
+        assert!(evaluate(&Policy::default(), reordered).contains(&Rule::Reordered));
+    }
+
+    #[test]
+    fn test_forbidden_conflicts_and_primary_exactness() {
+        let contigs = vec![
+            Contig::new("chr1", 1000).with_md5(md5(1)),
+            Contig::new("chr2", 900).with_md5(md5(2)),
+            Contig::new("chrM", 500).with_md5(md5(9)),
+        ];
+        let policy = Policy {
+            forbidden_conflicts: vec![ConflictType::MitochondrialMismatch],
+            require_primary_exact: true,
+            min_confidence: Confidence::Low,
+            ..Policy::default()
+        };
+        let rules = evaluate(&policy, contigs.clone());
+        assert!(rules.contains(&Rule::ForbiddenConflict));
+        assert!(rules.contains(&Rule::PrimaryNotExact));
+
+        let policy = Policy {
+            forbidden_conflicts: vec![ConflictType::SequenceMismatch],
+            min_confidence: Confidence::Low,
+            ..Policy::default()
+        };
+        assert!(!evaluate(&policy, contigs).contains(&Rule::ForbiddenConflict));
+    }
+
+    #[test]
+    fn test_ties_with_identical_sequences() {
+        // A twin of the test reference with the same sequences
+        let mut catalog = make_catalog();
+        let mut twin = catalog.references[0].clone();
+        twin.id = ReferenceId::new("twin_grch38");
+        twin.tags.clear();
+        catalog.add_reference(twin);
+        let engine = MatchingEngine::new(&catalog, MatchingConfig::default());
+        let expect = |allowed: &str| Policy {
+            allowed_references: vec![allowed.to_string()],
+            ..Policy::default()
+        };
+        let rules = |policy: &Policy, contigs: Vec<Contig>| -> Vec<Rule> {
+            let matches = engine.find_matches(&QueryHeader::new(contigs), 2);
+            assert!(matches[0].ambiguity.ambiguous);
+            policy
+                .evaluate(&matches, &catalog)
+                .into_iter()
+                .map(|v| v.rule)
+                .collect()
+        };
+
+        // With digests, either twin is the expected reference
+        for allowed in ["test_grch38", "twin_grch38"] {
+            let policy = expect(allowed);
+            assert!(rules(&policy, exact_contigs()).is_empty(), "{allowed}");
+        }
+
+        // Without digests, the twins cannot be told apart
+        let names_only: Vec<Contig> = exact_contigs()
+            .into_iter()
+            .map(|c| Contig::new(c.name, c.length))
+            .collect();
+        let policy = Policy {
+            min_confidence: Confidence::Low,
+            ..expect("twin_grch38")
+        };
+        assert_eq!(rules(&policy, names_only.clone()), vec![Rule::Ambiguous]);
+        let both = Policy {
+            allowed_references: vec!["test_grch38".to_string(), "twin_grch38".to_string()],
+            ..policy
+        };
+        assert!(rules(&both, names_only).is_empty());
+    }
+
+    #[test]
+    fn test_no_match() {
+        let violations = Policy::default().evaluate(&[], &make_catalog());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::NoMatch);
+    }
+}
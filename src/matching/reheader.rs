@@ -0,0 +1,299 @@
+//! Plans for rewriting an alignment header to conform to a catalog reference.
+//!
+//! A BAM aligned to the right sequences can still be rejected by tools that
+//! expect the reference's exact dictionary: contigs named `1` instead of
+//! `chr1`, or listed in another order. [`ReheaderPlan`] maps every contig of
+//! the header onto the reference's dictionary so that records can be moved to
+//! their new reference sequence IDs. It refuses headers whose sequences differ
+//! from the reference, since no renaming makes those alignments valid.
+//!
+//! Besides each record's reference sequence IDs, contig names also appear in
+//! the `SA` and `XA` tags, which [`rename_alignment_list`] rewrites.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::reference::KnownReference;
+use crate::matching::annotation::{pair_contigs, SkipReason};
+use crate::matching::diagnosis::{ConflictType, MatchDiagnosis};
+
+#[derive(Error, Debug)]
+pub enum ReheaderError {
+    #[error("Sequence conflicts with the reference: {}", .0.join("; "))]
+    SequenceConflicts(Vec<String>),
+
+    #[error("Contigs not in the reference: {}", .0.join(", "))]
+    Unmatched(Vec<String>),
+
+    #[error("Contigs {first} and {second} both correspond to {reference}")]
+    DuplicateTarget {
+        first: String,
+        second: String,
+        reference: String,
+    },
+}
+
+/// Mapping of a header's contigs onto a reference's dictionary
+#[derive(Debug, Clone)]
+pub struct ReheaderPlan {
+    /// Contigs of the new header, in order: the reference's contigs, then any
+    /// appended unmatched contigs
+    pub contigs: Vec<Contig>,
+
+    /// For each contig of the original header, its index in the new header
+    pub mapping: Vec<usize>,
+
+    /// Contigs whose name changes, as (old name, new name)
+    pub renamed: Vec<(String, String)>,
+
+    /// True if contigs of the original header change their relative order
+    pub reordered: bool,
+
+    /// Contigs not in the reference, kept under their own names at the end
+    pub appended: Vec<String>,
+}
+
+impl ReheaderPlan {
+    /// Plan how to move the contigs of `query` to the dictionary of `reference`.
+    ///
+    /// Contigs are paired by sequence digest, or by a unique name (or alias)
+    /// and length. The new dictionary lists every reference contig present in
+    /// the reference's FASTA, in the reference's order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReheaderError::SequenceConflicts` if the diagnosis shows a
+    /// contig whose sequence differs from the reference,
+    /// `ReheaderError::Unmatched` if a contig cannot be paired and
+    /// `append_unmatched` is false, or `ReheaderError::DuplicateTarget` if two
+    /// contigs pair with the same reference contig.
+    pub fn build(
+        query: &QueryHeader,
+        reference: &KnownReference,
+        diagnosis: &MatchDiagnosis,
+        append_unmatched: bool,
+    ) -> Result<Self, ReheaderError> {
+        let conflicts: Vec<String> = diagnosis
+            .conflicts
+            .iter()
+            .filter(|c| {
+                matches!(
+                    c.conflict_type,
+                    ConflictType::SequenceMismatch | ConflictType::MitochondrialMismatch
+                )
+            })
+            .map(|c| c.description.clone())
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(ReheaderError::SequenceConflicts(conflicts));
+        }
+
+        let mut contigs = Vec::new();
+        let mut new_index = vec![None; reference.contigs.len()];
+        for (i, contig) in reference.contigs.iter().enumerate() {
+            if !reference.contigs_missing_from_fasta.contains(&contig.name) {
+                new_index[i] = Some(contigs.len());
+                contigs.push(contig.clone());
+            }
+        }
+
+        let pairs = pair_contigs(query, reference);
+        let unmatched: Vec<String> = query
+            .contigs
+            .iter()
+            .zip(&pairs)
+            .filter(|(_, paired)| {
+                paired
+                    .as_ref()
+                    .map_or(true, |&index| new_index[index].is_none())
+            })
+            .map(|(contig, paired)| match paired {
+                Err(SkipReason::Ambiguous) => format!("{} (ambiguous)", contig.name),
+                _ => contig.name.clone(),
+            })
+            .collect();
+        if !unmatched.is_empty() && !append_unmatched {
+            return Err(ReheaderError::Unmatched(unmatched));
+        }
+
+        let mut mapping = Vec::with_capacity(query.contigs.len());
+        let mut source: Vec<Option<&str>> = vec![None; contigs.len()];
+        let mut renamed = Vec::new();
+        let mut appended = Vec::new();
+        for (contig, paired) in query.contigs.iter().zip(pairs) {
+            let Some(index) = paired.ok().and_then(|index| new_index[index]) else {
+                mapping.push(contigs.len());
+                appended.push(contig.name.clone());
+                contigs.push(contig.clone());
+                source.push(Some(&contig.name));
+                continue;
+            };
+
+            if let Some(first) = source[index] {
+                return Err(ReheaderError::DuplicateTarget {
+                    first: first.to_string(),
+                    second: contig.name.clone(),
+                    reference: contigs[index].name.clone(),
+                });
+            }
+            source[index] = Some(&contig.name);
+            if contig.name != contigs[index].name {
+                renamed.push((contig.name.clone(), contigs[index].name.clone()));
+            }
+            mapping.push(index);
+        }
+
+        let reordered = mapping.windows(2).any(|pair| pair[1] < pair[0]);
+
+        Ok(Self {
+            contigs,
+            mapping,
+            renamed,
+            reordered,
+            appended,
+        })
+    }
+
+    /// New name of each renamed contig, by old name
+    #[must_use]
+    pub fn renames(&self) -> HashMap<&str, &str> {
+        self.renamed
+            .iter()
+            .map(|(old, new)| (old.as_str(), new.as_str()))
+            .collect()
+    }
+}
+
+/// Tags listing other alignments of a read, each alignment starting with its
+/// contig name: `SA` (supplementary alignments) and BWA's `XA` (alternative hits)
+pub const ALIGNMENT_LIST_TAGS: [[u8; 2]; 2] = [*b"SA", *b"XA"];
+
+/// Rename the contigs of an `SA` or `XA` tag value, a list of
+/// `;`-terminated alignments such as `chr2,500,+,4M,60,0;`.
+///
+/// Returns `None` if no contig of the list is renamed.
+#[must_use]
+pub fn rename_alignment_list(value: &str, renames: &HashMap<&str, &str>) -> Option<String> {
+    let mut changed = false;
+    let alignments: Vec<String> = value
+        .split(';')
+        .map(|alignment| match alignment.split_once(',') {
+            Some((name, rest)) if renames.contains_key(name) => {
+                changed = true;
+                format!("{},{rest}", renames[name])
+            }
+            _ => alignment.to_string(),
+        })
+        .collect();
+    changed.then(|| alignments.join(";"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{Assembly, ReferenceSource};
+
+    fn make_reference() -> KnownReference {
+        let mut reference = KnownReference::new(
+            "test_ref",
+            "Test Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 1000)
+                .with_md5("aaaa")
+                .with_aliases(vec!["1".to_string()]),
+            Contig::new("chr2", 2000)
+                .with_md5("bbbb")
+                .with_aliases(vec!["2".to_string()]),
+            Contig::new("chrM", 16569)
+                .with_md5("cccc")
+                .with_aliases(vec!["MT".to_string()]),
+            Contig::new("chrY", 500),
+        ]);
+        reference.contigs_missing_from_fasta = vec!["chrY".to_string()];
+        reference
+    }
+
+    fn plan(query: &QueryHeader, append_unmatched: bool) -> Result<ReheaderPlan, ReheaderError> {
+        let reference = make_reference();
+        let diagnosis = MatchDiagnosis::analyze(query, &reference);
+        ReheaderPlan::build(query, &reference, &diagnosis, append_unmatched)
+    }
+
+    #[test]
+    fn test_rename_alignment_list() {
+        let renames = HashMap::from([("1", "chr1"), ("2", "chr2")]);
+        assert_eq!(
+            rename_alignment_list("2,500,+,4M,60,0;", &renames).as_deref(),
+            Some("chr2,500,+,4M,60,0;")
+        );
+        assert_eq!(
+            rename_alignment_list("2,+300,4M,0;X,-900,4M,1;1,-20,4M,2;", &renames).as_deref(),
+            Some("chr2,+300,4M,0;X,-900,4M,1;chr1,-20,4M,2;")
+        );
+        // Names only match whole fields
+        assert_eq!(rename_alignment_list("10,500,+,4M,60,0;", &renames), None);
+        assert_eq!(rename_alignment_list("", &renames), None);
+    }
+
+    #[test]
+    fn test_renames_and_reorders() {
+        let query = QueryHeader::new(vec![
+            Contig::new("MT", 16569),
+            Contig::new("1", 1000),
+            Contig::new("2", 2000),
+        ]);
+
+        let plan = plan(&query, false).unwrap();
+
+        let names: Vec<&str> = plan.contigs.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["chr1", "chr2", "chrM"]);
+        assert_eq!(plan.mapping, vec![2, 0, 1]);
+        assert_eq!(plan.renamed.len(), 3);
+        assert!(plan.reordered);
+        assert!(plan.appended.is_empty());
+    }
+
+    #[test]
+    fn test_refuses_sequence_conflicts() {
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 1000).with_md5("aaaa"),
+            Contig::new("chr2", 2000).with_md5("ffff"),
+        ]);
+
+        let result = plan(&query, true);
+
+        assert!(matches!(result, Err(ReheaderError::SequenceConflicts(_))));
+    }
+
+    #[test]
+    fn test_unmatched_contigs() {
+        let query = QueryHeader::new(vec![Contig::new("chr1", 1000), Contig::new("chrUn_x", 300)]);
+
+        let result = plan(&query, false);
+        assert!(matches!(result, Err(ReheaderError::Unmatched(names)) if names == ["chrUn_x"]));
+
+        let plan = plan(&query, true).unwrap();
+        assert_eq!(plan.contigs.len(), 4);
+        assert_eq!(plan.mapping, vec![0, 3]);
+        assert_eq!(plan.appended, vec!["chrUn_x"]);
+        assert!(!plan.reordered);
+    }
+
+    #[test]
+    fn test_refuses_duplicate_targets() {
+        let query = QueryHeader::new(vec![Contig::new("chr1", 1000), Contig::new("1", 1000)]);
+
+        let result = plan(&query, false);
+
+        assert!(matches!(
+            result,
+            Err(ReheaderError::DuplicateTarget { reference, .. }) if reference == "chr1"
+        ));
+    }
+}
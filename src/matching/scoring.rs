@@ -1,18 +1,19 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::core::contig::Contig;
 use crate::core::header::QueryHeader;
 use crate::core::reference::KnownReference;
 use crate::core::types::Confidence;
+use crate::matching::engine::{ScoringMode, ScoringWeights};
 
 /// Classification of how a query contig matches a reference
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContigMatchType {
-    /// Name+length match AND MD5 matches (or both are primary chromosomes with same length)
+    /// Name+length match AND sequence digest (MD5 or sha512t24u) matches
     Exact,
-    /// Name+length match, but MD5 not available on one or both sides (neutral)
+    /// Name+length match, but no digest type available on both sides (neutral)
     NameLengthNoMd5,
-    /// Name+length match, but MD5 differs (different sequence - heavy penalty!)
+    /// Name+length match, but a sequence digest differs (different sequence - heavy penalty!)
     Md5Conflict,
     /// No name+length match found
     Unmatched,
@@ -31,6 +32,144 @@ fn count_to_f64(count: usize) -> f64 {
     }
 }
 
+/// Convert a base-pair total to f64 for fraction calculations
+///
+/// Genome sizes are far below 2^53, so no precision is lost in practice.
+#[inline]
+fn bases_to_f64(bases: u64) -> f64 {
+    #[allow(clippy::cast_precision_loss)]
+    {
+        bases as f64
+    }
+}
+
+/// Divide, returning 0.0 for an empty denominator
+fn fraction(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+/// Length-weighted view of a match: contigs contribute by base pairs rather
+/// than by count, so a wrong chr1 outweighs thousands of missing decoys.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct LengthWeightedScore {
+    /// Query bases on contigs with an exact match (name+length+digest)
+    pub exact_bases: u64,
+
+    /// Query bases on contigs with a name+length match but no digest to compare
+    pub name_length_bases: u64,
+
+    /// Query bases on contigs whose name+length match but digest differs
+    pub conflict_bases: u64,
+
+    /// Query bases on contigs with no match
+    pub unmatched_bases: u64,
+
+    /// Total bases in the reference
+    pub reference_bases: u64,
+
+    /// Fraction of query bases whose sequence is verified identical
+    pub exact_fraction: f64,
+
+    /// Fraction of query bases matched by name+length only
+    pub name_length_fraction: f64,
+
+    /// Fraction of query bases on conflicting contigs
+    pub conflict_fraction: f64,
+
+    /// Fraction of query bases with no match
+    pub unmatched_fraction: f64,
+
+    /// Length-weighted match score: (exact + neutral + penalty*conflicts) / `query_bases`
+    pub match_quality: f64,
+
+    /// Length-weighted coverage: good bases / `reference_bases`
+    pub coverage_score: f64,
+}
+
+impl LengthWeightedScore {
+    /// Total bases across all query contigs
+    #[must_use]
+    pub fn query_bases(&self) -> u64 {
+        self.exact_bases + self.name_length_bases + self.conflict_bases + self.unmatched_bases
+    }
+}
+
+/// Per-contig classification totals, by count and by length
+#[derive(Default)]
+struct ContigTally {
+    exact_matches: usize,
+    name_length_matches: usize,
+    md5_conflicts: usize,
+    unmatched: usize,
+    length_weighted: LengthWeightedScore,
+}
+
+impl ContigTally {
+    fn new(query: &QueryHeader, reference: &KnownReference, classes: &[ContigMatchType]) -> Self {
+        let mut tally = Self::default();
+        for (contig, class) in query.contigs.iter().zip(classes) {
+            let bases = &mut tally.length_weighted;
+            match class {
+                ContigMatchType::Exact => {
+                    tally.exact_matches += 1;
+                    bases.exact_bases += contig.length;
+                }
+                ContigMatchType::NameLengthNoMd5 => {
+                    tally.name_length_matches += 1;
+                    bases.name_length_bases += contig.length;
+                }
+                ContigMatchType::Md5Conflict => {
+                    tally.md5_conflicts += 1;
+                    bases.conflict_bases += contig.length;
+                }
+                ContigMatchType::Unmatched => {
+                    tally.unmatched += 1;
+                    bases.unmatched_bases += contig.length;
+                }
+            }
+        }
+        tally.length_weighted.reference_bases = reference.contigs.iter().map(|c| c.length).sum();
+        tally
+    }
+
+    /// Count-based (`match_quality`, `coverage_score`)
+    fn count_scores(&self, conflict_penalty: f64, reference_contigs: usize) -> (f64, f64) {
+        let total_query =
+            self.exact_matches + self.name_length_matches + self.md5_conflicts + self.unmatched;
+        let good_matches = self.exact_matches + self.name_length_matches;
+
+        let weighted_matches =
+            count_to_f64(good_matches) + (count_to_f64(self.md5_conflicts) * conflict_penalty);
+        let match_quality = fraction(weighted_matches, count_to_f64(total_query));
+        let coverage_score =
+            fraction(count_to_f64(good_matches), count_to_f64(reference_contigs)).min(1.0);
+
+        (match_quality, coverage_score)
+    }
+
+    /// Fill in the fractions and scores of the length-weighted view
+    fn into_length_weighted(self, conflict_penalty: f64) -> LengthWeightedScore {
+        let mut lw = self.length_weighted;
+        let query_bases = bases_to_f64(lw.query_bases());
+        let good_bases = bases_to_f64(lw.exact_bases + lw.name_length_bases);
+
+        lw.exact_fraction = fraction(bases_to_f64(lw.exact_bases), query_bases);
+        lw.name_length_fraction = fraction(bases_to_f64(lw.name_length_bases), query_bases);
+        lw.conflict_fraction = fraction(bases_to_f64(lw.conflict_bases), query_bases);
+        lw.unmatched_fraction = fraction(bases_to_f64(lw.unmatched_bases), query_bases);
+        lw.match_quality = fraction(
+            good_bases + bases_to_f64(lw.conflict_bases) * conflict_penalty,
+            query_bases,
+        );
+        lw.coverage_score = fraction(good_bases, bases_to_f64(lw.reference_bases)).min(1.0);
+        lw
+    }
+}
+
 /// Detailed similarity scores between a query and a reference
 #[derive(Debug, Clone)]
 pub struct MatchScore {
@@ -60,6 +199,12 @@ pub struct MatchScore {
     /// Coverage score: `good_matches` / `reference_contigs`
     pub coverage_score: f64,
 
+    /// Base-pair weighted match metrics, always computed alongside the counts
+    pub length_weighted: LengthWeightedScore,
+
+    /// Which metrics (counts or base pairs) the composite was computed from
+    pub scoring_mode: ScoringMode,
+
     /// Fraction of contigs in correct relative order
     pub order_score: f64,
 
@@ -67,13 +212,14 @@ pub struct MatchScore {
     pub order_preserved: bool,
 
     // === Legacy metrics (for backward compatibility) ===
-    /// Jaccard similarity of MD5 sets: |intersection| / |union|
+    /// Jaccard similarity of the contigs with a sequence digest, matched by MD5
+    /// or sha512t24u: |intersection| / |union|
     pub md5_jaccard: f64,
 
     /// Jaccard similarity of (`normalized_name`, length) pairs
     pub name_length_jaccard: f64,
 
-    /// Fraction of query contigs matched by MD5
+    /// Fraction of query contigs with a sequence digest matched by MD5 or sha512t24u
     pub md5_query_coverage: f64,
 
     /// Fraction of query contigs matched by name+length
@@ -92,80 +238,19 @@ impl MatchScore {
     /// Composite = 70% `match_quality` + 20% `coverage_score` + 10% `order_score`
     #[must_use]
     pub fn calculate(query: &QueryHeader, reference: &KnownReference) -> Self {
-        // Classify each query contig
-        let mut exact_matches = 0usize;
-        let mut name_length_matches = 0usize; // No MD5 available
-        let mut md5_conflicts = 0usize;
-        let mut unmatched = 0usize;
-
-        for contig in &query.contigs {
-            match classify_contig_match(contig, reference) {
-                ContigMatchType::Exact => exact_matches += 1,
-                ContigMatchType::NameLengthNoMd5 => name_length_matches += 1,
-                ContigMatchType::Md5Conflict => md5_conflicts += 1,
-                ContigMatchType::Unmatched => unmatched += 1,
-            }
-        }
-
-        let total_query = query.contigs.len();
-
         // Contig match score: full credit for exact/neutral, 10% for conflicts, 0 for unmatched
         // Key principle: MD5 absence is neutral (full credit), MD5 conflict is penalized
-        let weighted_matches = count_to_f64(exact_matches)
-            + count_to_f64(name_length_matches)
-            + (count_to_f64(md5_conflicts) * 0.1);
-
-        let match_quality = if total_query > 0 {
-            weighted_matches / count_to_f64(total_query)
-        } else {
-            0.0
-        };
-
-        // Coverage: what fraction of reference is covered by good matches (exact + neutral)
-        let good_matches = exact_matches + name_length_matches;
-        let coverage_score = if reference.contigs.is_empty() {
-            0.0
-        } else {
-            (count_to_f64(good_matches) / count_to_f64(reference.contigs.len())).min(1.0)
-        };
-
-        // Order analysis (existing function)
-        let (order_preserved, order_score) = analyze_order(query, reference);
+        let classes = classify_contigs(query, reference);
+        let mut score = Self::from_components(query, reference, &classes, 0.1, ScoringMode::Count);
 
         // New composite formula: 70% match quality, 20% coverage, 10% order
         // Clamp to [0.0, 1.0] to handle floating point precision issues
-        let composite = ((0.70 * match_quality) + (0.20 * coverage_score) + (0.10 * order_score))
+        score.composite = ((0.70 * score.match_quality)
+            + (0.20 * score.coverage_score)
+            + (0.10 * score.order_score))
             .clamp(0.0, 1.0);
-
-        let confidence = Confidence::from_score(composite);
-
-        // Compute legacy metrics for backward compatibility
-        let md5_jaccard = jaccard_similarity(&query.md5_set, &reference.md5_set);
-        let (name_length_jaccard, name_length_query_coverage) =
-            calculate_name_length_similarity_with_aliases(query, reference);
-        let md5_query_coverage = if query.md5_set.is_empty() {
-            0.0
-        } else {
-            count_to_f64(query.md5_set.intersection(&reference.md5_set).count())
-                / count_to_f64(query.md5_set.len())
-        };
-
-        Self {
-            composite,
-            confidence,
-            exact_matches,
-            name_length_matches,
-            md5_conflicts,
-            unmatched,
-            match_quality,
-            coverage_score,
-            order_score,
-            order_preserved,
-            md5_jaccard,
-            name_length_jaccard,
-            md5_query_coverage,
-            name_length_query_coverage,
-        }
+        score.confidence = Confidence::from_score(score.composite);
+        score
     }
 
     /// Calculate match score with custom scoring weights
@@ -175,83 +260,84 @@ impl MatchScore {
     /// - `coverage_weight`: Weight for reference coverage (default 20%)
     /// - `order_weight`: Weight for contig ordering (default 10%)
     /// - `conflict_penalty`: Credit given to MD5 conflicts (default 0.1 = 10%)
+    /// - `scoring_mode`: Whether match and coverage count contigs or base pairs
     #[must_use]
     pub fn calculate_with_weights(
         query: &QueryHeader,
         reference: &KnownReference,
-        weights: &crate::matching::engine::ScoringWeights,
+        weights: &ScoringWeights,
     ) -> Self {
-        // Classify each query contig
-        let mut exact_matches = 0usize;
-        let mut name_length_matches = 0usize;
-        let mut md5_conflicts = 0usize;
-        let mut unmatched = 0usize;
-
-        for contig in &query.contigs {
-            match classify_contig_match(contig, reference) {
-                ContigMatchType::Exact => exact_matches += 1,
-                ContigMatchType::NameLengthNoMd5 => name_length_matches += 1,
-                ContigMatchType::Md5Conflict => md5_conflicts += 1,
-                ContigMatchType::Unmatched => unmatched += 1,
-            }
-        }
-
-        let total_query = query.contigs.len();
+        let classes = classify_contigs(query, reference);
+        Self::calculate_classified(query, reference, &classes, weights)
+    }
 
+    /// Calculate match score with custom scoring weights from contig
+    /// classifications computed beforehand by [`classify_contigs`], so that
+    /// callers scoring several views of one match classify each contig once.
+    ///
+    /// `classes` holds one entry per query contig, in query order.
+    #[must_use]
+    pub fn calculate_classified(
+        query: &QueryHeader,
+        reference: &KnownReference,
+        classes: &[ContigMatchType],
+        weights: &ScoringWeights,
+    ) -> Self {
         // Normalize weights
         let normalized_weights = weights.normalized();
+        let mut score = Self::from_components(
+            query,
+            reference,
+            classes,
+            normalized_weights.conflict_penalty,
+            normalized_weights.scoring_mode,
+        );
 
-        // Contig match score using configurable conflict penalty
-        let weighted_matches = count_to_f64(exact_matches)
-            + count_to_f64(name_length_matches)
-            + (count_to_f64(md5_conflicts) * normalized_weights.conflict_penalty);
+        // Composite with custom weights, from the metrics of the selected mode
+        // Clamp to [0.0, 1.0] to handle floating point precision issues
+        score.composite = ((normalized_weights.contig_match * score.active_match_quality())
+            + (normalized_weights.coverage * score.active_coverage_score())
+            + (normalized_weights.order * score.order_score))
+            .clamp(0.0, 1.0);
+        score.confidence = Confidence::from_score(score.composite);
+        score
+    }
 
-        let match_quality = if total_query > 0 {
-            weighted_matches / count_to_f64(total_query)
-        } else {
-            0.0
-        };
+    /// Compute every component score, leaving the composite for the caller to weight
+    fn from_components(
+        query: &QueryHeader,
+        reference: &KnownReference,
+        classes: &[ContigMatchType],
+        conflict_penalty: f64,
+        scoring_mode: ScoringMode,
+    ) -> Self {
+        // Tally the classification of each query contig
+        let tally = ContigTally::new(query, reference, classes);
 
-        // Coverage: what fraction of reference is covered by good matches
-        let good_matches = exact_matches + name_length_matches;
-        let coverage_score = if reference.contigs.is_empty() {
-            0.0
-        } else {
-            (count_to_f64(good_matches) / count_to_f64(reference.contigs.len())).min(1.0)
-        };
+        // Match quality with the conflict penalty, and coverage of the reference
+        // by good matches (exact + neutral)
+        let (match_quality, coverage_score) =
+            tally.count_scores(conflict_penalty, reference.contigs.len());
 
         // Order analysis
         let (order_preserved, order_score) = analyze_order(query, reference);
 
-        // Composite with custom weights
-        // Clamp to [0.0, 1.0] to handle floating point precision issues
-        let composite = ((normalized_weights.contig_match * match_quality)
-            + (normalized_weights.coverage * coverage_score)
-            + (normalized_weights.order * order_score))
-            .clamp(0.0, 1.0);
-
-        let confidence = Confidence::from_score(composite);
-
         // Compute legacy metrics for backward compatibility
-        let md5_jaccard = jaccard_similarity(&query.md5_set, &reference.md5_set);
+        let (md5_jaccard, md5_query_coverage) = calculate_digest_similarity(query, reference);
         let (name_length_jaccard, name_length_query_coverage) =
             calculate_name_length_similarity_with_aliases(query, reference);
-        let md5_query_coverage = if query.md5_set.is_empty() {
-            0.0
-        } else {
-            count_to_f64(query.md5_set.intersection(&reference.md5_set).count())
-                / count_to_f64(query.md5_set.len())
-        };
 
         Self {
-            composite,
-            confidence,
-            exact_matches,
-            name_length_matches,
-            md5_conflicts,
-            unmatched,
+            composite: 0.0,
+            confidence: Confidence::from_score(0.0),
+            exact_matches: tally.exact_matches,
+            name_length_matches: tally.name_length_matches,
+            md5_conflicts: tally.md5_conflicts,
+            unmatched: tally.unmatched,
             match_quality,
             coverage_score,
+            length_weighted: tally.into_length_weighted(conflict_penalty),
+            scoring_mode,
             order_score,
             order_preserved,
             md5_jaccard,
@@ -260,23 +346,99 @@ impl MatchScore {
             name_length_query_coverage,
         }
     }
+
+    /// Match score of the selected scoring mode (the one used in the composite)
+    #[must_use]
+    pub fn active_match_quality(&self) -> f64 {
+        match self.scoring_mode {
+            ScoringMode::Count => self.match_quality,
+            ScoringMode::LengthWeighted => self.length_weighted.match_quality,
+        }
+    }
+
+    /// Coverage score of the selected scoring mode (the one used in the composite)
+    #[must_use]
+    pub fn active_coverage_score(&self) -> f64 {
+        match self.scoring_mode {
+            ScoringMode::Count => self.coverage_score,
+            ScoringMode::LengthWeighted => self.length_weighted.coverage_score,
+        }
+    }
 }
 
-/// Jaccard similarity: |A ∩ B| / |A ∪ B|
+/// Jaccard similarity: |A ∩ B| / |A ∪ B|, from the size of the intersection
+/// and of each collection
 ///
-/// Returns 0.0 when both sets are empty (undefined mathematically, but 0.0 is safer
+/// Returns 0.0 when both are empty (undefined mathematically, but 0.0 is safer
 /// for matching to avoid false positives from two references with no MD5s).
-fn jaccard_similarity<T: Eq + std::hash::Hash>(a: &HashSet<T>, b: &HashSet<T>) -> f64 {
-    let intersection = a.intersection(b).count();
-    let union = a.union(b).count();
+fn jaccard_similarity(intersection: usize, a: usize, b: usize) -> f64 {
+    let union = a + b - intersection;
     if union == 0 {
-        // Both sets empty - return 0.0 to avoid false positive matches
+        // Both empty - return 0.0 to avoid false positive matches
         0.0
     } else {
         count_to_f64(intersection) / count_to_f64(union)
     }
 }
 
+/// Calculate the overlap of sequences between query and reference, counting
+/// only contigs with a digest. Sequences match by MD5 or sha512t24u, as
+/// decided by [`Contig::digests_match`], whatever their names.
+///
+/// Returns (`jaccard_similarity`, `query_coverage`)
+fn calculate_digest_similarity(query: &QueryHeader, reference: &KnownReference) -> (f64, f64) {
+    let has_digest = |contig: &&Contig| contig.md5.is_some() || contig.sha512t24u.is_some();
+
+    // Reference contigs by each of their digests
+    let mut by_digest: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, contig) in reference.contigs.iter().enumerate() {
+        if let Some(md5) = &contig.md5 {
+            by_digest
+                .entry(md5.to_ascii_lowercase())
+                .or_default()
+                .push(index);
+        }
+        if let Some(digest) = &contig.sha512t24u {
+            by_digest.entry(digest.clone()).or_default().push(index);
+        }
+    }
+
+    let mut matched_query = 0usize;
+    let mut matched_reference: HashSet<usize> = HashSet::new();
+    for contig in query.contigs.iter().filter(has_digest) {
+        let candidates = contig
+            .md5
+            .as_ref()
+            .map(|md5| md5.to_ascii_lowercase())
+            .into_iter()
+            .chain(contig.sha512t24u.clone())
+            .filter_map(|digest| by_digest.get(&digest))
+            .flatten();
+        let mut matched = false;
+        for &index in candidates {
+            if contig.digests_match(&reference.contigs[index]) == Some(true) {
+                matched = true;
+                matched_reference.insert(index);
+            }
+        }
+        if matched {
+            matched_query += 1;
+        }
+    }
+
+    let query_digested = query.contigs.iter().filter(has_digest).count();
+    let reference_digested = reference.contigs.iter().filter(has_digest).count();
+    // A sequence repeated on one side matches once, as in a set
+    let intersection = matched_query.min(matched_reference.len());
+    let jaccard = jaccard_similarity(intersection, query_digested, reference_digested);
+    let coverage = if query_digested == 0 {
+        0.0
+    } else {
+        count_to_f64(matched_query) / count_to_f64(query_digested)
+    };
+    (jaccard, coverage)
+}
+
 /// Calculate name+length similarity accounting for alias-based matching.
 ///
 /// A query contig matches a reference contig if:
@@ -396,6 +558,11 @@ fn analyze_order(query: &QueryHeader, reference: &KnownReference) -> (bool, f64)
     (is_sorted, order_score)
 }
 
+/// Whether the contigs the query shares with the reference are in the same order
+pub(crate) fn order_preserved(query: &QueryHeader, reference: &KnownReference) -> bool {
+    analyze_order(query, reference).0
+}
+
 /// Length of longest increasing subsequence (LIS).
 ///
 /// Returns 0 for empty arrays, otherwise the length of the LIS (minimum 1).
@@ -419,12 +586,22 @@ fn longest_increasing_subsequence(arr: &[usize]) -> usize {
     dp.into_iter().max().expect("dp is non-empty")
 }
 
+/// Classify every query contig against a reference, in query order
+#[must_use]
+pub fn classify_contigs(query: &QueryHeader, reference: &KnownReference) -> Vec<ContigMatchType> {
+    query
+        .contigs
+        .iter()
+        .map(|contig| classify_contig_match(contig, reference))
+        .collect()
+}
+
 /// Classify how a query contig matches against a reference.
 ///
 /// Returns the match type indicating whether the contig:
-/// - Has an exact match (name+length+MD5 all match)
-/// - Has a name+length match but no MD5 to compare (neutral)
-/// - Has a name+length match but MD5 differs (conflict - different sequence!)
+/// - Has an exact match (name+length+digest all match)
+/// - Has a name+length match but no shared digest to compare (neutral)
+/// - Has a name+length match but a digest differs (conflict - different sequence!)
 /// - Has no match
 fn classify_contig_match(query_contig: &Contig, reference: &KnownReference) -> ContigMatchType {
     // First, find if there's a name+length match (including aliases)
@@ -433,19 +610,13 @@ fn classify_contig_match(query_contig: &Contig, reference: &KnownReference) -> C
     match matched_ref_contig {
         None => ContigMatchType::Unmatched,
         Some(ref_contig) => {
-            // Name+length matched, now check MD5
-            match (&query_contig.md5, &ref_contig.md5) {
-                // Both have MD5 - compare them
-                (Some(q_md5), Some(r_md5)) => {
-                    if q_md5.eq_ignore_ascii_case(r_md5) {
-                        ContigMatchType::Exact
-                    } else {
-                        // MD5 mismatch - same name/length but different sequence!
-                        ContigMatchType::Md5Conflict
-                    }
-                }
-                // Either or both missing MD5 - neutral (can't verify but not a conflict)
-                _ => ContigMatchType::NameLengthNoMd5,
+            // Name+length matched, now check sequence digests (MD5 or sha512t24u)
+            match query_contig.digests_match(ref_contig) {
+                Some(true) => ContigMatchType::Exact,
+                // Digest mismatch - same name/length but different sequence!
+                Some(false) => ContigMatchType::Md5Conflict,
+                // No shared digest type - neutral (can't verify but not a conflict)
+                None => ContigMatchType::NameLengthNoMd5,
             }
         }
     }
@@ -501,24 +672,18 @@ mod tests {
 
     #[test]
     fn test_jaccard_similarity() {
-        let a: HashSet<i32> = [1, 2, 3].into_iter().collect();
-        let b: HashSet<i32> = [2, 3, 4].into_iter().collect();
-
-        let similarity = jaccard_similarity(&a, &b);
-        // intersection = {2, 3} = 2, union = {1, 2, 3, 4} = 4
+        // {1, 2, 3} and {2, 3, 4}: intersection = {2, 3} = 2, union = {1, 2, 3, 4} = 4
+        let similarity = jaccard_similarity(2, 3, 3);
         assert!((similarity - 0.5).abs() < 0.001);
 
         // Empty sets should return 0.0 to avoid false positives
-        let empty1: HashSet<i32> = HashSet::new();
-        let empty2: HashSet<i32> = HashSet::new();
-        assert!((jaccard_similarity(&empty1, &empty2) - 0.0).abs() < 0.001);
+        assert!((jaccard_similarity(0, 0, 0) - 0.0).abs() < 0.001);
 
         // One empty, one non-empty should return 0.0
-        assert!((jaccard_similarity(&a, &empty1) - 0.0).abs() < 0.001);
+        assert!((jaccard_similarity(0, 3, 0) - 0.0).abs() < 0.001);
 
         // Identical sets should return 1.0
-        let c: HashSet<i32> = [1, 2, 3].into_iter().collect();
-        assert!((jaccard_similarity(&a, &c) - 1.0).abs() < 0.001);
+        assert!((jaccard_similarity(3, 3, 3) - 1.0).abs() < 0.001);
     }
 
     #[test]
@@ -593,7 +758,6 @@ mod tests {
         use crate::core::contig::Contig;
         use crate::core::reference::KnownReference;
         use crate::core::types::{Assembly, ReferenceSource};
-        use crate::matching::engine::ScoringWeights;
 
         let ref_contigs = vec![Contig::new("chr1", 1000), Contig::new("chr2", 2000)];
         let reference = KnownReference::new(
@@ -613,18 +777,21 @@ mod tests {
                 coverage: 0.2,
                 order: 0.1,
                 conflict_penalty: 0.1,
+                scoring_mode: ScoringMode::Count,
             },
             ScoringWeights {
                 contig_match: 0.5,
                 coverage: 0.3,
                 order: 0.2,
                 conflict_penalty: 0.0,
+                scoring_mode: ScoringMode::Count,
             },
             ScoringWeights {
                 contig_match: 1.0,
                 coverage: 1.0,
                 order: 1.0,
                 conflict_penalty: 0.5,
+                scoring_mode: ScoringMode::Count,
             },
         ];
 
@@ -1022,6 +1189,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_classify_contig_match_sha512t24u() {
+        use crate::core::contig::Contig;
+        use crate::core::reference::KnownReference;
+        use crate::core::types::{Assembly, ReferenceSource};
+
+        let ref_contigs =
+            vec![Contig::new("chr1", 1000).with_sha512t24u("2648ae1bacce4ec4b6cf337dcae37816")];
+        let reference = KnownReference::new(
+            "test",
+            "Test",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(ref_contigs);
+
+        // sha512t24u alone is equivalent to MD5 evidence
+        let query_contig =
+            Contig::new("chr1", 1000).with_sha512t24u("2648ae1bacce4ec4b6cf337dcae37816");
+        assert_eq!(
+            classify_contig_match(&query_contig, &reference),
+            ContigMatchType::Exact
+        );
+
+        let query_contig =
+            Contig::new("chr1", 1000).with_sha512t24u("0123456789abcdef0123456789abcdef");
+        assert_eq!(
+            classify_contig_match(&query_contig, &reference),
+            ContigMatchType::Md5Conflict
+        );
+
+        // MD5 on one side and sha512t24u on the other cannot be compared
+        let query_contig = Contig::new("chr1", 1000).with_md5("abc123");
+        assert_eq!(
+            classify_contig_match(&query_contig, &reference),
+            ContigMatchType::NameLengthNoMd5
+        );
+    }
+
     #[test]
     fn test_classify_contig_match_unmatched() {
         use crate::core::contig::Contig;
@@ -1051,4 +1257,145 @@ mod tests {
             ContigMatchType::Unmatched
         );
     }
+
+    #[test]
+    fn test_length_weighted_metrics() {
+        use crate::core::contig::Contig;
+        use crate::core::reference::KnownReference;
+        use crate::core::types::{Assembly, ReferenceSource};
+
+        let reference = KnownReference::new(
+            "test_ref",
+            "Test Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 6000).with_md5("md5_1"),
+            Contig::new("chr2", 3000).with_md5("md5_2"),
+            Contig::new("chr3", 1000),
+        ]);
+
+        // chr1 exact, chr2 conflict, chr3 name+length only, chrUn unmatched
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 6000).with_md5("md5_1"),
+            Contig::new("chr2", 3000).with_md5("WRONG_MD5"),
+            Contig::new("chr3", 1000),
+            Contig::new("chrUn", 2000),
+        ]);
+
+        let score = MatchScore::calculate(&query, &reference);
+        let lw = &score.length_weighted;
+
+        assert_eq!(lw.exact_bases, 6000);
+        assert_eq!(lw.conflict_bases, 3000);
+        assert_eq!(lw.name_length_bases, 1000);
+        assert_eq!(lw.unmatched_bases, 2000);
+        assert_eq!(lw.query_bases(), 12000);
+        assert_eq!(lw.reference_bases, 10000);
+        assert!((lw.exact_fraction - 0.5).abs() < 1e-9);
+        assert!((lw.conflict_fraction - 0.25).abs() < 1e-9);
+        // (6000 + 1000 + 0.1 * 3000) / 12000
+        assert!((lw.match_quality - 7300.0 / 12000.0).abs() < 1e-9);
+        assert!((lw.coverage_score - 0.7).abs() < 1e-9);
+
+        // Count mode is the default, so the composite uses the count metrics
+        assert_eq!(score.scoring_mode, ScoringMode::Count);
+        assert!((score.active_match_quality() - score.match_quality).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_length_weighted_mode_prefers_correct_primary_chromosomes() {
+        use crate::core::contig::Contig;
+        use crate::core::reference::KnownReference;
+        use crate::core::types::{Assembly, ReferenceSource};
+
+        let mut ref_contigs = vec![Contig::new("chr1", 248_956_422).with_md5("chr1_md5")];
+        ref_contigs.extend(
+            (0..50).map(|i| Contig::new(format!("decoy{i}"), 2000).with_md5(format!("d{i}"))),
+        );
+        let reference = KnownReference::new(
+            "test_ref",
+            "Test Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(ref_contigs);
+
+        // Correct chr1, but none of the decoys
+        let missing_decoys =
+            QueryHeader::new(vec![Contig::new("chr1", 248_956_422).with_md5("chr1_md5")]);
+
+        // Every decoy, but a different chr1 sequence
+        let mut wrong_chr1_contigs = vec![Contig::new("chr1", 248_956_422).with_md5("other_md5")];
+        wrong_chr1_contigs.extend(
+            (0..50).map(|i| Contig::new(format!("decoy{i}"), 2000).with_md5(format!("d{i}"))),
+        );
+        let wrong_chr1 = QueryHeader::new(wrong_chr1_contigs);
+
+        let count = ScoringWeights::default();
+        let length_weighted = ScoringWeights {
+            scoring_mode: ScoringMode::LengthWeighted,
+            ..ScoringWeights::default()
+        };
+
+        // Counting contigs, the wrong chr1 looks better than the missing decoys
+        let missing_score = MatchScore::calculate_with_weights(&missing_decoys, &reference, &count);
+        let wrong_score = MatchScore::calculate_with_weights(&wrong_chr1, &reference, &count);
+        assert!(wrong_score.composite > missing_score.composite);
+
+        // Weighting by length, the correct chr1 wins
+        let missing_score =
+            MatchScore::calculate_with_weights(&missing_decoys, &reference, &length_weighted);
+        let wrong_score =
+            MatchScore::calculate_with_weights(&wrong_chr1, &reference, &length_weighted);
+        assert!(missing_score.composite > wrong_score.composite);
+        assert_eq!(missing_score.scoring_mode, ScoringMode::LengthWeighted);
+        assert!(
+            (missing_score.active_match_quality() - missing_score.length_weighted.match_quality)
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_digest_metrics_match_by_any_digest() {
+        use crate::core::types::{Assembly, ReferenceSource};
+
+        let reference = KnownReference::new(
+            "test",
+            "Test",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 1000)
+                .with_md5("6aef897c3d6ff0c78aff06ac189178dd")
+                .with_sha512t24u("2648ae1bacce4ec4b6cf337dcae37816"),
+            Contig::new("chr2", 2000).with_md5("f98db672eb0993dcfdabafe2a882905c"),
+        ]);
+
+        // sha512t24u alone matches, under another name; MD5s ignore case
+        let query = QueryHeader::new(vec![
+            Contig::new("1", 1000).with_sha512t24u("2648ae1bacce4ec4b6cf337dcae37816"),
+            Contig::new("chr2", 2000).with_md5("F98DB672EB0993DCFDABAFE2A882905C"),
+        ]);
+        let score = MatchScore::calculate(&query, &reference);
+        assert!((score.md5_jaccard - 1.0).abs() < 0.001);
+        assert!((score.md5_query_coverage - 1.0).abs() < 0.001);
+
+        // A matching MD5 with a conflicting sha512t24u is a different sequence,
+        // and contigs without a digest are left out
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 1000)
+                .with_md5("6aef897c3d6ff0c78aff06ac189178dd")
+                .with_sha512t24u("0123456789abcdef0123456789abcdef"),
+            Contig::new("chr2", 2000).with_md5("f98db672eb0993dcfdabafe2a882905c"),
+            Contig::new("chr3", 3000),
+        ]);
+        let score = MatchScore::calculate(&query, &reference);
+        // intersection = {chr2}, union = {query chr1, reference chr1, chr2}
+        assert!((score.md5_jaccard - 1.0 / 3.0).abs() < 0.001);
+        assert!((score.md5_query_coverage - 0.5).abs() < 0.001);
+    }
 }
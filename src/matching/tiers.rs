@@ -0,0 +1,382 @@
+//! Separate verdicts for the primary assembly and the auxiliary contigs.
+//!
+//! A single composite score cannot express "the primary assembly is identical
+//! but the decoys differ", which is acceptable for germline calling but not for
+//! ALT-aware alignment. [`TieredVerdict`] splits the query and the reference
+//! into two tiers and scores and diagnoses each on its own:
+//!
+//! - **Primary**: assembled molecules and the mitochondrion
+//!   ([`SequenceRole::AssembledMolecule`](crate::core::contig::SequenceRole))
+//! - **Auxiliary**: alts, decoys, HLA, unplaced/unlocalized scaffolds, viral sequences
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::reference::KnownReference;
+use crate::core::types::{Confidence, MatchType};
+use crate::matching::diagnosis::MatchDiagnosis;
+use crate::matching::engine::ScoringWeights;
+use crate::matching::scoring::{ContigMatchType, MatchScore};
+
+/// Part of an assembly a contig belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssemblyTier {
+    /// Assembled molecules (chromosomes) and the mitochondrion
+    Primary,
+    /// Alts, decoys, HLA, unplaced scaffolds and other extra sequences
+    Auxiliary,
+}
+
+impl std::fmt::Display for AssemblyTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Primary => write!(f, "primary"),
+            Self::Auxiliary => write!(f, "auxiliary"),
+        }
+    }
+}
+
+/// Score and diagnosis restricted to one tier of the assembly
+#[derive(Debug, Clone, Serialize)]
+pub struct TierVerdict {
+    /// Which tier this verdict covers
+    pub tier: AssemblyTier,
+
+    /// Match type for this tier
+    pub match_type: MatchType,
+
+    /// Confidence for this tier
+    pub confidence: Confidence,
+
+    /// Composite score for this tier
+    pub composite: f64,
+
+    /// True if every query contig in this tier has the same sequence as the
+    /// reference, allowing for renaming and reordering. For the primary tier,
+    /// every reference contig must also be present in the query.
+    pub identical: bool,
+
+    /// Number of query contigs in this tier
+    pub query_contigs: usize,
+
+    /// Number of reference contigs in this tier
+    pub reference_contigs: usize,
+
+    /// Number of reference contigs in this tier that no query contig matches
+    pub missing_contigs: usize,
+
+    /// Score of the query's contigs in this tier against the reference's
+    #[serde(skip)]
+    pub score: MatchScore,
+
+    /// Diagnosis of the query's contigs in this tier against the reference's
+    #[serde(skip)]
+    pub diagnosis: MatchDiagnosis,
+}
+
+/// Primary-assembly and auxiliary verdicts for a query against a reference
+#[derive(Debug, Clone, Serialize)]
+pub struct TieredVerdict {
+    /// Verdict for the primary assembly, `None` if the query has no primary contigs
+    pub primary: Option<TierVerdict>,
+
+    /// Verdict for auxiliary contigs, `None` if the query has no auxiliary contigs
+    pub auxiliary: Option<TierVerdict>,
+}
+
+impl TieredVerdict {
+    /// Split the query and reference into tiers and analyze each separately.
+    ///
+    /// Reference contigs are assigned a tier by their sequence role (or name, when
+    /// the role is unknown). Query contigs take the tier of the reference contig
+    /// they match by digest or name+length, falling back to their own name.
+    ///
+    /// `classes` is the classification of every query contig against the whole
+    /// reference from [`classify_contigs`](crate::matching::scoring::classify_contigs),
+    /// so the tiers reuse the work of the full-header score.
+    #[must_use]
+    pub fn analyze(
+        query: &QueryHeader,
+        reference: &KnownReference,
+        classes: &[ContigMatchType],
+        weights: &ScoringWeights,
+    ) -> Self {
+        let (ref_primary, ref_auxiliary): (Vec<Contig>, Vec<Contig>) = reference
+            .contigs
+            .iter()
+            .cloned()
+            .partition(Contig::is_primary_assembly);
+
+        let lookup = ReferenceLookup::new(reference);
+        let mut matched = HashSet::new();
+        let mut primary = TierContigs::default();
+        let mut auxiliary = TierContigs::default();
+        for (contig, class) in query.contigs.iter().zip(classes) {
+            let found = lookup.find(contig);
+            if let Some(reference_contig) = found {
+                matched.insert(reference_contig.name.as_str());
+            }
+            let tier = if found
+                .map_or_else(|| contig.is_primary_assembly(), Contig::is_primary_assembly)
+            {
+                &mut primary
+            } else {
+                &mut auxiliary
+            };
+            tier.contigs.push(contig.clone());
+            tier.classes.push(*class);
+        }
+
+        Self {
+            primary: analyze_tier(
+                AssemblyTier::Primary,
+                primary,
+                ref_primary,
+                &matched,
+                reference,
+                weights,
+            ),
+            auxiliary: analyze_tier(
+                AssemblyTier::Auxiliary,
+                auxiliary,
+                ref_auxiliary,
+                &matched,
+                reference,
+                weights,
+            ),
+        }
+    }
+
+    /// One-line summary, e.g. "primary identical, auxiliary differs"
+    #[must_use]
+    pub fn summary(&self) -> String {
+        [&self.primary, &self.auxiliary]
+            .into_iter()
+            .flatten()
+            .map(|verdict| {
+                let state = if verdict.identical {
+                    "identical"
+                } else {
+                    "differs"
+                };
+                format!("{} {state}", verdict.tier)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Query contigs assigned to one tier, with their classification against the
+/// whole reference
+#[derive(Default)]
+struct TierContigs {
+    contigs: Vec<Contig>,
+    classes: Vec<ContigMatchType>,
+}
+
+fn analyze_tier(
+    tier: AssemblyTier,
+    query_contigs: TierContigs,
+    reference_contigs: Vec<Contig>,
+    matched: &HashSet<&str>,
+    reference: &KnownReference,
+    weights: &ScoringWeights,
+) -> Option<TierVerdict> {
+    if query_contigs.contigs.is_empty() {
+        return None;
+    }
+
+    let missing_contigs = reference_contigs
+        .iter()
+        .filter(|contig| !matched.contains(contig.name.as_str()))
+        .count();
+    let query = QueryHeader::new(query_contigs.contigs);
+    let tier_reference = KnownReference::new(
+        reference.id.0.clone(),
+        reference.display_name.clone(),
+        reference.assembly.clone(),
+        reference.source.clone(),
+    )
+    .with_contigs(reference_contigs);
+
+    let score =
+        MatchScore::calculate_classified(&query, &tier_reference, &query_contigs.classes, weights);
+    let diagnosis = MatchDiagnosis::analyze(&query, &tier_reference);
+
+    // Identical sequences, allowing for renaming and reordering; the primary
+    // assembly must also be complete
+    let complete = tier == AssemblyTier::Auxiliary || missing_contigs == 0;
+    let identical = complete
+        && matches!(
+            diagnosis.match_type,
+            MatchType::Exact
+                | MatchType::Reordered
+                | MatchType::Renamed
+                | MatchType::ReorderedAndRenamed
+        )
+        && diagnosis.conflicts.is_empty()
+        && diagnosis.query_only.is_empty();
+
+    Some(TierVerdict {
+        tier,
+        match_type: diagnosis.match_type.clone(),
+        confidence: score.confidence,
+        composite: score.composite,
+        identical,
+        query_contigs: query.contigs.len(),
+        reference_contigs: tier_reference.contigs.len(),
+        missing_contigs,
+        score,
+        diagnosis,
+    })
+}
+
+/// Finds the reference contig a query contig corresponds to
+struct ReferenceLookup<'a> {
+    md5s: HashMap<String, &'a Contig>,
+    sha512t24us: HashMap<&'a str, &'a Contig>,
+    names: HashMap<(&'a str, u64), &'a Contig>,
+}
+
+impl<'a> ReferenceLookup<'a> {
+    fn new(reference: &'a KnownReference) -> Self {
+        let mut lookup = Self {
+            md5s: HashMap::new(),
+            sha512t24us: HashMap::new(),
+            names: HashMap::new(),
+        };
+        for contig in &reference.contigs {
+            if let Some(md5) = &contig.md5 {
+                lookup.md5s.insert(md5.to_lowercase(), contig);
+            }
+            if let Some(digest) = &contig.sha512t24u {
+                lookup.sha512t24us.insert(digest, contig);
+            }
+            for name in std::iter::once(&contig.name).chain(&contig.aliases) {
+                lookup.names.insert((name.as_str(), contig.length), contig);
+            }
+        }
+        lookup
+    }
+
+    fn find(&self, contig: &Contig) -> Option<&'a Contig> {
+        contig
+            .md5
+            .as_ref()
+            .and_then(|md5| self.md5s.get(&md5.to_lowercase()))
+            .or_else(|| {
+                contig
+                    .sha512t24u
+                    .as_deref()
+                    .and_then(|digest| self.sha512t24us.get(digest))
+            })
+            .or_else(|| {
+                std::iter::once(&contig.name)
+                    .chain(&contig.aliases)
+                    .find_map(|name| self.names.get(&(name.as_str(), contig.length)))
+            })
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::contig::SequenceRole;
+    use crate::core::types::{Assembly, ReferenceSource};
+    use crate::matching::scoring::classify_contigs;
+
+    fn analyze(query: &QueryHeader, reference: &KnownReference) -> TieredVerdict {
+        let classes = classify_contigs(query, reference);
+        TieredVerdict::analyze(query, reference, &classes, &ScoringWeights::default())
+    }
+
+    fn make_reference() -> KnownReference {
+        let mut chr1 = Contig::new("chr1", 248_956_422).with_md5("chr1_md5");
+        chr1.sequence_role = SequenceRole::AssembledMolecule;
+        let mut chr_m = Contig::new("chrM", 16569).with_md5("chrm_md5");
+        chr_m.sequence_role = SequenceRole::AssembledMolecule;
+        let mut alt = Contig::new("chr1_KI270762v1_alt", 354_444).with_md5("alt_md5");
+        alt.sequence_role = SequenceRole::AltScaffold;
+        let decoy = Contig::new("chrUn_JTFH01000001v1_decoy", 25139).with_md5("decoy_md5");
+
+        KnownReference::new(
+            "test_ref",
+            "Test Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![chr1, chr_m, alt, decoy])
+    }
+
+    #[test]
+    fn test_primary_identical_auxiliary_differs() {
+        let reference = make_reference();
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 248_956_422).with_md5("chr1_md5"),
+            Contig::new("chrM", 16569).with_md5("chrm_md5"),
+            Contig::new("chr1_KI270762v1_alt", 354_444).with_md5("alt_md5"),
+            Contig::new("chrUn_JTFH01000001v1_decoy", 25139).with_md5("other_decoy_md5"),
+        ]);
+
+        let tiers = analyze(&query, &reference);
+
+        let primary = tiers.primary.as_ref().unwrap();
+        assert_eq!(primary.query_contigs, 2);
+        assert_eq!(primary.reference_contigs, 2);
+        assert_eq!(primary.match_type, MatchType::Exact);
+        assert_eq!(primary.confidence, Confidence::Exact);
+        assert!(primary.identical);
+
+        let auxiliary = tiers.auxiliary.as_ref().unwrap();
+        assert_eq!(auxiliary.query_contigs, 2);
+        assert_eq!(auxiliary.score.md5_conflicts, 1);
+        assert!(!auxiliary.identical);
+
+        assert_eq!(tiers.summary(), "primary identical, auxiliary differs");
+    }
+
+    #[test]
+    fn test_query_tier_follows_matched_reference_contig() {
+        let reference = make_reference();
+        // Renamed primary contig is placed in the primary tier via its digest
+        let query = QueryHeader::new(vec![
+            Contig::new("NC_000001.11", 248_956_422).with_md5("chr1_md5"),
+            Contig::new("chrM", 16569).with_md5("chrm_md5"),
+        ]);
+
+        let tiers = analyze(&query, &reference);
+
+        assert_eq!(tiers.primary.as_ref().unwrap().query_contigs, 2);
+        assert!(tiers.primary.as_ref().unwrap().identical);
+        assert!(tiers.auxiliary.is_none());
+        assert_eq!(tiers.summary(), "primary identical");
+    }
+
+    #[test]
+    fn test_primary_missing_contig_is_not_identical() {
+        let reference = make_reference();
+        // No chrM, everything else the same
+        let query = QueryHeader::new(vec![
+            Contig::new("chr1", 248_956_422).with_md5("chr1_md5"),
+            Contig::new("chr1_KI270762v1_alt", 354_444).with_md5("alt_md5"),
+        ]);
+
+        let tiers = analyze(&query, &reference);
+
+        let primary = tiers.primary.as_ref().unwrap();
+        assert_eq!(primary.query_contigs, 1);
+        assert_eq!(primary.missing_contigs, 1);
+        assert!(!primary.identical);
+
+        // A subset of the auxiliary contigs is still identical
+        let auxiliary = tiers.auxiliary.as_ref().unwrap();
+        assert_eq!(auxiliary.missing_contigs, 1);
+        assert!(auxiliary.identical);
+        assert_eq!(tiers.summary(), "primary differs, auxiliary identical");
+    }
+}
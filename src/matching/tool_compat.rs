@@ -0,0 +1,588 @@
+//! Predicted verdicts of common tools' sequence dictionary checks.
+//!
+//! Scores say how similar two dictionaries are; users need to know whether
+//! their next command will run. Each tool validates dictionaries its own way,
+//! and [`predict`] reproduces those rules for a query against a reference
+//! (a catalog reference or another file):
+//!
+//! - **GATK**: `SequenceDictionaryUtils` classifies the pair (`IDENTICAL`,
+//!   `SUPERSET`, `OUT_OF_ORDER`, ...) by contig name and length, with contig
+//!   ordering checked; MD5s are not compared
+//! - **Picard**: htsjdk's strict equality check; both dictionaries must list
+//!   the same contigs with the same lengths in the same order, and MD5s must
+//!   agree where both have them
+//! - **bcftools/htslib**: contigs are looked up by name only; every query
+//!   contig must exist in the reference, whatever its length
+//! - **DeepVariant**: contigs with the same name and length in both are
+//!   common; they must span at least 90% of the reference's bases
+//!
+//! Contigs are matched by their exact names, as the tools do; aliases do not
+//! count.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::core::contig::Contig;
+
+/// Fraction of the reference's bases that DeepVariant requires in common contigs
+pub const DEEPVARIANT_MIN_COMMON_FRACTION: f64 = 0.9;
+
+/// hg18 and hg19 lengths of chr1, chr2 and chr10, which GATK uses to
+/// recognize human dictionaries when checking their order
+const HUMAN_CHR1_LENGTHS: [u64; 2] = [247_249_719, 249_250_621];
+const HUMAN_CHR2_LENGTHS: [u64; 2] = [242_951_149, 243_199_373];
+const HUMAN_CHR10_LENGTHS: [u64; 2] = [135_374_737, 135_534_747];
+
+/// Number of contig names listed in a message before the rest are counted
+const MAX_LISTED_NAMES: usize = 5;
+
+/// Tools whose dictionary checks can be predicted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Tool {
+    Gatk,
+    Picard,
+    Bcftools,
+    #[value(name = "deepvariant")]
+    #[serde(rename = "deepvariant")]
+    DeepVariant,
+}
+
+impl Tool {
+    /// All tools, in report order
+    pub const ALL: [Tool; 4] = [Tool::Gatk, Tool::Picard, Tool::Bcftools, Tool::DeepVariant];
+}
+
+impl std::fmt::Display for Tool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gatk => write!(f, "GATK"),
+            Self::Picard => write!(f, "Picard"),
+            Self::Bcftools => write!(f, "bcftools/htslib"),
+            Self::DeepVariant => write!(f, "DeepVariant"),
+        }
+    }
+}
+
+/// GATK's `SequenceDictionaryCompatibility` classes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GatkCompatibility {
+    /// Same contigs with the same lengths in the same order
+    Identical,
+    /// The reference has every contig of the query, in the same order
+    Superset,
+    /// The contigs in common have the same lengths and order, but the
+    /// reference lacks some of the query's
+    CommonSubset,
+    /// No contig names in common
+    NoCommonContigs,
+    /// A contig in common has different lengths
+    UnequalCommonContigs,
+    /// hg18 or hg19 chr1, chr2 and chr10 are sorted lexicographically
+    /// (chr1, chr10, chr2)
+    NonCanonicalHumanOrder,
+    /// The contigs in common are in a different relative order
+    OutOfOrder,
+    /// The contigs in common are at different positions in the two dictionaries
+    DifferentIndices,
+}
+
+impl GatkCompatibility {
+    /// Whether GATK tools accept the pair
+    #[must_use]
+    pub fn is_compatible(self) -> bool {
+        matches!(self, Self::Identical | Self::Superset | Self::CommonSubset)
+    }
+}
+
+impl std::fmt::Display for GatkCompatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Identical => write!(f, "IDENTICAL"),
+            Self::Superset => write!(f, "SUPERSET"),
+            Self::CommonSubset => write!(f, "COMMON_SUBSET"),
+            Self::NoCommonContigs => write!(f, "NO_COMMON_CONTIGS"),
+            Self::UnequalCommonContigs => write!(f, "UNEQUAL_COMMON_CONTIGS"),
+            Self::NonCanonicalHumanOrder => write!(f, "NON_CANONICAL_HUMAN_ORDER"),
+            Self::OutOfOrder => write!(f, "OUT_OF_ORDER"),
+            Self::DifferentIndices => write!(f, "DIFFERENT_INDICES"),
+        }
+    }
+}
+
+/// Whether a tool is expected to accept a query with a reference
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolVerdict {
+    /// The tool
+    pub tool: Tool,
+
+    /// Whether the tool is expected to run without a dictionary error
+    pub works: bool,
+
+    /// The tool's classification of the pair, e.g. GATK's `SUPERSET`
+    pub check: String,
+
+    /// Why the tool refuses the pair, or differences it will not notice
+    pub messages: Vec<String>,
+}
+
+/// Predict every tool's verdict for `query` used with `reference`
+#[must_use]
+pub fn predict(query: &[Contig], reference: &[Contig]) -> Vec<ToolVerdict> {
+    Tool::ALL
+        .iter()
+        .map(|&tool| predict_tool(tool, query, reference))
+        .collect()
+}
+
+/// Predict one tool's verdict for `query` used with `reference`
+#[must_use]
+pub fn predict_tool(tool: Tool, query: &[Contig], reference: &[Contig]) -> ToolVerdict {
+    match tool {
+        Tool::Gatk => gatk(query, reference),
+        Tool::Picard => picard(query, reference),
+        Tool::Bcftools => bcftools(query, reference),
+        Tool::DeepVariant => deepvariant(query, reference),
+    }
+}
+
+/// Contigs by exact name, with their index
+fn by_name(contigs: &[Contig]) -> HashMap<&str, (usize, &Contig)> {
+    let mut names = HashMap::new();
+    for (index, contig) in contigs.iter().enumerate() {
+        names.entry(contig.name.as_str()).or_insert((index, contig));
+    }
+    names
+}
+
+/// Classify a pair as GATK's `SequenceDictionaryUtils.compareDictionaries`
+/// does with contig ordering checked
+#[must_use]
+pub fn gatk_compatibility(query: &[Contig], reference: &[Contig]) -> GatkCompatibility {
+    if non_canonical_human_order(reference) || non_canonical_human_order(query) {
+        return GatkCompatibility::NonCanonicalHumanOrder;
+    }
+
+    let reference_names = by_name(reference);
+    let common: Vec<(usize, usize)> = query
+        .iter()
+        .enumerate()
+        .filter_map(|(query_index, contig)| {
+            reference_names
+                .get(contig.name.as_str())
+                .map(|&(reference_index, _)| (query_index, reference_index))
+        })
+        .collect();
+
+    if common.is_empty() {
+        return GatkCompatibility::NoCommonContigs;
+    }
+    if common
+        .iter()
+        .any(|&(q, r)| query[q].length != reference[r].length)
+    {
+        return GatkCompatibility::UnequalCommonContigs;
+    }
+    if common.windows(2).any(|pair| pair[1].1 < pair[0].1) {
+        return GatkCompatibility::OutOfOrder;
+    }
+    if common.len() == query.len() && common.len() == reference.len() {
+        return GatkCompatibility::Identical;
+    }
+    if common.iter().any(|&(q, r)| q != r) {
+        return GatkCompatibility::DifferentIndices;
+    }
+    if common.len() == query.len() {
+        GatkCompatibility::Superset
+    } else {
+        GatkCompatibility::CommonSubset
+    }
+}
+
+/// True if hg18 or hg19 chr1, chr2 and chr10 (recognized by length, as GATK
+/// does) are all present but not in that order, as in a lexicographically
+/// sorted dictionary
+fn non_canonical_human_order(contigs: &[Contig]) -> bool {
+    let position = |lengths: [u64; 2]| contigs.iter().position(|c| lengths.contains(&c.length));
+    match (
+        position(HUMAN_CHR1_LENGTHS),
+        position(HUMAN_CHR2_LENGTHS),
+        position(HUMAN_CHR10_LENGTHS),
+    ) {
+        (Some(chr1), Some(chr2), Some(chr10)) => !(chr1 < chr2 && chr2 < chr10),
+        _ => false,
+    }
+}
+
+fn gatk(query: &[Contig], reference: &[Contig]) -> ToolVerdict {
+    let compatibility = gatk_compatibility(query, reference);
+    let reference_names = by_name(reference);
+    let mut messages = Vec::new();
+    match compatibility {
+        GatkCompatibility::Identical | GatkCompatibility::Superset => {}
+        GatkCompatibility::CommonSubset => {
+            let missing: Vec<&str> = query
+                .iter()
+                .filter(|c| !reference_names.contains_key(c.name.as_str()))
+                .map(|c| c.name.as_str())
+                .collect();
+            messages.extend(list_names("contigs not in the reference", &missing));
+            messages.push(
+                "Tools that require the reference to cover every contig (e.g. for CRAM) fail"
+                    .to_string(),
+            );
+        }
+        GatkCompatibility::NoCommonContigs => {
+            messages.push("No contig names in common".to_string());
+        }
+        GatkCompatibility::UnequalCommonContigs => {
+            for contig in query {
+                if let Some((_, other)) = reference_names.get(contig.name.as_str()) {
+                    if other.length != contig.length {
+                        messages.push(format!(
+                            "{} has length {} but {} in the reference",
+                            contig.name, contig.length, other.length
+                        ));
+                    }
+                }
+            }
+        }
+        GatkCompatibility::NonCanonicalHumanOrder => messages.push(
+            "Human chr1, chr2 and chr10 are not in karyotypic order in one of the dictionaries"
+                .to_string(),
+        ),
+        GatkCompatibility::OutOfOrder => {
+            messages.push("Contigs in common are in a different relative order".to_string())
+        }
+        GatkCompatibility::DifferentIndices => messages.push(
+            "Contigs in common are at different absolute positions in the two dictionaries"
+                .to_string(),
+        ),
+    }
+    if compatibility.is_compatible() {
+        messages.extend(unchecked_md5s(query, reference));
+    }
+    ToolVerdict {
+        tool: Tool::Gatk,
+        works: compatibility.is_compatible(),
+        check: compatibility.to_string(),
+        messages,
+    }
+}
+
+fn picard(query: &[Contig], reference: &[Contig]) -> ToolVerdict {
+    let verdict = |check: &str, message: Option<String>| ToolVerdict {
+        tool: Tool::Picard,
+        works: message.is_none(),
+        check: check.to_string(),
+        messages: message.into_iter().collect(),
+    };
+
+    if query.len() != reference.len() {
+        return verdict(
+            "DIFFERENT_SIZE",
+            Some(format!(
+                "Sequence dictionaries are not the same size ({} vs {} contigs)",
+                query.len(),
+                reference.len()
+            )),
+        );
+    }
+    for (index, (ours, theirs)) in query.iter().zip(reference).enumerate() {
+        if ours.name != theirs.name || ours.length != theirs.length {
+            return verdict(
+                "MISMATCH_AT_INDEX",
+                Some(format!(
+                    "Sequences at index {index} don't match: {}/{} vs {}/{}",
+                    ours.name, ours.length, theirs.name, theirs.length
+                )),
+            );
+        }
+        if let (Some(a), Some(b)) = (&ours.md5, &theirs.md5) {
+            if !a.eq_ignore_ascii_case(b) {
+                return verdict(
+                    "MD5_MISMATCH",
+                    Some(format!(
+                        "{} has MD5 {a} but {b} in the reference",
+                        ours.name
+                    )),
+                );
+            }
+        }
+    }
+    verdict("EQUAL", None)
+}
+
+fn bcftools(query: &[Contig], reference: &[Contig]) -> ToolVerdict {
+    let reference_names = by_name(reference);
+    let undefined: Vec<&str> = query
+        .iter()
+        .filter(|c| !reference_names.contains_key(c.name.as_str()))
+        .map(|c| c.name.as_str())
+        .collect();
+
+    let mut messages: Vec<String> = list_names("contigs not defined in the reference", &undefined)
+        .into_iter()
+        .collect();
+    let works = undefined.is_empty();
+    if works {
+        let other_length: Vec<&str> = query
+            .iter()
+            .filter(|c| {
+                reference_names
+                    .get(c.name.as_str())
+                    .is_some_and(|(_, other)| other.length != c.length)
+            })
+            .map(|c| c.name.as_str())
+            .collect();
+        messages.extend(list_names(
+            "contigs with another length in the reference, which is not checked",
+            &other_length,
+        ));
+        messages.extend(unchecked_md5s(query, reference));
+    }
+    ToolVerdict {
+        tool: Tool::Bcftools,
+        works,
+        check: if works {
+            "NAMES_DEFINED"
+        } else {
+            "UNDEFINED_CONTIGS"
+        }
+        .to_string(),
+        messages,
+    }
+}
+
+fn deepvariant(query: &[Contig], reference: &[Contig]) -> ToolVerdict {
+    let query_names = by_name(query);
+    let reference_bases: u64 = reference.iter().map(|c| c.length).sum();
+    let common_bases: u64 = reference
+        .iter()
+        .filter(|c| {
+            query_names
+                .get(c.name.as_str())
+                .is_some_and(|(_, other)| other.length == c.length)
+        })
+        .map(|c| c.length)
+        .sum();
+
+    #[allow(clippy::cast_precision_loss)] // Genome sizes are far below 2^52
+    let fraction = if reference_bases == 0 {
+        0.0
+    } else {
+        common_bases as f64 / reference_bases as f64
+    };
+    let works = fraction >= DEEPVARIANT_MIN_COMMON_FRACTION;
+    let mut messages = Vec::new();
+    if works {
+        messages.extend(unchecked_md5s(query, reference));
+    } else {
+        messages.push(format!(
+            "Reference contigs span {reference_bases} bases but only {common_bases} ({:.2}%) are in contigs common to both; at least {:.0}% are required",
+            fraction * 100.0,
+            DEEPVARIANT_MIN_COMMON_FRACTION * 100.0
+        ));
+    }
+    ToolVerdict {
+        tool: Tool::DeepVariant,
+        works,
+        check: if works {
+            "SUFFICIENT_COMMON_CONTIGS"
+        } else {
+            "INSUFFICIENT_COMMON_CONTIGS"
+        }
+        .to_string(),
+        messages,
+    }
+}
+
+/// A note for contigs with the same name but different MD5s, which only
+/// Picard checks
+fn unchecked_md5s(query: &[Contig], reference: &[Contig]) -> Option<String> {
+    let reference_names = by_name(reference);
+    let differing: Vec<&str> = query
+        .iter()
+        .filter(|c| {
+            reference_names
+                .get(c.name.as_str())
+                .is_some_and(|(_, other)| c.digests_match(other) == Some(false))
+        })
+        .map(|c| c.name.as_str())
+        .collect();
+    list_names(
+        "contigs with another sequence in the reference, which is not checked",
+        &differing,
+    )
+}
+
+/// "N <what>: a, b, ...", or nothing for no names
+fn list_names(what: &str, names: &[&str]) -> Option<String> {
+    if names.is_empty() {
+        return None;
+    }
+    let mut listed = names
+        .iter()
+        .take(MAX_LISTED_NAMES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if names.len() > MAX_LISTED_NAMES {
+        listed.push_str(&format!(" and {} more", names.len() - MAX_LISTED_NAMES));
+    }
+    Some(format!("{} {what}: {listed}", names.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contigs(spec: &[(&str, u64)]) -> Vec<Contig> {
+        spec.iter()
+            .map(|(name, length)| Contig::new(*name, *length))
+            .collect()
+    }
+
+    fn reference() -> Vec<Contig> {
+        contigs(&[("chr1", 1000), ("chr2", 900), ("chr10", 500), ("chrM", 100)])
+    }
+
+    #[test]
+    fn test_gatk_compatibility() {
+        let reference = reference();
+        assert_eq!(
+            gatk_compatibility(&reference, &reference),
+            GatkCompatibility::Identical
+        );
+        assert_eq!(
+            gatk_compatibility(&contigs(&[("chr1", 1000), ("chr2", 900)]), &reference),
+            GatkCompatibility::Superset
+        );
+        assert_eq!(
+            gatk_compatibility(
+                &contigs(&[
+                    ("chr1", 1000),
+                    ("chr2", 900),
+                    ("chr10", 500),
+                    ("chrM", 100),
+                    ("chrUn", 5)
+                ]),
+                &reference
+            ),
+            GatkCompatibility::CommonSubset
+        );
+        assert_eq!(
+            gatk_compatibility(&contigs(&[("chr2", 900), ("chr10", 500)]), &reference),
+            GatkCompatibility::DifferentIndices
+        );
+        assert_eq!(
+            gatk_compatibility(&contigs(&[("chr2", 900), ("chr1", 1000)]), &reference),
+            GatkCompatibility::OutOfOrder
+        );
+        assert_eq!(
+            gatk_compatibility(&contigs(&[("chr1", 999)]), &reference),
+            GatkCompatibility::UnequalCommonContigs
+        );
+        assert_eq!(
+            gatk_compatibility(&contigs(&[("1", 1000), ("2", 900)]), &reference),
+            GatkCompatibility::NoCommonContigs
+        );
+    }
+
+    #[test]
+    fn test_gatk_non_canonical_human_order() {
+        // hg19 sorted lexicographically, as in UCSC's FASTA
+        let hg19 = contigs(&[
+            ("chr1", 249_250_621),
+            ("chr10", 135_534_747),
+            ("chr2", 243_199_373),
+        ]);
+        assert_eq!(
+            gatk_compatibility(&hg19, &hg19),
+            GatkCompatibility::NonCanonicalHumanOrder
+        );
+
+        // Only hg18 and hg19 lengths are recognized
+        let other = contigs(&[("chr1", 1000), ("chr10", 500), ("chr2", 900)]);
+        assert_eq!(
+            gatk_compatibility(&other, &other),
+            GatkCompatibility::Identical
+        );
+    }
+
+    #[test]
+    fn test_picard_is_strict() {
+        let reference = reference();
+        assert!(predict_tool(Tool::Picard, &reference, &reference).works);
+
+        let subset = contigs(&[("chr1", 1000), ("chr2", 900)]);
+        let verdict = predict_tool(Tool::Picard, &subset, &reference);
+        assert!(!verdict.works);
+        assert_eq!(verdict.check, "DIFFERENT_SIZE");
+
+        let mut other_mito = reference.clone();
+        other_mito[3] = Contig::new("chrM", 100).with_md5("a".repeat(32));
+        let mut with_md5 = reference.clone();
+        with_md5[3] = Contig::new("chrM", 100).with_md5("b".repeat(32));
+        let verdict = predict_tool(Tool::Picard, &other_mito, &with_md5);
+        assert_eq!(verdict.check, "MD5_MISMATCH");
+        // GATK does not compare MD5s, but the difference is noted
+        let verdict = predict_tool(Tool::Gatk, &other_mito, &with_md5);
+        assert!(verdict.works);
+        assert_eq!(verdict.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_bcftools_checks_names_only() {
+        let reference = reference();
+        let verdict = predict_tool(
+            Tool::Bcftools,
+            &contigs(&[("chr2", 900), ("chr1", 5)]),
+            &reference,
+        );
+        assert!(verdict.works);
+        assert_eq!(verdict.messages.len(), 1);
+
+        let verdict = predict_tool(
+            Tool::Bcftools,
+            &contigs(&[("chr1", 1000), ("chrUn", 5)]),
+            &reference,
+        );
+        assert!(!verdict.works);
+        assert_eq!(verdict.check, "UNDEFINED_CONTIGS");
+    }
+
+    #[test]
+    fn test_deepvariant_common_fraction() {
+        let reference = reference();
+        // chr1 + chr2 + chr10 = 2400 of 2500 bases
+        let verdict = predict_tool(
+            Tool::DeepVariant,
+            &contigs(&[("chr1", 1000), ("chr2", 900), ("chr10", 500)]),
+            &reference,
+        );
+        assert!(verdict.works);
+
+        // chr1 alone = 1000 of 2500 bases
+        let verdict = predict_tool(Tool::DeepVariant, &contigs(&[("chr1", 1000)]), &reference);
+        assert!(!verdict.works);
+
+        // Renamed contigs are not in common
+        let verdict = predict_tool(
+            Tool::DeepVariant,
+            &contigs(&[("1", 1000), ("2", 900), ("10", 500), ("MT", 100)]),
+            &reference,
+        );
+        assert!(!verdict.works);
+    }
+
+    #[test]
+    fn test_predict_all_tools() {
+        let reference = reference();
+        let verdicts = predict(&reference, &reference);
+        assert_eq!(verdicts.len(), Tool::ALL.len());
+        assert!(verdicts.iter().all(|v| v.works));
+    }
+}
@@ -0,0 +1,371 @@
+//! Contig-by-contig verification of a hashed FASTA against a catalog reference.
+//!
+//! Where matching asks "which reference is this?", verification asks "is this
+//! FASTA exactly the reference the catalog describes?". Every contig must be
+//! present under the same name, with the same length and digest, in the same
+//! order, and the FASTA must hold nothing else. [`FastaVerification`] reports
+//! each contig so that any difference can be traced.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::reference::KnownReference;
+
+/// Outcome of checking one contig
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContigStatus {
+    /// Same name, length and digest as the reference
+    Identical,
+    /// Same sequence as the reference, under another name
+    Renamed,
+    /// Same name but a different length
+    LengthMismatch,
+    /// Same name and length but a different sequence digest
+    DigestMismatch,
+    /// The reference has no digest for this contig, so its sequence cannot be checked
+    Unverifiable,
+    /// In the reference but not in the FASTA
+    Missing,
+    /// In the reference but known to be absent from its FASTA (e.g. the
+    /// mitochondrion in T2T-CHM13)
+    ExpectedAbsent,
+    /// In the FASTA but not in the reference
+    Extra,
+}
+
+impl ContigStatus {
+    /// Whether this contig is consistent with the FASTA being the reference
+    #[must_use]
+    pub fn is_ok(self) -> bool {
+        matches!(self, Self::Identical | Self::ExpectedAbsent)
+    }
+
+    /// Machine-readable name, as serialized to JSON
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Identical => "identical",
+            Self::Renamed => "renamed",
+            Self::LengthMismatch => "length_mismatch",
+            Self::DigestMismatch => "digest_mismatch",
+            Self::Unverifiable => "unverifiable",
+            Self::Missing => "missing",
+            Self::ExpectedAbsent => "expected_absent",
+            Self::Extra => "extra",
+        }
+    }
+}
+
+impl std::fmt::Display for ContigStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Identical => write!(f, "identical"),
+            Self::Renamed => write!(f, "renamed"),
+            Self::LengthMismatch => write!(f, "length mismatch"),
+            Self::DigestMismatch => write!(f, "digest mismatch"),
+            Self::Unverifiable => write!(f, "unverifiable"),
+            Self::Missing => write!(f, "missing"),
+            Self::ExpectedAbsent => write!(f, "expected absent"),
+            Self::Extra => write!(f, "extra"),
+        }
+    }
+}
+
+/// Result of checking one contig of the FASTA or the reference
+#[derive(Debug, Clone, Serialize)]
+pub struct ContigCheck {
+    /// Outcome of the check
+    pub status: ContigStatus,
+
+    /// Name in the FASTA, `None` if the contig is missing from it
+    pub fasta_name: Option<String>,
+
+    /// Name in the reference, `None` for a contig the reference does not have
+    pub reference_name: Option<String>,
+
+    /// Length in the FASTA
+    pub fasta_length: Option<u64>,
+
+    /// Length in the reference
+    pub reference_length: Option<u64>,
+
+    /// MD5 of the FASTA sequence
+    pub fasta_md5: Option<String>,
+
+    /// MD5 the reference expects
+    pub reference_md5: Option<String>,
+}
+
+/// Verification of a FASTA against a reference
+#[derive(Debug, Clone, Serialize)]
+pub struct FastaVerification {
+    /// Per-contig checks: FASTA contigs in file order, then missing reference contigs
+    pub contigs: Vec<ContigCheck>,
+
+    /// True if the contigs found in both appear in the reference's order
+    pub order_matches: bool,
+
+    /// First FASTA contig that appears earlier in the reference than its predecessor
+    pub first_out_of_order: Option<String>,
+
+    /// True if the FASTA is identical to the reference
+    pub passed: bool,
+}
+
+impl FastaVerification {
+    /// Check the contigs of a hashed FASTA against a reference.
+    ///
+    /// FASTA contigs are paired with reference contigs by name or alias, then
+    /// by MD5, so a renamed contig is reported as such rather than as one
+    /// missing and one extra contig.
+    #[must_use]
+    pub fn verify(fasta: &QueryHeader, reference: &KnownReference) -> Self {
+        let mut by_name: HashMap<&str, usize> = HashMap::new();
+        let mut by_md5: HashMap<String, usize> = HashMap::new();
+        for (i, contig) in reference.contigs.iter().enumerate() {
+            for name in std::iter::once(&contig.name).chain(&contig.aliases) {
+                by_name.entry(name.as_str()).or_insert(i);
+            }
+            if let Some(md5) = &contig.md5 {
+                by_md5.entry(md5.to_lowercase()).or_insert(i);
+            }
+        }
+
+        let mut paired = vec![false; reference.contigs.len()];
+        let mut contigs = Vec::new();
+        let mut order = Vec::new();
+        for contig in &fasta.contigs {
+            let md5 = contig.md5.as_ref().map(|md5| md5.to_lowercase());
+            let unpaired = |&&i: &&usize| !paired[i];
+            let index = by_name
+                .get(contig.name.as_str())
+                .filter(unpaired)
+                .or_else(|| {
+                    md5.as_ref()
+                        .and_then(|md5| by_md5.get(md5))
+                        .filter(unpaired)
+                })
+                .copied();
+
+            let Some(index) = index else {
+                contigs.push(ContigCheck::new(ContigStatus::Extra, Some(contig), None));
+                continue;
+            };
+            paired[index] = true;
+            order.push((index, &contig.name));
+
+            let expected = &reference.contigs[index];
+            contigs.push(ContigCheck::new(
+                compare(contig, expected),
+                Some(contig),
+                Some(expected),
+            ));
+        }
+
+        for (contig, _) in reference
+            .contigs
+            .iter()
+            .zip(&paired)
+            .filter(|(_, &paired)| !paired)
+        {
+            let status = if reference.contigs_missing_from_fasta.contains(&contig.name) {
+                ContigStatus::ExpectedAbsent
+            } else {
+                ContigStatus::Missing
+            };
+            contigs.push(ContigCheck::new(status, None, Some(contig)));
+        }
+
+        let first_out_of_order = order
+            .windows(2)
+            .find(|pair| pair[1].0 < pair[0].0)
+            .map(|pair| pair[1].1.clone());
+        let order_matches = first_out_of_order.is_none();
+        let passed = order_matches && contigs.iter().all(|check| check.status.is_ok());
+
+        Self {
+            contigs,
+            order_matches,
+            first_out_of_order,
+            passed,
+        }
+    }
+
+    /// Number of contigs with the given status
+    #[must_use]
+    pub fn count(&self, status: ContigStatus) -> usize {
+        self.contigs
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+
+    /// Checks that failed, in report order
+    pub fn problems(&self) -> impl Iterator<Item = &ContigCheck> {
+        self.contigs.iter().filter(|check| !check.status.is_ok())
+    }
+}
+
+impl ContigCheck {
+    fn new(status: ContigStatus, fasta: Option<&Contig>, reference: Option<&Contig>) -> Self {
+        Self {
+            status,
+            fasta_name: fasta.map(|c| c.name.clone()),
+            reference_name: reference.map(|c| c.name.clone()),
+            fasta_length: fasta.map(|c| c.length),
+            reference_length: reference.map(|c| c.length),
+            fasta_md5: fasta.and_then(|c| c.md5.clone()),
+            reference_md5: reference.and_then(|c| c.md5.clone()),
+        }
+    }
+}
+
+/// Compare a FASTA contig with the reference contig it was paired with
+fn compare(contig: &Contig, expected: &Contig) -> ContigStatus {
+    if contig.length != expected.length {
+        return ContigStatus::LengthMismatch;
+    }
+
+    let md5 = match (&contig.md5, &expected.md5) {
+        (Some(actual), Some(expected)) => Some(actual.eq_ignore_ascii_case(expected)),
+        _ => None,
+    };
+    let sha512t24u = match (&contig.sha512t24u, &expected.sha512t24u) {
+        (Some(actual), Some(expected)) => Some(actual == expected),
+        _ => None,
+    };
+    match (md5, sha512t24u) {
+        (Some(false), _) | (_, Some(false)) => ContigStatus::DigestMismatch,
+        (None, None) => ContigStatus::Unverifiable,
+        _ if contig.name != expected.name => ContigStatus::Renamed,
+        _ => ContigStatus::Identical,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{Assembly, ReferenceSource};
+
+    fn make_reference() -> KnownReference {
+        let mut chr1 = Contig::new("chr1", 1000).with_md5("aaaa");
+        chr1.aliases = vec!["1".to_string()];
+        let mut reference = KnownReference::new(
+            "test_ref",
+            "Test Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            chr1,
+            Contig::new("chr2", 2000).with_md5("bbbb"),
+            Contig::new("chrM", 16569).with_md5("cccc"),
+            Contig::new("chrY", 500),
+        ]);
+        reference.contigs_missing_from_fasta = vec!["chrY".to_string()];
+        reference
+    }
+
+    #[test]
+    fn test_identical_fasta_passes() {
+        let fasta = QueryHeader::new(vec![
+            Contig::new("chr1", 1000).with_md5("AAAA"),
+            Contig::new("chr2", 2000).with_md5("bbbb"),
+            Contig::new("chrM", 16569).with_md5("cccc"),
+        ]);
+
+        let verification = FastaVerification::verify(&fasta, &make_reference());
+
+        assert!(verification.passed);
+        assert_eq!(verification.count(ContigStatus::Identical), 3);
+        assert_eq!(verification.count(ContigStatus::ExpectedAbsent), 1);
+        assert_eq!(verification.problems().count(), 0);
+    }
+
+    #[test]
+    fn test_reports_each_difference() {
+        let fasta = QueryHeader::new(vec![
+            // Renamed via alias
+            Contig::new("1", 1000).with_md5("aaaa"),
+            // Wrong mitochondrion, and out of order
+            Contig::new("chrM", 16569).with_md5("dddd"),
+            // Renamed, found by MD5
+            Contig::new("2", 2000).with_md5("bbbb"),
+            Contig::new("chrEBV", 171_823).with_md5("eeee"),
+        ]);
+
+        let verification = FastaVerification::verify(&fasta, &make_reference());
+
+        assert!(!verification.passed);
+        assert_eq!(verification.count(ContigStatus::Renamed), 2);
+        assert_eq!(verification.count(ContigStatus::DigestMismatch), 1);
+        assert_eq!(verification.count(ContigStatus::Extra), 1);
+        assert!(!verification.order_matches);
+        assert_eq!(verification.first_out_of_order.as_deref(), Some("2"));
+
+        let renamed = &verification.contigs[0];
+        assert_eq!(renamed.reference_name.as_deref(), Some("chr1"));
+    }
+
+    #[test]
+    fn test_name_taken_by_an_earlier_contig_falls_back_to_md5() {
+        let fasta = QueryHeader::new(vec![
+            Contig::new("chr1", 1000).with_md5("aaaa"),
+            // An alias of chr1, which is already paired, with chr2's bases
+            Contig::new("1", 2000).with_md5("bbbb"),
+            Contig::new("chrM", 16569).with_md5("cccc"),
+        ]);
+
+        let verification = FastaVerification::verify(&fasta, &make_reference());
+
+        assert_eq!(verification.count(ContigStatus::Renamed), 1);
+        assert_eq!(verification.count(ContigStatus::Extra), 0);
+        assert_eq!(verification.count(ContigStatus::Missing), 0);
+        assert_eq!(
+            verification.contigs[1].reference_name.as_deref(),
+            Some("chr2")
+        );
+    }
+
+    #[test]
+    fn test_missing_contig_fails() {
+        let fasta = QueryHeader::new(vec![
+            Contig::new("chr1", 1000).with_md5("aaaa"),
+            Contig::new("chr2", 1999).with_md5("bbbb"),
+        ]);
+
+        let verification = FastaVerification::verify(&fasta, &make_reference());
+
+        assert!(!verification.passed);
+        assert_eq!(verification.count(ContigStatus::LengthMismatch), 1);
+        let missing: Vec<_> = verification
+            .problems()
+            .filter(|check| check.status == ContigStatus::Missing)
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].reference_name.as_deref(), Some("chrM"));
+    }
+
+    #[test]
+    fn test_status_names_match_json() {
+        for status in [
+            ContigStatus::Identical,
+            ContigStatus::Renamed,
+            ContigStatus::LengthMismatch,
+            ContigStatus::DigestMismatch,
+            ContigStatus::Unverifiable,
+            ContigStatus::Missing,
+            ContigStatus::ExpectedAbsent,
+            ContigStatus::Extra,
+        ] {
+            assert_eq!(
+                serde_json::to_value(status).unwrap(),
+                serde_json::Value::from(status.as_str())
+            );
+        }
+    }
+}
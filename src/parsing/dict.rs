@@ -1,7 +1,15 @@
+use std::io::Write;
+use std::num::NonZeroUsize;
 use std::path::Path;
 
+use noodles::sam;
+use noodles::sam::header::record::value::map::header::Version;
+use noodles::sam::header::record::value::map::{self, ReferenceSequence};
+use noodles::sam::header::record::value::Map;
+
+use crate::core::contig::Contig;
 use crate::core::header::QueryHeader;
-use crate::parsing::sam::ParseError;
+use crate::parsing::sam::{fill_reference_sequence_tags, ParseError};
 
 /// Parse a Picard sequence dictionary (.dict) file
 ///
@@ -25,6 +33,40 @@ pub fn parse_dict_text(text: &str) -> Result<QueryHeader, ParseError> {
     crate::parsing::sam::parse_header_text(text)
 }
 
+/// Write contigs as a Picard sequence dictionary: an `@HD` line followed by
+/// one `@SQ` line per contig, with `M5`, `AS`, `UR`, `SP` and `AN` tags where
+/// the contig has them.
+///
+/// # Errors
+///
+/// Returns `ParseError::InvalidFormat` if a contig has zero length or a tag
+/// value that cannot be written, or `ParseError::Io` if writing fails.
+pub fn write_dict<W: Write>(writer: W, contigs: &[Contig]) -> Result<(), ParseError> {
+    let mut builder =
+        sam::Header::builder().set_header(Map::<map::Header>::new(Version::new(1, 6)));
+    for contig in contigs {
+        let length = usize::try_from(contig.length)
+            .ok()
+            .and_then(NonZeroUsize::new)
+            .ok_or_else(|| {
+                ParseError::InvalidFormat(format!(
+                    "Contig {} has invalid length {}",
+                    contig.name, contig.length
+                ))
+            })?;
+        let mut reference_sequence = Map::<ReferenceSequence>::new(length);
+        fill_reference_sequence_tags(&mut reference_sequence, contig);
+        builder = builder.add_reference_sequence(contig.name.as_str(), reference_sequence);
+    }
+
+    sam::io::Writer::new(writer)
+        .write_header(&builder.build())
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidInput => ParseError::InvalidFormat(e.to_string()),
+            _ => ParseError::Io(e),
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,4 +86,30 @@ mod tests {
             Some("file:///reference/hg38.fa".to_string())
         );
     }
+
+    #[test]
+    fn test_write_dict_round_trip() {
+        let mut chr1 = Contig::new("chr1", 248_956_422)
+            .with_md5("6aef897c3d6ff0c78aff06ac189178dd")
+            .with_aliases(vec!["1".to_string(), "NC_000001.11".to_string()]);
+        chr1.assembly = Some("GRCh38".to_string());
+        chr1.species = Some("Homo sapiens".to_string());
+        chr1.uri = Some("file:///reference/hg38.fa".to_string());
+        let chr_m = Contig::new("chrM", 16569);
+
+        let mut buffer = Vec::new();
+        write_dict(&mut buffer, &[chr1, chr_m]).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+
+        assert!(text.starts_with("@HD\tVN:1.6\n"));
+        assert!(text.contains(
+            "@SQ\tSN:chr1\tLN:248956422\tM5:6aef897c3d6ff0c78aff06ac189178dd\tAS:GRCh38\t\
+             UR:file:///reference/hg38.fa\tSP:Homo sapiens\tAN:1,NC_000001.11\n"
+        ));
+        assert!(text.ends_with("@SQ\tSN:chrM\tLN:16569\n"));
+
+        let query = parse_dict_text(&text).unwrap();
+        assert_eq!(query.contigs[0].aliases, vec!["1", "NC_000001.11"]);
+        assert_eq!(query.contigs[0].species.as_deref(), Some("Homo sapiens"));
+    }
 }
@@ -0,0 +1,336 @@
+//! Persistent on-disk cache of FASTA sequence digests.
+//!
+//! Hashing a whole genome takes minutes, so the MD5 and sha512t24u digests
+//! computed for a FASTA are stored as JSON keyed by the file's canonical path,
+//! together with its size and modification time. An entry is only used while
+//! both still match the file on disk, and entries for FASTAs that were
+//! deleted or changed are dropped on the next save.
+//!
+//! Hashing progress is checkpointed into the cache as contigs complete, so an
+//! interrupted run resumes where it stopped. Saves merge with whatever is on
+//! disk under a file lock, so concurrent processes do not discard each other's
+//! entries.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::core::contig::Contig;
+use crate::utils::json_file::{self, JsonFileError};
+
+/// Cache file format version
+const CACHE_VERSION: u32 = 1;
+
+/// Size and modification time of a file, used to detect changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileFingerprint {
+    /// File size in bytes
+    pub size: u64,
+    /// Modification time in nanoseconds since the Unix epoch
+    pub modified_ns: u64,
+}
+
+impl FileFingerprint {
+    /// Fingerprint the file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the file metadata cannot be read.
+    pub fn of(path: &Path) -> std::io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        let modified_ns = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX));
+        Ok(Self {
+            size: metadata.len(),
+            modified_ns,
+        })
+    }
+}
+
+/// Digests of one FASTA record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedContig {
+    pub name: String,
+    pub length: u64,
+    pub md5: String,
+    pub sha512t24u: String,
+}
+
+impl CachedContig {
+    /// Capture the digests of a hashed contig, `None` if it has not been hashed
+    #[must_use]
+    pub fn from_contig(contig: &Contig) -> Option<Self> {
+        Some(Self {
+            name: contig.name.clone(),
+            length: contig.length,
+            md5: contig.md5.clone()?,
+            sha512t24u: contig.sha512t24u.clone()?,
+        })
+    }
+
+    /// Convert back into a contig carrying both digests
+    #[must_use]
+    pub fn to_contig(&self) -> Contig {
+        let mut contig = Contig::new(self.name.clone(), self.length);
+        contig.md5 = Some(self.md5.clone());
+        contig.sha512t24u = Some(self.sha512t24u.clone());
+        contig
+    }
+}
+
+/// Cached digests for one FASTA file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedFasta {
+    /// Fingerprint of the file when it was hashed
+    pub fingerprint: FileFingerprint,
+    /// Whether every record was hashed; otherwise this is a checkpoint
+    pub complete: bool,
+    /// Hashed records, in file order once complete
+    pub contigs: Vec<CachedContig>,
+    /// When the entry was last written
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Persistent FASTA digest cache, keyed by canonical path
+#[derive(Debug)]
+pub struct DigestCache {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, CachedFasta>>,
+}
+
+impl DigestCache {
+    /// Default cache location: `$XDG_CACHE_HOME/ref-solver/fasta-digests.json`,
+    /// falling back to `~/.cache/ref-solver/fasta-digests.json`.
+    ///
+    /// Returns `None` if neither `XDG_CACHE_HOME` nor `HOME` is set.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        crate::utils::cache_dir().map(|dir| dir.join("fasta-digests.json"))
+    }
+
+    /// Open the cache at `path`, loading existing entries if the file exists
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, JsonFileError> {
+        let path = path.into();
+        let entries = if path.exists() {
+            read_cache_file(&path)?.into_iter().collect()
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Path of the cache file
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Look up the entry for a FASTA, if it was hashed with this fingerprint
+    #[must_use]
+    pub fn get(&self, fasta: &Path, fingerprint: &FileFingerprint) -> Option<CachedFasta> {
+        self.lock()
+            .get(&cache_key(fasta))
+            .filter(|entry| entry.fingerprint == *fingerprint)
+            .cloned()
+    }
+
+    /// Record the digests hashed so far for a FASTA
+    pub fn insert(
+        &self,
+        fasta: &Path,
+        fingerprint: FileFingerprint,
+        contigs: Vec<CachedContig>,
+        complete: bool,
+    ) {
+        self.lock().insert(
+            cache_key(fasta),
+            CachedFasta {
+                fingerprint,
+                complete,
+                contigs,
+                updated_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Save the cache to disk, merging with any entries written by other
+    /// processes (the most recently updated entry for each file wins) and
+    /// dropping entries whose FASTA no longer exists or has changed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file cannot be locked, read or written.
+    pub fn save(&self) -> Result<(), JsonFileError> {
+        let _lock = json_file::lock(&self.path)?;
+        if self.path.exists() {
+            let on_disk = read_cache_file(&self.path)?;
+            let mut entries = self.lock();
+            for (key, entry) in on_disk {
+                let newer = entries
+                    .get(&key)
+                    .map_or(true, |existing| entry.updated_at > existing.updated_at);
+                if newer {
+                    entries.insert(key, entry);
+                }
+            }
+        }
+
+        let mut entries = self.lock();
+        entries.retain(|key, entry| {
+            FileFingerprint::of(Path::new(key)).is_ok_and(|current| current == entry.fingerprint)
+        });
+        let entries: BTreeMap<String, CachedFasta> = entries
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        json_file::write_entries(&self.path, CACHE_VERSION, &entries, false)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedFasta>> {
+        // A poisoned lock only means another thread panicked mid-update of a map
+        // of independent entries, so the data is still usable.
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Cache key for a FASTA: its canonical path, or the path as given if it
+/// cannot be canonicalized
+fn cache_key(fasta: &Path) -> String {
+    std::fs::canonicalize(fasta)
+        .unwrap_or_else(|_| fasta.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn read_cache_file(path: &Path) -> Result<BTreeMap<String, CachedFasta>, JsonFileError> {
+    json_file::read_entries(path, CACHE_VERSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contig() -> CachedContig {
+        CachedContig {
+            name: "chr1".to_string(),
+            length: 4,
+            md5: "f1f8f4bf413b16ad135722aa4591043e".to_string(),
+            sha512t24u: "aKF498dAxcJAqme6QYQ7EZ07-fiw8Kw2".to_string(),
+        }
+    }
+
+    fn write_fasta(dir: &Path, name: &str) -> (PathBuf, FileFingerprint) {
+        let fasta = dir.join(name);
+        std::fs::write(&fasta, ">chr1\nACGT\n").unwrap();
+        let fingerprint = FileFingerprint::of(&fasta).unwrap();
+        (fasta, fingerprint)
+    }
+
+    #[test]
+    fn test_entry_invalidated_by_fingerprint() {
+        let dir = tempfile::tempdir().unwrap();
+        let (fasta, fingerprint) = write_fasta(dir.path(), "ref.fa");
+        let cache_path = dir.path().join("cache").join("digests.json");
+
+        let cache = DigestCache::open(&cache_path).unwrap();
+        cache.insert(&fasta, fingerprint, vec![contig()], true);
+        cache.save().unwrap();
+
+        let reopened = DigestCache::open(&cache_path).unwrap();
+        let entry = reopened.get(&fasta, &fingerprint).unwrap();
+        assert!(entry.complete);
+        assert_eq!(entry.contigs[0].to_contig().length, 4);
+
+        let changed = FileFingerprint {
+            size: fingerprint.size + 1,
+            ..fingerprint
+        };
+        assert!(reopened.get(&fasta, &changed).is_none());
+    }
+
+    #[test]
+    fn test_save_evicts_deleted_and_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (kept, kept_fingerprint) = write_fasta(dir.path(), "kept.fa");
+        let (deleted, deleted_fingerprint) = write_fasta(dir.path(), "deleted.fa");
+        let (changed, changed_fingerprint) = write_fasta(dir.path(), "changed.fa");
+        let cache_path = dir.path().join("digests.json");
+
+        let cache = DigestCache::open(&cache_path).unwrap();
+        cache.insert(&kept, kept_fingerprint, vec![contig()], true);
+        cache.insert(&deleted, deleted_fingerprint, vec![contig()], true);
+        cache.insert(&changed, changed_fingerprint, vec![contig()], false);
+        std::fs::remove_file(&deleted).unwrap();
+        std::fs::write(&changed, ">chr1\nACGTACGT\n").unwrap();
+        cache.save().unwrap();
+
+        let reopened = DigestCache::open(&cache_path).unwrap();
+        assert_eq!(reopened.lock().len(), 1);
+        assert!(reopened.get(&kept, &kept_fingerprint).is_some());
+    }
+
+    #[test]
+    fn test_save_merges_with_other_writers() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, a_fingerprint) = write_fasta(dir.path(), "a.fa");
+        let (b, b_fingerprint) = write_fasta(dir.path(), "b.fa");
+        let cache_path = dir.path().join("digests.json");
+
+        let first = DigestCache::open(&cache_path).unwrap();
+        let second = DigestCache::open(&cache_path).unwrap();
+        first.insert(&a, a_fingerprint, vec![contig()], false);
+        second.insert(&b, b_fingerprint, Vec::new(), false);
+        first.save().unwrap();
+        // The later checkpoint of a.fa wins over the one first saved
+        second.insert(&a, a_fingerprint, vec![contig()], true);
+        second.save().unwrap();
+
+        let reopened = DigestCache::open(&cache_path).unwrap();
+        assert!(reopened.get(&a, &a_fingerprint).unwrap().complete);
+        assert!(reopened.get(&b, &b_fingerprint).is_some());
+    }
+
+    #[test]
+    fn test_concurrent_saves_keep_every_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("digests.json");
+        let fastas: Vec<_> = (0..8)
+            .map(|i| write_fasta(dir.path(), &format!("{i}.fa")))
+            .collect();
+
+        // Each writer opens the cache before any other has saved
+        let caches: Vec<_> = fastas
+            .iter()
+            .map(|_| DigestCache::open(&cache_path).unwrap())
+            .collect();
+        std::thread::scope(|scope| {
+            for (cache, (fasta, fingerprint)) in caches.iter().zip(&fastas) {
+                scope.spawn(move || {
+                    cache.insert(fasta, *fingerprint, vec![contig()], true);
+                    cache.save().unwrap();
+                });
+            }
+        });
+
+        let reopened = DigestCache::open(&cache_path).unwrap();
+        for (fasta, fingerprint) in &fastas {
+            assert!(reopened.get(fasta, fingerprint).is_some(), "{fasta:?}");
+        }
+    }
+}
@@ -100,76 +100,97 @@ pub fn parse_fai_text(text: &str) -> Result<QueryHeader, ParseError> {
     Ok(QueryHeader::new(contigs))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Parsed contig from FAI file with offset information
+#[derive(Debug, Clone)]
+pub struct FaiEntry {
+    /// Contig name
+    pub name: String,
+    /// Sequence length in bases
+    pub length: u64,
+    /// Byte offset of the first base in the FASTA
+    pub offset: u64,
+    /// Bases per line
+    pub line_bases: u32,
+    /// Bytes per line, including the line terminator
+    pub line_width: u32,
+}
 
-    /// Parsed contig from FAI file with offset information
-    #[derive(Debug, Clone)]
-    pub struct FaiEntry {
-        pub name: String,
-        pub length: u64,
-        pub offset: u64,
-        pub line_bases: u32,
-        pub line_width: u32,
-    }
+/// Parse FAI text with full entry information
+///
+/// # Errors
+///
+/// Returns `ParseError::InvalidFormat` if a line does not have five valid fields
+/// or no entries are found, or `ParseError::TooManyContigs` if the limit is exceeded.
+pub fn parse_fai_entries(text: &str) -> Result<Vec<FaiEntry>, ParseError> {
+    let mut entries = Vec::new();
 
-    /// Parse FAI file with full entry information
-    pub fn parse_fai_entries(text: &str) -> Result<Vec<FaiEntry>, ParseError> {
-        let mut entries = Vec::new();
-
-        for line in text.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-
-            let fields: Vec<&str> = line.split('\t').collect();
-            if fields.len() < 5 {
-                return Err(ParseError::InvalidFormat(format!(
-                    "FAI line has {} fields, expected 5: {}",
-                    fields.len(),
-                    line
-                )));
-            }
-
-            // Check limit
-            if check_contig_limit(entries.len()).is_some() {
-                return Err(ParseError::TooManyContigs(entries.len()));
-            }
-
-            let name = fields[0].to_string();
-            let length: u64 = fields[1]
-                .parse()
-                .map_err(|_| ParseError::InvalidFormat(format!("Invalid length: {}", fields[1])))?;
-            let offset: u64 = fields[2]
-                .parse()
-                .map_err(|_| ParseError::InvalidFormat(format!("Invalid offset: {}", fields[2])))?;
-            let line_bases: u32 = fields[3].parse().map_err(|_| {
-                ParseError::InvalidFormat(format!("Invalid line_bases: {}", fields[3]))
-            })?;
-            let line_width: u32 = fields[4].parse().map_err(|_| {
-                ParseError::InvalidFormat(format!("Invalid line_width: {}", fields[4]))
-            })?;
-
-            entries.push(FaiEntry {
-                name,
-                length,
-                offset,
-                line_bases,
-                line_width,
-            });
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
         }
 
-        if entries.is_empty() {
-            return Err(ParseError::InvalidFormat(
-                "No entries found in FAI file".to_string(),
-            ));
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 5 {
+            return Err(ParseError::InvalidFormat(format!(
+                "FAI line has {} fields, expected 5: {}",
+                fields.len(),
+                line
+            )));
+        }
+
+        // Check limit
+        if check_contig_limit(entries.len()).is_some() {
+            return Err(ParseError::TooManyContigs(entries.len()));
         }
 
-        Ok(entries)
+        let name = fields[0].to_string();
+        let length: u64 = fields[1]
+            .parse()
+            .map_err(|_| ParseError::InvalidFormat(format!("Invalid length: {}", fields[1])))?;
+        let offset: u64 = fields[2]
+            .parse()
+            .map_err(|_| ParseError::InvalidFormat(format!("Invalid offset: {}", fields[2])))?;
+        let line_bases: u32 = fields[3]
+            .parse()
+            .map_err(|_| ParseError::InvalidFormat(format!("Invalid line_bases: {}", fields[3])))?;
+        let line_width: u32 = fields[4]
+            .parse()
+            .map_err(|_| ParseError::InvalidFormat(format!("Invalid line_width: {}", fields[4])))?;
+
+        entries.push(FaiEntry {
+            name,
+            length,
+            offset,
+            line_bases,
+            line_width,
+        });
     }
 
+    if entries.is_empty() {
+        return Err(ParseError::InvalidFormat(
+            "No entries found in FAI file".to_string(),
+        ));
+    }
+
+    Ok(entries)
+}
+
+/// Read a FASTA index (.fai) file with full entry information, for random access
+/// into the FASTA
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if the file cannot be read, or any error from
+/// [`parse_fai_entries`].
+pub fn parse_fai_entries_file(path: &Path) -> Result<Vec<FaiEntry>, ParseError> {
+    parse_fai_entries(&std::fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn test_parse_fai_text() {
         let fai = r"chr1	248956422	112	70	71
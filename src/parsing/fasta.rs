@@ -1,24 +1,56 @@
 //! Parser for FASTA files using noodles.
 //!
-//! Extracts contig names and lengths from FASTA files.
-//! Supports both uncompressed and gzip/bgzip compressed files.
+//! Extracts contig names and lengths from FASTA files, optionally computing
+//! MD5 and sha512t24u digests of each sequence in parallel.
+//! Supports both uncompressed and gzip/bgzip compressed files; with a `.fai`
+//! index (plus `.gzi` for bgzip) sequences are read by random access.
 //!
 //! Supported extensions:
 //! - `.fa`, `.fasta`, `.fna` (uncompressed)
 //! - `.fa.gz`, `.fasta.gz`, `.fna.gz` (gzip compressed)
 //! - `.fa.bgz`, `.fasta.bgz`, `.fna.bgz` (bgzip compressed)
 
+use std::collections::HashMap;
 use std::ffi::OsStr;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-
-use flate2::read::GzDecoder;
-use noodles::fasta;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use flate2::read::MultiGzDecoder;
+use noodles::{bgzf, fasta};
+use sha2::{Digest, Sha512};
 
 use crate::core::contig::Contig;
 use crate::core::header::QueryHeader;
+use crate::parsing::digest_cache::{CachedContig, DigestCache, FileFingerprint};
+use crate::parsing::fai::{parse_fai_entries_file, FaiEntry};
 use crate::parsing::sam::ParseError;
-use crate::utils::validation::{check_contig_limit, compute_sha512t24u, is_valid_sha512t24u};
+use crate::utils::validation::{check_contig_limit, is_valid_sha512t24u};
+
+/// Maximum number of threads hashing sequences, shared by all FASTAs hashed
+/// at once (as by batch identify)
+const MAX_HASH_THREADS: usize = 8;
+
+/// Bases read at a time when hashing a sequence of an indexed FASTA, so each
+/// thread holds one chunk rather than a whole chromosome
+const HASH_CHUNK_BASES: u64 = 4 << 20;
+
+/// Bases of whole sequences read from unindexed FASTAs that may wait for or
+/// be in hashing at once. A single longer sequence is still hashed, alone.
+const MAX_BUFFERED_BASES: u64 = 1 << 30;
+
+/// Hashing threads running across all FASTAs being hashed
+static HASH_THREADS_IN_USE: AtomicUsize = AtomicUsize::new(0);
+
+/// Bases held by all unindexed FASTAs being hashed
+static BUFFERED_BASES: ByteBudget = ByteBudget::new(MAX_BUFFERED_BASES);
+
+/// Minimum time between checkpoints of hashing progress to the digest cache
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Check if the path has a FASTA extension
 pub fn is_fasta_file(path: &Path) -> bool {
@@ -63,111 +95,577 @@ fn is_gzipped(path: &Path) -> bool {
 /// parsing fails, `ParseError::InvalidFormat` if no contigs are found, or
 /// `ParseError::TooManyContigs` if the limit is exceeded.
 pub fn parse_fasta_file(path: &Path) -> Result<QueryHeader, ParseError> {
-    if is_gzipped(path) {
-        parse_fasta_gzipped(path)
-    } else {
-        parse_fasta_uncompressed(path)
-    }
+    parse_fasta_stream(path)
 }
 
-/// Parse a FASTA file and compute MD5 checksums for each sequence.
+/// Parse a FASTA file and compute MD5 and sha512t24u digests for each sequence.
 ///
-/// This reads through the entire FASTA file and computes MD5 on the
-/// uppercase sequence (standard for sequence checksums).
-/// This is slower than `parse_fasta_file` but provides MD5 for matching.
+/// Digests are computed on the uppercase sequence (standard for sequence
+/// checksums), hashing sequences in parallel. When a `.fai` index is present
+/// (plus a `.gzi` index for BGZF-compressed files) each thread reads its own
+/// sequences; otherwise one thread reads the file while the others hash.
+/// This is slower than `parse_fasta_file` but provides digests for matching.
 ///
 /// # Errors
 ///
 /// Returns `ParseError::Io` if the file cannot be read, `ParseError::Noodles` if
-/// parsing fails, `ParseError::InvalidFormat` if no contigs are found, or
-/// `ParseError::TooManyContigs` if the limit is exceeded.
+/// parsing fails, `ParseError::InvalidFormat` if no contigs are found or a
+/// sequence does not match its index entry, or `ParseError::TooManyContigs` if
+/// the limit is exceeded.
+#[allow(dead_code)] // Library API; the CLI commands always pass their digest cache
 pub fn parse_fasta_file_with_md5(path: &Path) -> Result<QueryHeader, ParseError> {
-    if is_gzipped(path) {
-        parse_fasta_gzipped_with_md5(path)
-    } else {
-        parse_fasta_uncompressed_with_md5(path)
+    parse_fasta_file_with_md5_cached(path, None)
+}
+
+/// Like [`parse_fasta_file_with_md5`], but reuses digests from `cache` while the
+/// file's size and modification time are unchanged, and checkpoints progress
+/// into it so an interrupted run resumes where it stopped.
+///
+/// Failures to write the cache are logged and otherwise ignored.
+///
+/// # Errors
+///
+/// See [`parse_fasta_file_with_md5`].
+pub fn parse_fasta_file_with_md5_cached(
+    path: &Path,
+    cache: Option<&DigestCache>,
+) -> Result<QueryHeader, ParseError> {
+    let fingerprint = FileFingerprint::of(path)?;
+    let cached = cache.and_then(|cache| cache.get(path, &fingerprint));
+    if let (Some(entry), Some(cache)) = (cached.as_ref().filter(|entry| entry.complete), cache) {
+        tracing::debug!(
+            "Using cached digests for {} from {}",
+            path.display(),
+            cache.path().display()
+        );
+        return Ok(QueryHeader::new(
+            entry.contigs.iter().map(CachedContig::to_contig).collect(),
+        ));
+    }
+
+    let progress = Progress::new(
+        path,
+        fingerprint,
+        cache,
+        cached.map(|entry| entry.contigs).unwrap_or_default(),
+    );
+    let contigs = match fasta_index(path)? {
+        Some(entries) => hash_indexed(path, &entries, &progress)?,
+        None => hash_sequential(open_sequential(path)?, &progress)?,
+    };
+
+    if contigs.is_empty() {
+        return Err(ParseError::InvalidFormat(
+            "No sequences found in FASTA file".to_string(),
+        ));
     }
+
+    progress.finish(&contigs);
+    Ok(QueryHeader::new(contigs))
 }
 
-/// Parse an uncompressed FASTA file with MD5 computation
-fn parse_fasta_uncompressed_with_md5(path: &Path) -> Result<QueryHeader, ParseError> {
-    let file = std::fs::File::open(path)?;
-    let reader = BufReader::new(file);
-    let mut fasta_reader = fasta::io::Reader::new(reader);
+/// Threads reserved to hash one FASTA, released when dropped
+struct HashThreads(usize);
+
+impl HashThreads {
+    /// Reserve threads to hash `jobs` sequences: as many as are free of the
+    /// shared limit, but always one so every FASTA makes progress
+    fn reserve(jobs: usize) -> Self {
+        let limit = std::thread::available_parallelism()
+            .map_or(1, std::num::NonZeroUsize::get)
+            .min(MAX_HASH_THREADS);
+        let wanted = jobs.clamp(1, limit);
+        let mut reserved = 1;
+        // The closure always returns Some, so the update cannot fail
+        let _ = HASH_THREADS_IN_USE.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |in_use| {
+            reserved = wanted.min(limit.saturating_sub(in_use)).max(1);
+            Some(in_use + reserved)
+        });
+        Self(reserved)
+    }
+}
 
-    parse_fasta_reader_with_md5(&mut fasta_reader)
+impl Drop for HashThreads {
+    fn drop(&mut self) {
+        HASH_THREADS_IN_USE.fetch_sub(self.0, Ordering::Relaxed);
+    }
 }
 
-/// Parse a gzip-compressed FASTA file with MD5 computation
-fn parse_fasta_gzipped_with_md5(path: &Path) -> Result<QueryHeader, ParseError> {
-    let file = std::fs::File::open(path)?;
-    let decoder = GzDecoder::new(file);
-    let reader = BufReader::new(decoder);
-    let mut fasta_reader = fasta::io::Reader::new(reader);
+/// Hash every sequence of an indexed FASTA, each thread reading its own
+/// sequences by offset
+fn hash_indexed(
+    path: &Path,
+    entries: &[FaiEntry],
+    progress: &Progress,
+) -> Result<Vec<Contig>, ParseError> {
+    let next = AtomicUsize::new(0);
+    let threads = HashThreads::reserve(entries.len());
+
+    let hashed = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.0)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut hashed = Vec::new();
+                    let mut reader = IndexedFastaReader::open(path)?;
+                    while let Some(entry) = entries.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        if let Some(contig) = progress.resumed(&entry.name, entry.length) {
+                            hashed.push(contig);
+                            continue;
+                        }
+                        match hash_indexed_sequence(&mut reader, entry) {
+                            Ok(contig) => {
+                                progress.record(&contig);
+                                hashed.push(contig);
+                            }
+                            Err(e) => {
+                                // Stop the other workers
+                                next.store(entries.len(), Ordering::Relaxed);
+                                return Err(e);
+                            }
+                        }
+                    }
+                    Ok::<_, ParseError>(hashed)
+                })
+            })
+            .collect();
 
-    parse_fasta_reader_with_md5(&mut fasta_reader)
+        workers
+            .into_iter()
+            .map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    // Restore index order
+    let order: HashMap<&str, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| (entry.name.as_str(), i))
+        .collect();
+    let mut contigs: Vec<Contig> = hashed.into_iter().flatten().collect();
+    contigs.sort_by_key(|contig| order.get(contig.name.as_str()).copied());
+    Ok(contigs)
 }
 
-/// Parse from a noodles FASTA reader with MD5 computation
-fn parse_fasta_reader_with_md5<R: BufRead>(
-    reader: &mut fasta::io::Reader<R>,
-) -> Result<QueryHeader, ParseError> {
-    let mut contigs = Vec::new();
+/// Hash every sequence of a FASTA stream, reading on this thread and hashing
+/// on worker threads. Whole sequences are handed to the workers, so the bases
+/// waiting for or in hashing are bounded by [`MAX_BUFFERED_BASES`].
+fn hash_sequential<R: BufRead>(reader: R, progress: &Progress) -> Result<Vec<Contig>, ParseError> {
+    let mut reader = fasta::io::Reader::new(reader);
+    let threads = HashThreads::reserve(usize::MAX);
+    let (sender, receiver) = mpsc::sync_channel::<(usize, String, Vec<u8>)>(threads.0);
+    let receiver = Mutex::new(receiver);
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.0)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut hashed = Vec::new();
+                    loop {
+                        let message = receiver
+                            .lock()
+                            .unwrap_or_else(std::sync::PoisonError::into_inner)
+                            .recv();
+                        let Ok((index, name, sequence)) = message else {
+                            return hashed;
+                        };
+                        let bases = sequence.len() as u64;
+                        let contig = contig_with_digests(name, sequence);
+                        BUFFERED_BASES.release(bases);
+                        progress.record(&contig);
+                        hashed.push((index, contig));
+                    }
+                })
+            })
+            .collect();
 
-    for result in reader.records() {
-        let record = result
-            .map_err(|e| ParseError::Noodles(format!("Failed to parse FASTA record: {e}")))?;
+        let mut contigs = Vec::new();
+        let read = (|| {
+            for (index, result) in reader.records().enumerate() {
+                let record = result.map_err(|e| {
+                    ParseError::Noodles(format!("Failed to parse FASTA record: {e}"))
+                })?;
+
+                // Check contig limit for DOS protection
+                if check_contig_limit(index).is_some() {
+                    return Err(ParseError::TooManyContigs(index));
+                }
+
+                let name = String::from_utf8_lossy(record.name()).to_string();
+                let length = record.sequence().len() as u64;
+                if let Some(contig) = progress.resumed(&name, length) {
+                    contigs.push((index, contig));
+                    continue;
+                }
+                BUFFERED_BASES.acquire(length);
+                if sender
+                    .send((index, name, record.sequence().as_ref().to_vec()))
+                    .is_err()
+                {
+                    // Workers only hang up if one panicked, which join reports
+                    BUFFERED_BASES.release(length);
+                    break;
+                }
+            }
+            Ok(())
+        })();
+        drop(sender);
+
+        for worker in workers {
+            contigs.extend(
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+            );
+        }
+        read?;
 
-        // Check contig limit for DOS protection
-        if check_contig_limit(contigs.len()).is_some() {
-            return Err(ParseError::TooManyContigs(contigs.len()));
+        contigs.sort_by_key(|(index, _)| *index);
+        Ok(contigs.into_iter().map(|(_, contig)| contig).collect())
+    })
+}
+
+/// Build a contig with MD5 and sha512t24u digests of its sequence
+fn contig_with_digests(name: String, mut sequence: Vec<u8>) -> Contig {
+    let mut digests = SequenceDigests::default();
+    digests.update(&mut sequence);
+    digests.finish(name)
+}
+
+/// MD5 and sha512t24u digests of a sequence fed in chunks
+struct SequenceDigests {
+    md5: md5::Context,
+    sha512: Sha512,
+    length: u64,
+}
+
+impl Default for SequenceDigests {
+    fn default() -> Self {
+        Self {
+            md5: md5::Context::new(),
+            sha512: Sha512::new(),
+            length: 0,
         }
+    }
+}
 
-        let name = String::from_utf8_lossy(record.name()).to_string();
-        let sequence = record.sequence();
-        let length = sequence.len() as u64;
-
-        // Compute digests on uppercase sequence (standard convention)
-        let uppercase: Vec<u8> = sequence
-            .as_ref()
-            .iter()
-            .map(u8::to_ascii_uppercase)
-            .collect();
-        let md5 = format!("{:x}", md5::compute(&uppercase));
-        let sha512t24u = compute_sha512t24u(&uppercase);
+impl SequenceDigests {
+    /// Add the next bases, uppercasing them in place (digests are computed on
+    /// the uppercase sequence by convention)
+    fn update(&mut self, bases: &mut [u8]) {
+        bases.make_ascii_uppercase();
+        self.md5.consume(&*bases);
+        self.sha512.update(&*bases);
+        self.length += bases.len() as u64;
+    }
+
+    fn finish(self, name: String) -> Contig {
+        let md5 = format!("{:x}", self.md5.compute());
+        let sha512t24u = URL_SAFE_NO_PAD.encode(&self.sha512.finalize()[..24]);
         debug_assert!(is_valid_sha512t24u(&sha512t24u));
 
-        let mut contig = Contig::new(name, length);
-        contig.md5 = Some(md5);
-        contig.sha512t24u = Some(sha512t24u);
-        contigs.push(contig);
+        Contig::new(name, self.length)
+            .with_md5(md5)
+            .with_sha512t24u(sha512t24u)
     }
+}
 
-    if contigs.is_empty() {
-        return Err(ParseError::InvalidFormat(
-            "No sequences found in FASTA file".to_string(),
-        ));
+/// Bases that may be held in memory at once, shared by the threads holding them
+struct ByteBudget {
+    limit: u64,
+    available: Mutex<u64>,
+    released: Condvar,
+}
+
+impl ByteBudget {
+    const fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            available: Mutex::new(limit),
+            released: Condvar::new(),
+        }
     }
 
-    Ok(QueryHeader::new(contigs))
+    /// Wait until `bases` (at most the whole budget) are available and take them
+    fn acquire(&self, bases: u64) {
+        let bases = bases.min(self.limit);
+        let mut available = self
+            .available
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        while *available < bases {
+            available = self
+                .released
+                .wait(available)
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+        }
+        *available -= bases;
+    }
+
+    /// Return bases taken by [`acquire`](Self::acquire)
+    fn release(&self, bases: u64) {
+        let mut available = self
+            .available
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *available = (*available + bases.min(self.limit)).min(self.limit);
+        self.released.notify_all();
+    }
 }
 
-/// Parse an uncompressed FASTA file
-fn parse_fasta_uncompressed(path: &Path) -> Result<QueryHeader, ParseError> {
-    let file = std::fs::File::open(path)?;
-    let reader = BufReader::new(file);
-    let mut fasta_reader = fasta::io::Reader::new(reader);
+/// Hashed contigs of one FASTA, resumed from and checkpointed to the digest cache
+struct Progress<'a> {
+    path: &'a Path,
+    fingerprint: FileFingerprint,
+    cache: Option<&'a DigestCache>,
+    /// Contigs hashed by an earlier, interrupted run
+    resumed: HashMap<(String, u64), CachedContig>,
+    /// Contigs hashed by this run, and when they were last checkpointed
+    hashed: Mutex<(Vec<CachedContig>, Instant)>,
+}
 
-    parse_fasta_reader(&mut fasta_reader)
+impl<'a> Progress<'a> {
+    fn new(
+        path: &'a Path,
+        fingerprint: FileFingerprint,
+        cache: Option<&'a DigestCache>,
+        resumed: Vec<CachedContig>,
+    ) -> Self {
+        if !resumed.is_empty() {
+            tracing::debug!(
+                "Resuming hashing of {} from {} checkpointed sequences",
+                path.display(),
+                resumed.len()
+            );
+        }
+        Self {
+            path,
+            fingerprint,
+            cache,
+            resumed: resumed
+                .into_iter()
+                .map(|contig| ((contig.name.clone(), contig.length), contig))
+                .collect(),
+            hashed: Mutex::new((Vec::new(), Instant::now())),
+        }
+    }
+
+    /// A contig hashed by an earlier run
+    fn resumed(&self, name: &str, length: u64) -> Option<Contig> {
+        self.resumed
+            .get(&(name.to_string(), length))
+            .map(CachedContig::to_contig)
+    }
+
+    /// Note a newly hashed contig, checkpointing if one is due
+    fn record(&self, contig: &Contig) {
+        let Some(cache) = self.cache else {
+            return;
+        };
+        let Some(cached) = CachedContig::from_contig(contig) else {
+            return;
+        };
+
+        let mut hashed = self
+            .hashed
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        hashed.0.push(cached);
+        if hashed.1.elapsed() >= CHECKPOINT_INTERVAL {
+            let contigs = self.resumed.values().chain(&hashed.0).cloned().collect();
+            self.save(cache, contigs, false);
+            hashed.1 = Instant::now();
+        }
+    }
+
+    /// Store the digests of every contig, in file order
+    fn finish(&self, contigs: &[Contig]) {
+        if let Some(cache) = self.cache {
+            let contigs = contigs
+                .iter()
+                .filter_map(CachedContig::from_contig)
+                .collect();
+            self.save(cache, contigs, true);
+        }
+    }
+
+    fn save(&self, cache: &DigestCache, contigs: Vec<CachedContig>, complete: bool) {
+        cache.insert(self.path, self.fingerprint, contigs, complete);
+        if let Err(e) = cache.save() {
+            tracing::warn!("Failed to save digest cache: {e}");
+        }
+    }
+}
+
+/// Compute MD5 and sha512t24u digests for selected contigs of an indexed FASTA.
+///
+/// Uses the `.fai` offsets (and, for BGZF-compressed files, the `.gzi` index)
+/// to read only the requested sequences, so hashing a few small contigs takes
+/// seconds regardless of the size of the genome.
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if the file or its `.gzi` index cannot be read, or
+/// `ParseError::InvalidFormat` if a sequence does not match its index entry.
+pub fn hash_indexed_contigs(path: &Path, entries: &[&FaiEntry]) -> Result<Vec<Contig>, ParseError> {
+    let mut reader = IndexedFastaReader::open(path)?;
+    entries
+        .iter()
+        .map(|entry| hash_indexed_sequence(&mut reader, entry))
+        .collect()
+}
+
+/// Hash one sequence at its indexed offset in chunks of [`HASH_CHUNK_BASES`],
+/// checking its length
+fn hash_indexed_sequence(
+    reader: &mut IndexedFastaReader,
+    entry: &FaiEntry,
+) -> Result<Contig, ParseError> {
+    let mut digests = SequenceDigests::default();
+    let mut start = 0;
+    while start < entry.length {
+        let mut chunk = reader.read_range(entry, start, start + HASH_CHUNK_BASES)?;
+        if chunk.is_empty() {
+            break;
+        }
+        start += chunk.len() as u64;
+        digests.update(&mut chunk);
+    }
+    if digests.length != entry.length {
+        return Err(ParseError::InvalidFormat(format!(
+            "Sequence {} has {} bases but the index expects {}",
+            entry.name, digests.length, entry.length
+        )));
+    }
+    Ok(digests.finish(entry.name.clone()))
+}
+
+/// Random access to the sequences of an indexed FASTA: uncompressed with a
+/// `.fai` index, or BGZF-compressed with `.fai` and `.gzi` indexes
+pub struct IndexedFastaReader {
+    reader: Box<dyn ReadSeek + Send>,
+}
+
+impl IndexedFastaReader {
+    /// Open an indexed FASTA whose entries come from [`fasta_index`]
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::Io` if the file or its `.gzi` index cannot be read.
+    pub fn open(path: &Path) -> Result<Self, ParseError> {
+        let file = std::fs::File::open(path)?;
+        let reader: Box<dyn ReadSeek + Send> = if is_gzipped(path) {
+            let index = bgzf::gzi::read(sibling_path(path, "gzi"))?;
+            Box::new(bgzf::IndexedReader::new(file, index))
+        } else {
+            Box::new(BufReader::new(file))
+        };
+        Ok(Self { reader })
+    }
+
+    /// Read the bases in `[start, end)` (0-based, half-open) of the sequence of
+    /// `entry`, without line terminators and in the file's case. The range is
+    /// clipped to the sequence.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::InvalidFormat` if the entry's line layout is
+    /// invalid, or `ParseError::Io` if the file cannot be read.
+    pub fn read_range(
+        &mut self,
+        entry: &FaiEntry,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<u8>, ParseError> {
+        if entry.line_bases == 0 || entry.line_width < entry.line_bases {
+            return Err(ParseError::InvalidFormat(format!(
+                "Invalid line layout in FAI entry for {}",
+                entry.name
+            )));
+        }
+        let end = end.min(entry.length);
+        if start >= end {
+            return Ok(Vec::new());
+        }
+
+        // From the first base to just past the last, without the terminator
+        // of the last base's line
+        let line_bases = u64::from(entry.line_bases);
+        let line_width = u64::from(entry.line_width);
+        let byte_offset = |pos: u64| pos / line_bases * line_width + pos % line_bases;
+        let first = byte_offset(start);
+        let span = byte_offset(end - 1) + 1 - first;
+
+        self.reader.seek(SeekFrom::Start(entry.offset + first))?;
+        let mut sequence = Vec::with_capacity(usize::try_from(end - start).unwrap_or(0));
+        (&mut self.reader).take(span).read_to_end(&mut sequence)?;
+
+        sequence.retain(|&b| b != b'\n' && b != b'\r');
+        Ok(sequence)
+    }
 }
 
-/// Parse a gzip-compressed FASTA file
-fn parse_fasta_gzipped(path: &Path) -> Result<QueryHeader, ParseError> {
+/// Seekable reader over uncompressed sequence bytes
+trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// The `.fai` entries of a FASTA that supports random access: uncompressed with
+/// a `.fai` index, or BGZF-compressed with `.fai` and `.gzi` indexes. `None`
+/// if the FASTA can only be read from start to end.
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if the file or index cannot be read, or any error
+/// from [`parse_fai_entries`](crate::parsing::fai::parse_fai_entries).
+pub fn fasta_index(path: &Path) -> Result<Option<Vec<FaiEntry>>, ParseError> {
+    let fai = sibling_path(path, "fai");
+    if !fai.is_file()
+        || (is_gzipped(path) && !(is_bgzf(path)? && sibling_path(path, "gzi").is_file()))
+    {
+        return Ok(None);
+    }
+    parse_fai_entries_file(&fai).map(Some)
+}
+
+/// Open a FASTA for reading from start to end, decompressing BGZF on
+/// several threads
+fn open_sequential(path: &Path) -> Result<Box<dyn BufRead>, ParseError> {
     let file = std::fs::File::open(path)?;
-    let decoder = GzDecoder::new(file);
-    let reader = BufReader::new(decoder);
-    let mut fasta_reader = fasta::io::Reader::new(reader);
+    if !is_gzipped(path) {
+        Ok(Box::new(BufReader::new(file)))
+    } else if is_bgzf(path)? {
+        Ok(Box::new(bgzf::MultithreadedReader::new(file)))
+    } else {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(file))))
+    }
+}
+
+/// Check for a BGZF header: a gzip member with a `BC` extra subfield
+fn is_bgzf(path: &Path) -> std::io::Result<bool> {
+    let mut header = [0u8; 14];
+    let mut file = std::fs::File::open(path)?;
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(header[..4] == [0x1f, 0x8b, 0x08, 0x04] && &header[12..14] == b"BC"),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// `path` with `.{extension}` appended, e.g. `ref.fa.gz` -> `ref.fa.gz.gzi`
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut sibling = path.as_os_str().to_owned();
+    sibling.push(".");
+    sibling.push(extension);
+    PathBuf::from(sibling)
+}
 
+/// Parse a FASTA file (uncompressed, gzip or BGZF)
+fn parse_fasta_stream(path: &Path) -> Result<QueryHeader, ParseError> {
+    let mut fasta_reader = fasta::io::Reader::new(open_sequential(path)?);
     parse_fasta_reader(&mut fasta_reader)
 }
 
@@ -268,6 +766,96 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hash_indexed_contigs_matches_full_scan() {
+        let fasta_content = b">chr1\nACGTA\nCGTAC\nGT\n>chrM\nacgta\ncg\n>chrY\nTTTTT\n";
+        let fai = "chr1\t12\t6\t5\t6\nchrM\t7\t27\t5\t6\nchrY\t5\t42\t5\t6\n";
+
+        let mut temp = NamedTempFile::with_suffix(".fa").unwrap();
+        temp.write_all(fasta_content).unwrap();
+        temp.flush().unwrap();
+
+        let entries = crate::parsing::fai::parse_fai_entries(fai).unwrap();
+        let hashed = hash_indexed_contigs(temp.path(), &[&entries[2], &entries[1]]).unwrap();
+        let full = parse_fasta_file_with_md5(temp.path()).unwrap();
+
+        assert_eq!(hashed.len(), 2);
+        assert_eq!(hashed[0].name, "chrY");
+        assert_eq!(hashed[0].md5, full.contigs[2].md5);
+        assert_eq!(hashed[1].name, "chrM");
+        assert_eq!(hashed[1].length, 7);
+        assert_eq!(hashed[1].md5, full.contigs[1].md5);
+        assert_eq!(hashed[1].sha512t24u, full.contigs[1].sha512t24u);
+    }
+
+    #[test]
+    fn test_bgzf_fasta_matches_plain() {
+        let fasta_content = b">chr1\nACGTA\nCGTAC\nGT\n>chrM\nacgta\ncg\n";
+        let fai = "chr1\t12\t6\t5\t6\nchrM\t7\t27\t5\t6\n";
+
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("ref.fa");
+        std::fs::write(&plain, fasta_content).unwrap();
+        let expected = parse_fasta_file_with_md5(&plain).unwrap();
+
+        let compressed = dir.path().join("ref.fa.gz");
+        let mut writer = noodles::bgzf::Writer::new(std::fs::File::create(&compressed).unwrap());
+        writer.write_all(fasta_content).unwrap();
+        writer.finish().unwrap();
+
+        // Without an index the file is streamed
+        let streamed = parse_fasta_file_with_md5(&compressed).unwrap();
+        assert_eq!(streamed.contigs, expected.contigs);
+
+        // With .fai and .gzi the sequences are read by offset. A single-block
+        // file has an empty .gzi index.
+        std::fs::write(dir.path().join("ref.fa.gz.fai"), fai).unwrap();
+        std::fs::write(dir.path().join("ref.fa.gz.gzi"), 0u64.to_le_bytes()).unwrap();
+        let indexed = parse_fasta_file_with_md5(&compressed).unwrap();
+        assert_eq!(indexed.contigs, expected.contigs);
+
+        // Ranges across line breaks, clipped to the sequence
+        let entries = fasta_index(&compressed).unwrap().unwrap();
+        let mut reader = IndexedFastaReader::open(&compressed).unwrap();
+        assert_eq!(reader.read_range(&entries[0], 3, 7).unwrap(), b"TACG");
+        assert_eq!(reader.read_range(&entries[1], 4, 100).unwrap(), b"acg");
+        assert!(reader.read_range(&entries[1], 7, 7).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_digest_cache_resumes_and_hits() {
+        let dir = tempfile::tempdir().unwrap();
+        let fasta = dir.path().join("ref.fa");
+        std::fs::write(&fasta, ">chr1\nACGT\n>chr2\nGGCC\n").unwrap();
+        let cache = DigestCache::open(dir.path().join("digests.json")).unwrap();
+        let fingerprint = FileFingerprint::of(&fasta).unwrap();
+
+        // A checkpoint from an interrupted run is reused, not re-hashed
+        let checkpointed = CachedContig {
+            name: "chr1".to_string(),
+            length: 4,
+            md5: "checkpointed".to_string(),
+            sha512t24u: "checkpointed".to_string(),
+        };
+        cache.insert(&fasta, fingerprint, vec![checkpointed], false);
+
+        let query = parse_fasta_file_with_md5_cached(&fasta, Some(&cache)).unwrap();
+        assert_eq!(query.contigs[0].md5.as_deref(), Some("checkpointed"));
+        assert_eq!(
+            query.contigs[1].md5,
+            Some(format!("{:x}", md5::compute(b"GGCC")))
+        );
+
+        let entry = cache.get(&fasta, &fingerprint).unwrap();
+        assert!(entry.complete);
+        assert_eq!(entry.contigs.len(), 2);
+
+        // A complete entry is returned without reading the file
+        let reopened = DigestCache::open(dir.path().join("digests.json")).unwrap();
+        let cached = parse_fasta_file_with_md5_cached(&fasta, Some(&reopened)).unwrap();
+        assert_eq!(cached.contigs, query.contigs);
+    }
+
     #[test]
     fn test_parse_fasta_with_md5_lowercase() {
         // MD5 should be computed on uppercase, so "acgt" should give same result as "ACGT"
@@ -283,4 +871,40 @@ mod tests {
             Some("f1f8f4bf413b16ad135722aa4591043e".to_string())
         );
     }
+
+    #[test]
+    fn test_sequence_digests_in_chunks() {
+        let sequence: Vec<u8> = b"acgtNNACGT".iter().copied().cycle().take(1000).collect();
+        let upper = sequence.to_ascii_uppercase();
+
+        let mut digests = SequenceDigests::default();
+        for chunk in sequence.chunks(333) {
+            digests.update(&mut chunk.to_vec());
+        }
+        let contig = digests.finish("chr1".to_string());
+
+        assert_eq!(contig.length, 1000);
+        assert_eq!(contig.md5, Some(format!("{:x}", md5::compute(&upper))));
+        assert_eq!(
+            contig.sha512t24u,
+            Some(crate::utils::validation::compute_sha512t24u(&upper))
+        );
+        assert_eq!(contig, contig_with_digests("chr1".to_string(), sequence));
+    }
+
+    #[test]
+    fn test_byte_budget_admits_oversized_sequence() {
+        let budget = ByteBudget::new(10);
+        budget.acquire(6);
+        std::thread::scope(|scope| {
+            // Waits for the first 6 bases, then takes the whole budget
+            let waiting = scope.spawn(|| budget.acquire(100));
+            std::thread::sleep(Duration::from_millis(20));
+            assert!(!waiting.is_finished());
+            budget.release(6);
+            waiting.join().unwrap();
+        });
+        budget.release(100);
+        assert_eq!(*budget.available.lock().unwrap(), 10);
+    }
 }
@@ -0,0 +1,228 @@
+//! Contigs named by the features of BED and GTF/GFF files.
+//!
+//! Feature files have no sequence dictionary: they only name contigs in their
+//! records, so all that can be learned is which contigs are used and how far
+//! along each the features reach. The largest end coordinate seen is a lower
+//! bound on a contig's length. GFF `##sequence-region` directives count as
+//! features spanning the region.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+use serde::Serialize;
+
+use crate::parsing::rename::RenameFormat;
+use crate::parsing::sam::ParseError;
+use crate::utils::validation::check_contig_limit;
+
+/// Feature file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FeatureFormat {
+    /// BED: 0-based half-open intervals
+    Bed,
+    /// GTF/GFF: 1-based closed intervals
+    Gff,
+}
+
+impl FeatureFormat {
+    /// Detect the format from a file extension, ignoring a trailing `.gz`
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match RenameFormat::from_path(path)? {
+            RenameFormat::Bed => Some(Self::Bed),
+            RenameFormat::Gff => Some(Self::Gff),
+            RenameFormat::Vcf | RenameFormat::IntervalList => None,
+        }
+    }
+}
+
+/// A contig named by a feature file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureContig {
+    /// Contig name as written in the file
+    pub name: String,
+
+    /// Largest end coordinate of its features; the contig is at least this long
+    pub max_end: u64,
+
+    /// Number of records on the contig
+    pub records: u64,
+}
+
+/// Read the contigs named by a feature file, plain or gzip/BGZF-compressed,
+/// in order of first appearance
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if the file cannot be read, or other parse errors
+/// if the content is invalid.
+pub fn parse_feature_file(
+    path: &Path,
+    format: FeatureFormat,
+) -> Result<Vec<FeatureContig>, ParseError> {
+    let mut magic = [0u8; 2];
+    let gzipped = std::fs::File::open(path)?.read(&mut magic)? == 2 && magic == [0x1f, 0x8b];
+    let file = std::fs::File::open(path)?;
+    if gzipped {
+        read_feature_contigs(BufReader::new(MultiGzDecoder::new(file)), format)
+    } else {
+        read_feature_contigs(BufReader::new(file), format)
+    }
+}
+
+/// Read the contigs named by the features in `reader`, in order of first
+/// appearance
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if reading fails, `ParseError::InvalidFormat` if
+/// the input is not valid UTF-8 or a coordinate is not a number, or
+/// `ParseError::TooManyContigs` if the limit is exceeded.
+pub fn read_feature_contigs<R: BufRead>(
+    reader: R,
+    format: FeatureFormat,
+) -> Result<Vec<FeatureContig>, ParseError> {
+    let mut contigs: Vec<FeatureContig> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidData => {
+                ParseError::InvalidFormat("Input is not valid UTF-8 text".to_string())
+            }
+            _ => ParseError::Io(e),
+        })?;
+        if format == FeatureFormat::Gff && line.starts_with("##FASTA") {
+            break;
+        }
+        let Some((name, end, is_record)) = feature(&line, format)
+            .map_err(|e| ParseError::InvalidFormat(format!("line {}: {e}", line_number + 1)))?
+        else {
+            continue;
+        };
+
+        let position = if let Some(&position) = index.get(name) {
+            position
+        } else {
+            // Check contig limit for DOS protection
+            if check_contig_limit(contigs.len()).is_some() {
+                return Err(ParseError::TooManyContigs(contigs.len()));
+            }
+            index.insert(name.to_string(), contigs.len());
+            contigs.push(FeatureContig {
+                name: name.to_string(),
+                max_end: 0,
+                records: 0,
+            });
+            contigs.len() - 1
+        };
+        let contig = &mut contigs[position];
+        contig.max_end = contig.max_end.max(end);
+        if is_record {
+            contig.records += 1;
+        }
+    }
+
+    Ok(contigs)
+}
+
+/// The contig, end coordinate and whether the line is a record, or `None` if
+/// the line names no contig
+fn feature(line: &str, format: FeatureFormat) -> Result<Option<(&str, u64, bool)>, String> {
+    let (columns, end_column, is_record) = match format {
+        FeatureFormat::Bed => {
+            if line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with("track")
+                || line.starts_with("browser")
+            {
+                return Ok(None);
+            }
+            (line.split('\t').collect::<Vec<_>>(), 2, true)
+        }
+        FeatureFormat::Gff => {
+            if let Some(rest) = line.strip_prefix("##sequence-region") {
+                (rest.split_whitespace().collect(), 2, false)
+            } else if line.is_empty() || line.starts_with('#') {
+                return Ok(None);
+            } else {
+                (line.split('\t').collect(), 4, true)
+            }
+        }
+    };
+
+    let name = columns.first().copied().unwrap_or_default();
+    let end = columns
+        .get(end_column)
+        .ok_or_else(|| format!("expected at least {} columns", end_column + 1))?;
+    let end = end
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("invalid end coordinate '{end}'"))?;
+    Ok((!name.is_empty()).then_some((name, end, is_record)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_bed_contigs() {
+        let bed = "track name=targets\n#comment\nchr2\t100\t200\tA\nchr1\t0\t50\nchr2\t300\t450\n";
+        let contigs = read_feature_contigs(bed.as_bytes(), FeatureFormat::Bed).unwrap();
+        assert_eq!(
+            contigs,
+            vec![
+                FeatureContig {
+                    name: "chr2".to_string(),
+                    max_end: 450,
+                    records: 2
+                },
+                FeatureContig {
+                    name: "chr1".to_string(),
+                    max_end: 50,
+                    records: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_gff_contigs() {
+        let gff = "##gff-version 3\n##sequence-region 1 1 248956422\n\
+                   1\tsrc\tgene\t11869\t14409\t.\t+\t.\tID=g1\n\
+                   MT\tsrc\tgene\t577\t647\t.\t+\t.\tID=g2\n\
+                   ##FASTA\n>1\nACGT\n";
+        let contigs = read_feature_contigs(gff.as_bytes(), FeatureFormat::Gff).unwrap();
+        assert_eq!(contigs.len(), 2);
+        assert_eq!(contigs[0].name, "1");
+        assert_eq!(contigs[0].max_end, 248_956_422);
+        assert_eq!(contigs[0].records, 1);
+        assert_eq!(contigs[1].name, "MT");
+        assert_eq!(contigs[1].max_end, 647);
+    }
+
+    #[test]
+    fn test_read_feature_contigs_invalid() {
+        let result = read_feature_contigs("chr1\t10\n".as_bytes(), FeatureFormat::Bed);
+        assert!(matches!(result, Err(ParseError::InvalidFormat(_))));
+        let result = read_feature_contigs("chr1\t10\tx\n".as_bytes(), FeatureFormat::Bed);
+        assert!(matches!(result, Err(ParseError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_feature_format_from_path() {
+        assert_eq!(
+            FeatureFormat::from_path(Path::new("targets.bed.gz")),
+            Some(FeatureFormat::Bed)
+        );
+        assert_eq!(
+            FeatureFormat::from_path(Path::new("genes.gtf")),
+            Some(FeatureFormat::Gff)
+        );
+        assert_eq!(FeatureFormat::from_path(Path::new("calls.vcf")), None);
+    }
+}
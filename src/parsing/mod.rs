@@ -5,9 +5,15 @@
 //! - **SAM/BAM/CRAM files**: Extract `@SQ` lines from alignment file headers
 //! - **Picard .dict files**: Parse sequence dictionary files
 //! - **FASTA index (.fai) files**: Parse FASTA index files
+//! - **FASTA files**: Contig lengths, or digests (cached in [`digest_cache`])
 //! - **NCBI assembly reports**: Parse NCBI assembly reports with multiple naming conventions
 //! - **VCF headers**: Extract `##contig` lines from VCF files
 //! - **TSV/CSV files**: Parse tabular contig definitions
+//! - **UCSC .2bit files**: Sequence names and lengths
+//!
+//! It can also list the contigs named by BED and GTF/GFF features
+//! ([`features`]) and rewrite contig names in VCF, BED, GTF/GFF and interval
+//! list files ([`rename`]).
 //!
 //! ## Example
 //!
@@ -38,9 +44,13 @@
 //! | AN  | Alternate names (aliases) | No |
 
 pub mod dict;
+pub mod digest_cache;
 pub mod fai;
 pub mod fasta;
+pub mod features;
 pub mod ncbi_report;
+pub mod rename;
 pub mod sam;
 pub mod tsv;
+pub mod twobit;
 pub mod vcf;
@@ -0,0 +1,503 @@
+//! Rewriting of contig names in VCF, BED, GTF/GFF and interval list files.
+//!
+//! Only the fields that name a contig are touched; everything else, line
+//! endings included, is copied through byte for byte:
+//!
+//! - **VCF**: `##contig=<ID=...>` header lines, the CHROM column, the mate
+//!   contig of breakend ALT alleles (`G]17:198982]`) and contig-valued INFO
+//!   fields such as `CHR2`
+//! - **BED**: the chrom column (`track`, `browser` and `#` lines are kept)
+//! - **GTF/GFF**: the seqid column and `##sequence-region` directives, up to
+//!   a `##FASTA` section
+//! - **Picard interval lists**: `@SQ` `SN:` tags and the contig column
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::core::naming::NameMap;
+use crate::parsing::sam::ParseError;
+
+/// File formats whose contig names can be rewritten
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum RenameFormat {
+    Vcf,
+    Bed,
+    Gff,
+    IntervalList,
+}
+
+impl RenameFormat {
+    /// Detect the format from a file extension, ignoring a trailing `.gz`
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        let name = name
+            .strip_suffix(".gz")
+            .or_else(|| name.strip_suffix(".bgz"))
+            .unwrap_or(&name);
+        let extension = name.rsplit_once('.')?.1;
+        match extension {
+            "vcf" => Some(Self::Vcf),
+            "bed" => Some(Self::Bed),
+            "gtf" | "gff" | "gff3" => Some(Self::Gff),
+            "interval_list" => Some(Self::IntervalList),
+            _ => None,
+        }
+    }
+}
+
+/// VCF INFO keys whose value names a contig (the mate of a structural variant)
+pub const VCF_CONTIG_INFO_KEYS: [&str; 1] = ["CHR2"];
+
+/// Summary of a rename
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RenameReport {
+    /// Data lines read (header and comment lines are not counted)
+    pub records: u64,
+
+    /// Data lines whose contig name changed
+    pub renamed: u64,
+
+    /// VCF records whose breakend ALT or contig-valued INFO names a contig
+    /// with no target name; these are kept but point at the old name
+    pub unmapped_mates: u64,
+
+    /// Data lines removed because their contig has no target name
+    pub dropped: u64,
+
+    /// Contig names with no target name, with the number of data lines naming them
+    pub unmapped: BTreeMap<String, u64>,
+}
+
+/// Rewrite the contig names of `reader` into `writer` using `names`.
+///
+/// Lines naming a contig that `names` does not know are kept unchanged, or
+/// removed if `drop_unmapped` is set; either way the contig is listed in
+/// the report.
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if reading or writing fails, or
+/// `ParseError::InvalidFormat` if the input is not valid UTF-8.
+pub fn rename_contigs<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    format: RenameFormat,
+    names: &NameMap,
+    drop_unmapped: bool,
+) -> Result<RenameReport, ParseError> {
+    let mut report = RenameReport::default();
+    let mut in_gff_fasta = false;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        // Keep the line's own ending ("\r\n", "\n", or none on the last line)
+        let ending_len = if buf.ends_with(b"\r\n") {
+            2
+        } else {
+            usize::from(buf.ends_with(b"\n"))
+        };
+        let (line, ending) = buf.split_at(buf.len() - ending_len);
+        let line = std::str::from_utf8(line)
+            .map_err(|_| ParseError::InvalidFormat("Input is not valid UTF-8 text".to_string()))?;
+
+        let field = if in_gff_fasta {
+            None
+        } else {
+            contig_field(line, format)
+        };
+        if format == RenameFormat::Gff && line.starts_with("##FASTA") {
+            in_gff_fasta = true;
+        }
+
+        let Some(field) = field else {
+            writer.write_all(line.as_bytes())?;
+            writer.write_all(ending)?;
+            continue;
+        };
+
+        let is_record = field.record;
+        let old = &line[field.start..field.end];
+        if is_record {
+            report.records += 1;
+        }
+        let (mut output, mut renamed) = match names.get(old) {
+            Some(new) => (
+                format!("{}{new}{}", &line[..field.start], &line[field.end..]),
+                new != old,
+            ),
+            None => {
+                if is_record {
+                    *report.unmapped.entry(old.to_string()).or_default() += 1;
+                } else {
+                    report.unmapped.entry(old.to_string()).or_default();
+                }
+                if drop_unmapped {
+                    if is_record {
+                        report.dropped += 1;
+                    }
+                    continue;
+                }
+                (line.to_string(), false)
+            }
+        };
+
+        if format == RenameFormat::Vcf && is_record {
+            let mates = rename_vcf_mates(&output, names);
+            if !mates.unmapped.is_empty() {
+                report.unmapped_mates += 1;
+                for name in mates.unmapped {
+                    *report.unmapped.entry(name).or_default() += 1;
+                }
+            }
+            if let Some(line) = mates.line {
+                output = line;
+                renamed = true;
+            }
+        }
+
+        if is_record && renamed {
+            report.renamed += 1;
+        }
+        writer.write_all(output.as_bytes())?;
+        writer.write_all(ending)?;
+    }
+
+    writer.flush()?;
+    Ok(report)
+}
+
+/// Byte range of the contig name in a line
+struct ContigField {
+    start: usize,
+    end: usize,
+    /// True for data lines, false for header lines declaring a contig
+    record: bool,
+}
+
+/// Locate the contig name in a line, or `None` if the line names no contig
+fn contig_field(line: &str, format: RenameFormat) -> Option<ContigField> {
+    match format {
+        RenameFormat::Vcf => {
+            if let Some(rest) = line.strip_prefix("##contig=<") {
+                header_value(line, rest, "ID=", ',')
+            } else if line.starts_with('#') {
+                None
+            } else {
+                first_column(line)
+            }
+        }
+        RenameFormat::Bed => {
+            if line.starts_with('#') || line.starts_with("track") || line.starts_with("browser") {
+                None
+            } else {
+                first_column(line)
+            }
+        }
+        RenameFormat::Gff => {
+            if let Some(rest) = line.strip_prefix("##sequence-region") {
+                let offset = line.len() - rest.len();
+                let trimmed = rest.trim_start();
+                let start = offset + (rest.len() - trimmed.len());
+                let end = start + trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+                (end > start).then_some(ContigField {
+                    start,
+                    end,
+                    record: false,
+                })
+            } else if line.starts_with('#') {
+                None
+            } else {
+                first_column(line)
+            }
+        }
+        RenameFormat::IntervalList => {
+            if let Some(rest) = line.strip_prefix("@SQ\t") {
+                header_value(line, rest, "SN:", '\t')
+            } else if line.starts_with('@') {
+                None
+            } else {
+                first_column(line)
+            }
+        }
+    }
+}
+
+/// Mate contigs of a VCF record, renamed
+struct VcfMates {
+    /// The record with its mate contigs renamed, `None` if nothing changed
+    line: Option<String>,
+    /// Mate contig names with no target name
+    unmapped: Vec<String>,
+}
+
+/// Rename the mate contigs a VCF record names outside its CHROM column: in
+/// breakend ALT alleles and in the INFO fields of [`VCF_CONTIG_INFO_KEYS`]
+fn rename_vcf_mates(line: &str, names: &NameMap) -> VcfMates {
+    let mut unmapped = Vec::new();
+    let mut changed = false;
+    let mut rename = |name: &str| -> Option<String> {
+        match names.get(name) {
+            Some(new) => {
+                changed |= new != name;
+                Some(new.to_string())
+            }
+            None => {
+                unmapped.push(name.to_string());
+                None
+            }
+        }
+    };
+
+    let mut columns: Vec<String> = line.split('\t').map(str::to_string).collect();
+    if let Some(alt) = columns.get_mut(4) {
+        if alt.contains(['[', ']']) {
+            *alt = alt
+                .split(',')
+                .map(|allele| rename_breakend(allele, &mut rename))
+                .collect::<Vec<_>>()
+                .join(",");
+        }
+    }
+    if let Some(info) = columns.get_mut(7) {
+        *info = info
+            .split(';')
+            .map(|field| match field.split_once('=') {
+                Some((key, value)) if VCF_CONTIG_INFO_KEYS.contains(&key) => {
+                    format!(
+                        "{key}={}",
+                        rename(value).unwrap_or_else(|| value.to_string())
+                    )
+                }
+                _ => field.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(";");
+    }
+
+    VcfMates {
+        line: changed.then(|| columns.join("\t")),
+        unmapped,
+    }
+}
+
+/// Rename the mate contig of a breakend allele such as `G]17:198982]` or
+/// `[<ctg1>:7[A`; other alleles are returned unchanged
+fn rename_breakend(allele: &str, rename: &mut impl FnMut(&str) -> Option<String>) -> String {
+    let Some(open) = allele.find(['[', ']']) else {
+        return allele.to_string();
+    };
+    let bracket = allele[open..].chars().next().unwrap_or('[');
+    let Some(length) = allele[open + 1..].find(bracket) else {
+        return allele.to_string();
+    };
+    let close = open + 1 + length;
+    let Some((contig, position)) = allele[open + 1..close].rsplit_once(':') else {
+        return allele.to_string();
+    };
+    let (name, wrapped) = match contig.strip_prefix('<').and_then(|c| c.strip_suffix('>')) {
+        Some(name) => (name, true),
+        None => (contig, false),
+    };
+    let Some(new) = rename(name) else {
+        return allele.to_string();
+    };
+    let contig = if wrapped { format!("<{new}>") } else { new };
+    format!(
+        "{}{contig}:{position}{}",
+        &allele[..=open],
+        &allele[close..]
+    )
+}
+
+/// The tab-delimited first column of a non-empty data line
+fn first_column(line: &str) -> Option<ContigField> {
+    let end = line.find('\t').unwrap_or(line.len());
+    (end > 0).then_some(ContigField {
+        start: 0,
+        end,
+        record: true,
+    })
+}
+
+/// The value of `key` within `rest`, a suffix of `line`, where fields are
+/// separated by `separator`
+fn header_value(line: &str, rest: &str, key: &str, separator: char) -> Option<ContigField> {
+    let offset = line.len() - rest.len();
+    let mut position = 0;
+    for field in rest.split(separator) {
+        if let Some(value) = field.strip_prefix(key) {
+            let value = value.trim_end_matches('>');
+            let start = offset + position + key.len();
+            return Some(ContigField {
+                start,
+                end: start + value.len(),
+                record: false,
+            });
+        }
+        position += field.len() + separator.len_utf8();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::contig::Contig;
+    use crate::core::reference::KnownReference;
+    use crate::core::types::{Assembly, ReferenceSource};
+
+    fn make_names() -> NameMap {
+        let reference = KnownReference::new(
+            "test_ref",
+            "Test Reference",
+            Assembly::Grch38,
+            ReferenceSource::Custom("test".to_string()),
+        )
+        .with_contigs(vec![
+            Contig::new("chr1", 1000).with_aliases(vec!["1".to_string()]),
+            Contig::new("chrM", 16569).with_aliases(vec!["MT".to_string()]),
+        ]);
+        NameMap::to_reference(&reference)
+    }
+
+    fn rename(text: &str, format: RenameFormat, drop_unmapped: bool) -> (String, RenameReport) {
+        let mut output = Vec::new();
+        let report = rename_contigs(
+            text.as_bytes(),
+            &mut output,
+            format,
+            &make_names(),
+            drop_unmapped,
+        )
+        .unwrap();
+        (String::from_utf8(output).unwrap(), report)
+    }
+
+    #[test]
+    fn test_rename_vcf() {
+        let vcf = "##fileformat=VCFv4.2\n\
+##contig=<ID=1,length=1000>\n\
+##contig=<ID=MT>\n\
+##contig=<ID=GL000192.1,length=547496>\n\
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+1\t10\t.\tA\tG\t.\tPASS\t.\n\
+GL000192.1\t5\t.\tC\tT\t.\tPASS\t.\n";
+
+        let (output, report) = rename(vcf, RenameFormat::Vcf, false);
+
+        assert!(output.contains("##contig=<ID=chr1,length=1000>\n"));
+        assert!(output.contains("##contig=<ID=chrM>\n"));
+        assert!(output.contains("\nchr1\t10\t"));
+        assert!(output.contains("\nGL000192.1\t5\t"));
+        assert_eq!(report.records, 2);
+        assert_eq!(report.renamed, 1);
+        assert_eq!(report.unmapped.get("GL000192.1"), Some(&1));
+
+        let (output, report) = rename(vcf, RenameFormat::Vcf, true);
+
+        assert!(!output.contains("GL000192.1"));
+        assert_eq!(report.dropped, 1);
+    }
+
+    #[test]
+    fn test_rename_vcf_mates() {
+        let vcf = "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+1\t10\tbnd1\tA\tA[MT:500[\t.\tPASS\tSVTYPE=BND\n\
+MT\t500\tbnd2\tG\t]1:10]G,]<1>:20]G\t.\tPASS\tSVTYPE=BND\n\
+chr1\t30\tdel\tC\t<DEL>\t.\tPASS\tSVTYPE=TRA;CHR2=MT;END=40\n\
+1\t50\tbnd3\tT\tT]GL000192.1:5]\t.\tPASS\tCHR2=GL000192.1\n";
+
+        let (output, report) = rename(vcf, RenameFormat::Vcf, false);
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines[1],
+            "chr1\t10\tbnd1\tA\tA[chrM:500[\t.\tPASS\tSVTYPE=BND"
+        );
+        assert_eq!(
+            lines[2],
+            "chrM\t500\tbnd2\tG\t]chr1:10]G,]<chr1>:20]G\t.\tPASS\tSVTYPE=BND"
+        );
+        // Only the mate needed renaming
+        assert_eq!(
+            lines[3],
+            "chr1\t30\tdel\tC\t<DEL>\t.\tPASS\tSVTYPE=TRA;CHR2=chrM;END=40"
+        );
+        assert_eq!(
+            lines[4],
+            "chr1\t50\tbnd3\tT\tT]GL000192.1:5]\t.\tPASS\tCHR2=GL000192.1"
+        );
+        assert_eq!(report.renamed, 4);
+        assert_eq!(report.unmapped_mates, 1);
+        assert_eq!(report.unmapped.get("GL000192.1"), Some(&2));
+    }
+
+    #[test]
+    fn test_rename_bed_and_gff() {
+        let bed = "track name=test\n1\t0\t100\tfeature\nMT\t5\t10\n";
+        let (output, _) = rename(bed, RenameFormat::Bed, false);
+        assert_eq!(
+            output,
+            "track name=test\nchr1\t0\t100\tfeature\nchrM\t5\t10\n"
+        );
+
+        let gff = "##gff-version 3\n##sequence-region 1 1 1000\n1\tsrc\tgene\t1\t10\t.\t+\t.\tID=g\n##FASTA\n>1\nACGT\n";
+        let (output, report) = rename(gff, RenameFormat::Gff, false);
+        assert_eq!(
+            output,
+            "##gff-version 3\n##sequence-region chr1 1 1000\nchr1\tsrc\tgene\t1\t10\t.\t+\t.\tID=g\n##FASTA\n>1\nACGT\n"
+        );
+        assert_eq!(report.records, 1);
+    }
+
+    #[test]
+    fn test_rename_keeps_line_endings() {
+        let bed = "track name=test\r\n1\t0\t100\r\nMT\t5\t10";
+        let (output, report) = rename(bed, RenameFormat::Bed, false);
+        assert_eq!(output, "track name=test\r\nchr1\t0\t100\r\nchrM\t5\t10");
+        assert_eq!(report.renamed, 2);
+
+        let (output, _) = rename("1\t0\t100\r\nMT\t5\t10\r\n", RenameFormat::Bed, true);
+        assert_eq!(output, "chr1\t0\t100\r\nchrM\t5\t10\r\n");
+    }
+
+    #[test]
+    fn test_rename_interval_list() {
+        let intervals =
+            "@HD\tVN:1.6\n@SQ\tSN:1\tLN:1000\n@SQ\tSN:2\tLN:2000\n1\t1\t100\t+\ttarget\n";
+
+        let (output, report) = rename(intervals, RenameFormat::IntervalList, true);
+
+        assert_eq!(
+            output,
+            "@HD\tVN:1.6\n@SQ\tSN:chr1\tLN:1000\nchr1\t1\t100\t+\ttarget\n"
+        );
+        assert_eq!(report.unmapped.get("2"), Some(&0));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            RenameFormat::from_path(Path::new("calls.vcf.gz")),
+            Some(RenameFormat::Vcf)
+        );
+        assert_eq!(
+            RenameFormat::from_path(Path::new("genes.gff3")),
+            Some(RenameFormat::Gff)
+        );
+        assert_eq!(
+            RenameFormat::from_path(Path::new("targets.interval_list")),
+            Some(RenameFormat::IntervalList)
+        );
+        assert_eq!(RenameFormat::from_path(Path::new("reads.bam")), None);
+    }
+}
@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::io::BufReader;
+use std::io::{BufRead, BufReader};
 use std::path::Path;
 use thiserror::Error;
 use tracing::warn;
@@ -35,53 +35,79 @@ pub enum ParseError {
 /// `ParseError::InvalidFormat` if no contigs are found, or
 /// `ParseError::TooManyContigs` if the limit is exceeded.
 pub fn parse_file(path: &Path) -> Result<QueryHeader, ParseError> {
+    let header = read_header(path)?;
+    header_to_query(&header, Some(path))
+}
+
+/// Parse the SAM-style header of a Picard interval list
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if the file cannot be read, or
+/// `ParseError::InvalidFormat` if the header has no `@SQ` lines.
+pub fn parse_interval_list_file(path: &Path) -> Result<QueryHeader, ParseError> {
+    let mut header = String::new();
+    for line in BufReader::new(std::fs::File::open(path)?).lines() {
+        let line = line?;
+        if !line.starts_with('@') {
+            break;
+        }
+        header.push_str(&line);
+        header.push('\n');
+    }
+    parse_header_text(&header)
+}
+
+/// Read the full header of a SAM/BAM/CRAM file, chosen by extension
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if the file cannot be read, `ParseError::Noodles` if
+/// parsing fails, or `ParseError::UnsupportedFormat` for unknown extensions.
+pub fn read_header(path: &Path) -> Result<noodles::sam::Header, ParseError> {
     let extension = path
         .extension()
         .and_then(|e| e.to_str())
         .map(str::to_lowercase);
 
     match extension.as_deref() {
-        Some("sam") => parse_sam_file(path),
-        Some("bam") => parse_bam_file(path),
-        Some("cram") => parse_cram_file(path),
+        Some("sam") => read_sam_header(path),
+        Some("bam") => read_bam_header(path),
+        Some("cram") => read_cram_header(path),
         Some(ext) => Err(ParseError::UnsupportedFormat(ext.to_string())),
         None => {
             // Try to detect from content - default to SAM
-            parse_sam_file(path)
+            read_sam_header(path)
         }
     }
 }
 
-/// Parse a SAM file (text format)
-fn parse_sam_file(path: &Path) -> Result<QueryHeader, ParseError> {
+/// Read the header of a SAM file (text format)
+fn read_sam_header(path: &Path) -> Result<noodles::sam::Header, ParseError> {
     use noodles::sam;
 
     let mut reader = std::fs::File::open(path)
         .map(BufReader::new)
         .map(sam::io::Reader::new)?;
 
-    let header = reader
+    reader
         .read_header()
-        .map_err(|e| ParseError::Noodles(e.to_string()))?;
-
-    header_to_query(&header, Some(path))
+        .map_err(|e| ParseError::Noodles(e.to_string()))
 }
 
-/// Parse a BAM file (binary format)
-fn parse_bam_file(path: &Path) -> Result<QueryHeader, ParseError> {
+/// Read the header of a BAM file (binary format)
+fn read_bam_header(path: &Path) -> Result<noodles::sam::Header, ParseError> {
     use noodles::bam;
 
     let mut reader = std::fs::File::open(path).map(bam::io::Reader::new)?;
 
-    let header = reader
+    reader
         .read_header()
-        .map_err(|e| ParseError::Noodles(e.to_string()))?;
-
-    header_to_query(&header, Some(path))
+        .map_err(|e| ParseError::Noodles(e.to_string()))
 }
 
-/// Parse a CRAM file
-fn parse_cram_file(path: &Path) -> Result<QueryHeader, ParseError> {
+/// Read the header of a CRAM file
+fn read_cram_header(path: &Path) -> Result<noodles::sam::Header, ParseError> {
     use noodles::cram;
 
     let mut reader = std::fs::File::open(path).map(cram::io::Reader::new)?;
@@ -91,11 +117,22 @@ fn parse_cram_file(path: &Path) -> Result<QueryHeader, ParseError> {
         .read_file_definition()
         .map_err(|e| ParseError::Noodles(e.to_string()))?;
 
-    let header = reader
+    reader
         .read_file_header()
-        .map_err(|e| ParseError::Noodles(e.to_string()))?;
+        .map_err(|e| ParseError::Noodles(e.to_string()))
+}
 
-    header_to_query(&header, Some(path))
+/// Parse raw header text (stdin or pasted) into a full noodles header,
+/// keeping every record, not just `@SQ` lines
+///
+/// # Errors
+///
+/// Returns `ParseError::Noodles` if the text is not a valid SAM header.
+pub fn read_header_text(text: &str) -> Result<noodles::sam::Header, ParseError> {
+    let (normalized_text, _) = normalize_sam_whitespace(text);
+    normalized_text
+        .parse()
+        .map_err(|e: noodles::sam::header::ParseError| ParseError::Noodles(e.to_string()))
 }
 
 /// Parse a BAM header from any reader (no file path required).
@@ -149,7 +186,11 @@ pub fn parse_cram_from_reader<R: std::io::Read>(reader: R) -> Result<QueryHeader
 }
 
 /// Convert noodles header to `QueryHeader`
-fn header_to_query(
+///
+/// # Errors
+///
+/// Returns `ParseError::TooManyContigs` if the limit is exceeded.
+pub fn header_to_query(
     header: &noodles::sam::Header,
     source: Option<&Path>,
 ) -> Result<QueryHeader, ParseError> {
@@ -233,6 +274,41 @@ fn header_to_query(
     Ok(query)
 }
 
+/// Fill the `M5`, `AS`, `UR`, `SP` and `AN` tags of an `@SQ` record from a
+/// contig, keeping any tag the record already has.
+///
+/// Returns the tags that were added.
+pub fn fill_reference_sequence_tags(
+    map: &mut noodles::sam::header::record::value::Map<
+        noodles::sam::header::record::value::map::ReferenceSequence,
+    >,
+    contig: &Contig,
+) -> Vec<&'static str> {
+    use noodles::sam::header::record::value::map::reference_sequence::tag;
+
+    let aliases = (!contig.aliases.is_empty()).then(|| contig.aliases.join(","));
+    let fields = [
+        (tag::MD5_CHECKSUM, "M5", contig.md5.as_ref()),
+        (tag::ASSEMBLY_ID, "AS", contig.assembly.as_ref()),
+        (tag::URI, "UR", contig.uri.as_ref()),
+        (tag::SPECIES, "SP", contig.species.as_ref()),
+        (tag::ALTERNATIVE_NAMES, "AN", aliases.as_ref()),
+    ];
+
+    let mut added = Vec::new();
+    for (tag, label, value) in fields {
+        let Some(value) = value else {
+            continue;
+        };
+        if map.other_fields().contains_key(&tag) {
+            continue;
+        }
+        map.other_fields_mut().insert(tag, value.as_str().into());
+        added.push(label);
+    }
+    added
+}
+
 /// Normalize SAM header lines that use spaces instead of tabs.
 ///
 /// Browsers and copy-paste often convert tabs to spaces. This function detects
@@ -426,6 +502,24 @@ mod tests {
         assert!(query.contigs[2].md5.is_none());
     }
 
+    #[test]
+    fn test_parse_interval_list_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("targets.interval_list");
+        std::fs::write(
+            &path,
+            "@HD\tVN:1.6\n@SQ\tSN:chr1\tLN:1000\n@SQ\tSN:chr2\tLN:2000\nchr1\t1\t100\t+\ttarget\n",
+        )
+        .unwrap();
+
+        let query = parse_interval_list_file(&path).unwrap();
+        assert_eq!(query.contigs.len(), 2);
+        assert_eq!(query.contigs[1].name, "chr2");
+
+        std::fs::write(&path, "chr1\t1\t100\t+\ttarget\n").unwrap();
+        assert!(parse_interval_list_file(&path).is_err());
+    }
+
     #[test]
     fn test_parse_header_text_no_sq() {
         let header = "@HD\tVN:1.6\n@RG\tID:sample1\n";
@@ -462,6 +556,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fill_reference_sequence_tags_keeps_existing() {
+        let mut header =
+            read_header_text("@HD\tVN:1.6\n@SQ\tSN:chr1\tLN:1000\tAS:hg38\n@RG\tID:rg1\n").unwrap();
+        let mut contig = Contig::new("chr1", 1000)
+            .with_md5("6aef897c3d6ff0c78aff06ac189178dd")
+            .with_aliases(vec!["1".to_string()]);
+        contig.assembly = Some("GRCh38".to_string());
+
+        let (_, map) = header.reference_sequences_mut().get_index_mut(0).unwrap();
+        let added = fill_reference_sequence_tags(map, &contig);
+        assert_eq!(added, vec!["M5", "AN"]);
+
+        let mut buffer = Vec::new();
+        noodles::sam::io::Writer::new(&mut buffer)
+            .write_header(&header)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "@HD\tVN:1.6\n\
+             @SQ\tSN:chr1\tLN:1000\tAS:hg38\tM5:6aef897c3d6ff0c78aff06ac189178dd\tAN:1\n\
+             @RG\tID:rg1\n"
+        );
+    }
+
     #[test]
     fn test_normalize_sam_whitespace_spaces_to_tabs() {
         let input = "@SQ SN:chr1 LN:248956422 M5:6aef897c3d6ff0c78aff06ac189178dd\n";
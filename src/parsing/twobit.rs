@@ -0,0 +1,167 @@
+//! Parser for UCSC `.2bit` files.
+//!
+//! Only the sequence names and lengths are read: the file index is followed to
+//! each record's header, and the packed sequence itself is never decoded, so
+//! no digests are available.
+//!
+//! Format: <https://genome.ucsc.edu/FAQ/FAQformat.html#format7>
+
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::parsing::sam::ParseError;
+use crate::utils::validation::check_contig_limit;
+
+/// Signature at the start of every `.2bit` file, in the file's byte order
+const TWOBIT_SIGNATURE: u32 = 0x1A41_2743;
+
+/// Check if a path has a `.2bit` extension
+#[must_use]
+pub fn is_twobit_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("2bit"))
+}
+
+/// Parse the sequence names and lengths of a `.2bit` file
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if the file cannot be read, `ParseError::InvalidFormat`
+/// if it is not a valid `.2bit` file or has no sequences, or
+/// `ParseError::TooManyContigs` if the limit is exceeded.
+pub fn parse_twobit_file(path: &Path) -> Result<QueryHeader, ParseError> {
+    let mut reader = BufReader::new(std::fs::File::open(path)?);
+    parse_twobit_reader(&mut reader)
+}
+
+fn parse_twobit_reader<R: Read + Seek>(reader: &mut R) -> Result<QueryHeader, ParseError> {
+    let signature = read_array::<4, _>(reader)?;
+    let big_endian = if u32::from_le_bytes(signature) == TWOBIT_SIGNATURE {
+        false
+    } else if u32::from_be_bytes(signature) == TWOBIT_SIGNATURE {
+        true
+    } else {
+        return Err(ParseError::InvalidFormat(
+            "Not a 2bit file (bad signature)".to_string(),
+        ));
+    };
+    let read_u32 = |reader: &mut R| -> Result<u32, ParseError> {
+        let bytes = read_array::<4, _>(reader)?;
+        Ok(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+
+    // Version 1 uses 64-bit record offsets
+    let version = read_u32(reader)?;
+    if version > 1 {
+        return Err(ParseError::InvalidFormat(format!(
+            "Unsupported 2bit version {version}"
+        )));
+    }
+    let count = read_u32(reader)? as usize;
+    let _reserved = read_u32(reader)?;
+
+    if check_contig_limit(count).is_some() {
+        return Err(ParseError::TooManyContigs(count));
+    }
+
+    let mut index = Vec::with_capacity(count);
+    for _ in 0..count {
+        let [name_len] = read_array::<1, _>(reader)?;
+        let mut name = vec![0; usize::from(name_len)];
+        reader.read_exact(&mut name)?;
+        let offset = if version == 1 {
+            let bytes = read_array::<8, _>(reader)?;
+            if big_endian {
+                u64::from_be_bytes(bytes)
+            } else {
+                u64::from_le_bytes(bytes)
+            }
+        } else {
+            u64::from(read_u32(reader)?)
+        };
+        index.push((String::from_utf8_lossy(&name).into_owned(), offset));
+    }
+
+    // Each record starts with its sequence length
+    let mut contigs = Vec::with_capacity(count);
+    for (name, offset) in index {
+        reader.seek(SeekFrom::Start(offset))?;
+        let length = read_u32(reader)?;
+        contigs.push(Contig::new(name, u64::from(length)));
+    }
+
+    if contigs.is_empty() {
+        return Err(ParseError::InvalidFormat(
+            "No sequences found in 2bit file".to_string(),
+        ));
+    }
+
+    Ok(QueryHeader::new(contigs))
+}
+
+fn read_array<const N: usize, R: Read>(reader: &mut R) -> Result<[u8; N], ParseError> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::UnexpectedEof {
+            ParseError::InvalidFormat("Truncated 2bit file".to_string())
+        } else {
+            ParseError::Io(e)
+        }
+    })?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Build a version 0 `.2bit` file with empty record bodies
+    fn twobit(records: &[(&str, u32)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(TWOBIT_SIGNATURE.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(u32::try_from(records.len()).unwrap().to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+
+        let index_len: usize = records.iter().map(|(name, _)| 1 + name.len() + 4).sum();
+        let mut offset = bytes.len() + index_len;
+        for (name, _) in records {
+            bytes.push(u8::try_from(name.len()).unwrap());
+            bytes.extend(name.as_bytes());
+            bytes.extend(u32::try_from(offset).unwrap().to_le_bytes());
+            // dnaSize, nBlockCount, maskBlockCount, reserved
+            offset += 16;
+        }
+        for (_, length) in records {
+            bytes.extend(length.to_le_bytes());
+            bytes.extend([0; 12]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_parse_twobit() {
+        let bytes = twobit(&[("chr1", 248_956_422), ("chrM", 16569)]);
+        let query = parse_twobit_reader(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(query.contigs.len(), 2);
+        assert_eq!(query.contigs[0].name, "chr1");
+        assert_eq!(query.contigs[0].length, 248_956_422);
+        assert_eq!(query.contigs[1].name, "chrM");
+        assert_eq!(query.contigs[1].length, 16569);
+    }
+
+    #[test]
+    fn test_parse_twobit_rejects_other_files() {
+        let result = parse_twobit_reader(&mut Cursor::new(b">chr1\nACGT\n".to_vec()));
+        assert!(matches!(result, Err(ParseError::InvalidFormat(_))));
+    }
+}
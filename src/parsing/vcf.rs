@@ -3,13 +3,17 @@
 //! VCF files have contig definitions in the header as:
 //! `##contig=<ID=chr1,length=248956422>`
 //!
-//! Additional fields like `md5` and `assembly` may also be present.
+//! Additional fields like `md5` and `assembly` may also be present, and the
+//! FASTA the calls were made against is often named by a `##reference=` line.
 //!
 //! Uses noodles for file parsing, with manual fallback for text parsing
 //! to ensure all fields (including MD5) are properly extracted.
 
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 
+use flate2::read::MultiGzDecoder;
+
 use crate::core::contig::Contig;
 use crate::core::header::QueryHeader;
 use crate::parsing::sam::ParseError;
@@ -22,8 +26,55 @@ use crate::utils::validation::{check_contig_limit, normalize_md5};
 /// Returns `ParseError::Io` if the file cannot be read, or other parse errors
 /// if the content is invalid.
 pub fn parse_vcf_file(path: &Path) -> Result<QueryHeader, ParseError> {
-    let content = std::fs::read_to_string(path)?;
-    parse_vcf_header_text(&content)
+    parse_vcf_header_text(&read_vcf_header(path)?)
+}
+
+/// Read the header of a VCF file, plain or gzip/BGZF-compressed, up to and
+/// including the `#CHROM` line. The records are not read.
+///
+/// # Errors
+///
+/// Returns `ParseError::Io` if the file cannot be read or decompressed, or
+/// `ParseError::InvalidFormat` if the header is not valid UTF-8.
+pub fn read_vcf_header(path: &Path) -> Result<String, ParseError> {
+    let mut magic = [0u8; 2];
+    let gzipped = std::fs::File::open(path)?.read(&mut magic)? == 2 && magic == [0x1f, 0x8b];
+    let file = std::fs::File::open(path)?;
+    let reader: Box<dyn BufRead> = if gzipped {
+        Box::new(BufReader::new(MultiGzDecoder::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    let mut header = String::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidData => {
+                ParseError::InvalidFormat("VCF header is not valid UTF-8 text".to_string())
+            }
+            _ => ParseError::Io(e),
+        })?;
+        if !line.starts_with('#') {
+            break;
+        }
+        header.push_str(&line);
+        header.push('\n');
+        if line.starts_with("#CHROM") {
+            break;
+        }
+    }
+    Ok(header)
+}
+
+/// The value of the `##reference=` header line, e.g.
+/// `file:///refs/Homo_sapiens_assembly38.fasta`, if present
+#[must_use]
+pub fn reference_line(text: &str) -> Option<&str> {
+    text.lines()
+        .take_while(|line| !line.starts_with("#CHROM"))
+        .find_map(|line| line.strip_prefix("##reference="))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
 }
 
 /// Parse VCF header text and extract contig definitions
@@ -174,6 +225,97 @@ mod tests {
         assert_eq!(query.contigs[2].assembly, Some("GRCh38".to_string()));
     }
 
+    #[test]
+    fn test_reference_line() {
+        let header = "##fileformat=VCFv4.2\n##reference=file:///refs/hs37d5.fa\n#CHROM\tPOS\n";
+        assert_eq!(reference_line(header), Some("file:///refs/hs37d5.fa"));
+        assert_eq!(reference_line("##fileformat=VCFv4.2\n"), None);
+    }
+
+    #[test]
+    fn test_read_vcf_header_gzipped() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("calls.vcf.gz");
+        let mut encoder = GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder
+            .write_all(b"##contig=<ID=chr1,length=1000>\n#CHROM\tPOS\nchr1\t5\n")
+            .unwrap();
+        encoder.finish().unwrap();
+
+        let header = read_vcf_header(&path).unwrap();
+        assert_eq!(header, "##contig=<ID=chr1,length=1000>\n#CHROM\tPOS\n");
+        assert_eq!(parse_vcf_file(&path).unwrap().contigs.len(), 1);
+    }
+
+    #[test]
+    fn test_read_vcf_header_stops_at_records() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("calls.vcf");
+        // Records are never read, so bytes that are not UTF-8 in them are harmless
+        let mut content =
+            b"##fileformat=VCFv4.2\n##contig=<ID=chr1,length=1000>\n#CHROM\tPOS\n".to_vec();
+        content.extend_from_slice(b"chr1\t5\t\xff\xfe\n");
+        std::fs::write(&path, content).unwrap();
+
+        let header = read_vcf_header(&path).unwrap();
+        assert!(header.ends_with("#CHROM\tPOS\n"));
+        assert_eq!(parse_vcf_file(&path).unwrap().contigs.len(), 1);
+    }
+
+    #[test]
+    fn test_read_vcf_header_without_chrom_line() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("calls.vcf");
+        std::fs::write(&path, "##contig=<ID=chr1,length=1000>\nchr1\t5\n").unwrap();
+
+        assert_eq!(
+            read_vcf_header(&path).unwrap(),
+            "##contig=<ID=chr1,length=1000>\n"
+        );
+    }
+
+    #[test]
+    fn test_read_vcf_header_bgzf_blocks() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        // BGZF files are concatenated gzip members; the header spans two
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("calls.vcf.gz");
+        let mut file = std::fs::File::create(&path).unwrap();
+        for block in [
+            &b"##fileformat=VCFv4.2\n##contig=<ID=chr1,"[..],
+            &b"length=1000>\n##contig=<ID=chr2,length=2000>\n#CHROM\tPOS\nchr1\t5\n"[..],
+        ] {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(block).unwrap();
+            file.write_all(&encoder.finish().unwrap()).unwrap();
+        }
+        drop(file);
+
+        let query = parse_vcf_file(&path).unwrap();
+        assert_eq!(query.contigs.len(), 2);
+        assert_eq!(query.contigs[1].length, 2000);
+    }
+
+    #[test]
+    fn test_read_vcf_header_invalid_utf8() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("calls.vcf");
+        std::fs::write(&path, b"##contig=<ID=chr\xff,length=1000>\n#CHROM\n").unwrap();
+
+        assert!(matches!(
+            read_vcf_header(&path),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
     #[test]
     fn test_parse_vcf_no_contigs() {
         let vcf = "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\n";
@@ -0,0 +1,463 @@
+//! Persistent on-disk cache for refget lookups.
+//!
+//! Lookup results (`Found` and `NotFound`) are stored as JSON keyed by server
+//! and digest, each with the time it was fetched. Servers are kept apart
+//! because one server's "not found" says nothing about another's holdings.
+//! Entries older than the cache TTL are ignored and dropped on the next save.
+//! Errors are never cached so transient failures are retried.
+//!
+//! The same cache file is shared by the CLI and the web server. Saves merge with
+//! whatever is on disk under a file lock, so concurrent processes do not discard
+//! each other's entries.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::refget::RefgetLookupResult;
+use crate::utils::json_file::{self, JsonFileError, VersionedJson};
+
+/// Default time-to-live for cached lookups, in days
+pub const DEFAULT_CACHE_TTL_DAYS: u64 = 30;
+
+/// Cache file format version. Version 2 keys entries by server and requires
+/// an `md5` in found results.
+const CACHE_VERSION: u32 = 2;
+
+/// Version 1 files keyed entries by digest alone, and found results written
+/// before lookups back-filled MD5s lack one
+const CACHE_VERSION_V1: u32 = 1;
+
+/// Upper bound on the TTL, in days (effectively "never expire")
+const MAX_CACHE_TTL_DAYS: i64 = 365 * 1000;
+
+/// A cached refget lookup result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// The digest that was looked up
+    pub digest: String,
+    /// The lookup result (`Found` or `NotFound`)
+    pub result: RefgetLookupResult,
+    /// When the result was fetched from the server
+    pub fetched_at: DateTime<Utc>,
+    /// The server the result was fetched from
+    pub server: String,
+}
+
+/// Entries are keyed by `(server, digest)`
+type CacheKey = (String, String);
+
+/// Version 1 entry: the digest was the map key. The result is parsed
+/// separately so that entries without an `md5` can be dropped.
+#[derive(Deserialize)]
+struct CacheEntryV1 {
+    result: serde_json::Value,
+    fetched_at: DateTime<Utc>,
+    server: String,
+}
+
+/// Persistent refget lookup cache, keyed by server and digest
+#[derive(Debug)]
+pub struct RefgetCache {
+    path: PathBuf,
+    ttl: chrono::Duration,
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+}
+
+impl RefgetCache {
+    /// Default cache location: `$XDG_CACHE_HOME/ref-solver/refget-cache.json`,
+    /// falling back to `~/.cache/ref-solver/refget-cache.json`.
+    ///
+    /// Returns `None` if neither `XDG_CACHE_HOME` nor `HOME` is set.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        crate::utils::cache_dir().map(|dir| dir.join("refget-cache.json"))
+    }
+
+    /// Open the cache at `path`, loading existing entries if the file exists
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn open(path: impl Into<PathBuf>, ttl_days: u64) -> Result<Self, JsonFileError> {
+        let path = path.into();
+        let entries = if path.exists() {
+            read_cache_file(&path)?.into_iter().map(keyed).collect()
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path,
+            ttl: chrono::Duration::days(
+                i64::try_from(ttl_days).map_or(MAX_CACHE_TTL_DAYS, |d| d.min(MAX_CACHE_TTL_DAYS)),
+            ),
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Path of the cache file
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Look up an unexpired result for a digest from `server`
+    #[must_use]
+    pub fn get(&self, digest: &str, server: &str) -> Option<RefgetLookupResult> {
+        let entries = self.lock();
+        entries
+            .get(&cache_key(server, digest))
+            .filter(|entry| !self.is_expired(entry))
+            .map(|entry| entry.result.clone())
+    }
+
+    /// Record a lookup result. Errors are not cached.
+    pub fn insert(&self, digest: &str, result: &RefgetLookupResult, server: &str) {
+        if matches!(result, RefgetLookupResult::Error { .. }) {
+            return;
+        }
+        let key = cache_key(server, digest);
+        self.lock().insert(
+            key.clone(),
+            CacheEntry {
+                digest: key.1,
+                result: result.clone(),
+                fetched_at: Utc::now(),
+                server: key.0,
+            },
+        );
+    }
+
+    /// Whether an entry is older than the cache TTL
+    #[must_use]
+    pub fn is_expired(&self, entry: &CacheEntry) -> bool {
+        Utc::now().signed_duration_since(entry.fetched_at) > self.ttl
+    }
+
+    /// All entries (including expired ones), sorted by digest and server
+    #[must_use]
+    pub fn entries(&self) -> Vec<CacheEntry> {
+        let mut entries: Vec<_> = self.lock().values().cloned().collect();
+        entries.sort_by(|a, b| (&a.digest, &a.server).cmp(&(&b.digest, &b.server)));
+        entries
+    }
+
+    /// Remove expired entries, returning how many were removed
+    pub fn prune(&self) -> usize {
+        let mut entries = self.lock();
+        let before = entries.len();
+        entries.retain(|_, entry| !self.is_expired(entry));
+        before - entries.len()
+    }
+
+    /// Merge entries from another cache file, keeping the newer entry for each
+    /// server and digest. Returns the number of entries added or updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn import(&self, path: &Path) -> Result<usize, JsonFileError> {
+        let imported = read_cache_file(path)?;
+        Ok(self.merge(imported))
+    }
+
+    /// Write all unexpired entries to `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn export(&self, path: &Path) -> Result<usize, JsonFileError> {
+        let entries: Vec<CacheEntry> = self
+            .entries()
+            .into_iter()
+            .filter(|entry| !self.is_expired(entry))
+            .collect();
+        let count = entries.len();
+        write_cache_file(path, &entries)?;
+        Ok(count)
+    }
+
+    /// Save the cache to disk, merging with any entries written by other processes.
+    /// Expired entries are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file cannot be locked, read or written.
+    pub fn save(&self) -> Result<(), JsonFileError> {
+        let _lock = json_file::lock(&self.path)?;
+        if self.path.exists() {
+            let on_disk = read_cache_file(&self.path)?;
+            self.merge(on_disk);
+        }
+        self.prune();
+
+        write_cache_file(&self.path, &self.entries())
+    }
+
+    /// Merge entries, keeping the newer entry for each key and skipping expired ones
+    fn merge(&self, other: Vec<CacheEntry>) -> usize {
+        let mut entries = self.lock();
+        let mut merged = 0;
+        for (key, entry) in other.into_iter().map(keyed) {
+            if self.is_expired(&entry) {
+                continue;
+            }
+            let newer = entries
+                .get(&key)
+                .map_or(true, |existing| entry.fetched_at > existing.fetched_at);
+            if newer {
+                entries.insert(key, entry);
+                merged += 1;
+            }
+        }
+        merged
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<CacheKey, CacheEntry>> {
+        // A poisoned lock only means another thread panicked mid-update of a map
+        // of independent entries, so the data is still usable.
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Normalize a server URL and digest for use as a cache key (a trailing
+/// slash does not change the server, and MD5 is case-insensitive)
+fn cache_key(server: &str, digest: &str) -> CacheKey {
+    let digest = if digest.len() == 32 && digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+        digest.to_ascii_lowercase()
+    } else {
+        digest.to_string()
+    };
+    (server.trim_end_matches('/').to_string(), digest)
+}
+
+/// Pair an entry read from disk with its key, normalizing its server and digest
+fn keyed(mut entry: CacheEntry) -> (CacheKey, CacheEntry) {
+    let key = cache_key(&entry.server, &entry.digest);
+    (entry.server, entry.digest) = key.clone();
+    (key, entry)
+}
+
+fn read_cache_file(path: &Path) -> Result<Vec<CacheEntry>, JsonFileError> {
+    let file = VersionedJson::read(path)?;
+    match file.version {
+        CACHE_VERSION => file.entries(),
+        CACHE_VERSION_V1 => {
+            let entries: BTreeMap<String, CacheEntryV1> = file.entries()?;
+            // Results that no longer parse (found results without an MD5)
+            // are dropped, so they are looked up again
+            Ok(entries
+                .into_iter()
+                .filter_map(|(digest, entry)| {
+                    Some(CacheEntry {
+                        digest,
+                        result: serde_json::from_value(entry.result).ok()?,
+                        fetched_at: entry.fetched_at,
+                        server: entry.server,
+                    })
+                })
+                .collect())
+        }
+        _ => Err(file.unsupported()),
+    }
+}
+
+/// Write the cache, indented since it is meant to be exported and shared
+fn write_cache_file(path: &Path, entries: &[CacheEntry]) -> Result<(), JsonFileError> {
+    json_file::write_entries(path, CACHE_VERSION, &entries, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::refget::RefgetAlias;
+
+    fn found() -> RefgetLookupResult {
+        RefgetLookupResult::Found {
+            aliases: vec![RefgetAlias {
+                naming_authority: "insdc".to_string(),
+                value: "CM000663.2".to_string(),
+            }],
+            md5: MD5.to_string(),
+            sha512t24u: "SQ.2YnepKM7OkBoOrKmvHbGqguVfF9amCST".to_string(),
+            circular: false,
+        }
+    }
+
+    const MD5: &str = "6aef897c3d6ff0c78aff06ac189178dd";
+
+    #[test]
+    fn test_insert_save_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("cache.json");
+
+        let cache = RefgetCache::open(&path, 30).unwrap();
+        cache.insert(MD5, &found(), "s");
+        cache.insert(
+            "00000000000000000000000000000000",
+            &RefgetLookupResult::NotFound,
+            "s",
+        );
+        cache.insert(
+            "11111111111111111111111111111111",
+            &RefgetLookupResult::Error {
+                message: "timeout".to_string(),
+            },
+            "s",
+        );
+        cache.save().unwrap();
+
+        let reopened = RefgetCache::open(&path, 30).unwrap();
+        assert_eq!(reopened.entries().len(), 2);
+        // MD5 keys are case-insensitive
+        assert!(matches!(
+            reopened.get(&MD5.to_uppercase(), "s"),
+            Some(RefgetLookupResult::Found { .. })
+        ));
+        assert!(matches!(
+            reopened.get("00000000000000000000000000000000", "s"),
+            Some(RefgetLookupResult::NotFound)
+        ));
+        assert!(reopened
+            .get("11111111111111111111111111111111", "s")
+            .is_none());
+    }
+
+    #[test]
+    fn test_expired_entries_are_ignored_and_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = RefgetCache::open(dir.path().join("cache.json"), 0).unwrap();
+        cache.lock().insert(
+            cache_key("s", MD5),
+            CacheEntry {
+                digest: MD5.to_string(),
+                result: found(),
+                fetched_at: Utc::now() - chrono::Duration::days(1),
+                server: "s".to_string(),
+            },
+        );
+
+        assert!(cache.get(MD5, "s").is_none());
+        assert_eq!(cache.prune(), 1);
+        assert!(cache.entries().is_empty());
+    }
+
+    #[test]
+    fn test_save_merges_with_other_writers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+
+        let a = RefgetCache::open(&path, 30).unwrap();
+        let b = RefgetCache::open(&path, 30).unwrap();
+        a.insert(MD5, &found(), "s");
+        a.save().unwrap();
+        b.insert(
+            "00000000000000000000000000000000",
+            &RefgetLookupResult::NotFound,
+            "s",
+        );
+        b.save().unwrap();
+
+        let reopened = RefgetCache::open(&path, 30).unwrap();
+        assert_eq!(reopened.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_export_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = RefgetCache::open(dir.path().join("a.json"), 30).unwrap();
+        source.insert(MD5, &found(), "s");
+        let export_path = dir.path().join("export.json");
+        assert_eq!(source.export(&export_path).unwrap(), 1);
+
+        let target = RefgetCache::open(dir.path().join("b.json"), 30).unwrap();
+        assert_eq!(target.import(&export_path).unwrap(), 1);
+        assert!(target.get(MD5, "s").is_some());
+        // Importing the same entries again changes nothing
+        assert_eq!(target.import(&export_path).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_open_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            RefgetCache::open(&path, 30),
+            Err(JsonFileError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lookups_are_kept_per_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+
+        let cache = RefgetCache::open(&path, 30).unwrap();
+        cache.insert(MD5, &found(), "https://a.example.org/");
+        cache.insert(MD5, &RefgetLookupResult::NotFound, "https://b.example.org");
+        cache.save().unwrap();
+
+        let reopened = RefgetCache::open(&path, 30).unwrap();
+        assert_eq!(reopened.entries().len(), 2);
+        assert!(matches!(
+            reopened.get(MD5, "https://a.example.org"),
+            Some(RefgetLookupResult::Found { .. })
+        ));
+        assert!(matches!(
+            reopened.get(MD5, "https://b.example.org"),
+            Some(RefgetLookupResult::NotFound)
+        ));
+        assert!(reopened.get(MD5, "https://c.example.org").is_none());
+    }
+
+    #[test]
+    fn test_open_version_1_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let entry = serde_json::json!({
+            "result": {"status": "not_found"},
+            "fetched_at": Utc::now(),
+            "server": "https://example.org",
+        });
+        // Found results were written without an MD5 before it was back-filled
+        let found_without_md5 = serde_json::json!({
+            "result": {
+                "status": "found",
+                "aliases": [],
+                "sha512t24u": "SQ.2YnepKM7OkBoOrKmvHbGqguVfF9amCST",
+                "circular": false,
+            },
+            "fetched_at": Utc::now(),
+            "server": "https://example.org",
+        });
+        std::fs::write(
+            &path,
+            serde_json::json!({
+                "version": 1,
+                "entries": {MD5: entry, "SQ.2YnepKM7OkBoOrKmvHbGqguVfF9amCST": found_without_md5},
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let cache = RefgetCache::open(&path, 30).unwrap();
+        assert!(matches!(
+            cache.get(MD5, "https://example.org"),
+            Some(RefgetLookupResult::NotFound)
+        ));
+        assert!(cache.get(MD5, "https://other.example.org").is_none());
+        assert_eq!(cache.entries().len(), 1);
+
+        // Saving writes the current format
+        cache.save().unwrap();
+        let entries: Vec<CacheEntry> = json_file::read_entries(&path, CACHE_VERSION).unwrap();
+        assert_eq!(entries[0].digest, MD5);
+    }
+}
@@ -9,15 +9,128 @@ use crate::refget::{EnrichedContig, RefgetAlias, RefgetConfig, RefgetLookupResul
 
 /// Look up metadata for a set of contigs from a refget server.
 ///
-/// Only contigs that have an MD5 digest are queried. Lookups run concurrently
-/// up to `config.max_concurrent` at a time. Errors are captured per-contig
-/// and never propagate — the caller always gets results back.
+/// Contigs are looked up by MD5, falling back to the `SQ.`-prefixed sha512t24u
+/// digest for contigs without an MD5; contigs with neither are not queried.
+/// Digests missing from a contig are back-filled from the refget metadata.
+/// If `config.cache` is set, results cached for `config.server_url` are used
+/// instead of querying the server, and new results are written back to the
+/// cache on a blocking thread. Lookups run concurrently up to
+/// `config.max_concurrent` at a time. Errors are captured per-contig and never
+/// propagate — the caller always gets results back.
 ///
 /// # Panics
 ///
 /// Panics if the internal semaphore is closed, which should not happen
 /// during normal operation.
 pub async fn enrich_contigs(contigs: &[Contig], config: &RefgetConfig) -> Vec<EnrichedContig> {
+    let mut results: Vec<Option<RefgetLookupResult>> = vec![None; contigs.len()];
+
+    // Contigs without any digest can't be queried; we'll handle these in the
+    // result collection below. Cached results don't need to be queried either.
+    let mut to_query: Vec<(usize, String)> = Vec::new();
+    for (idx, contig) in contigs.iter().enumerate() {
+        let Some(digest) = lookup_digest(contig) else {
+            continue;
+        };
+        match config
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get(&digest, &config.server_url))
+        {
+            Some(cached) => results[idx] = Some(cached),
+            None => to_query.push((idx, digest)),
+        }
+    }
+
+    if !to_query.is_empty() {
+        query_server(&to_query, config, &mut results).await;
+
+        if let Some(cache) = &config.cache {
+            let mut cacheable = false;
+            for (idx, digest) in &to_query {
+                if let Some(result) = &results[*idx] {
+                    cache.insert(digest, result, &config.server_url);
+                    cacheable |= !matches!(result, RefgetLookupResult::Error { .. });
+                }
+            }
+            if cacheable {
+                // Saving re-reads and rewrites the whole file, so keep it off
+                // the async workers that serve other requests
+                let cache = Arc::clone(cache);
+                match tokio::task::spawn_blocking(move || cache.save()).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => tracing::warn!("Failed to save refget cache: {e}"),
+                    Err(e) => tracing::warn!("Refget cache save task failed: {e}"),
+                }
+            }
+        }
+    }
+
+    contigs
+        .iter()
+        .enumerate()
+        .map(|(idx, contig)| {
+            let refget_metadata = results[idx].take().unwrap_or_else(|| {
+                if contig.md5.is_none() && contig.sha512t24u.is_none() {
+                    RefgetLookupResult::Error {
+                        message: "No MD5 or sha512t24u digest available for lookup".to_string(),
+                    }
+                } else {
+                    RefgetLookupResult::Error {
+                        message: "Lookup task failed".to_string(),
+                    }
+                }
+            });
+
+            // Back-fill whichever digest type the contig is missing
+            let (md5, sha512t24u) = match &refget_metadata {
+                RefgetLookupResult::Found {
+                    md5, sha512t24u, ..
+                } => (
+                    contig
+                        .md5
+                        .clone()
+                        .or_else(|| Some(md5.to_ascii_lowercase())),
+                    contig
+                        .sha512t24u
+                        .clone()
+                        .or_else(|| Some(strip_sha512t24u_prefix(sha512t24u).to_string())),
+                ),
+                _ => (contig.md5.clone(), contig.sha512t24u.clone()),
+            };
+
+            EnrichedContig {
+                name: contig.name.clone(),
+                md5,
+                sha512t24u,
+                refget_metadata,
+            }
+        })
+        .collect()
+}
+
+/// The digest to look a contig up by: its MD5, or its `SQ.`-prefixed sha512t24u.
+fn lookup_digest(contig: &Contig) -> Option<String> {
+    contig.md5.clone().or_else(|| {
+        contig
+            .sha512t24u
+            .as_deref()
+            .map(|sha| format!("SQ.{}", strip_sha512t24u_prefix(sha)))
+    })
+}
+
+/// Strip any `ga4gh:` / `SQ.` prefix from a sha512t24u digest.
+fn strip_sha512t24u_prefix(digest: &str) -> &str {
+    let digest = digest.strip_prefix("ga4gh:").unwrap_or(digest);
+    digest.strip_prefix("SQ.").unwrap_or(digest)
+}
+
+/// Query the refget server for each `(index, digest)` pair, storing results by index.
+async fn query_server(
+    to_query: &[(usize, String)],
+    config: &RefgetConfig,
+    results: &mut [Option<RefgetLookupResult>],
+) {
     let semaphore = Arc::new(Semaphore::new(config.max_concurrent));
 
     // Build a reqwest client with the configured timeout
@@ -25,7 +138,12 @@ pub async fn enrich_contigs(contigs: &[Contig], config: &RefgetConfig) -> Vec<En
         Ok(c) => c,
         Err(e) => {
             tracing::warn!("Failed to create HTTP client for refget: {e}");
-            return error_for_all(contigs, &format!("Failed to create HTTP client: {e}"));
+            error_for_all(
+                to_query,
+                results,
+                &format!("Failed to create HTTP client: {e}"),
+            );
+            return;
         }
     };
 
@@ -34,35 +152,30 @@ pub async fn enrich_contigs(contigs: &[Contig], config: &RefgetConfig) -> Vec<En
             Ok(c) => Arc::new(c),
             Err(e) => {
                 tracing::warn!("Failed to create refget client: {e}");
-                return error_for_all(contigs, &format!("Failed to create refget client: {e}"));
+                error_for_all(
+                    to_query,
+                    results,
+                    &format!("Failed to create refget client: {e}"),
+                );
+                return;
             }
         };
 
     let mut join_set = tokio::task::JoinSet::new();
 
-    for (idx, contig) in contigs.iter().enumerate() {
-        let md5 = match &contig.md5 {
-            Some(md5) => md5.clone(),
-            None => {
-                // No MD5 — we can't query refget, skip this contig.
-                // We'll handle these in the result collection below.
-                continue;
-            }
-        };
-
+    for (idx, digest) in to_query {
+        let (idx, digest) = (*idx, digest.clone());
         let sem = Arc::clone(&semaphore);
         let client = Arc::clone(&refget_client);
 
         join_set.spawn(async move {
             let _permit = sem.acquire().await.expect("semaphore closed unexpectedly");
-            let result = lookup_single(&client, &md5).await;
+            let result = lookup_single(&client, &digest).await;
             (idx, result)
         });
     }
 
     // Collect results, preserving original order
-    let mut results: Vec<Option<RefgetLookupResult>> = vec![None; contigs.len()];
-
     while let Some(join_result) = join_set.join_next().await {
         match join_result {
             Ok((idx, lookup_result)) => {
@@ -73,49 +186,24 @@ pub async fn enrich_contigs(contigs: &[Contig], config: &RefgetConfig) -> Vec<En
             }
         }
     }
-
-    contigs
-        .iter()
-        .enumerate()
-        .map(|(idx, contig)| {
-            let refget_metadata = results[idx].take().unwrap_or_else(|| {
-                if contig.md5.is_none() {
-                    RefgetLookupResult::Error {
-                        message: "No MD5 digest available for lookup".to_string(),
-                    }
-                } else {
-                    RefgetLookupResult::Error {
-                        message: "Lookup task failed".to_string(),
-                    }
-                }
-            });
-
-            EnrichedContig {
-                name: contig.name.clone(),
-                md5: contig.md5.clone(),
-                refget_metadata,
-            }
-        })
-        .collect()
 }
 
-/// Create error results for all contigs when the client cannot be initialized.
-fn error_for_all(contigs: &[Contig], message: &str) -> Vec<EnrichedContig> {
-    contigs
-        .iter()
-        .map(|c| EnrichedContig {
-            name: c.name.clone(),
-            md5: c.md5.clone(),
-            refget_metadata: RefgetLookupResult::Error {
-                message: message.to_string(),
-            },
-        })
-        .collect()
+/// Record the same error for every queried contig when the client cannot be initialized.
+fn error_for_all(
+    to_query: &[(usize, String)],
+    results: &mut [Option<RefgetLookupResult>],
+    message: &str,
+) {
+    for (idx, _) in to_query {
+        results[*idx] = Some(RefgetLookupResult::Error {
+            message: message.to_string(),
+        });
+    }
 }
 
-/// Query the refget server for a single MD5 digest.
-async fn lookup_single(client: &refget_client::RefgetClient, md5: &str) -> RefgetLookupResult {
-    match client.get_metadata(md5).await {
+/// Query the refget server for a single digest (MD5 or `SQ.`-prefixed sha512t24u).
+async fn lookup_single(client: &refget_client::RefgetClient, digest: &str) -> RefgetLookupResult {
+    match client.get_metadata(digest).await {
         Ok(Some(metadata)) => RefgetLookupResult::Found {
             aliases: metadata
                 .aliases
@@ -125,12 +213,13 @@ async fn lookup_single(client: &refget_client::RefgetClient, md5: &str) -> Refge
                     value: a.value,
                 })
                 .collect(),
+            md5: metadata.md5,
             sha512t24u: metadata.sha512t24u,
             circular: metadata.circular,
         },
         Ok(None) => RefgetLookupResult::NotFound,
         Err(e) => {
-            tracing::debug!("Refget lookup failed for {md5}: {e}");
+            tracing::debug!("Refget lookup failed for {digest}: {e}");
             RefgetLookupResult::Error {
                 message: e.to_string(),
             }
@@ -142,9 +231,40 @@ async fn lookup_single(client: &refget_client::RefgetClient, md5: &str) -> Refge
 mod tests {
     use super::*;
     use crate::core::contig::Contig;
+    use crate::refget::cache::RefgetCache;
+
+    const SHA512T24U: &str = "2YnepKM7OkBoOrKmvHbGqguVfF9amCST";
+    const MD5: &str = "6aef897c3d6ff0c78aff06ac189178dd";
+
+    /// Start a refget server that only knows one sequence, by its `SQ.` digest
+    async fn start_mock_server() -> String {
+        use axum::{extract::Path, http::StatusCode, routing::get, Json, Router};
+
+        let app = Router::new().route(
+            "/sequence/{digest}/metadata",
+            get(|Path(digest): Path<String>| async move {
+                if digest == format!("SQ.{SHA512T24U}") {
+                    Ok(Json(serde_json::json!({
+                        "metadata": {
+                            "md5": MD5,
+                            "ga4gh": format!("SQ.{SHA512T24U}"),
+                            "length": 248_956_422,
+                            "aliases": [{"naming_authority": "insdc", "value": "CM000663.2"}],
+                        }
+                    })))
+                } else {
+                    Err(StatusCode::NOT_FOUND)
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
 
     #[tokio::test]
-    async fn test_contigs_without_md5_are_skipped() {
+    async fn test_contigs_without_digests_are_skipped() {
         // Use a non-routable address so no real HTTP happens
         let config = RefgetConfig::new("http://192.0.2.1:1");
         let contigs = vec![Contig::new("chr_no_md5".to_string(), 1000)];
@@ -154,11 +274,83 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "chr_no_md5");
         assert!(results[0].md5.is_none());
-        // Should be an error since no MD5 was available
+        assert!(results[0].sha512t24u.is_none());
+        // Should be an error since neither MD5 nor sha512t24u was available
+        match &results[0].refget_metadata {
+            RefgetLookupResult::Error { message } => {
+                assert!(message.starts_with("No MD5 or sha512t24u digest"));
+            }
+            other => panic!("expected an error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sha512t24u_fallback_backfills_md5() {
+        let config = RefgetConfig::new(&start_mock_server().await);
+        let contigs = vec![
+            Contig::new("chr1".to_string(), 248_956_422).with_sha512t24u(SHA512T24U),
+            Contig::new("chrUn".to_string(), 1000).with_sha512t24u("unknown"),
+        ];
+
+        let results = enrich_contigs(&contigs, &config).await;
+
         assert!(matches!(
             results[0].refget_metadata,
-            RefgetLookupResult::Error { .. }
+            RefgetLookupResult::Found { .. }
+        ));
+        assert_eq!(results[0].md5.as_deref(), Some(MD5));
+        assert_eq!(results[0].sha512t24u.as_deref(), Some(SHA512T24U));
+
+        assert!(matches!(
+            results[1].refget_metadata,
+            RefgetLookupResult::NotFound
         ));
+        assert!(results[1].md5.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lookups_are_saved_under_their_server() {
+        let server = start_mock_server().await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let cache = Arc::new(RefgetCache::open(&path, 30).unwrap());
+        let config = RefgetConfig::new(&server).with_cache(cache);
+        let contigs =
+            vec![Contig::new("chr1".to_string(), 248_956_422).with_sha512t24u(SHA512T24U)];
+
+        enrich_contigs(&contigs, &config).await;
+
+        let reopened = RefgetCache::open(&path, 30).unwrap();
+        let digest = format!("SQ.{SHA512T24U}");
+        assert!(matches!(
+            reopened.get(&digest, &server),
+            Some(RefgetLookupResult::Found { .. })
+        ));
+        assert!(reopened.get(&digest, "http://192.0.2.1:1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_md5_lookup_backfills_sha512t24u() {
+        // Serve the MD5 lookup from the cache so no server is needed
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Arc::new(RefgetCache::open(dir.path().join("cache.json"), 30).unwrap());
+        cache.insert(
+            MD5,
+            &RefgetLookupResult::Found {
+                aliases: Vec::new(),
+                md5: MD5.to_string(),
+                sha512t24u: format!("SQ.{SHA512T24U}"),
+                circular: false,
+            },
+            "http://192.0.2.1:1",
+        );
+        let config = RefgetConfig::new("http://192.0.2.1:1").with_cache(cache);
+        let contigs = vec![Contig::new("chr1".to_string(), 248_956_422).with_md5(MD5)];
+
+        let results = enrich_contigs(&contigs, &config).await;
+
+        assert_eq!(results[0].md5.as_deref(), Some(MD5));
+        assert_eq!(results[0].sha512t24u.as_deref(), Some(SHA512T24U));
     }
 
     #[tokio::test]
@@ -208,6 +400,42 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_cached_results_skip_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Arc::new(RefgetCache::open(dir.path().join("cache.json"), 30).unwrap());
+        cache.insert(
+            "6aef897c3d6ff0c78aff06ac189178dd",
+            &RefgetLookupResult::NotFound,
+            "http://192.0.2.1:1",
+        );
+
+        // Unreachable server: only the uncached contig should produce an error
+        let mut config = RefgetConfig::new("http://192.0.2.1:1").with_cache(Arc::clone(&cache));
+        config.timeout = std::time::Duration::from_millis(100);
+
+        let contigs = vec![
+            Contig::new("chr1".to_string(), 1000).with_md5("6aef897c3d6ff0c78aff06ac189178dd"),
+            Contig::new("chr2".to_string(), 1000).with_md5("b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2"),
+        ];
+
+        let results = enrich_contigs(&contigs, &config).await;
+
+        assert!(matches!(
+            results[0].refget_metadata,
+            RefgetLookupResult::NotFound
+        ));
+        assert!(matches!(
+            results[1].refget_metadata,
+            RefgetLookupResult::Error { .. }
+        ));
+        // Errors are not cached, so there is nothing new to save
+        assert!(cache
+            .get("b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2b2", "http://192.0.2.1:1")
+            .is_none());
+        assert!(!dir.path().join("cache.json").exists());
+    }
+
     #[tokio::test]
     async fn test_concurrency_limit_respected() {
         let mut config = RefgetConfig::new("http://192.0.2.1:1");
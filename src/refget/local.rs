@@ -0,0 +1,432 @@
+//! Local sequence store for serving the refget sequences API from indexed FASTA files.
+//!
+//! Sequences are discovered in FASTA files that support random access: `.fa`,
+//! `.fasta` or `.fna` files with a `.fai` index alongside them, or BGZF-compressed
+//! ones with `.fai` and `.gzi` indexes. Every record is hashed when the store is
+//! built (reusing the FASTA digest cache, if given), so a sequence is only ever
+//! served under the digests of its own bases. Catalog contigs with the same
+//! digest supply its aliases.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::core::contig::Contig;
+use crate::parsing::digest_cache::DigestCache;
+use crate::parsing::fai::FaiEntry;
+use crate::parsing::fasta::{self, IndexedFastaReader};
+use crate::parsing::sam::ParseError;
+
+#[derive(Error, Debug)]
+pub enum LocalStoreError {
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to read {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: ParseError,
+    },
+
+    #[error("Not a directory: {0}")]
+    NotADirectory(PathBuf),
+}
+
+/// A sequence in an indexed local FASTA file
+#[derive(Debug, Clone)]
+pub struct LocalSequence {
+    /// Path to the FASTA file
+    pub path: PathBuf,
+    /// Record name in the FASTA file
+    pub name: String,
+    /// Sequence length
+    pub length: u64,
+    /// MD5 digest (lowercase hex)
+    pub md5: String,
+    /// GA4GH sha512t24u digest (without `SQ.` prefix)
+    pub sha512t24u: String,
+    /// Names and aliases this sequence is known by in the catalog
+    pub aliases: Vec<String>,
+    /// Whether the sequence is circular (mitochondrial)
+    pub circular: bool,
+    /// Location of the sequence in the FASTA file
+    entry: FaiEntry,
+}
+
+impl LocalSequence {
+    /// Open the FASTA for reading ranges of this sequence with [`LocalSequence::read`]
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::Io` if the FASTA file or its `.gzi` index cannot be read.
+    pub fn open(&self) -> Result<IndexedFastaReader, ParseError> {
+        IndexedFastaReader::open(&self.path)
+    }
+
+    /// Read the bases in `[start, end)` (0-based, half-open), uppercased.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the FASTA file cannot be read.
+    pub fn read(
+        &self,
+        reader: &mut IndexedFastaReader,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<u8>, ParseError> {
+        // Digests are of the uppercase sequence
+        let mut bases = reader.read_range(&self.entry, start, end)?;
+        bases.make_ascii_uppercase();
+        Ok(bases)
+    }
+}
+
+/// Sequences from indexed local FASTA files, indexed by digest
+#[derive(Debug, Default)]
+pub struct LocalSequenceStore {
+    sequences: Vec<LocalSequence>,
+    /// Index: MD5 -> index in sequences vec
+    md5_to_index: HashMap<String, usize>,
+    /// Index: sha512t24u -> index in sequences vec
+    sha512t24u_to_index: HashMap<String, usize>,
+}
+
+impl LocalSequenceStore {
+    /// Build a store from the indexed FASTA files in the given directories,
+    /// hashing every sequence or taking its digests from `cache`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LocalStoreError::NotADirectory` if a path is not a directory,
+    /// `LocalStoreError::Io` if a directory cannot be read, or
+    /// `LocalStoreError::Parse` if an index or FASTA file cannot be read.
+    pub fn from_directories(
+        dirs: &[PathBuf],
+        catalog: &ReferenceCatalog,
+        cache: Option<&DigestCache>,
+    ) -> Result<Self, LocalStoreError> {
+        let mut store = Self::default();
+
+        for dir in dirs {
+            if !dir.is_dir() {
+                return Err(LocalStoreError::NotADirectory(dir.clone()));
+            }
+
+            let entries = std::fs::read_dir(dir).map_err(|source| LocalStoreError::Io {
+                path: dir.clone(),
+                source,
+            })?;
+            let mut fastas: Vec<PathBuf> = entries
+                .filter_map(Result::ok)
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && fasta::is_fasta_file(p))
+                .collect();
+            fastas.sort();
+
+            for path in fastas {
+                store.add_fasta(&path, catalog, cache)?;
+            }
+        }
+
+        Ok(store)
+    }
+
+    /// Add every record of a FASTA file that supports random access
+    fn add_fasta(
+        &mut self,
+        path: &Path,
+        catalog: &ReferenceCatalog,
+        cache: Option<&DigestCache>,
+    ) -> Result<(), LocalStoreError> {
+        let parse_error = |source| LocalStoreError::Parse {
+            path: path.to_path_buf(),
+            source,
+        };
+        let Some(entries) = fasta::fasta_index(path).map_err(parse_error)? else {
+            tracing::debug!("Skipping {}: no index for random access", path.display());
+            return Ok(());
+        };
+
+        let hashed = fasta::parse_fasta_file_with_md5_cached(path, cache).map_err(parse_error)?;
+        let mut digests: HashMap<String, Contig> = hashed
+            .contigs
+            .into_iter()
+            .map(|contig| (contig.name.clone(), contig))
+            .collect();
+
+        let mut count = 0;
+        for entry in entries {
+            let Some(contig) = digests.remove(&entry.name) else {
+                continue;
+            };
+            let (Some(md5), Some(sha512t24u)) = (contig.md5, contig.sha512t24u) else {
+                continue;
+            };
+
+            let catalog_contigs =
+                find_catalog_contigs(catalog, &entry.name, entry.length, &md5, &sha512t24u);
+            let mut aliases: Vec<String> = catalog_contigs
+                .iter()
+                .flat_map(|c| std::iter::once(&c.name).chain(&c.aliases))
+                .filter(|a| **a != entry.name)
+                .cloned()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            aliases.sort();
+
+            let inserted = self.insert(LocalSequence {
+                path: path.to_path_buf(),
+                circular: catalog_contigs.iter().any(|c| c.is_mitochondrial()),
+                name: entry.name.clone(),
+                length: entry.length,
+                md5,
+                sha512t24u,
+                aliases,
+                entry,
+            });
+            if inserted {
+                count += 1;
+            }
+        }
+
+        tracing::info!("Serving {count} sequences from {}", path.display());
+        Ok(())
+    }
+
+    /// Add a sequence unless one with the same digest is already served.
+    /// Returns whether it was added.
+    fn insert(&mut self, sequence: LocalSequence) -> bool {
+        if self.md5_to_index.contains_key(&sequence.md5)
+            || self.sha512t24u_to_index.contains_key(&sequence.sha512t24u)
+        {
+            return false;
+        }
+        let index = self.sequences.len();
+        self.md5_to_index.insert(sequence.md5.clone(), index);
+        self.sha512t24u_to_index
+            .insert(sequence.sha512t24u.clone(), index);
+        self.sequences.push(sequence);
+        true
+    }
+
+    /// Number of sequences available
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sequences.len()
+    }
+
+    /// Check if the store has no sequences
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sequences.is_empty()
+    }
+
+    /// Look up a sequence by refget identifier.
+    ///
+    /// Accepts an MD5 (`<hex>` or `md5:<hex>`) or a sha512t24u digest
+    /// (`<digest>`, `SQ.<digest>`, or `ga4gh:SQ.<digest>`).
+    #[must_use]
+    pub fn get(&self, id: &str) -> Option<&LocalSequence> {
+        let index = if let Some(md5) = id.strip_prefix("md5:") {
+            self.md5_to_index.get(&md5.to_ascii_lowercase())
+        } else {
+            let bare = id.strip_prefix("ga4gh:").unwrap_or(id);
+            let bare = bare.strip_prefix("SQ.").unwrap_or(bare);
+            self.sha512t24u_to_index
+                .get(bare)
+                .or_else(|| self.md5_to_index.get(&bare.to_ascii_lowercase()))
+        };
+        index.map(|&idx| &self.sequences[idx])
+    }
+}
+
+/// Find catalog contigs with the given name (or alias) and length, and the
+/// same MD5 or sha512t24u digest
+fn find_catalog_contigs<'a>(
+    catalog: &'a ReferenceCatalog,
+    name: &str,
+    length: u64,
+    md5: &str,
+    sha512t24u: &str,
+) -> Vec<&'a Contig> {
+    let key = (name.to_string(), length);
+    let ref_indices: HashSet<usize> = catalog
+        .name_length_to_refs
+        .get(&key)
+        .into_iter()
+        .chain(catalog.alias_length_to_refs.get(&key))
+        .flatten()
+        .copied()
+        .collect();
+
+    ref_indices
+        .into_iter()
+        .flat_map(|idx| &catalog.references[idx].contigs)
+        .filter(|c| c.length == length && (c.name == name || c.aliases.iter().any(|a| a == name)))
+        .filter(|c| {
+            c.md5.as_ref().is_some_and(|m| m.eq_ignore_ascii_case(md5))
+                || c.sha512t24u
+                    .as_deref()
+                    .is_some_and(|d| d.strip_prefix("SQ.").unwrap_or(d) == sha512t24u)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::reference::KnownReference;
+    use crate::core::types::{Assembly, ReferenceSource};
+    use crate::utils::validation::compute_sha512t24u;
+
+    fn write_fasta(dir: &Path) -> PathBuf {
+        // chr1 spans multiple lines to exercise offset arithmetic
+        let fasta = dir.join("test.fa");
+        std::fs::write(
+            &fasta,
+            ">chr1\nACGTA\nCGTAC\nGT\n>chr2\nggaa\n>other\nTTTT\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("test.fa.fai"),
+            "chr1\t12\t6\t5\t6\nchr2\t4\t27\t4\t5\nother\t4\t39\t4\t5\n",
+        )
+        .unwrap();
+        fasta
+    }
+
+    fn make_catalog() -> ReferenceCatalog {
+        let mut catalog = ReferenceCatalog::new();
+        catalog.add_reference(
+            KnownReference::new(
+                "test",
+                "Test",
+                Assembly::Other("test".to_string()),
+                ReferenceSource::Custom("test".to_string()),
+            )
+            .with_contigs(vec![
+                Contig::new("chr1", 12)
+                    .with_md5(format!("{:x}", md5::compute(b"ACGTACGTACGT")))
+                    .with_aliases(vec!["1".to_string()]),
+                // Only sha512t24u in the catalog - MD5 is computed from the FASTA
+                Contig::new("chr2", 4).with_sha512t24u(compute_sha512t24u(b"GGAA")),
+            ]),
+        );
+        catalog
+    }
+
+    fn open_store(dir: &Path) -> LocalSequenceStore {
+        LocalSequenceStore::from_directories(&[dir.to_path_buf()], &make_catalog(), None).unwrap()
+    }
+
+    #[test]
+    fn test_store_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        write_fasta(dir.path());
+        let store = open_store(dir.path());
+
+        // "other" is not in the catalog but is served under its own digests
+        assert_eq!(store.len(), 3);
+        let other = store.get(&format!("{:x}", md5::compute(b"TTTT"))).unwrap();
+        assert_eq!(other.name, "other");
+        assert!(other.aliases.is_empty());
+
+        let md5 = format!("{:x}", md5::compute(b"ACGTACGTACGT"));
+        let chr1 = store.get(&md5).unwrap();
+        assert_eq!(chr1.name, "chr1");
+        assert_eq!(chr1.aliases, vec!["1".to_string()]);
+        assert_eq!(chr1.sha512t24u, compute_sha512t24u(b"ACGTACGTACGT"));
+        assert!(store.get(&format!("md5:{md5}")).is_some());
+
+        // Matched to the catalog by sha512t24u; digests of the uppercase bases
+        let sha = compute_sha512t24u(b"GGAA");
+        let chr2 = store.get(&format!("ga4gh:SQ.{sha}")).unwrap();
+        assert_eq!(chr2.name, "chr2");
+        assert_eq!(chr2.md5, format!("{:x}", md5::compute(b"GGAA")));
+        assert!(store.get(&format!("SQ.{sha}")).is_some());
+
+        assert!(store.get("unknown").is_none());
+    }
+
+    #[test]
+    fn test_other_bases_are_not_served_under_catalog_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        // Same name and length as the catalog's chr1, different bases
+        let fasta = dir.path().join("test.fa");
+        std::fs::write(&fasta, ">chr1\nTTTTTTTTTTTT\n").unwrap();
+        std::fs::write(dir.path().join("test.fa.fai"), "chr1\t12\t6\t12\t13\n").unwrap();
+        let store = open_store(dir.path());
+
+        assert!(store
+            .get(&format!("{:x}", md5::compute(b"ACGTACGTACGT")))
+            .is_none());
+        let chr1 = store
+            .get(&format!("{:x}", md5::compute(b"TTTTTTTTTTTT")))
+            .unwrap();
+        assert!(chr1.aliases.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_sequences_are_served_once() {
+        let dir = tempfile::tempdir().unwrap();
+        write_fasta(dir.path());
+        // A second copy of the same file, and a renamed copy of "other"
+        std::fs::copy(dir.path().join("test.fa"), dir.path().join("copy.fa")).unwrap();
+        std::fs::copy(
+            dir.path().join("test.fa.fai"),
+            dir.path().join("copy.fa.fai"),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("renamed.fa"), ">renamed\nTTTT\n").unwrap();
+        std::fs::write(dir.path().join("renamed.fa.fai"), "renamed\t4\t9\t4\t5\n").unwrap();
+        let store = open_store(dir.path());
+
+        assert_eq!(store.len(), 3);
+        let other = store.get(&format!("{:x}", md5::compute(b"TTTT"))).unwrap();
+        assert_eq!(other.path, dir.path().join("copy.fa"));
+    }
+
+    #[test]
+    fn test_skips_fasta_without_index() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("test.fa"), ">chr1\nACGT\n").unwrap();
+        assert!(open_store(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_read_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        write_fasta(dir.path());
+        let store = open_store(dir.path());
+        let chr1 = store
+            .get(&format!("{:x}", md5::compute(b"ACGTACGTACGT")))
+            .unwrap();
+        let mut reader = chr1.open().unwrap();
+
+        assert_eq!(chr1.read(&mut reader, 0, 12).unwrap(), b"ACGTACGTACGT");
+        assert_eq!(chr1.read(&mut reader, 3, 7).unwrap(), b"TACG");
+        assert_eq!(chr1.read(&mut reader, 10, 100).unwrap(), b"GT");
+        assert!(chr1.read(&mut reader, 5, 5).unwrap().is_empty());
+
+        let chr2 = store.get(&format!("{:x}", md5::compute(b"GGAA"))).unwrap();
+        assert_eq!(chr2.read(&mut chr2.open().unwrap(), 1, 3).unwrap(), b"GA");
+    }
+
+    #[test]
+    fn test_not_a_directory() {
+        let result = LocalSequenceStore::from_directories(
+            &[PathBuf::from("/nonexistent")],
+            &make_catalog(),
+            None,
+        );
+        assert!(matches!(result, Err(LocalStoreError::NotADirectory(_))));
+    }
+}
@@ -2,12 +2,18 @@
 //!
 //! After matching, contigs that don't match any known reference can be queried against
 //! a refget server (e.g., EBI's ENA CRAM server) to retrieve aliases and other metadata.
-//! This helps users identify what unknown contigs actually are.
+//! This helps users identify what unknown contigs actually are. Lookup results can be
+//! persisted across runs with a [`cache::RefgetCache`].
 
+pub mod cache;
 pub mod enrichment;
+pub mod local;
 
+use std::sync::Arc;
 use std::time::Duration;
 
+use cache::RefgetCache;
+
 /// Default refget server URL (EBI's ENA CRAM refget endpoint).
 pub const DEFAULT_REFGET_SERVER: &str = "https://www.ebi.ac.uk/ena/cram";
 
@@ -27,6 +33,8 @@ pub struct RefgetConfig {
     pub timeout: Duration,
     /// Maximum number of concurrent requests to the refget server.
     pub max_concurrent: usize,
+    /// Persistent cache of lookup results, if enabled.
+    pub cache: Option<Arc<RefgetCache>>,
 }
 
 impl RefgetConfig {
@@ -37,8 +45,16 @@ impl RefgetConfig {
             server_url: server_url.to_string(),
             timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
             max_concurrent: DEFAULT_MAX_CONCURRENT,
+            cache: None,
         }
     }
+
+    /// Use a persistent cache for lookup results.
+    #[must_use]
+    pub fn with_cache(mut self, cache: Arc<RefgetCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
 }
 
 impl Default for RefgetConfig {
@@ -48,7 +64,7 @@ impl Default for RefgetConfig {
 }
 
 /// Result of looking up a single contig in refget.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "status", rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum RefgetLookupResult {
@@ -56,7 +72,9 @@ pub enum RefgetLookupResult {
     Found {
         /// Known aliases for this sequence.
         aliases: Vec<RefgetAlias>,
-        /// GA4GH sha512t24u digest.
+        /// MD5 digest (lowercase hex).
+        md5: String,
+        /// GA4GH sha512t24u digest, as returned by the server (`SQ.`-prefixed).
         sha512t24u: String,
         /// Whether the sequence is circular (e.g., mitochondrial).
         circular: bool,
@@ -71,7 +89,7 @@ pub enum RefgetLookupResult {
 }
 
 /// A naming-authority alias for a sequence, as returned by refget.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[non_exhaustive]
 pub struct RefgetAlias {
     /// The naming authority (e.g., "insdc", "ensembl").
@@ -86,8 +104,12 @@ pub struct RefgetAlias {
 pub struct EnrichedContig {
     /// Name of the contig.
     pub name: String,
-    /// MD5 digest used for the lookup, if available.
+    /// MD5 digest of the contig, back-filled from refget metadata if the
+    /// contig did not have one.
     pub md5: Option<String>,
+    /// GA4GH sha512t24u digest of the contig (without the `SQ.` prefix),
+    /// back-filled from refget metadata if the contig did not have one.
+    pub sha512t24u: Option<String>,
     /// Result of the refget lookup.
     pub refget_metadata: RefgetLookupResult,
 }
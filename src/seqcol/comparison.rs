@@ -0,0 +1,235 @@
+//! The seqcol "comparison" function.
+//!
+//! Compares two sequence collections attribute by attribute, reporting which
+//! attributes are shared, how many array elements overlap, and whether the
+//! overlapping elements appear in the same order.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::SeqCol;
+
+/// Result of comparing two sequence collections (seqcol comparison function)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeqColComparison {
+    /// Level-0 digests of the two collections (`None` if not computable)
+    pub digests: ComparisonDigests,
+
+    /// Attribute-level comparison
+    pub attributes: AttributeComparison,
+
+    /// Element-level comparison for each attribute
+    pub array_elements: ArrayElementComparison,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComparisonDigests {
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeComparison {
+    /// Attributes present only in collection A
+    pub a_only: Vec<String>,
+    /// Attributes present only in collection B
+    pub b_only: Vec<String>,
+    /// Attributes present in both collections
+    pub a_and_b: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArrayElementComparison {
+    /// Number of elements in each attribute of A
+    pub a_count: BTreeMap<String, usize>,
+    /// Number of elements in each attribute of B
+    pub b_count: BTreeMap<String, usize>,
+    /// Number of overlapping elements for each shared attribute
+    pub a_and_b_count: BTreeMap<String, usize>,
+    /// Whether overlapping elements are in the same order for each shared attribute.
+    /// `None` when there are fewer than two overlapping elements or the overlap
+    /// contains unbalanced duplicates.
+    pub a_and_b_same_order: BTreeMap<String, Option<bool>>,
+}
+
+impl SeqColComparison {
+    /// Compare two sequence collections
+    #[must_use]
+    pub fn compare(a: &SeqCol, b: &SeqCol) -> Self {
+        let a_attributes = a.attributes();
+        let b_attributes = b.attributes();
+
+        let mut attributes = AttributeComparison {
+            a_only: Vec::new(),
+            b_only: Vec::new(),
+            a_and_b: Vec::new(),
+        };
+        let mut array_elements = ArrayElementComparison {
+            a_count: BTreeMap::new(),
+            b_count: BTreeMap::new(),
+            a_and_b_count: BTreeMap::new(),
+            a_and_b_same_order: BTreeMap::new(),
+        };
+
+        for (name, a_values) in &a_attributes {
+            array_elements
+                .a_count
+                .insert((*name).to_string(), a_values.len());
+
+            match b_attributes.get(name) {
+                Some(b_values) => {
+                    let (overlap, same_order) = compare_elements(a_values, b_values);
+                    attributes.a_and_b.push((*name).to_string());
+                    array_elements
+                        .a_and_b_count
+                        .insert((*name).to_string(), overlap);
+                    array_elements
+                        .a_and_b_same_order
+                        .insert((*name).to_string(), same_order);
+                }
+                None => attributes.a_only.push((*name).to_string()),
+            }
+        }
+
+        for (name, b_values) in &b_attributes {
+            array_elements
+                .b_count
+                .insert((*name).to_string(), b_values.len());
+            if !a_attributes.contains_key(name) {
+                attributes.b_only.push((*name).to_string());
+            }
+        }
+
+        Self {
+            digests: ComparisonDigests {
+                a: a.digest(),
+                b: b.digest(),
+            },
+            attributes,
+            array_elements,
+        }
+    }
+
+    /// Check if both collections have identical level-0 digests
+    #[must_use]
+    pub fn is_identical(&self) -> bool {
+        matches!((&self.digests.a, &self.digests.b), (Some(a), Some(b)) if a == b)
+    }
+}
+
+/// Compare two attribute arrays, returning (overlap count, same order).
+///
+/// Overlap counts elements as a multiset, so duplicated values only overlap as
+/// many times as they appear in both arrays.
+fn compare_elements(a: &[String], b: &[String]) -> (usize, Option<bool>) {
+    let mut b_counts: HashMap<&str, usize> = HashMap::new();
+    for value in b {
+        *b_counts.entry(value.as_str()).or_default() += 1;
+    }
+
+    let mut overlap = 0usize;
+    let mut remaining = b_counts.clone();
+    for value in a {
+        if let Some(count) = remaining.get_mut(value.as_str()) {
+            if *count > 0 {
+                *count -= 1;
+                overlap += 1;
+            }
+        }
+    }
+
+    // Order is only meaningful with at least two shared elements
+    if overlap < 2 {
+        return (overlap, None);
+    }
+
+    let a_filtered: Vec<&str> = a
+        .iter()
+        .map(String::as_str)
+        .filter(|v| b_counts.contains_key(v))
+        .collect();
+    let b_filtered: Vec<&str> = b
+        .iter()
+        .map(String::as_str)
+        .filter(|v| a_filtered.contains(v))
+        .collect();
+
+    // Unbalanced duplicates make the order ambiguous
+    if a_filtered.len() != overlap || b_filtered.len() != overlap {
+        return (overlap, None);
+    }
+
+    (overlap, Some(a_filtered == b_filtered))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::contig::Contig;
+
+    fn seqcol(contigs: &[(&str, u64, &str)]) -> SeqCol {
+        let contigs: Vec<Contig> = contigs
+            .iter()
+            .map(|(name, length, digest)| Contig::new(*name, *length).with_sha512t24u(*digest))
+            .collect();
+        SeqCol::from_contigs(&contigs)
+    }
+
+    #[test]
+    fn test_compare_identical() {
+        let a = seqcol(&[("chr1", 4, "d1"), ("chr2", 4, "d2")]);
+        let comparison = SeqColComparison::compare(&a, &a.clone());
+
+        assert!(comparison.is_identical());
+        assert_eq!(
+            comparison.attributes.a_and_b,
+            vec!["lengths", "names", "sequences"]
+        );
+        assert!(comparison.attributes.a_only.is_empty());
+        assert_eq!(comparison.array_elements.a_and_b_count["names"], 2);
+        assert_eq!(
+            comparison.array_elements.a_and_b_same_order["sequences"],
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_compare_renamed_and_reordered() {
+        let a = seqcol(&[("chr1", 4, "d1"), ("chr2", 5, "d2"), ("chr3", 6, "d3")]);
+        let b = seqcol(&[("2", 5, "d2"), ("1", 4, "d1")]);
+        let comparison = SeqColComparison::compare(&a, &b);
+
+        assert!(!comparison.is_identical());
+        assert_eq!(comparison.array_elements.a_count["names"], 3);
+        assert_eq!(comparison.array_elements.b_count["names"], 2);
+        assert_eq!(comparison.array_elements.a_and_b_count["names"], 0);
+        assert_eq!(comparison.array_elements.a_and_b_count["sequences"], 2);
+        assert_eq!(
+            comparison.array_elements.a_and_b_same_order["sequences"],
+            Some(false)
+        );
+        assert_eq!(comparison.array_elements.a_and_b_same_order["names"], None);
+    }
+
+    #[test]
+    fn test_compare_missing_sequences_attribute() {
+        let a = seqcol(&[("chr1", 4, "d1"), ("chr2", 4, "d2")]);
+        let b = SeqCol::from_contigs(&[Contig::new("chr1", 4), Contig::new("chr2", 4)]);
+        let comparison = SeqColComparison::compare(&a, &b);
+
+        assert_eq!(comparison.attributes.a_only, vec!["sequences"]);
+        assert!(comparison.attributes.b_only.is_empty());
+        assert!(comparison.digests.a.is_some());
+        assert!(comparison.digests.b.is_none());
+        assert!(!comparison.is_identical());
+    }
+
+    #[test]
+    fn test_compare_elements_duplicates() {
+        let a: Vec<String> = ["4", "4", "5"].iter().map(ToString::to_string).collect();
+        let b: Vec<String> = ["4", "5"].iter().map(ToString::to_string).collect();
+        assert_eq!(compare_elements(&a, &b), (2, None));
+        assert_eq!(compare_elements(&b, &b), (2, Some(true)));
+    }
+}
@@ -0,0 +1,221 @@
+//! GA4GH sequence collections (seqcol) digests and comparison.
+//!
+//! A sequence collection is represented by three parallel arrays: contig `names`,
+//! `lengths`, and refget `sequences` digests (`SQ.`-prefixed sha512t24u). The spec
+//! defines three levels of representation:
+//!
+//! - **Level 2**: the arrays themselves ([`SeqCol`])
+//! - **Level 1**: one digest per attribute array ([`SeqColLevel1`])
+//! - **Level 0**: a single digest over the level-1 digests of the inherent
+//!   attributes (`names` and `sequences`)
+//!
+//! Every digest is the sha512t24u of the RFC-8785 canonical JSON serialization of
+//! the value being digested.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ref_solver::seqcol::SeqCol;
+//!
+//! let seqcol = SeqCol {
+//!     names: vec!["chrX".into(), "chr1".into(), "chr2".into()],
+//!     lengths: vec![8, 4, 4],
+//!     sequences: Some(vec![
+//!         "SQ.iYtREV555dUFKg2_agSJW6suquUyPpMw".into(),
+//!         "SQ.YBbVX0dLKG1ieEDCiMmkrTZFt_Z5Vdaj".into(),
+//!         "SQ.AcLxtBuKEPk_7PGE_H4dGElwZHCujwH6".into(),
+//!     ]),
+//! };
+//! assert_eq!(seqcol.digest().as_deref(), Some("XZlrcEGi6mlopZ2uD8ObHkQB1d0oDwKk"));
+//! ```
+
+pub mod comparison;
+
+pub use comparison::SeqColComparison;
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::contig::Contig;
+use crate::core::header::QueryHeader;
+use crate::core::reference::KnownReference;
+use crate::utils::validation::compute_sha512t24u;
+
+/// Prefix used by refget for sha512t24u sequence identifiers
+pub const SEQUENCE_DIGEST_PREFIX: &str = "SQ.";
+
+/// Attributes that contribute to the level-0 digest
+pub const INHERENT_ATTRIBUTES: &[&str] = &["names", "sequences"];
+
+/// Level-2 representation of a sequence collection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeqCol {
+    /// Contig names, in collection order
+    pub names: Vec<String>,
+
+    /// Contig lengths, in collection order
+    pub lengths: Vec<u64>,
+
+    /// Refget sequence digests (`SQ.<sha512t24u>`), in collection order.
+    /// `None` when any contig lacks a sha512t24u digest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequences: Option<Vec<String>>,
+}
+
+/// Level-1 representation of a sequence collection (one digest per attribute)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeqColLevel1 {
+    pub names: String,
+    pub lengths: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequences: Option<String>,
+}
+
+impl SeqCol {
+    /// Build a sequence collection from contigs in their original order
+    #[must_use]
+    pub fn from_contigs(contigs: &[Contig]) -> Self {
+        let names = contigs.iter().map(|c| c.name.clone()).collect();
+        let lengths = contigs.iter().map(|c| c.length).collect();
+        let sequences = contigs
+            .iter()
+            .map(|c| c.sha512t24u.as_deref().map(sequence_digest))
+            .collect();
+
+        Self {
+            names,
+            lengths,
+            sequences,
+        }
+    }
+
+    /// Compute the level-1 representation (per-attribute digests)
+    #[must_use]
+    pub fn level1(&self) -> SeqColLevel1 {
+        SeqColLevel1 {
+            names: digest_json(&serde_json::json!(self.names)),
+            lengths: digest_json(&serde_json::json!(self.lengths)),
+            sequences: self
+                .sequences
+                .as_ref()
+                .map(|s| digest_json(&serde_json::json!(s))),
+        }
+    }
+
+    /// Compute the level-0 digest (the seqcol identifier).
+    ///
+    /// Returns `None` when the `sequences` attribute is unavailable, since it is
+    /// an inherent attribute and the digest cannot be computed without it.
+    #[must_use]
+    pub fn digest(&self) -> Option<String> {
+        self.level1().digest()
+    }
+
+    /// Attribute arrays as strings, keyed by attribute name (used for comparison)
+    pub(crate) fn attributes(&self) -> BTreeMap<&'static str, Vec<String>> {
+        let mut attributes = BTreeMap::new();
+        attributes.insert("names", self.names.clone());
+        attributes.insert(
+            "lengths",
+            self.lengths.iter().map(ToString::to_string).collect(),
+        );
+        if let Some(sequences) = &self.sequences {
+            attributes.insert("sequences", sequences.clone());
+        }
+        attributes
+    }
+}
+
+impl SeqColLevel1 {
+    /// Compute the level-0 digest from the inherent attribute digests
+    #[must_use]
+    pub fn digest(&self) -> Option<String> {
+        let sequences = self.sequences.as_ref()?;
+        let inherent: BTreeMap<&str, &str> = INHERENT_ATTRIBUTES
+            .iter()
+            .copied()
+            .zip([self.names.as_str(), sequences.as_str()])
+            .collect();
+        Some(digest_json(&serde_json::json!(inherent)))
+    }
+}
+
+impl From<&QueryHeader> for SeqCol {
+    fn from(query: &QueryHeader) -> Self {
+        Self::from_contigs(&query.contigs)
+    }
+}
+
+impl From<&KnownReference> for SeqCol {
+    fn from(reference: &KnownReference) -> Self {
+        Self::from_contigs(&reference.contigs)
+    }
+}
+
+/// Format a sha512t24u digest as a refget sequence identifier (`SQ.` prefix)
+#[must_use]
+pub fn sequence_digest(sha512t24u: &str) -> String {
+    let bare = sha512t24u
+        .strip_prefix(SEQUENCE_DIGEST_PREFIX)
+        .unwrap_or(sha512t24u);
+    format!("{SEQUENCE_DIGEST_PREFIX}{bare}")
+}
+
+/// sha512t24u of the canonical (RFC-8785) JSON serialization of a value.
+///
+/// `serde_json` emits compact output with object keys in sorted order, which is
+/// canonical for the strings, integers, arrays and objects used by seqcol.
+fn digest_json(value: &serde_json::Value) -> String {
+    compute_sha512t24u(value.to_string().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The `base.fa` example collection from the seqcol specification
+    fn base_seqcol() -> SeqCol {
+        SeqCol::from_contigs(&[
+            Contig::new("chrX", 8).with_sha512t24u("iYtREV555dUFKg2_agSJW6suquUyPpMw"),
+            Contig::new("chr1", 4).with_sha512t24u("YBbVX0dLKG1ieEDCiMmkrTZFt_Z5Vdaj"),
+            Contig::new("chr2", 4).with_sha512t24u("SQ.AcLxtBuKEPk_7PGE_H4dGElwZHCujwH6"),
+        ])
+    }
+
+    #[test]
+    fn test_level1_digests() {
+        let level1 = base_seqcol().level1();
+        assert_eq!(level1.names, "Fw1r9eRxfOZD98KKrhlYQNEdSRHoVxAG");
+        assert_eq!(level1.lengths, "cGRMZIb3AVgkcAfNv39RN7hnT5Chk7RX");
+        assert_eq!(
+            level1.sequences.as_deref(),
+            Some("0uDQVLuHaOZi1u76LjV__yrVUIz9Bwhr")
+        );
+    }
+
+    #[test]
+    fn test_level0_digest() {
+        assert_eq!(
+            base_seqcol().digest().as_deref(),
+            Some("XZlrcEGi6mlopZ2uD8ObHkQB1d0oDwKk")
+        );
+    }
+
+    #[test]
+    fn test_digest_unavailable_without_sequences() {
+        let seqcol = SeqCol::from_contigs(&[
+            Contig::new("chr1", 4).with_sha512t24u("YBbVX0dLKG1ieEDCiMmkrTZFt_Z5Vdaj"),
+            Contig::new("chr2", 4).with_md5("abc123"),
+        ]);
+        assert!(seqcol.sequences.is_none());
+        assert!(seqcol.digest().is_none());
+        assert!(seqcol.level1().sequences.is_none());
+    }
+
+    #[test]
+    fn test_sequence_digest_prefix() {
+        assert_eq!(sequence_digest("abc"), "SQ.abc");
+        assert_eq!(sequence_digest("SQ.abc"), "SQ.abc");
+    }
+}
@@ -0,0 +1,148 @@
+//! Expansion of shell-style glob patterns in paths given on the command line.
+//!
+//! Shells expand unquoted globs themselves, but a quoted pattern such as
+//! `'partners/**/*.bam'` reaches us intact, as do patterns on platforms whose
+//! shells do not expand them. Patterns are matched by the `glob` crate:
+//!
+//! - `*` matches any run of characters, `?` any single character
+//! - `[abc]`, `[a-z]` and `[!a-z]` match character classes
+//! - a `**` component matches zero or more directories
+//!
+//! Wildcards do not match hidden files and directories (names starting with
+//! `.`), though a pattern may name a hidden directory literally.
+
+use std::path::{Path, PathBuf};
+
+use ::glob::{MatchOptions, PatternError};
+
+/// Whether `path` contains glob syntax
+#[must_use]
+pub fn is_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// The existing files matching `pattern`, sorted.
+///
+/// Paths that cannot be read are skipped.
+///
+/// # Errors
+///
+/// Returns a `PatternError` locating the problem if `pattern` is not valid
+/// glob syntax.
+pub fn expand(pattern: &Path) -> Result<Vec<PathBuf>, PatternError> {
+    let options = MatchOptions {
+        require_literal_leading_dot: true,
+        ..MatchOptions::new()
+    };
+    let paths = ::glob::glob_with(&pattern.to_string_lossy(), options)?;
+    let mut files: Vec<PathBuf> = paths.flatten().filter(|path| path.is_file()).collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tree(files: &[&str]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for file in files {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_is_pattern() {
+        assert!(is_pattern(Path::new("data/*.bam")));
+        assert!(is_pattern(Path::new("data/sample?.bam")));
+        assert!(is_pattern(Path::new("data/sample[12].bam")));
+        assert!(!is_pattern(Path::new("data/sample.bam")));
+    }
+
+    #[test]
+    fn test_expand() {
+        let dir = tree(&[
+            "a.bam",
+            "b.cram",
+            "batch1/c.bam",
+            "batch1/deep/d.bam",
+            "batch2/e.bam",
+            ".hidden/f.bam",
+        ]);
+        let root = dir.path();
+
+        assert_eq!(
+            expand(&root.join("*.bam")).unwrap(),
+            vec![root.join("a.bam")]
+        );
+        assert_eq!(
+            expand(&root.join("batch*/*.bam")).unwrap(),
+            vec![root.join("batch1/c.bam"), root.join("batch2/e.bam")]
+        );
+        assert_eq!(
+            expand(&root.join("**/*.bam")).unwrap(),
+            vec![
+                root.join("a.bam"),
+                root.join("batch1/c.bam"),
+                root.join("batch1/deep/d.bam"),
+                root.join("batch2/e.bam"),
+            ]
+        );
+        assert!(expand(&root.join("*.vcf")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expand_character_classes_and_wildcards() {
+        let dir = tree(&["sample1.bam", "sample2.bam", "sampleX.bam", "sample10.bam"]);
+        let root = dir.path();
+
+        assert_eq!(
+            expand(&root.join("sample[0-9].bam")).unwrap(),
+            vec![root.join("sample1.bam"), root.join("sample2.bam")]
+        );
+        assert_eq!(
+            expand(&root.join("sample[!0-9].bam")).unwrap(),
+            vec![root.join("sampleX.bam")]
+        );
+        assert_eq!(
+            expand(&root.join("sample?.bam")).unwrap(),
+            vec![
+                root.join("sample1.bam"),
+                root.join("sample2.bam"),
+                root.join("sampleX.bam"),
+            ]
+        );
+        assert_eq!(
+            expand(&root.join("*10*")).unwrap(),
+            vec![root.join("sample10.bam")]
+        );
+    }
+
+    #[test]
+    fn test_expand_skips_hidden_and_directories() {
+        let dir = tree(&[
+            ".hidden.bam",
+            "visible.bam",
+            "dir.bam/inner.bam",
+            ".cache/cached.bam",
+        ]);
+        let root = dir.path();
+
+        // Directories matching the pattern are not files to identify
+        assert_eq!(
+            expand(&root.join("*.bam")).unwrap(),
+            vec![root.join("visible.bam")]
+        );
+        assert_eq!(
+            expand(&root.join(".cache/*.bam")).unwrap(),
+            vec![root.join(".cache/cached.bam")]
+        );
+        // Invalid patterns are errors, not empty matches
+        assert!(expand(&root.join("[*.bam")).is_err());
+    }
+}
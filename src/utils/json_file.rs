@@ -0,0 +1,285 @@
+//! Versioned JSON files, shared by the on-disk caches (refget lookups, FASTA
+//! digests and the local reference index).
+//!
+//! Each file is a JSON object holding a format `version` and the cache's
+//! `entries`. Files are written atomically: to a temporary file in the same
+//! directory, which is then renamed over the old one, so readers never see
+//! a partial file. Writers that merge with the file on disk hold a [`lock`]
+//! on it from the read to the write, so they do not lose each other's entries.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use fs2::FileExt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum JsonFileError {
+    #[error("Failed to access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Unsupported file version {version} in {path}")]
+    UnsupportedVersion { path: PathBuf, version: u32 },
+
+    #[error("Failed to serialize {path}: {source}")]
+    Serialize {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Only the version is read first, to pick the format of the entries
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct Entries<T> {
+    entries: T,
+}
+
+#[derive(Serialize)]
+struct FileRef<'a, T> {
+    version: u32,
+    entries: &'a T,
+}
+
+/// A versioned JSON file read from disk whose entries are not parsed yet,
+/// for callers that read older versions too
+pub struct VersionedJson {
+    path: PathBuf,
+    /// Format version of the file
+    pub version: u32,
+    contents: String,
+}
+
+impl VersionedJson {
+    /// Read the file at `path` and its version
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or has no version.
+    pub fn read(path: &Path) -> Result<Self, JsonFileError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| JsonFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let Version { version } =
+            serde_json::from_str(&contents).map_err(|source| JsonFileError::Parse {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            version,
+            contents,
+        })
+    }
+
+    /// Parse the entries as `T`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entries are not a valid `T`.
+    pub fn entries<T: DeserializeOwned>(&self) -> Result<T, JsonFileError> {
+        let file: Entries<T> =
+            serde_json::from_str(&self.contents).map_err(|source| JsonFileError::Parse {
+                path: self.path.clone(),
+                source,
+            })?;
+        Ok(file.entries)
+    }
+
+    /// The error for a version the caller cannot read
+    #[must_use]
+    pub fn unsupported(&self) -> JsonFileError {
+        JsonFileError::UnsupportedVersion {
+            path: self.path.clone(),
+            version: self.version,
+        }
+    }
+}
+
+/// Read the entries of a file written with [`write_entries`] at `version`
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, or has another version.
+pub fn read_entries<T: DeserializeOwned>(path: &Path, version: u32) -> Result<T, JsonFileError> {
+    let file = VersionedJson::read(path)?;
+    if file.version != version {
+        return Err(file.unsupported());
+    }
+    file.entries()
+}
+
+/// Write `entries` to `path` atomically, creating its directory if needed.
+/// `pretty` indents the JSON for files meant to be read or shared by people.
+///
+/// # Errors
+///
+/// Returns an error if the entries cannot be serialized or the file written.
+pub fn write_entries<T: Serialize>(
+    path: &Path,
+    version: u32,
+    entries: &T,
+    pretty: bool,
+) -> Result<(), JsonFileError> {
+    let file = FileRef { version, entries };
+    let json = if pretty {
+        serde_json::to_string_pretty(&file)
+    } else {
+        serde_json::to_string(&file)
+    }
+    .map_err(|source| JsonFileError::Serialize {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let io_err = |source| JsonFileError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(dir).map_err(io_err)?;
+
+    let mut temp = tempfile::NamedTempFile::new_in(dir).map_err(io_err)?;
+    temp.write_all(json.as_bytes()).map_err(io_err)?;
+    temp.persist(path).map_err(|e| io_err(e.error))?;
+    Ok(())
+}
+
+/// An exclusive advisory lock on a JSON file, released when dropped
+pub struct FileLock {
+    _file: File,
+}
+
+/// Lock `path` against other processes, waiting for any that holds it. The
+/// lock is taken on a `.lock` file beside it, since the file itself is
+/// replaced on every write.
+///
+/// # Errors
+///
+/// Returns an error if the lock file cannot be created or locked.
+pub fn lock(path: &Path) -> Result<FileLock, JsonFileError> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    let lock_path = path.with_file_name(name);
+    let io_err = |source| JsonFileError::Io {
+        path: lock_path.clone(),
+        source,
+    };
+
+    if let Some(parent) = lock_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(io_err)?;
+    }
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(io_err)?;
+    file.lock_exclusive().map_err(io_err)?;
+    Ok(FileLock { _file: file })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn entries() -> BTreeMap<String, u64> {
+        BTreeMap::from([("chr1".to_string(), 1000), ("chr2".to_string(), 2000)])
+    }
+
+    #[test]
+    fn test_write_and_read_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("cache.json");
+
+        write_entries(&path, 3, &entries(), false).unwrap();
+        let read: BTreeMap<String, u64> = read_entries(&path, 3).unwrap();
+        assert_eq!(read, entries());
+
+        // Overwriting leaves only the file itself behind
+        write_entries(&path, 3, &BTreeMap::<String, u64>::new(), true).unwrap();
+        let read: BTreeMap<String, u64> = read_entries(&path, 3).unwrap();
+        assert!(read.is_empty());
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_read_other_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        write_entries(&path, 1, &entries(), false).unwrap();
+
+        assert!(matches!(
+            read_entries::<BTreeMap<String, u64>>(&path, 2),
+            Err(JsonFileError::UnsupportedVersion { version: 1, .. })
+        ));
+        let file = VersionedJson::read(&path).unwrap();
+        assert_eq!(file.version, 1);
+        assert_eq!(file.entries::<BTreeMap<String, u64>>().unwrap(), entries());
+    }
+
+    #[test]
+    fn test_read_invalid_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+
+        assert!(matches!(
+            read_entries::<BTreeMap<String, u64>>(&path, 1),
+            Err(JsonFileError::Io { .. })
+        ));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            read_entries::<BTreeMap<String, u64>>(&path, 1),
+            Err(JsonFileError::Parse { .. })
+        ));
+
+        std::fs::write(&path, r#"{"version": 1, "entries": [1, 2]}"#).unwrap();
+        assert!(matches!(
+            read_entries::<BTreeMap<String, u64>>(&path, 1),
+            Err(JsonFileError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lock_excludes_other_holders() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("cache.json");
+
+        let held = lock(&path).unwrap();
+        let other = File::open(dir.path().join("nested").join("cache.json.lock")).unwrap();
+        assert!(other.try_lock_exclusive().is_err());
+
+        drop(held);
+        other.try_lock_exclusive().unwrap();
+        other.unlock().unwrap();
+        drop(lock(&path).unwrap());
+    }
+}
@@ -1 +1,22 @@
+pub mod glob;
+pub mod json_file;
 pub mod validation;
+
+use std::path::PathBuf;
+
+/// Per-user cache directory for ref-solver: `$XDG_CACHE_HOME/ref-solver`,
+/// falling back to `~/.cache/ref-solver`.
+///
+/// Returns `None` if neither `XDG_CACHE_HOME` nor `HOME` is set.
+#[must_use]
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .filter(|v| !v.is_empty())
+                .map(|home| PathBuf::from(home).join(".cache"))
+        })?;
+    Some(base.join("ref-solver"))
+}
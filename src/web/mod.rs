@@ -21,6 +21,12 @@
 //! - `GET /` - Main page with header input form
 //! - `POST /api/identify` - Identify reference from header (multipart form)
 //! - `GET /api/catalog` - List all references in the catalog
+//! - `GET /service-info`, `/collection/{digest}`, `/comparison/{d1}/{d2}`,
+//!   `/list/collection` - GA4GH seqcol API (see [`seqcol`])
+//! - `GET /sequence/service-info`, `/sequence/{digest}`, `/sequence/{digest}/metadata` -
+//!   GA4GH refget sequences API, served with `--refget-fasta` (see [`refget`])
 
 pub mod format_detection;
+pub mod refget;
+pub mod seqcol;
 pub mod server;
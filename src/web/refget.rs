@@ -0,0 +1,486 @@
+//! GA4GH refget sequences API endpoints backed by local FASTA files.
+//!
+//! Enabled with `ref-solver serve --refget-fasta <dir>`. See
+//! [`crate::refget::local`] for how sequences are discovered and resolved.
+//!
+//! - `GET /sequence/service-info` - GA4GH service-info for the refget API
+//! - `GET /sequence/{digest}?start=&end=` - Sequence bases (also honors `Range: bytes=`),
+//!   streamed in chunks so whole chromosomes are never held in memory
+//! - `GET /sequence/{digest}/metadata` - Sequence digests, length, and aliases
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+use tokio::io::{AsyncWriteExt, DuplexStream};
+use tokio_util::io::ReaderStream;
+
+use crate::parsing::fasta::IndexedFastaReader;
+use crate::parsing::sam::ParseError;
+use crate::refget::local::LocalSequence;
+use crate::web::server::{create_safe_error_response, AppState, ErrorType};
+
+/// Content type for refget sequence responses
+const SEQUENCE_CONTENT_TYPE: &str = "text/vnd.ga4gh.refget.v2.0.0+plain; charset=us-ascii";
+
+/// Bases read at a time while streaming a sequence
+const CHUNK_BASES: u64 = 1 << 20;
+
+/// Query parameters for `/sequence/{digest}`
+#[derive(Deserialize)]
+pub(crate) struct SequenceParams {
+    /// 0-based inclusive start
+    start: Option<u64>,
+    /// 0-based exclusive end
+    end: Option<u64>,
+}
+
+/// GA4GH service-info for the refget sequences API
+pub(crate) async fn service_info_handler() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "id": "org.fulcrumgenomics.ref-solver.refget",
+        "name": "ref-solver refget",
+        "type": {
+            "group": "org.ga4gh",
+            "artifact": "refget",
+            "version": "2.0.0",
+        },
+        "description": "Reference sequences served from local FASTA files",
+        "organization": {
+            "name": "Fulcrum Genomics",
+            "url": "https://www.fulcrumgenomics.com",
+        },
+        "version": env!("CARGO_PKG_VERSION"),
+        "refget": {
+            "circular_supported": false,
+            "algorithms": ["md5", "ga4gh"],
+            "identifier_types": ["md5", "ga4gh"],
+            "subsequence_limit": 0,
+            "supported_api_versions": ["2.0.0"],
+        },
+    }))
+}
+
+/// Return the bases of a sequence, optionally restricted to a range
+pub(crate) async fn sequence_handler(
+    State(state): State<Arc<AppState>>,
+    Path(digest): Path<String>,
+    Query(params): Query<SequenceParams>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(sequence) = find_sequence(&state, &digest) else {
+        return sequence_not_found(&digest);
+    };
+
+    let range_header = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .map(ToString::to_string);
+
+    let (start, end, partial) = match (range_header, params.start.or(params.end)) {
+        (Some(_), Some(_)) => {
+            return refget_error(
+                StatusCode::BAD_REQUEST,
+                "Cannot combine start/end parameters with a Range header",
+            );
+        }
+        (Some(range), None) => match parse_range_header(&range, sequence.length) {
+            Some((start, end)) => (start, end, true),
+            None => {
+                return refget_error(StatusCode::RANGE_NOT_SATISFIABLE, "Invalid range");
+            }
+        },
+        (None, _) => {
+            let start = params.start.unwrap_or(0);
+            let end = params.end.unwrap_or(sequence.length);
+            // A start at or past the end of the sequence is unsatisfiable, even
+            // when it would select no bases
+            let past_end = params.start.is_some_and(|start| start >= sequence.length);
+            if past_end || start > end || end > sequence.length {
+                return refget_error(StatusCode::RANGE_NOT_SATISFIABLE, "Invalid range");
+            }
+            (start, end, false)
+        }
+    };
+
+    // Read the first chunk before responding, so an unreadable file is an error
+    // status rather than a truncated body
+    let sequence = sequence.clone();
+    let first_end = end.min(start + CHUNK_BASES);
+    let opened = tokio::task::spawn_blocking(move || {
+        let mut reader = sequence.open()?;
+        let bases = sequence.read(&mut reader, start, first_end)?;
+        Ok::<_, ParseError>((sequence, reader, bases))
+    })
+    .await;
+    let (sequence, reader, bases) = match opened {
+        Ok(Ok(opened)) => opened,
+        Ok(Err(e)) => {
+            tracing::error!("Failed to read sequence {digest}: {e}");
+            return refget_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read sequence");
+        }
+        Err(e) => {
+            tracing::error!("Sequence read task failed for {digest}: {e}");
+            return refget_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read sequence");
+        }
+    };
+
+    let (writer, body) = tokio::io::duplex(usize::try_from(CHUNK_BASES).unwrap_or(usize::MAX));
+    tokio::spawn(stream_bases(
+        sequence,
+        reader,
+        bases,
+        first_end..end,
+        writer,
+    ));
+
+    let status = if partial {
+        StatusCode::PARTIAL_CONTENT
+    } else {
+        StatusCode::OK
+    };
+    (
+        status,
+        [
+            (header::CONTENT_TYPE, SEQUENCE_CONTENT_TYPE.to_string()),
+            (header::CONTENT_LENGTH, (end - start).to_string()),
+        ],
+        Body::from_stream(ReaderStream::new(body)),
+    )
+        .into_response()
+}
+
+/// Write `bases`, then the rest of the sequence in `remaining` chunk by chunk,
+/// reading each chunk on the blocking pool. Stops early if the client goes
+/// away or a read fails, leaving the response short of its Content-Length.
+async fn stream_bases(
+    mut sequence: LocalSequence,
+    mut reader: IndexedFastaReader,
+    mut bases: Vec<u8>,
+    mut remaining: std::ops::Range<u64>,
+    mut writer: DuplexStream,
+) {
+    loop {
+        if writer.write_all(&bases).await.is_err() || remaining.is_empty() {
+            return;
+        }
+        let (start, end) = (
+            remaining.start,
+            remaining.end.min(remaining.start + CHUNK_BASES),
+        );
+        let read = tokio::task::spawn_blocking(move || {
+            let bases = sequence.read(&mut reader, start, end);
+            (sequence, reader, bases)
+        })
+        .await;
+        match read {
+            Ok((read_sequence, read_reader, Ok(read_bases))) => {
+                (sequence, reader, bases) = (read_sequence, read_reader, read_bases);
+                remaining.start = end;
+            }
+            Ok((sequence, _, Err(e))) => {
+                tracing::error!("Failed to read sequence {}: {e}", sequence.name);
+                return;
+            }
+            Err(e) => {
+                tracing::error!("Sequence read task failed: {e}");
+                return;
+            }
+        }
+    }
+}
+
+/// Return the metadata of a sequence
+pub(crate) async fn metadata_handler(
+    State(state): State<Arc<AppState>>,
+    Path(digest): Path<String>,
+) -> Response {
+    let Some(sequence) = find_sequence(&state, &digest) else {
+        return sequence_not_found(&digest);
+    };
+
+    let aliases: Vec<serde_json::Value> = std::iter::once(&sequence.name)
+        .chain(&sequence.aliases)
+        .map(|name| {
+            serde_json::json!({
+                "naming_authority": naming_authority(name),
+                "value": name,
+            })
+        })
+        .collect();
+
+    Json(serde_json::json!({
+        "metadata": {
+            "md5": sequence.md5,
+            "ga4gh": format!("SQ.{}", sequence.sha512t24u),
+            "length": sequence.length,
+            "aliases": aliases,
+            "circular": sequence.circular,
+        }
+    }))
+    .into_response()
+}
+
+fn find_sequence<'a>(state: &'a AppState, digest: &str) -> Option<&'a LocalSequence> {
+    state.refget_store.as_ref()?.get(digest)
+}
+
+/// Parse a single `Range: bytes=<first>-<last>` header into a half-open range.
+///
+/// Returns `None` if the range is malformed or starts beyond the sequence.
+fn parse_range_header(value: &str, length: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    let (first, last) = spec.split_once('-')?;
+    let first: u64 = first.trim().parse().ok()?;
+    let last: u64 = match last.trim() {
+        "" => length.checked_sub(1)?,
+        s => s.parse().ok()?,
+    };
+
+    if first > last || first >= length {
+        return None;
+    }
+    Some((first, last.min(length - 1) + 1))
+}
+
+/// Best-effort naming authority for a sequence name
+fn naming_authority(name: &str) -> &'static str {
+    if name.starts_with("chr") {
+        "ucsc"
+    } else if name.starts_with("NC_") || name.starts_with("NT_") || name.starts_with("NW_") {
+        "refseq"
+    } else if name.contains('.')
+        && name.chars().next().is_some_and(|c| c.is_ascii_uppercase())
+        && name
+            .split('.')
+            .next_back()
+            .is_some_and(|v| v.chars().all(|c| c.is_ascii_digit()))
+    {
+        "insdc"
+    } else {
+        "unknown"
+    }
+}
+
+fn sequence_not_found(digest: &str) -> Response {
+    refget_error(
+        StatusCode::NOT_FOUND,
+        &format!("Sequence not found: {digest}"),
+    )
+}
+
+fn refget_error(status: StatusCode, message: &str) -> Response {
+    let error_type = match status {
+        StatusCode::NOT_FOUND => ErrorType::SequenceNotFound,
+        StatusCode::INTERNAL_SERVER_ERROR => ErrorType::InternalError,
+        _ => ErrorType::InvalidRange,
+    };
+    (
+        status,
+        Json(create_safe_error_response(error_type, message, None)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::store::ReferenceCatalog;
+    use crate::core::contig::Contig;
+    use crate::core::reference::KnownReference;
+    use crate::core::types::{Assembly, ReferenceSource};
+    use crate::refget::local::LocalSequenceStore;
+    use crate::web::seqcol::SeqColStore;
+
+    const CHR1: &[u8] = b"ACGTACGTACGT";
+
+    fn make_state(dir: &std::path::Path) -> Arc<AppState> {
+        let fasta = dir.join("test.fa");
+        std::fs::write(&fasta, ">chr1\nACGTACGT\nACGT\n").unwrap();
+        std::fs::write(dir.join("test.fa.fai"), "chr1\t12\t6\t8\t9\n").unwrap();
+
+        let mut catalog = ReferenceCatalog::new();
+        catalog.add_reference(
+            KnownReference::new(
+                "test",
+                "Test",
+                Assembly::Other("test".to_string()),
+                ReferenceSource::Custom("test".to_string()),
+            )
+            .with_contigs(vec![
+                Contig::new("chr1", 12).with_md5(format!("{:x}", md5::compute(CHR1)))
+            ]),
+        );
+
+        let refget_store =
+            LocalSequenceStore::from_directories(&[dir.to_path_buf()], &catalog, None).unwrap();
+        Arc::new(AppState {
+            seqcols: SeqColStore::from_catalog(&catalog),
+            catalog,
+            refget_config: None,
+            refget_store: Some(refget_store),
+        })
+    }
+
+    async fn get_sequence(
+        state: &Arc<AppState>,
+        start: Option<u64>,
+        end: Option<u64>,
+        range: Option<&str>,
+    ) -> Response {
+        let mut headers = HeaderMap::new();
+        if let Some(range) = range {
+            headers.insert(header::RANGE, range.parse().unwrap());
+        }
+        sequence_handler(
+            State(state.clone()),
+            Path(format!("{:x}", md5::compute(CHR1))),
+            Query(SequenceParams { start, end }),
+            headers,
+        )
+        .await
+    }
+
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_sequence_handler() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = make_state(dir.path());
+
+        let response = get_sequence(&state, None, None, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_bytes(response).await, CHR1);
+
+        let response = get_sequence(&state, Some(6), Some(10), None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_bytes(response).await, b"GTAC");
+
+        let response = get_sequence(&state, None, None, Some("bytes=6-9")).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body_bytes(response).await, b"GTAC");
+
+        let response = get_sequence(&state, Some(0), None, Some("bytes=0-1")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = get_sequence(&state, Some(10), Some(5), None).await;
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        let response = get_sequence(&state, None, Some(13), None).await;
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        // Starting at or beyond the last base is unsatisfiable
+        for start in [12, 13] {
+            let response = get_sequence(&state, Some(start), None, None).await;
+            assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        }
+        let response = get_sequence(&state, Some(12), Some(12), None).await;
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        let response = sequence_handler(
+            State(state),
+            Path("unknown".to_string()),
+            Query(SequenceParams {
+                start: None,
+                end: None,
+            }),
+            HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_sequence_handler_streams_in_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // A sequence of several chunks, in 60-base lines
+        let length = 2 * CHUNK_BASES + 100;
+        let bases: Vec<u8> = (0..length).map(|i| b"ACGT"[(i % 4) as usize]).collect();
+        let mut fasta = b">big\n".to_vec();
+        for line in bases.chunks(60) {
+            fasta.extend_from_slice(line);
+            fasta.push(b'\n');
+        }
+        std::fs::write(dir.path().join("big.fa"), fasta).unwrap();
+        std::fs::write(
+            dir.path().join("big.fa.fai"),
+            format!("big\t{length}\t5\t60\t61\n"),
+        )
+        .unwrap();
+        let catalog = ReferenceCatalog::new();
+        let store =
+            LocalSequenceStore::from_directories(&[dir.path().to_path_buf()], &catalog, None)
+                .unwrap();
+        let state = Arc::new(AppState {
+            seqcols: SeqColStore::from_catalog(&catalog),
+            catalog,
+            refget_config: None,
+            refget_store: Some(store),
+        });
+
+        let response = sequence_handler(
+            State(state),
+            Path(format!("{:x}", md5::compute(&bases))),
+            Query(SequenceParams {
+                start: Some(10),
+                end: None,
+            }),
+            HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_LENGTH],
+            (length - 10).to_string()
+        );
+        assert_eq!(body_bytes(response).await, &bases[10..]);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_handler() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = make_state(dir.path());
+        let md5 = format!("{:x}", md5::compute(CHR1));
+
+        let response = metadata_handler(State(state), Path(format!("md5:{md5}"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(body["metadata"]["md5"], md5);
+        assert_eq!(body["metadata"]["length"], 12);
+        assert!(body["metadata"]["ga4gh"]
+            .as_str()
+            .unwrap()
+            .starts_with("SQ."));
+        assert_eq!(body["metadata"]["aliases"][0]["naming_authority"], "ucsc");
+    }
+
+    #[test]
+    fn test_parse_range_header() {
+        assert_eq!(parse_range_header("bytes=0-9", 100), Some((0, 10)));
+        assert_eq!(parse_range_header("bytes=10-", 100), Some((10, 100)));
+        assert_eq!(parse_range_header("bytes=90-200", 100), Some((90, 100)));
+        assert_eq!(parse_range_header("bytes=100-110", 100), None);
+        assert_eq!(parse_range_header("bytes=9-0", 100), None);
+        assert_eq!(parse_range_header("items=0-9", 100), None);
+    }
+
+    #[test]
+    fn test_naming_authority() {
+        assert_eq!(naming_authority("chr1"), "ucsc");
+        assert_eq!(naming_authority("NC_000001.11"), "refseq");
+        assert_eq!(naming_authority("CM000663.2"), "insdc");
+        assert_eq!(naming_authority("1"), "unknown");
+    }
+}
@@ -0,0 +1,374 @@
+//! GA4GH sequence collections (seqcol) API endpoints.
+//!
+//! Serves the references in the loaded catalog as sequence collections, keyed by
+//! their level-0 digest. References where any contig lacks a sha512t24u digest
+//! have no level-0 digest and are not served.
+//!
+//! - `GET /service-info` - GA4GH service-info for the seqcol API
+//! - `GET /collection/{digest}?level=1|2` - A collection at level 1 or 2 (default 2)
+//! - `GET /comparison/{digest1}/{digest2}` - Compare two collections
+//! - `GET /list/collection?page=&page_size=&<attribute>=<digest>` - List collection digests
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+
+use crate::catalog::store::ReferenceCatalog;
+use crate::seqcol::{SeqCol, SeqColComparison, SeqColLevel1};
+use crate::web::server::{create_safe_error_response, AppState, ErrorType};
+
+/// Default page size for `/list/collection`
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Maximum page size for `/list/collection`
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// A collection served by the seqcol API
+#[derive(Debug)]
+struct ServedCollection {
+    digest: String,
+    level1: SeqColLevel1,
+    level2: SeqCol,
+}
+
+/// Sequence collections derived from the reference catalog, indexed by level-0 digest
+#[derive(Debug, Default)]
+pub struct SeqColStore {
+    /// Collections in catalog order (duplicates removed)
+    collections: Vec<ServedCollection>,
+    /// Index: level-0 digest -> index in collections vec
+    digest_to_index: HashMap<String, usize>,
+}
+
+impl SeqColStore {
+    /// Build the store from every catalog reference that has a level-0 digest
+    #[must_use]
+    pub fn from_catalog(catalog: &ReferenceCatalog) -> Self {
+        let mut store = Self::default();
+
+        for reference in &catalog.references {
+            let level2 = SeqCol::from(reference);
+            let level1 = level2.level1();
+            let Some(digest) = level1.digest() else {
+                continue;
+            };
+            if store.digest_to_index.contains_key(&digest) {
+                continue;
+            }
+
+            store
+                .digest_to_index
+                .insert(digest.clone(), store.collections.len());
+            store.collections.push(ServedCollection {
+                digest,
+                level1,
+                level2,
+            });
+        }
+
+        store
+    }
+
+    /// Number of collections served
+    #[must_use]
+    pub fn collection_count(&self) -> usize {
+        self.collections.len()
+    }
+
+    fn get(&self, digest: &str) -> Option<&ServedCollection> {
+        self.digest_to_index
+            .get(digest)
+            .map(|&idx| &self.collections[idx])
+    }
+}
+
+/// Query parameters for `/collection/{digest}`
+#[derive(Deserialize)]
+pub(crate) struct CollectionParams {
+    /// Level of detail (1 or 2, default 2)
+    level: Option<u8>,
+}
+
+/// Query parameters for `/list/collection`
+#[derive(Deserialize)]
+pub(crate) struct ListParams {
+    /// Page number (0-based)
+    page: Option<usize>,
+    /// Page size (default: 100, max: 1000)
+    page_size: Option<usize>,
+    /// Filter by level-1 `names` digest
+    names: Option<String>,
+    /// Filter by level-1 `lengths` digest
+    lengths: Option<String>,
+    /// Filter by level-1 `sequences` digest
+    sequences: Option<String>,
+}
+
+/// GA4GH service-info for the seqcol API
+pub(crate) async fn service_info_handler() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "id": "org.fulcrumgenomics.ref-solver",
+        "name": "ref-solver",
+        "type": {
+            "group": "org.ga4gh",
+            "artifact": "refget-seqcol",
+            "version": "1.0.0",
+        },
+        "description": "Sequence collections for the references in the ref-solver catalog",
+        "organization": {
+            "name": "Fulcrum Genomics",
+            "url": "https://www.fulcrumgenomics.com",
+        },
+        "version": env!("CARGO_PKG_VERSION"),
+        "seqcol": {
+            "schema": {
+                "type": "object",
+                "properties": {
+                    "names": { "type": "array", "items": { "type": "string" } },
+                    "lengths": { "type": "array", "items": { "type": "integer" } },
+                    "sequences": { "type": "array", "items": { "type": "string" } },
+                },
+                "required": ["names", "lengths", "sequences"],
+                "ga4gh": { "inherent": crate::seqcol::INHERENT_ATTRIBUTES },
+            },
+            "sorted_name_length_pairs": false,
+        },
+    }))
+}
+
+/// Return a collection at level 1 or level 2
+pub(crate) async fn collection_handler(
+    State(state): State<Arc<AppState>>,
+    Path(digest): Path<String>,
+    Query(params): Query<CollectionParams>,
+) -> Response {
+    let Some(collection) = state.seqcols.get(&digest) else {
+        return collection_not_found(&digest);
+    };
+
+    match params.level.unwrap_or(2) {
+        1 => Json(serde_json::json!(collection.level1)).into_response(),
+        2 => Json(serde_json::json!(collection.level2)).into_response(),
+        _ => (
+            StatusCode::BAD_REQUEST,
+            Json(create_safe_error_response(
+                ErrorType::InvalidLevel,
+                "Level must be 1 or 2",
+                None,
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Compare two collections from the catalog
+pub(crate) async fn comparison_handler(
+    State(state): State<Arc<AppState>>,
+    Path((digest1, digest2)): Path<(String, String)>,
+) -> Response {
+    let Some(a) = state.seqcols.get(&digest1) else {
+        return collection_not_found(&digest1);
+    };
+    let Some(b) = state.seqcols.get(&digest2) else {
+        return collection_not_found(&digest2);
+    };
+
+    Json(SeqColComparison::compare(&a.level2, &b.level2)).into_response()
+}
+
+/// List collection digests, optionally filtered by level-1 attribute digests
+pub(crate) async fn list_collections_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ListParams>,
+) -> Json<serde_json::Value> {
+    let page = params.page.unwrap_or(0);
+    let page_size = params
+        .page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let matching: Vec<&str> = state
+        .seqcols
+        .collections
+        .iter()
+        .filter(|c| params.names.as_ref().map_or(true, |d| *d == c.level1.names))
+        .filter(|c| {
+            params
+                .lengths
+                .as_ref()
+                .map_or(true, |d| *d == c.level1.lengths)
+        })
+        .filter(|c| {
+            params
+                .sequences
+                .as_ref()
+                .map_or(true, |d| c.level1.sequences.as_ref() == Some(d))
+        })
+        .map(|c| c.digest.as_str())
+        .collect();
+
+    let results: Vec<&str> = matching
+        .iter()
+        .skip(page.saturating_mul(page_size))
+        .take(page_size)
+        .copied()
+        .collect();
+
+    Json(serde_json::json!({
+        "results": results,
+        "pagination": {
+            "page": page,
+            "page_size": page_size,
+            "total": matching.len(),
+        },
+    }))
+}
+
+fn collection_not_found(digest: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(create_safe_error_response(
+            ErrorType::CollectionNotFound,
+            &format!("Collection not found: {digest}"),
+            None,
+        )),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::contig::Contig;
+    use crate::core::reference::KnownReference;
+    use crate::core::types::{Assembly, ReferenceSource};
+
+    fn make_state() -> Arc<AppState> {
+        let mut catalog = ReferenceCatalog::new();
+        catalog.add_reference(
+            KnownReference::new(
+                "base",
+                "Base",
+                Assembly::Other("test".to_string()),
+                ReferenceSource::Custom("test".to_string()),
+            )
+            .with_contigs(vec![
+                Contig::new("chrX", 8).with_sha512t24u("iYtREV555dUFKg2_agSJW6suquUyPpMw"),
+                Contig::new("chr1", 4).with_sha512t24u("YBbVX0dLKG1ieEDCiMmkrTZFt_Z5Vdaj"),
+                Contig::new("chr2", 4).with_sha512t24u("AcLxtBuKEPk_7PGE_H4dGElwZHCujwH6"),
+            ]),
+        );
+        catalog.add_reference(
+            KnownReference::new(
+                "no_digests",
+                "No Digests",
+                Assembly::Other("test".to_string()),
+                ReferenceSource::Custom("test".to_string()),
+            )
+            .with_contigs(vec![Contig::new("chr1", 4)]),
+        );
+
+        let seqcols = SeqColStore::from_catalog(&catalog);
+        Arc::new(AppState {
+            catalog,
+            refget_config: None,
+            seqcols,
+            refget_store: None,
+        })
+    }
+
+    const BASE_DIGEST: &str = "XZlrcEGi6mlopZ2uD8ObHkQB1d0oDwKk";
+
+    #[test]
+    fn test_store_skips_references_without_digest() {
+        let state = make_state();
+        assert_eq!(state.seqcols.collection_count(), 1);
+        assert!(state.seqcols.get(BASE_DIGEST).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_collection_levels() {
+        let state = make_state();
+
+        let response = collection_handler(
+            State(state.clone()),
+            Path(BASE_DIGEST.to_string()),
+            Query(CollectionParams { level: None }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = collection_handler(
+            State(state.clone()),
+            Path(BASE_DIGEST.to_string()),
+            Query(CollectionParams { level: Some(3) }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = collection_handler(
+            State(state),
+            Path("unknown".to_string()),
+            Query(CollectionParams { level: Some(1) }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_comparison() {
+        let state = make_state();
+
+        let response = comparison_handler(
+            State(state.clone()),
+            Path((BASE_DIGEST.to_string(), BASE_DIGEST.to_string())),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = comparison_handler(
+            State(state),
+            Path((BASE_DIGEST.to_string(), "unknown".to_string())),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_list_collections() {
+        let state = make_state();
+
+        let Json(all) = list_collections_handler(
+            State(state.clone()),
+            Query(ListParams {
+                page: None,
+                page_size: None,
+                names: None,
+                lengths: None,
+                sequences: None,
+            }),
+        )
+        .await;
+        assert_eq!(all["results"], serde_json::json!([BASE_DIGEST]));
+        assert_eq!(all["pagination"]["total"], 1);
+
+        let Json(filtered) = list_collections_handler(
+            State(state),
+            Query(ListParams {
+                page: None,
+                page_size: None,
+                names: Some("unknown".to_string()),
+                lengths: None,
+                sequences: None,
+            }),
+        )
+        .await;
+        assert_eq!(filtered["pagination"]["total"], 0);
+    }
+}
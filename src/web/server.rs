@@ -9,6 +9,7 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpListener;
@@ -20,12 +21,19 @@ use tower_http::timeout::TimeoutLayer;
 
 use crate::catalog::store::ReferenceCatalog;
 use crate::cli::ServeArgs;
-use crate::matching::engine::{MatchingConfig, MatchingEngine, ScoringWeights};
+use crate::matching::disambiguation::Discriminator;
+use crate::matching::engine::{
+    MatchingConfig, MatchingEngine, ScoringMode, ScoringWeights, DEFAULT_AMBIGUITY_TOLERANCE,
+};
 use crate::matching::Suggestion;
+use crate::parsing::digest_cache::DigestCache;
+use crate::refget::local::LocalSequenceStore;
 use crate::utils::validation::{validate_upload, ValidationError};
 use crate::web::format_detection::{
     detect_format, parse_binary_file, parse_binary_file_from_path, parse_with_format, FileFormat,
 };
+use crate::web::refget;
+use crate::web::seqcol::{self, SeqColStore};
 
 /// Security configuration constants to prevent `DoS` attacks
 pub const MAX_MULTIPART_FIELDS: usize = 10;
@@ -54,6 +62,8 @@ fn count_to_f64(count: usize) -> f64 {
 pub struct AppState {
     pub catalog: ReferenceCatalog,
     pub refget_config: Option<crate::refget::RefgetConfig>,
+    pub seqcols: SeqColStore,
+    pub refget_store: Option<LocalSequenceStore>,
 }
 
 /// Binary content from an upload, either fully buffered or streamed to a temp file.
@@ -98,6 +108,10 @@ pub enum ErrorType {
     FormatDetectionFailed,
     ParseFailed,
     BinaryParseFailed,
+    CollectionNotFound,
+    InvalidLevel,
+    SequenceNotFound,
+    InvalidRange,
 }
 
 /// Enhanced error response
@@ -164,18 +178,33 @@ pub fn run(args: ServeArgs) -> anyhow::Result<()> {
 
 /// Create the application router with all routes and middleware configured.
 ///
-/// Pass `None` for `refget_config` to disable refget enrichment.
+/// Pass `None` for `refget_config` to disable refget enrichment. The refget
+/// sequences API is only served when `refget_fasta` names at least one directory;
+/// their sequences are hashed, taking digests from `digest_cache` where it has them.
 ///
 /// # Errors
 ///
-/// Returns an error if the catalog cannot be loaded.
+/// Returns an error if the catalog cannot be loaded or a FASTA directory cannot be read.
 #[allow(clippy::missing_panics_doc)] // Panics only on invalid governor config (constants are valid)
-pub fn create_router(refget_config: Option<crate::refget::RefgetConfig>) -> anyhow::Result<Router> {
+pub fn create_router(
+    refget_config: Option<crate::refget::RefgetConfig>,
+    refget_fasta: &[PathBuf],
+    digest_cache: Option<&DigestCache>,
+) -> anyhow::Result<Router> {
     // Load catalog
     let catalog = ReferenceCatalog::load_embedded()?;
+    let seqcols = SeqColStore::from_catalog(&catalog);
+    tracing::debug!(
+        "Serving {} sequence collections",
+        seqcols.collection_count()
+    );
+    let refget_store = load_refget_store(refget_fasta, &catalog, digest_cache)?;
+    let serve_sequences = refget_store.is_some();
     let state = Arc::new(AppState {
         catalog,
         refget_config,
+        seqcols,
+        refget_store,
     });
 
     // Configure IP-based rate limiting
@@ -190,6 +219,25 @@ pub fn create_router(refget_config: Option<crate::refget::RefgetConfig>) -> anyh
         .route("/", get(index_handler))
         .route("/api/identify", post(identify_handler))
         .route("/api/catalog", get(catalog_handler))
+        // GA4GH seqcol API
+        .route("/service-info", get(seqcol::service_info_handler))
+        .route("/collection/{digest}", get(seqcol::collection_handler))
+        .route(
+            "/comparison/{digest1}/{digest2}",
+            get(seqcol::comparison_handler),
+        )
+        .route("/list/collection", get(seqcol::list_collections_handler));
+
+    // GA4GH refget sequences API (only with --refget-fasta)
+    let app = if serve_sequences {
+        app.route("/sequence/service-info", get(refget::service_info_handler))
+            .route("/sequence/{digest}", get(refget::sequence_handler))
+            .route("/sequence/{digest}/metadata", get(refget::metadata_handler))
+    } else {
+        app
+    };
+
+    let app = app
         // Static file routes
         .route("/static/css/styles.css", get(styles_css_handler))
         .route("/static/js/main.js", get(main_js_handler))
@@ -257,13 +305,41 @@ pub fn create_router(refget_config: Option<crate::refget::RefgetConfig>) -> anyh
     Ok(app)
 }
 
+/// Build the local refget sequence store, or `None` if no FASTA directories were given
+fn load_refget_store(
+    refget_fasta: &[PathBuf],
+    catalog: &ReferenceCatalog,
+    digest_cache: Option<&DigestCache>,
+) -> anyhow::Result<Option<LocalSequenceStore>> {
+    if refget_fasta.is_empty() {
+        return Ok(None);
+    }
+
+    let store = LocalSequenceStore::from_directories(refget_fasta, catalog, digest_cache)?;
+    if store.is_empty() {
+        tracing::warn!("No indexed FASTA sequences found for the refget API");
+    } else {
+        tracing::info!("Serving {} sequences via refget", store.len());
+    }
+    Ok(Some(store))
+}
+
 async fn run_server(args: ServeArgs) -> anyhow::Result<()> {
     let refget_config = if args.no_refget {
         None
     } else {
-        Some(crate::refget::RefgetConfig::new(&args.refget_server))
+        let config = crate::refget::RefgetConfig::new(&args.refget_server);
+        match args.refget_cache.open()? {
+            Some(cache) => Some(config.with_cache(cache)),
+            None => Some(config),
+        }
     };
-    let app = create_router(refget_config)?;
+    let digest_cache = if args.refget_fasta.is_empty() {
+        None
+    } else {
+        args.digest_cache.open()?
+    };
+    let app = create_router(refget_config, &args.refget_fasta, digest_cache.as_deref())?;
 
     let addr = format!("{}:{}", args.address, args.port);
     println!("Starting ref-solver web server at http://{addr}");
@@ -391,6 +467,7 @@ async fn identify_handler(
     let matching_config = MatchingConfig {
         min_score: config.score_threshold,
         scoring_weights: config.scoring_weights.clone(),
+        ambiguity_tolerance: DEFAULT_AMBIGUITY_TOLERANCE,
     };
 
     let engine = MatchingEngine::new(&state.catalog, matching_config);
@@ -432,6 +509,12 @@ async fn identify_handler(
                     },
                 },
                 "match_type": format!("{:?}", m.diagnosis.match_type),
+                "ambiguity": m.ambiguity,
+                "tiers": {
+                    "summary": m.tiers.summary(),
+                    "primary": m.tiers.primary,
+                    "auxiliary": m.tiers.auxiliary,
+                },
                 "reordered": m.diagnosis.reordered,
                 "exact_matches": m.diagnosis.exact_matches.len(),
                 "renamed_matches": m.diagnosis.renamed_matches.len(),
@@ -476,6 +559,14 @@ async fn identify_handler(
                         Suggestion::Realign { reason, suggested_reference } => {
                             serde_json::json!({"type": "realign", "reason": reason, "reference": suggested_reference})
                         }
+                        Suggestion::Disambiguate { plan } => {
+                            serde_json::json!({
+                                "type": "disambiguate",
+                                "candidates": plan.candidates,
+                                "steps": plan.discriminators.iter().map(Discriminator::description).collect::<Vec<_>>(),
+                                "unresolved": plan.unresolved,
+                            })
+                        }
                     }
                 }).collect::<Vec<_>>(),
             })
@@ -494,6 +585,7 @@ async fn identify_handler(
         },
         "warnings": parse_warnings,
         "matches": results,
+        "ambiguous": matches.first().is_some_and(|m| m.ambiguity.ambiguous),
         "processing_info": {
             "detected_format": input_data.format.as_ref().map_or("unknown", super::format_detection::FileFormat::display_name),
             "processing_time_ms": processing_time,
@@ -734,10 +826,13 @@ async fn handle_detailed_response(
                     "match_status": match_status
                 });
 
-                // Attach refget metadata for missing contigs that were enriched
+                // Attach refget metadata (and any back-filled digests) for missing
+                // contigs that were enriched
                 if match_status == "missing" {
                     if let Some(enriched) = enriched_map.get(&contig.name) {
                         entry["refget_metadata"] = serde_json::json!(&enriched.refget_metadata);
+                        entry["md5"] = serde_json::json!(enriched.md5);
+                        entry["sha512t24u"] = serde_json::json!(enriched.sha512t24u);
                     }
                 }
 
@@ -1275,6 +1370,7 @@ fn parse_scoring_weights(weights: &HashMap<String, f64>) -> ScoringWeights {
         coverage,
         order,
         conflict_penalty,
+        scoring_mode: ScoringMode::Count,
     }
 }
 
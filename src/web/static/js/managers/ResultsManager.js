@@ -21,10 +21,13 @@ import { escapeHtml } from '../utils/helpers.js';
 
 /**
  * @typedef {Object} Suggestion
- * @property {'rename'|'reorder'|'replace'|'use_as_is'|'realign'} type - Suggestion type
+ * @property {'rename'|'reorder'|'replace'|'use_as_is'|'realign'|'disambiguate'} type - Suggestion type
  * @property {string} [command] - Command to execute
  * @property {string} [contig] - Contig name
  * @property {string} [reason] - Reason for suggestion
+ * @property {string[]} [candidates] - Tied references to tell apart
+ * @property {string[]} [steps] - Contig checks that distinguish the candidates
+ * @property {string[][]} [unresolved] - Groups of candidates no contig distinguishes
  */
 
 /**
@@ -36,9 +39,17 @@ import { escapeHtml } from '../utils/helpers.js';
  * @property {number} renamed_matches - Number of renamed matches
  * @property {number} conflicts - Number of conflicts
  * @property {boolean} reordered - Whether contigs are reordered
+ * @property {Ambiguity} ambiguity - How clearly the match stands out from other candidates
  * @property {Suggestion[]} suggestions - Array of suggestions
  */
 
+/**
+ * @typedef {Object} Ambiguity
+ * @property {boolean} ambiguous - Whether another candidate is tied with this one
+ * @property {number|null} margin - Score difference to the best other candidate
+ * @property {string[]} tied_with - IDs of tied candidates
+ */
+
 /**
  * @typedef {Object} QueryInfo
  * @property {number} contig_count - Number of contigs
@@ -50,6 +61,7 @@ import { escapeHtml } from '../utils/helpers.js';
  * @typedef {Object} ResultsData
  * @property {QueryInfo} query - Query information
  * @property {Match[]} matches - Array of matches
+ * @property {boolean} ambiguous - Whether the top match is tied with another candidate
  * @property {string} [error] - Error message if any
  */
 
@@ -113,6 +125,18 @@ export class ResultsManager {
             match.score.composite >= config.scoreThreshold
         );
 
+        // Warn when the evidence cannot tell the top candidates apart
+        const top = this.currentResults[0];
+        if (data.ambiguous && top) {
+            warningsHtml += `
+                <div class="warning-banner">
+                    <strong>Ambiguous:</strong>
+                    ${escapeHtml(top.reference.display_name)} is indistinguishable from
+                    ${escapeHtml(top.ambiguity.tied_with.join(', '))} given the evidence in this header.
+                </div>
+            `;
+        }
+
         let html = warningsHtml + `
             <div class="stats">
                 <div>Contigs: <span>${data.query.contig_count}</span></div>
@@ -190,6 +214,7 @@ export class ResultsManager {
                                 <div class="meta-item">Renamed: <span>${match.renamed_matches}</span></div>
                                 ${match.conflicts > 0 ? `<div class="meta-item" style="color: var(--error)">Conflicts: <span>${match.conflicts}</span></div>` : ''}
                                 ${match.reordered ? `<div class="meta-item" style="color: var(--warning)">Reordered: <span>Yes</span></div>` : ''}
+                                ${match.ambiguity.ambiguous ? `<div class="meta-item" style="color: var(--warning)">Tied With: <span>${escapeHtml(match.ambiguity.tied_with.join(', '))}</span></div>` : ''}
                             </div>
                             ${this.renderSuggestions(match.suggestions)}
                             <div style="margin-top: 1rem;">
@@ -244,6 +269,15 @@ export class ResultsManager {
                 case 'realign':
                     html += `<p style="color: var(--error)"><strong>Realignment needed:</strong> ${escapeHtml(s.reason)}</p>`;
                     break;
+                case 'disambiguate':
+                    html += `<p style="color: var(--warning)"><strong>Disambiguate ${escapeHtml(s.candidates.join(', '))}:</strong></p>`;
+                    for (const step of s.steps) {
+                        html += `<div class="suggestion-code">${escapeHtml(step)}</div>`;
+                    }
+                    for (const group of s.unresolved) {
+                        html += `<p>No contig distinguishes ${escapeHtml(group.join(', '))}</p>`;
+                    }
+                    break;
             }
         }
 
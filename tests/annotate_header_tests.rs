@@ -0,0 +1,62 @@
+//! Integration tests for `annotate-header` on headers that tie several
+//! catalog references.
+
+use std::process::Command;
+
+use ref_solver::{ReferenceCatalog, ReferenceId};
+
+#[test]
+fn test_annotate_header_with_tied_references() {
+    // The primary chromosomes of GRCh38 without MD5s tie every GRCh38 build
+    // that names them the UCSC way
+    let catalog = ReferenceCatalog::load_embedded().unwrap();
+    let reference = catalog.get(&ReferenceId::new("hs38DH")).unwrap();
+    let mut header = String::from("@HD\tVN:1.6\n");
+    for contig in reference.contigs.iter().take(25) {
+        header.push_str(&format!("@SQ\tSN:{}\tLN:{}\n", contig.name, contig.length));
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("primary.sam");
+    std::fs::write(&input, header).unwrap();
+    let output = dir.path().join("annotated.sam");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_ref-solver"))
+        .args(["--format", "json", "annotate-header"])
+        .arg(&input)
+        .arg("--output")
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(result.status.success(), "{result:?}");
+
+    let report: serde_json::Value = serde_json::from_slice(&result.stdout).unwrap();
+    let tied_with = report["tied_with"].as_array().unwrap();
+    assert!(!tied_with.is_empty(), "{report}");
+    assert_eq!(report["contigs"], 25);
+
+    // chr1 is the same sequence in every GRCh38 build
+    let annotated = std::fs::read_to_string(&output).unwrap();
+    let chr1 = annotated
+        .lines()
+        .find(|line| line.starts_with("@SQ\tSN:chr1\t"))
+        .unwrap();
+    assert!(
+        chr1.contains("M5:6aef897c3d6ff0c78aff06ac189178dd"),
+        "{chr1}"
+    );
+
+    // The analysis sets mask chrY's PARs, so the builds disagree on it
+    let skipped = report["skipped"].as_array().unwrap();
+    assert!(
+        skipped
+            .iter()
+            .any(|s| s["name"] == "chrY" && s["reason"] == "disagreement"),
+        "{report}"
+    );
+    let chr_y = annotated
+        .lines()
+        .find(|line| line.starts_with("@SQ\tSN:chrY\t"))
+        .unwrap();
+    assert!(!chr_y.contains("M5:"), "{chr_y}");
+}
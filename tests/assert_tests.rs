@@ -0,0 +1,85 @@
+//! Integration tests for the `assert` command's exit status.
+//!
+//! The embedded catalog holds four GRCh38 analysis sets with the same
+//! contigs (the Broad and 1000 Genomes analysis sets, hs38DH and DRAGEN's
+//! ALT-masked build), which makes a realistic tie of more than two references.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ref_solver::{ReferenceCatalog, ReferenceId};
+
+/// Write a SAM header with the contigs of a catalog reference that have an
+/// MD5 in the catalog, as `samtools dict` would list them
+fn write_header(dir: &Path, reference_id: &str, with_md5: bool) -> PathBuf {
+    let catalog = ReferenceCatalog::load_embedded().unwrap();
+    let reference = catalog.get(&ReferenceId::new(reference_id)).unwrap();
+
+    let mut header = String::from("@HD\tVN:1.6\n");
+    for contig in &reference.contigs {
+        let Some(md5) = &contig.md5 else {
+            continue;
+        };
+        header.push_str(&format!("@SQ\tSN:{}\tLN:{}", contig.name, contig.length));
+        if with_md5 {
+            header.push_str(&format!("\tM5:{md5}"));
+        }
+        header.push('\n');
+    }
+
+    let path = dir.join(format!("{reference_id}.sam"));
+    std::fs::write(&path, header).unwrap();
+    path
+}
+
+fn assert_status(input: &Path, expected: &[&str]) -> (Option<i32>, serde_json::Value) {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_ref-solver"));
+    command.args(["--format", "json", "assert"]).arg(input);
+    for reference in expected {
+        command.args(["--expect", reference]);
+    }
+    let output = command.output().unwrap();
+    let report = serde_json::from_slice(&output.stdout).unwrap();
+    (output.status.code(), report)
+}
+
+#[test]
+fn test_assert_fails_on_partially_allowed_tie() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = write_header(dir.path(), "hs38DH", false);
+
+    // Names and lengths alone tie four references; allowing two is not enough
+    let (status, report) = assert_status(
+        &input,
+        &["grch38_broad_analysis_set", "grch38_1kg_analysis"],
+    );
+    assert_eq!(status, Some(2), "{report}");
+    let violations = report["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["rule"], "ambiguous");
+    let message = violations[0]["message"].as_str().unwrap();
+    assert!(message.contains("hs38DH"), "{message}");
+    assert!(message.contains("grch38_dragen_altmasked"), "{message}");
+
+    // Allowing every tied reference passes
+    let (status, report) = assert_status(
+        &input,
+        &[
+            "grch38_broad_analysis_set",
+            "grch38_1kg_analysis",
+            "hs38DH",
+            "grch38_dragen_altmasked",
+        ],
+    );
+    assert_eq!(status, Some(0), "{report}");
+}
+
+#[test]
+fn test_assert_accepts_any_reference_with_identical_sequences() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = write_header(dir.path(), "hs38DH", true);
+
+    let (status, report) = assert_status(&input, &["hs38DH"]);
+    assert_eq!(status, Some(0), "{report}");
+    assert_eq!(report["match"]["reference"]["id"], "hs38DH");
+}
@@ -0,0 +1,95 @@
+//! Integration tests for batch `identify`: row order, the summary and the
+//! exit status when some inputs cannot be identified.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ref_solver::{ReferenceCatalog, ReferenceId};
+
+/// Write a sequence dictionary with the contigs of a catalog reference
+fn write_dict(path: &Path, reference_id: &str) {
+    let catalog = ReferenceCatalog::load_embedded().unwrap();
+    let reference = catalog.get(&ReferenceId::new(reference_id)).unwrap();
+
+    let mut dict = String::from("@HD\tVN:1.6\n");
+    for contig in &reference.contigs {
+        dict.push_str(&format!("@SQ\tSN:{}\tLN:{}", contig.name, contig.length));
+        if let Some(md5) = &contig.md5 {
+            dict.push_str(&format!("\tM5:{md5}"));
+        }
+        dict.push('\n');
+    }
+    std::fs::write(path, dict).unwrap();
+}
+
+fn identify(inputs: &[PathBuf]) -> (bool, serde_json::Value) {
+    let output = Command::new(env!("CARGO_BIN_EXE_ref-solver"))
+        .args(["--format", "json", "identify"])
+        .args(inputs)
+        .output()
+        .unwrap();
+    let report = serde_json::from_slice(&output.stdout).unwrap();
+    (output.status.success(), report)
+}
+
+fn row_paths(report: &serde_json::Value) -> Vec<String> {
+    report["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| row["path"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_batch_rows_follow_inputs_and_summary_counts_references() {
+    let dir = tempfile::tempdir().unwrap();
+    let chm13 = dir.path().join("b_chm13.dict");
+    let hs37d5 = dir.path().join("a_hs37d5.dict");
+    write_dict(&chm13, "chm13v2");
+    write_dict(&hs37d5, "hs37d5");
+
+    // The directory adds no rows: both files were already named
+    let (success, report) = identify(&[chm13.clone(), hs37d5.clone(), dir.path().to_path_buf()]);
+    assert!(success, "{report}");
+    assert_eq!(
+        row_paths(&report),
+        vec![chm13.display().to_string(), hs37d5.display().to_string()]
+    );
+    assert_eq!(report["files"][0]["reference"], "chm13v2");
+    assert_eq!(report["files"][1]["reference"], "hs37d5");
+
+    let summary = &report["summary"];
+    assert_eq!(summary["files"], 2);
+    assert_eq!(summary["identified"], 2);
+    assert_eq!(summary["errors"], 0);
+    assert_eq!(summary["by_reference"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_batch_fails_when_an_input_cannot_be_identified() {
+    let dir = tempfile::tempdir().unwrap();
+    let hs37d5 = dir.path().join("hs37d5.dict");
+    write_dict(&hs37d5, "hs37d5");
+    let missing = dir.path().join("missing.bam");
+    let no_match = dir.path().join("*.cram");
+
+    // Every input is still reported
+    let (success, report) = identify(&[missing.clone(), hs37d5.clone(), no_match.clone()]);
+    assert!(!success, "{report}");
+    assert_eq!(
+        row_paths(&report),
+        vec![
+            missing.display().to_string(),
+            hs37d5.display().to_string(),
+            no_match.display().to_string(),
+        ]
+    );
+    assert_eq!(report["files"][1]["reference"], "hs37d5");
+    assert_eq!(report["summary"]["errors"], 2);
+
+    // A glob that matches every file passes
+    let (success, report) = identify(&[dir.path().join("*.dict")]);
+    assert!(success, "{report}");
+    assert_eq!(row_paths(&report), vec![hs37d5.display().to_string()]);
+}
@@ -0,0 +1,148 @@
+//! End-to-end tests for the `reheader` command: records written under the
+//! input's contig names must read back under the reference's names.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use noodles::sam::alignment::record::data::field::Tag;
+use noodles::sam::alignment::record_buf::data::field::Value;
+use noodles::sam::alignment::RecordBuf;
+use noodles::{bam, sam};
+
+/// NCBI-style names with GRCh38 lengths, aligned as by BWA with `SA` and `XA` tags
+const INPUT: &str = "@HD\tVN:1.6\tSO:coordinate\n\
+@SQ\tSN:1\tLN:248956422\n\
+@SQ\tSN:2\tLN:242193529\n\
+r1\t65\t1\t100\t60\t4M\t2\t200\t0\tACGT\tIIII\tSA:Z:2,500,+,4M,60,0;\tXA:Z:2,+300,4M,0;1,-900,4M,1;\n\
+r1\t129\t2\t200\t60\t4M\t1\t100\t0\tACGT\tIIII\tNM:i:0\n\
+r2\t4\t*\t0\t0\t*\t*\t0\t0\tACGT\tIIII\n";
+
+fn reheader(input: &Path, output: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ref-solver"))
+        .arg("reheader")
+        .arg(input)
+        .args(["--to", "hg38_ucsc", "--output"])
+        .arg(output)
+        .output()
+        .unwrap()
+}
+
+fn contig_name(header: &sam::Header, id: Option<usize>) -> Option<String> {
+    id.map(|id| {
+        let (name, _) = header.reference_sequences().get_index(id).unwrap();
+        name.to_string()
+    })
+}
+
+fn string_tag(record: &RecordBuf, tag: [u8; 2]) -> Option<String> {
+    match record.data().get(&Tag::new(tag[0], tag[1])) {
+        Some(Value::String(value)) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn check_records(header: &sam::Header, records: &[RecordBuf]) {
+    assert_eq!(records.len(), 3);
+
+    let first = &records[0];
+    assert_eq!(
+        contig_name(header, first.reference_sequence_id()).as_deref(),
+        Some("chr1")
+    );
+    assert_eq!(
+        contig_name(header, first.mate_reference_sequence_id()).as_deref(),
+        Some("chr2")
+    );
+    assert_eq!(
+        string_tag(first, *b"SA").as_deref(),
+        Some("chr2,500,+,4M,60,0;")
+    );
+    assert_eq!(
+        string_tag(first, *b"XA").as_deref(),
+        Some("chr2,+300,4M,0;chr1,-900,4M,1;")
+    );
+
+    let mate = &records[1];
+    assert_eq!(
+        contig_name(header, mate.reference_sequence_id()).as_deref(),
+        Some("chr2")
+    );
+    assert_eq!(string_tag(mate, *b"SA"), None);
+
+    assert_eq!(records[2].reference_sequence_id(), None);
+}
+
+#[test]
+fn test_reheader_sam_renames_records_and_tags() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.sam");
+    std::fs::write(&input, INPUT).unwrap();
+    let output = dir.path().join("out.sam");
+
+    let result = reheader(&input, &output);
+    assert!(result.status.success(), "{result:?}");
+
+    let mut reader = sam::io::Reader::new(BufReader::new(File::open(&output).unwrap()));
+    let header = reader.read_header().unwrap();
+    let records: Vec<RecordBuf> = reader
+        .record_bufs(&header)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    check_records(&header, &records);
+}
+
+#[test]
+fn test_reheader_bam_renames_records_and_tags() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.sam");
+    std::fs::write(&input, INPUT).unwrap();
+    let output = dir.path().join("out.bam");
+
+    let result = reheader(&input, &output);
+    assert!(result.status.success(), "{result:?}");
+
+    let mut reader = bam::io::Reader::new(File::open(&output).unwrap());
+    let header = reader.read_header().unwrap();
+    let records: Vec<RecordBuf> = reader
+        .record_bufs(&header)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    check_records(&header, &records);
+}
+
+#[test]
+fn test_reheader_failure_leaves_no_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.sam");
+    // A record on a contig the header lacks fails mid-stream
+    std::fs::write(
+        &input,
+        format!("{INPUT}r3\t0\t3\t100\t60\t4M\t*\t0\t0\tACGT\tIIII\n"),
+    )
+    .unwrap();
+    let output = dir.path().join("out.sam");
+
+    let result = reheader(&input, &output);
+    assert!(!result.status.success());
+    assert!(!output.exists());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_reheader_output_takes_input_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.sam");
+    std::fs::write(&input, INPUT).unwrap();
+    std::fs::set_permissions(&input, std::fs::Permissions::from_mode(0o644)).unwrap();
+    let output = dir.path().join("out.sam");
+
+    let result = reheader(&input, &output);
+    assert!(result.status.success(), "{result:?}");
+    let mode = std::fs::metadata(&output).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o644);
+}